@@ -32,10 +32,7 @@ local function bullet_hell(bullet_count)
 	return 1
 end
 
-local original_init_level = luola_init_level
-function luola_init_level(settings)
-	original_init_level(settings)
-
+game.on("round_start", function(settings)
 	-- run this function 1 second from now
 	Scheduler.add_global(1, function()
 		bullet_hell(settings.bullets_per_second)
@@ -43,4 +40,4 @@ function luola_init_level(settings)
 		-- rerun after one second
 		return 1
 	end)
-end
+end)
@@ -1,9 +1,17 @@
 local Scheduler = require("utils.scheduler")
 local Impacts = require("weapons.impacts")
 local Level = require("level")
+local Pilot = require("pilot")
+local Targeting = require("utils.targeting")
 
 local Bat = {}
 
+local BITE_DAMAGE = 10
+local BITE_COOLDOWN = 1.5
+
+-- Impact damage needed to kill a bat
+local RAM_DAMAGE_LIMIT = 5
+
 function Bat._timer_fly(critter)
 	if not critter.state.roosting then
 		local speed = 50
@@ -34,7 +42,7 @@ function Bat._timer_decide_target(critter)
 
 	if critter.state.aggro > 0 then
 		critter.state.aggro = critter.state.aggro - 1
-		local nearest_enemy_pos = game.ships_nearest_pos(critter.pos, 300, 0)
+		local nearest_enemy_pos = Targeting.nearest_enemy_pos(critter.pos, 300, 0)
 		if nearest_enemy_pos then
 			critter.state.target = nearest_enemy_pos
 			return 0.3
@@ -103,20 +111,15 @@ function Bat.on_bullet_hit(critter, bullet)
 
 	local hit_angle = bullet.vel:angle()
 
-	-- blood splatter
-	for _ = 0, 4 do
-		game.effect("AddTerrainParticle", {
-			pos = critter.pos,
-			vel = Vec2_for_angle(-hit_angle + math.random(-30, 30), 300.0),
-			imass = 1,
-			drag = 0.002,
-			stain = true,
-			color = 0x80ff0000,
-		})
-	end
+	game.effect("Emit", { preset = "blood_spray", pos = critter.pos, direction = -hit_angle })
 end
 
-function Bat.on_object_hit(critter, obj)
+function Bat.on_object_hit(critter, obj, damage)
+	if damage > RAM_DAMAGE_LIMIT then
+		Bat.on_stomp(critter, obj)
+		return
+	end
+
 	if obj.is_ship then
 		critter.state.aggro = 40
 		if obj.controller > 0 then
@@ -133,10 +136,27 @@ function Bat.on_object_hit(critter, obj)
 		if critter.state.explosive then
 			Impacts.grenade(critter, 0, obj)
 		end
+	elseif obj.is_pilot then
+		-- Angry bats bite pilots
+		if critter.state.aggro > 0 and not critter.state.bite_cooldown then
+			critter.state.bite_cooldown = true
+			Scheduler.add_to_object(critter, BITE_COOLDOWN, function(critter)
+				critter.state.bite_cooldown = false
+			end)
+			Pilot.hurt(obj, BITE_DAMAGE)
+		end
+		critter.state.aggro = 40
 	end
 end
 
+function Bat.on_stomp(critter, pilot)
+	critter:destroy()
+	game.effect("Emit", { preset = "blood_spray", pos = critter.pos, direction = 90 })
+end
+
+-- Returns the scripting state of the new critter
 function Bat.create(pos)
+	local state = Bat:new(pos)
 	game.effect("AddCritter", {
 		pos = pos,
 		vel = Vec2(0, 0),
@@ -144,9 +164,10 @@ function Bat.create(pos)
 		radius = 4,
 		drag = 1 / 1.2, -- neutral buoyancy
 		texture = textures.get("bat"),
-		state = Bat:new(pos),
+		state = state,
 		timer = 0,
 	})
+	return state
 end
 
 function Bat.create_random(config)
@@ -43,6 +43,17 @@ function Bird._timer_flock(critter)
 	return 0.2
 end
 
+-- Impact damage needed to kill a bird
+local RAM_DAMAGE_LIMIT = 5
+
+function Bird.on_object_hit(critter, obj, damage)
+	if damage > RAM_DAMAGE_LIMIT then
+		critter:destroy()
+		game.effect("Emit", { preset = "blood_spray", pos = critter.pos, direction = -obj.vel:angle() })
+		game.effect("Emit", { preset = "feathers", pos = critter.pos })
+	end
+end
+
 function Bird.on_bullet_hit(critter, bullet)
 	if bullet.state ~= nil and bullet.state.is_nitro then
 		bullet:destroy()
@@ -58,35 +69,14 @@ function Bird.on_bullet_hit(critter, bullet)
 
 	if bullet.state ~= nil and bullet.state.is_laser then
 		-- TODO fire effect
-		game.effect("AddParticle", {
-			pos = critter.pos,
-			texture = textures.get("bigboom"),
-		})
+		game.effect("Emit", { preset = "explosion", pos = critter.pos })
 		return
 	end
 
 	local hit_angle = bullet.vel:angle()
 
-	-- blood splatter
-	for _ = 0, 4 do
-		game.effect("AddTerrainParticle", {
-			pos = critter.pos,
-			vel = Vec2_for_angle(-hit_angle + math.random(-30, 30), 300.0),
-			imass = 1,
-			drag = 0.002,
-			stain = true,
-			color = 0x80ff0000,
-		})
-	end
-
-	-- puff of feathers
-	for a = 0, 360, (360 / 20) do
-		game.effect("AddTerrainParticle", {
-			pos = critter.pos,
-			vel = Vec2_for_angle(a + math.random(-10, 10), 400.0),
-			color = 0x99ffffff,
-		})
-	end
+	game.effect("Emit", { preset = "blood_spray", pos = critter.pos, direction = -hit_angle })
+	game.effect("Emit", { preset = "feathers", pos = critter.pos })
 end
 
 function Bird:new(pos)
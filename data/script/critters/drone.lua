@@ -1,6 +1,7 @@
 local Scheduler = require("utils.scheduler")
 local Impacts = require("weapons.impacts")
 local UniqID = require("utils.uniqid")
+local Targeting = require("utils.targeting")
 
 local Drone = {}
 
@@ -16,7 +17,7 @@ function Drone._timer_fly(critter)
 end
 
 function Drone._timer_targeting(critter)
-	local nearest_enemy_pos = game.ships_nearest_pos(critter.pos, PURSUE_DIST, critter.owner)
+	local nearest_enemy_pos = Targeting.nearest_enemy_pos(critter.pos, PURSUE_DIST, critter.owner)
 
 	if nearest_enemy_pos then
 		-- Pursue nearby enemies and shoot if they're close enough
@@ -43,7 +44,7 @@ function Drone._timer_targeting(critter)
 end
 
 function Drone._timer_shoot(critter)
-	local nearest_enemy_pos = game.ships_nearest_pos(critter.pos, FIRING_DIST, critter.owner)
+	local nearest_enemy_pos = Targeting.nearest_enemy_pos(critter.pos, FIRING_DIST, critter.owner)
 
 	if nearest_enemy_pos then
 		local firing_vector = (nearest_enemy_pos - critter.pos):normalized()
@@ -95,6 +96,7 @@ function Drone.on_destroy(critter)
 	game.effect("AddParticle", {
 		pos = critter.pos,
 		texture = textures.get("bigboom"),
+		light = Impacts.EXPLOSION_LIGHT,
 	})
 end
 
@@ -111,7 +113,9 @@ function Drone:new(pos)
 	return drone
 end
 
+-- Returns the scripting state of the new critter
 function Drone.create(pos, owner)
+	local state = Drone:new(pos)
 	game.effect("AddCritter", {
 		id = UniqID.new(),
 		pos = pos,
@@ -122,9 +126,10 @@ function Drone.create(pos, owner)
 		owner = owner,
 		waterproof = false,
 		texture = textures.get("drone"),
-		state = Drone:new(pos),
+		state = state,
 		timer = 0,
 	})
+	return state
 end
 
 -- Count the number of drones deployed by this player in the area
@@ -36,16 +36,7 @@ function Fish.on_bullet_hit(critter, bullet)
 	critter:destroy()
 
 	-- blood splatter
-	for a = 0, 360, (360 / 8) do
-		game.effect("AddParticle", {
-			pos = critter.pos,
-			vel = Vec2_for_angle(a + math.random(-30, 30), 10.0),
-			color = 0x80ff0000,
-			target_color = 0x00ff0000,
-			lifetime = 1 + math.random() * 3,
-			texture = textures.get("dot8x8"),
-		})
-	end
+	game.effect("Emit", { preset = "underwater_blood", pos = critter.pos })
 end
 
 function Fish:new(pos)
@@ -1,9 +1,16 @@
 local Scheduler = require("utils.scheduler")
 local Impacts = require("weapons.impacts")
 local Level = require("level")
+local Pilot = require("pilot")
 
 local Spider = {}
 
+local BITE_DAMAGE = 20
+local BITE_COOLDOWN = 1
+
+-- Impact damage needed to squash a spider
+local RAM_DAMAGE_LIMIT = 5
+
 function Spider.on_touch_ledge(critter)
 	critter.walking = -critter.walking
 end
@@ -83,21 +90,38 @@ function Spider.on_bullet_hit(critter, bullet)
 		return
 	end
 
+	Spider._squash(critter)
+end
+
+function Spider._squash(critter)
 	critter:destroy()
 
-	-- blood splatter
-	for a = 0, 360, (360 / 16) do
-		game.effect("AddTerrainParticle", {
-			pos = critter.pos,
-			vel = Vec2_for_angle(a + math.random(-30, 30), 300.0),
-			color = 0x80c5be02,
-			imass = 1,
-			drag = 0.002,
-			stain = true,
-		})
+	-- insect blood splatter
+	game.effect("Emit", { preset = "blood_splatter", pos = critter.pos, color = 0x80c5be02 })
+end
+
+-- Spiders bite pilots who come too close
+function Spider.on_object_hit(critter, obj, damage)
+	if damage > RAM_DAMAGE_LIMIT then
+		Spider._squash(critter)
+		return
+	end
+
+	if obj.is_pilot and not critter.state.bite_cooldown then
+		critter.state.bite_cooldown = true
+		Scheduler.add_to_object(critter, BITE_COOLDOWN, function(critter)
+			critter.state.bite_cooldown = false
+		end)
+
+		obj:impulse(game.wrapped_delta(critter.pos, obj.pos):normalized() * 30000)
+		Pilot.hurt(obj, BITE_DAMAGE)
 	end
 end
 
+function Spider.on_stomp(critter, pilot)
+	Spider._squash(critter)
+end
+
 function Spider:new(pos)
 	local spider = {
 		scheduler = Scheduler:new():add(math.random(6, 16), Spider._try_ceiling),
@@ -107,7 +131,9 @@ function Spider:new(pos)
 	return spider
 end
 
+-- Returns the scripting state of the new critter
 function Spider.create(pos)
+	local state = Spider:new(pos)
 	game.effect("AddCritter", {
 		pos = pos,
 		vel = Vec2(0, 0),
@@ -116,9 +142,10 @@ function Spider.create(pos)
 		walking = 1,
 		drag = 0.1,
 		texture = textures.get("spider"),
-		state = Spider:new(pos),
+		state = state,
 		timer = 0,
 	})
+	return state
 end
 
 -- confg: [ [count, [x, y, w, h]], ... ]
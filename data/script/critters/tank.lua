@@ -1,6 +1,7 @@
 local Scheduler = require("utils.scheduler")
 local Level = require("level")
 local Rockets = require("weapons.rockets")
+local Impacts = require("weapons.impacts")
 local maths = require("utils.maths")
 local UniqID = require("utils.uniqid")
 
@@ -22,19 +23,31 @@ function Tank._seek_target(critter)
 	local nearest_enemy_pos = nil
 	local nearest_enemy_dist2 = FIRING_DIST2
 
-	game.ships_iter(function(ship)
-		if ship.player ~= critter.owner and not ship.cloaked then
-			if ship.pos.y <= critter.pos.y then
-				local angle = (ship.pos - critter.pos):angle()
-				if angle > 35 and angle < 145 then
-					local dist2 = ship.pos:dist_squared(critter.pos)
-					if dist2 < nearest_enemy_dist2 then
-						nearest_enemy_pos = ship.pos
-						nearest_enemy_dist2 = dist2
-					end
+	-- Tanks can only shoot upwards
+	local function consider(pos)
+		if pos.y <= critter.pos.y then
+			local delta = game.wrapped_delta(critter.pos, pos)
+			local angle = delta:angle()
+			if angle > 35 and angle < 145 then
+				local dist2 = delta.x * delta.x + delta.y * delta.y
+				if dist2 < nearest_enemy_dist2 then
+					nearest_enemy_pos = critter.pos + delta
+					nearest_enemy_dist2 = dist2
 				end
 			end
 		end
+	end
+
+	game.ships_iter(function(ship)
+		if ship.player ~= critter.owner and not ship.cloaked then
+			consider(ship.pos)
+		end
+	end)
+
+	game.pilots_iter(function(pilot)
+		if pilot.player ~= critter.owner then
+			consider(pilot.pos)
+		end
 	end)
 
 	if nearest_enemy_pos ~= nil then
@@ -78,6 +91,7 @@ function Tank.on_destroy(critter)
 	game.effect("AddParticle", {
 		pos = critter.pos,
 		texture = textures.get("bigboom"),
+		light = Impacts.EXPLOSION_LIGHT,
 	})
 end
 
@@ -92,7 +106,9 @@ function Tank:new(pos)
 	return tank
 end
 
+-- Returns the scripting state of the new critter
 function Tank.create(pos, owner)
+	local state = Tank:new(pos)
 	game.effect("AddCritter", {
 		id = UniqID.new(),
 		pos = pos,
@@ -102,11 +118,12 @@ function Tank.create(pos, owner)
 		walking = 1,
 		texture = textures.get("tank"),
 		action_texture = textures.get("tank_attack"),
-		state = Tank:new(pos),
+		state = state,
 		owner = owner,
 		waterproof = false,
 		timer = 0,
 	})
+	return state
 end
 
 -- Count the number of tanks deployed by this player in the area
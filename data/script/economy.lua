@@ -0,0 +1,42 @@
+-- Credits earned during a round when the shop rule is enabled.
+-- Round win bonuses are awarded by the game itself.
+local Economy = {
+	enabled = false,
+	KILL_CREDITS = 100,
+	-- Credits per hitpoint of damage dealt to enemy ships
+	DAMAGE_CREDITS = 1,
+}
+
+function Economy.init(rules)
+	Economy.enabled = rules.economy == true
+end
+
+-- Give credits to a player
+function Economy.award(player, amount)
+	if Economy.enabled and player > 0 and amount > 0 then
+		game.player_effect("credits", player, amount)
+	end
+end
+
+-- Award credits for damaging another player's ship
+function Economy.damage_dealt(attacker, victim, damage)
+	if attacker > 0 and attacker ~= victim then
+		Economy.award(attacker, damage * Economy.DAMAGE_CREDITS)
+	end
+end
+
+-- Award credits for destroying another player's ship
+function Economy.kill(attacker, victim)
+	if Economy.enabled and attacker ~= nil and attacker > 0 and attacker ~= victim then
+		Economy.award(attacker, Economy.KILL_CREDITS)
+		game.player_effect("hud_overlay", attacker, {
+			text = textures.font("menu", tr("+{1} credits", Economy.KILL_CREDITS)),
+			pos = Vec2(0.5, 0.2),
+			color = 0xffffd700,
+			lifetime = 2,
+			fadeout = 1,
+		})
+	end
+end
+
+return Economy
@@ -0,0 +1,238 @@
+-- Horde co-op mode
+-- All players fight together against escalating waves of critters spawned
+-- by the wave director. The round is lost when all players are dead and the
+-- score is the number of waves survived.
+--
+-- Waves and spawn zones can be set in the level's script settings:
+--
+-- [script-settings.horde]
+-- delay = 8                              # seconds between waves
+-- zones = [[x, y, w, h], ...]            # spawn areas (level coordinates)
+-- waves = [{ bat = 4 }, { bat = 4, spider = 2 }, ...]
+--
+-- Waves past the end of the list (or all waves, if there is no list) are
+-- generated. Without spawn zones, enemies spawn anywhere away from the ships.
+
+local Scheduler = require("utils.scheduler")
+local Level = require("level")
+local Economy = require("economy")
+local Pilot = require("pilot")
+local Bat = require("critters.bat")
+local Spider = require("critters.spider")
+local Drone = require("critters.drone")
+local Tank = require("critters.tank")
+
+local Horde = {
+	enabled = false,
+	wave = 0,
+	remaining = 0,
+	countdown = 0,
+	-- Waves survived in the best previous round on this level
+	high_score = 0,
+	players = {},
+	zones = {},
+	waves = {},
+	delay = 8,
+	WAVE_CREDITS = 150,
+	-- Enemies don't spawn closer than this to a ship
+	SAFE_DISTANCE = 300,
+}
+
+-- Enemy types that can appear in waves.
+-- The cost and first wave are used when generating waves.
+local ENEMIES = {
+	bat = {
+		cost = 1,
+		first_wave = 1,
+		create = function(pos)
+			local state = Bat.create(pos)
+			state.aggro = math.huge
+			return state
+		end,
+	},
+	spider = {
+		cost = 1,
+		first_wave = 2,
+		create = Spider.create,
+	},
+	drone = {
+		cost = 3,
+		first_wave = 3,
+		create = function(pos)
+			return Drone.create(pos, 0)
+		end,
+	},
+	tank = {
+		cost = 4,
+		first_wave = 5,
+		create = function(pos)
+			return Tank.create(pos, 0)
+		end,
+	},
+}
+
+local function announce(text, color)
+	for _, player in ipairs(Horde.players) do
+		game.player_effect("hud_overlay", player, {
+			text = textures.font("menu_big", text),
+			pos = Vec2(0.5, 0.3),
+			color = color,
+			lifetime = 3,
+			fadein = 0.3,
+			fadeout = 1,
+		})
+	end
+end
+
+local function update_status()
+	local text
+	if Horde.remaining > 0 then
+		text = tr("Wave {1} - enemies left: {2}", Horde.wave, Horde.remaining)
+	else
+		text = tr("Next wave in {1}", Horde.countdown)
+	end
+
+	for _, player in ipairs(Horde.players) do
+		game.player_effect("hud_status", player, textures.font("menu", text))
+	end
+end
+
+-- Find a spawn point in one of the spawn zones, preferably away from the ships
+local function find_spawn_point()
+	local pos
+	for _ = 1, 5 do
+		local zone = nil
+		if #Horde.zones > 0 then
+			zone = Horde.zones[math.random(#Horde.zones)]
+		end
+		pos = game.find_spawnpoint(zone, false)
+		if game.ships_nearest_pos(pos, Horde.SAFE_DISTANCE, 0) == nil then
+			break
+		end
+	end
+	return pos
+end
+
+-- Get the number of enemies of each type in the given wave
+local function wave_composition(wave)
+	if Horde.waves[wave] ~= nil then
+		return Horde.waves[wave]
+	end
+
+	-- Generated wave: spend a threat budget that grows with each wave
+	local available = {}
+	for name, enemy in pairs(ENEMIES) do
+		if enemy.first_wave <= wave then
+			table.insert(available, name)
+		end
+	end
+
+	local counts = {}
+	local budget = 3 + wave * 2
+	while budget > 0 do
+		local name = available[math.random(#available)]
+		counts[name] = (counts[name] or 0) + 1
+		budget = budget - ENEMIES[name].cost
+	end
+	return counts
+end
+
+local start_next_wave
+
+local function countdown_timer()
+	Horde.countdown = Horde.countdown - 1
+	if Horde.countdown <= 0 then
+		start_next_wave()
+		return nil
+	end
+	update_status()
+	return 1
+end
+
+local function start_countdown()
+	Horde.countdown = Horde.delay
+	update_status()
+	Scheduler.add_global(1, countdown_timer)
+end
+
+local function wave_cleared()
+	game.effect("SetScore", Horde.wave)
+
+	for _, player in ipairs(Horde.players) do
+		Economy.award(player, Horde.WAVE_CREDITS)
+	end
+
+	if Horde.high_score > 0 and Horde.wave == Horde.high_score + 1 then
+		announce(tr("New high score!"), 0xffffd700)
+	else
+		announce(tr("Wave {1} cleared!", Horde.wave), 0xff80ff80)
+	end
+
+	start_countdown()
+end
+
+start_next_wave = function()
+	Horde.wave = Horde.wave + 1
+
+	for name, count in pairs(wave_composition(Horde.wave)) do
+		local enemy = ENEMIES[name]
+		if enemy == nil then
+			print("Unknown horde enemy type:", name)
+		else
+			for _ = 1, count do
+				local state = enemy.create(find_spawn_point())
+				state.horde_wave = Horde.wave
+				Horde.remaining = Horde.remaining + 1
+			end
+		end
+	end
+
+	if Horde.remaining == 0 then
+		wave_cleared()
+		return
+	end
+
+	announce(tr("Wave {1}", Horde.wave), 0xffff4040)
+	update_status()
+end
+
+local function on_critter_destroyed(critter)
+	local state = critter.state
+	if state ~= nil and state.horde_wave ~= nil and not state.horde_counted then
+		state.horde_counted = true
+		Horde.remaining = Horde.remaining - 1
+		if Horde.remaining == 0 then
+			wave_cleared()
+		else
+			update_status()
+		end
+	end
+end
+
+function Horde.init(settings)
+	Horde.enabled = settings.rules.horde == true
+	if not Horde.enabled then
+		return
+	end
+
+	-- A round that ends before the first wave is cleared scores zero
+	game.effect("SetScore", 0)
+	Pilot.friendly_fire = false
+
+	Horde.high_score = settings.high_score or 0
+	for _, p in ipairs(settings.players) do
+		table.insert(Horde.players, p.player)
+	end
+
+	local config = settings.level.horde or {}
+	Horde.delay = config.delay or Horde.delay
+	Horde.waves = config.waves or {}
+	for _, zone in ipairs(config.zones or {}) do
+		table.insert(Horde.zones, Level.to_world_coordinates(zone))
+	end
+
+	game.on("critter_destroyed", on_critter_destroyed)
+	start_countdown()
+end
+
+return Horde
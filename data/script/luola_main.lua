@@ -16,13 +16,20 @@ local Spider = require("critters.spider")
 local Forcefields = require("forcefields")
 local Level = require("level")
 local Turrets = require("turrets")
+local Economy = require("economy")
+local Horde = require("horde")
+local Mission = require("mission")
 
 local player_settings = {}
+local game_rules = {}
 
 -- Main entrypoint
 -- This is called when initializing the game for a new round.
 -- A fresh scripting environment is created for each round.
 function luola_init_game(settings)
+	game_rules = settings.rules
+	Economy.init(game_rules)
+
 	for _, p in ipairs(settings.players) do
 		player_settings[p.player] = p
 
@@ -38,7 +45,7 @@ function luola_init_game(settings)
 		create_ship_for_player(p.player, pos, not p.pilot_spawn)
 
 		game.player_effect("hud_overlay", p.player, {
-			text = textures.font("menu", "Get ready!"),
+			text = textures.font("menu", tr("Get ready!")),
 			pos = Vec2(0.5, 0.1),
 			color = game.player_color(p.player),
 			lifetime = 3,
@@ -47,25 +54,68 @@ function luola_init_game(settings)
 	end
 
 	luola_init_level(settings.level)
+	Horde.init(settings)
+	Mission.init(settings)
 end
 
 -- Create a new ship (global function)
 function create_ship_for_player(player_id, pos, with_controller)
 	local player = player_settings[player_id]
-	local tpl = ships[player.ship].template
+	local tpl = luola_ships[player.ship].template
 	local controller = player.controller
+	local rules = {}
+	if not game_rules.fuel then
+		rules.fuel = 0
+	end
+	if not game_rules.heat then
+		rules.engine_heat = 0
+		rules.weapon_heat = 0
+	end
 	if with_controller == false then
 		controller = 0
 	end
 
+	-- Upgrades bought from the shop
+	local upgrades = {}
+	local upgraded_state = {}
+	for name, count in pairs(player.purchases) do
+		local item = luola_shop[name]
+		if item ~= nil and item.apply ~= nil and count > 0 then
+			local overrides = item.apply(tpl, count)
+			if overrides.state ~= nil then
+				upgraded_state = tableutils.combined(upgraded_state, overrides.state)
+				overrides.state = nil
+			end
+			upgrades = tableutils.combined(upgrades, overrides)
+		end
+	end
+
+	-- Special weapon loadout. The ship switches between these with fire3
+	local loadout = {}
+	local slots = {}
+	for _, name in ipairs(player.weapons) do
+		local weapon = luola_weapons[name]
+		if weapon ~= nil then
+			table.insert(loadout, name)
+			table.insert(slots, { icon = weapon.icon })
+		end
+	end
+	if #loadout == 0 then
+		loadout = { luola_weapons_default }
+		slots = { { icon = luola_weapons[luola_weapons_default].icon } }
+	end
+
 	game.effect(
 		"AddShip",
-		tableutils.combined(tpl, {
+		tableutils.combined(tpl, rules, upgrades, {
 			pos = pos,
 			controller = controller,
 			player = player.player,
-			state = tableutils.combined(tpl.state, {
-				on_fire_secondary = luola_weapons[player.weapon].fire_func,
+			weapons = slots,
+			state = tableutils.combined(tpl.state, upgraded_state, {
+				weapons = loadout,
+				weapon = loadout[1],
+				on_fire_secondary = luola_weapons[loadout[1]].fire_func,
 			})
 		})
 	)
@@ -73,7 +123,8 @@ end
 
 -- Standard level initialization function
 -- This is called indirectly by luola_init_game
--- This may be overridden in a level script to customize the level
+-- Level scripts can customize the level by subscribing to the
+-- "round_start" event with game.on
 function luola_init_level(settings)
 	for k, v in pairs(settings) do
 		print("Level setting:", k, v)
@@ -118,10 +169,7 @@ end
 function luola_explosive_terrain(pos, color)
 	local tex = textures.get("pewpew")
 
-	game.effect("AddParticle", {
-		pos = pos,
-		texture = textures.get("bigboom"),
-	})
+	game.effect("Emit", { preset = "explosion", pos = pos })
 
 	for a = 0, 360, (360 / 5) do
 		game.effect("AddBullet", {
@@ -142,21 +190,15 @@ end
 function luola_splash(pos, vel, imass)
 	local mag = vel:magnitude()
 	if mag > 120 then
-		for a = 0, 360, 10 do
-			game.effect("AddTerrainParticle", {
-				pos = pos + Vec2_for_angle(a, 6),
-				vel = Vec2_for_angle(a, 300.0),
-				color = game.water_color,
-				imass = 1,
-				drag = 0.002,
-			})
-		end
+		game.effect("Emit", { preset = "splash", pos = pos, color = game.water_color })
 	end
 end
 
 -- Check if the round has a winner
 -- Returns the player ID if there is, 0 if the round is tied or nil
--- if the round is still ongoing
+-- if the round is still ongoing.
+-- In horde mode, the round goes on until all players are dead.
+-- In a mission, the round goes on until the mission is over.
 function luola_get_round_winner()
 	local last_player_standing = 0
 	local count = 0
@@ -169,11 +211,20 @@ function luola_get_round_winner()
 	end)
 
 	game.pilots_iter(function(pilot)
-		count = count + 1
-		last_player_standing = pilot.player
+		-- Stranded pilots waiting for rescue don't belong to any player
+		if pilot.player ~= 0 then
+			count = count + 1
+			last_player_standing = pilot.player
+		end
 	end)
 
-	if count <= 1 then
+	if Mission.enabled then
+		return Mission.round_winner(count)
+	elseif Horde.enabled then
+		if count == 0 then
+			return 0
+		end
+	elseif count <= 1 then
 		return last_player_standing
 	end
 	return nil
@@ -199,127 +250,168 @@ end
 
 -- List of special weapons
 -- This is referenced by the weapon selection screen and luola_init_game()
+-- Titles and descriptions are translated when the game loads the list
+-- Each player can pick as many weapons as they have slots, as long as their
+-- total cost fits in the budget. Extra slots (up to three) are bought from the shop.
+-- The icon is shown in the HUD.
 luola_weapons_default = "grenade"
+luola_weapons_budget = 3
+luola_weapons_slots = 1
 luola_weapons = {
 	grenade = {
 		title = "Grenade",
 		fire_func = sweapons.grenade,
+		cost = 1,
+		icon = textures.get("explosive_icon"),
 		description = "The grenade launcher fires a lightweight fragmentation grenade that can deal a surprising amount of damage for its size.",
 	},
 	megabomb = {
 		title = "Megabomb",
 		fire_func = sweapons.megabomb,
+		cost = 2,
+		icon = textures.get("megabomb"),
 		description = "An unguided bomb packed full of high explosives for massive damage.",
 	},
 	rocket = {
 		title = "Rocket launcher",
 		fire_func = sweapons.rocket,
+		cost = 1,
+		icon = textures.get("rocket"),
 		description = "Though smaller than the Megabomb, this self propelled weapon can still carry a large explosive payload.",
 	},
 	missile = {
 		title = "Homing missile",
 		fire_func = sweapons.missile,
+		cost = 2,
+		icon = textures.get("rocket"),
 		description = "The addition of a guidance system has reduced the available payload capacity but the autonomous target seeking capability makes up for it.",
 	},
 	mine = {
 		title = "Mine",
 		fire_func = sweapons.mine,
+		cost = 1,
+		icon = textures.get("mine"),
 		description = "A floating mine with variable buoyancy suitable for use in both water and open air.",
 	},
 	magmine = {
 		title = "Magnetic mine",
 		fire_func = sweapons.magmine,
+		cost = 1,
+		icon = textures.get("magmine"),
 		description = "A mine augmented with a short range magnetic target seeking system.",
 	},
 	landmine = {
 		title = "Claymore",
 		fire_func = sweapons.landmine,
+		cost = 1,
+		icon = textures.get("explosive_icon"),
 		description = "A remote detonable directional charge that can be placed on hard terrain. First trigger pull fires the mine from a rear facing launcher, second detonates.",
 	},
 	gravmine = {
 		title = "Gravity mine",
 		fire_func = sweapons.gravmine,
+		cost = 1,
 		description = "Generates a short-lived artificial gravity well far deeper than the device's own mass-energy would permit according to classical physics.",
 	},
 	moving_gravmine = {
 		title = "Gravity mine (unbalanced)",
 		fire_func = sweapons.moving_gravmine,
+		cost = 1,
 		description = "A variant of the gravity mine. A deliberately engineered inbalance in the field causes the anomaly to move in a straight line.",
 	},
 	drone = {
 		title = "Drone (flying)",
 		fire_func = sweapons.drone,
+		cost = 2,
+		icon = textures.get("drone"),
 		description = "An autonomous target seeking drone equipped with a rapid-fire cannon and a payload capacity of up to 30 armor piercing rounds. Due to signal interference, only a limited number of drones can be deployed in an area.",
 	},
 	tank = {
 		title = "Drone (wheeled)",
 		fire_func = sweapons.tank,
+		cost = 2,
+		icon = textures.get("tank"),
 		description = "A wheeled autonomous munition delivery platform. Compared to flying drones, these ground based units can carry much heavier weaponry.",
 	},
 	cloak = {
 		title = "Chameleon skin",
 		fire_func = sweapons.cloaking_device,
+		cost = 1,
 		description = "Active optical surface coating that can render the ship nearly invisible.",
 	},
 	ghostship = {
 		title = "Improbability drive",
 		fire_func = sweapons.ghostship,
+		cost = 2,
 		description = "A quantum mechanical device that alters the natural probability field around the ship, allowing it to pass through solid ground.",
 	},
 	shield = {
 		title = "Shield",
 		fire_func = sweapons.shield,
+		cost = 2,
+		icon = textures.get("shield"),
 		description = "A grav-tech deflector shield that offers up to 99% protection against incoming fire.",
 	},
 	foam = {
 		title = "Foam grenade",
 		fire_func = sweapons.foam_grenade,
+		cost = 1,
 		description = "Originally developed as a firefighting tool, this weapon fires a glass sphere filled with pressurized foam that hardens in contact with air.",
 	},
 	greygoo = {
 		title = "Grey goo",
 		fire_func = sweapons.greygoo,
+		cost = 2,
 		description = "Universal self replicating nano-disassemblers. Each individual nanite contains a limiter to prevent out-of-control spread.",
 	},
 	freezer = {
 		title = "Hailstone",
 		fire_func = sweapons.freezer,
+		cost = 1,
 		description = "A glass sphere filled with liquid nitrogen. Can freeze a ship solid.",
 	},
 	nitroglycerin = {
 		title = "Nitro-ampule",
 		fire_func = sweapons.nitroglycerin,
+		cost = 1,
 		description = "A glass sphere filled with nitroglycerin. The soaked ground may detonate if disturbed by a sufficiently large shock.",
 	},
 	laser = {
 		title = "Laser cannon",
 		fire_func = sweapons.laser,
+		cost = 2,
 		description = "A directed energy weapon that hits targets at the speed of light.",
 	},
 	digger = {
 		title = "Sonic chisel",
 		fire_func = sweapons.diggerbeam,
+		cost = 1,
 		description = "Emits an ultrasonic beam that breaks down rock and loosens dirt material. Primarily a digging tool; not very effective against modern armor.",
 	},
 	chemtrail = {
 		title = "Chemtrail dispenser",
 		fire_func = sweapons.chemtrail,
+		cost = 1,
 		description = "Releases a toxic mist behind the ship.",
 	},
 	jumpengine = {
 		title = "Jump engine",
 		fire_func = sweapons.jumpengine,
+		cost = 2,
+		icon = textures.get("portal_icon"),
 		description = "Generates a wormhole allowing instantaneous travel across any distance.",
 	},
 	autorepair = {
 		title = "Repair Droid",
 		fire_func = sweapons.autorepair,
+		cost = 2,
 		description = "Equip the ship with an AutoMech droid capable of carrying out repairs on the fly."
 	}
 }
 
 -- List of selectable ships
--- This is used in the ship/weapon selection screen
+-- This is used in the ship/weapon selection screen and create_ship_for_player()
+-- Mods can add their own ships (and weapons to luola_weapons) from their mod script.
 luola_ships = {}
 luola_ships_default = "vwing"
 for name, ship in pairs(ships) do
@@ -327,5 +419,71 @@ for name, ship in pairs(ships) do
 		title = ship.title,
 		description = ship.description,
 		texture = ship.template.texture,
+		template = ship.template,
 	}
 end
+
+-- Items sold in the between-round shop (when the shop rule is enabled)
+-- The shop screen shows the title, description and price. Items can be bought
+-- up to max times. Consumable items only last for the next round.
+-- The apply function is called with the ship template and the number of items owned
+-- and it returns template overrides for create_ship_for_player().
+luola_shop = {
+	cargobay = {
+		title = "Cargo bay extension",
+		description = "Increases special weapon ammunition capacity by 25%.",
+		price = 150,
+		max = 3,
+		apply = function(tpl, count)
+			return { ammo = (tpl.ammo or 100) * (1 + 0.25 * count) }
+		end,
+	},
+	armour = {
+		title = "Armour plating",
+		description = "Reinforced hull plating gives extra hitpoints and absorbs collision damage.",
+		price = 200,
+		max = 3,
+		apply = function(tpl, count)
+			return {
+				hitpoints = (tpl.hitpoints or 100) + 20 * count,
+				armour = math.min(0.9, (tpl.armour or 0) + 0.1 * count),
+			}
+		end,
+	},
+	engine = {
+		title = "Engine tuning",
+		description = "More thrust and faster turning.",
+		price = 150,
+		max = 3,
+		apply = function(tpl, count)
+			return {
+				thrust = (tpl.thrust or 50) * (1 + 0.1 * count),
+				turn_speed = (tpl.turn_speed or 260) * (1 + 0.05 * count),
+			}
+		end,
+	},
+	weapon_slot = {
+		title = "Extra weapon slot",
+		description = "Carry one more special weapon. Switch between the weapons with the Fire3 button.",
+		price = 400,
+		max = 2,
+		weapon_slots = 1,
+	},
+	weapon_points = {
+		title = "Weapons licence",
+		description = "One extra special weapon loadout point.",
+		price = 300,
+		max = 2,
+		weapon_points = 1,
+	},
+	shield = {
+		title = "Shield charge",
+		description = "Each charge absorbs 50 points of damage. Lasts for one round.",
+		price = 100,
+		max = 5,
+		consumable = true,
+		apply = function(tpl, count)
+			return { state = { shield = 50 * count } }
+		end,
+	},
+}
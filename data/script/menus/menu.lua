@@ -6,15 +6,19 @@ function main_menu()
 		}),
 		Spacer(32),
 		Link({
-			label = "Start!",
+			label = tr("Start!"),
 			action = function() return Action.Return("start") end,
 		}),
 		Link({
-			label = "Settings",
+			label = tr("Missions"),
+			action = function() return Action.Return("missions") end,
+		}),
+		Link({
+			label = tr("Settings"),
 			action = settings_menu
 		}),
 		Link({
-			label = "Quit",
+			label = tr("Quit"),
 			action = function() return Action.Return("quit") end,
 		}),
 	})
@@ -26,30 +30,38 @@ function settings_menu()
 
 	return Action.Push(Menu({
 		Heading({
-			label = "Settings",
+			label = tr("Settings"),
 			center = true,
 			font = "caption",
 		}),
 		Spacer(32),
 		Link({
-			label = "Video",
+			label = tr("Video"),
 			action = video_menu,
 		}),
 		Link({
-			label = "Game",
+			label = tr("Game"),
 			action = game_menu,
 		}),
 		Link({
-			label = "Gamepad",
+			label = tr("Gamepad"),
 			action = gamepad_menu,
 		}),
 		Link({
-			label = "Keyboard",
+			label = tr("Keyboard"),
 			action = keyboard_menu,
 		}),
+		Link({
+			label = tr("Mods"),
+			action = mods_menu,
+		}),
+		Link({
+			label = tr("Language"),
+			action = language_menu,
+		}),
 		Spacer(16),
 		Link({
-			label = "Back",
+			label = tr("Back"),
 			action = Action.Pop,
 		}),
 		on_exit = function()
@@ -62,39 +74,188 @@ function settings_menu()
 end
 
 function video_menu()
+	local displays = get_displays()
+	if #displays == 0 then
+		displays = { tr("Default") }
+	end
+
 	return Action.Push(Menu({
 		Heading({
-			label = "Video settings",
+			label = tr("Video settings"),
 			center = true,
 			font = "caption",
 		}),
 		Spacer(32),
 		Link({
-			label = "Start in fullscreen mode:",
+			label = tr("Start in fullscreen mode:"),
 			value = Value.Toggle(SETTINGS.video.fullscreen),
 			action = function(item)
 				SETTINGS.video.fullscreen = item:toggle()
 				SETTINGS_CHANGED = true
 			end,
 		}),
+		Link({
+			label = tr("Fullscreen mode: "),
+			value = Value.Choice(FULLSCREEN_MODE_LABELS, option_index(FULLSCREEN_MODES, SETTINGS.video.fullscreen_mode)),
+			action = function(item)
+				SETTINGS.video.fullscreen_mode = FULLSCREEN_MODES[item:value()]
+				SETTINGS_CHANGED = true
+			end,
+		}),
+		Link({
+			label = tr("Display: "),
+			value = Value.Choice(displays, math.min(SETTINGS.video.display + 1, #displays)),
+			action = function(item)
+				SETTINGS.video.display = item:value() - 1
+				SETTINGS_CHANGED = true
+			end,
+		}),
+		Link({
+			label = tr("Window size: "),
+			value = Value.Choice(size_labels(WINDOW_SIZES), size_index(WINDOW_SIZES, SETTINGS.video.window_size, 2)),
+			action = function(item)
+				local size = WINDOW_SIZES[item:value()]
+				SETTINGS.video.window_size = { size[1], size[2] }
+				SETTINGS_CHANGED = true
+			end,
+		}),
+		Link({
+			label = tr("V-Sync: "),
+			value = Value.Toggle(SETTINGS.video.vsync),
+			action = function(item)
+				SETTINGS.video.vsync = item:toggle()
+				SETTINGS_CHANGED = true
+			end,
+		}),
+		Link({
+			label = tr("Frame rate: "),
+			value = Value.Choice(FRAME_RATE_LABELS, option_index(FRAME_RATES, SETTINGS.video.frame_rate, 2)),
+			action = function(item)
+				SETTINGS.video.frame_rate = FRAME_RATES[item:value()]
+				SETTINGS_CHANGED = true
+			end,
+		}),
+		Link({
+			label = tr("Scaling: "),
+			value = Value.Choice(SCALING_MODE_LABELS, option_index(SCALING_MODES, SETTINGS.video.scaling)),
+			action = function(item)
+				SETTINGS.video.scaling = SCALING_MODES[item:value()]
+				SETTINGS_CHANGED = true
+			end,
+		}),
+		Link({
+			label = tr("Resolution: "),
+			value = Value.Choice(size_labels(LOGICAL_SIZES), size_index(LOGICAL_SIZES, SETTINGS.video.logical_size, 3)),
+			action = function(item)
+				local size = LOGICAL_SIZES[item:value()]
+				SETTINGS.video.logical_size = { size[1], size[2] }
+				SETTINGS_CHANGED = true
+			end,
+		}),
+		Link({
+			label = tr("UI scale: "),
+			value = Value.Slider(0.5, 3.0, 0.25, SETTINGS.video.ui_scale),
+			action = function(item)
+				SETTINGS.video.ui_scale = item:value()
+				SETTINGS_CHANGED = true
+			end,
+		}),
+		Spacer(16),
+		Heading({
+			label = tr("Restart to apply changes"),
+			center = true,
+		}),
 		Spacer(16),
 		Link({
-			label = "Back",
+			label = tr("Back"),
 			action = Action.Pop,
 		}),
 	}))
 end
 
+WINDOW_SIZES = {
+	{ 1024, 576 },
+	{ 1280, 720 },
+	{ 1366, 768 },
+	{ 1600, 900 },
+	{ 1920, 1080 },
+	{ 2560, 1440 },
+}
+
+-- Logical resolutions for scaled rendering
+LOGICAL_SIZES = {
+	{ 640, 360 },
+	{ 960, 540 },
+	{ 1280, 720 },
+	{ 1920, 1080 },
+}
+
+FULLSCREEN_MODES = { "borderless", "exclusive" }
+FULLSCREEN_MODE_LABELS = { tr("Borderless"), tr("Exclusive") }
+
+SCALING_MODES = { "off", "letterbox", "integer" }
+SCALING_MODE_LABELS = { tr("Off"), tr("Letterbox"), tr("Pixel perfect") }
+
+-- Multiples of the simulation rate (or half of it), so game motion stays evenly paced
+FRAME_RATES = { 30, 60, 120, 180, 240 }
+FRAME_RATE_LABELS = { "30", "60", "120", "180", "240" }
+
+-- Find the index of a value in a list of options
+function option_index(options, value, default)
+	for i, v in ipairs(options) do
+		if v == value then
+			return i
+		end
+	end
+	return default or 1
+end
+
+function size_labels(sizes)
+	local labels = {}
+	for _, size in ipairs(sizes) do
+		table.insert(labels, size[1] .. "x" .. size[2])
+	end
+	return labels
+end
+
+function size_index(sizes, current, default)
+	for i, size in ipairs(sizes) do
+		if size[1] == current[1] and size[2] == current[2] then
+			return i
+		end
+	end
+	return default
+end
+
+function set_player_name(player, name)
+	local names = SETTINGS.game.player_names
+	-- Fill the gaps so the list stays a sequence
+	for i = #names + 1, player - 1 do
+		names[i] = ""
+	end
+	names[player] = name
+	SETTINGS_CHANGED = true
+end
+
+PALETTES = { "standard", "deuteranopia", "protanopia", "tritanopia", "high_contrast" }
+PALETTE_LABELS = {
+	tr("Standard"),
+	tr("Deuteranopia"),
+	tr("Protanopia"),
+	tr("Tritanopia"),
+	tr("High contrast"),
+}
+
 function game_menu()
-	return Action.Push(Menu({
+	local items = {
 		Heading({
-			label = "Game options",
+			label = tr("Game options"),
 			center = true,
 			font = "caption",
 		}),
 		Spacer(32),
 		Link({
-			label = "Show minimap: ",
+			label = tr("Show minimap: "),
 			value = Value.Toggle(SETTINGS.game.minimap),
 			action = function(item)
 				SETTINGS.game.minimap = item:toggle()
@@ -102,31 +263,69 @@ function game_menu()
 			end,
 		}),
 		Link({
-			label = "Rebuild bases: ",
+			label = tr("Rebuild bases: "),
 			value = Value.Toggle(SETTINGS.game.baseregen),
 			action = function(item)
 				SETTINGS.game.baseregen = item:toggle()
 				SETTINGS_CHANGED = true
 			end,
 		}),
-		Spacer(16),
 		Link({
-			label = "Back",
-			action = Action.Pop,
+			label = tr("Player colors: "),
+			value = Value.Choice(PALETTE_LABELS, option_index(PALETTES, SETTINGS.game.palette)),
+			action = function(item)
+				SETTINGS.game.palette = PALETTES[item:value()]
+				SETTINGS_CHANGED = true
+			end,
+		}),
+		Link({
+			label = tr("Player badges: "),
+			value = Value.Toggle(SETTINGS.game.player_badges),
+			action = function(item)
+				SETTINGS.game.player_badges = item:toggle()
+				SETTINGS_CHANGED = true
+			end,
 		}),
+		Link({
+			label = tr("Max players: "),
+			value = Value.Slider(1, MAX_PLAYERS, 1, SETTINGS.game.max_players),
+			action = function(item)
+				SETTINGS.game.max_players = math.floor(item:value())
+				SETTINGS_CHANGED = true
+			end,
+		}),
+		Spacer(16),
+	}
+
+	for player = 1, MAX_PLAYERS do
+		table.insert(items, Link({
+			label = tr("Player {1} name: ", player),
+			value = Value.Text(SETTINGS.game.player_names[player]),
+			action = function(item)
+				set_player_name(player, item:value())
+			end,
+		}))
+	end
+
+	table.insert(items, Spacer(16))
+	table.insert(items, Link({
+		label = tr("Back"),
+		action = Action.Pop,
 	}))
+
+	return Action.Push(Menu(items))
 end
 
 function gamepad_menu()
-	return Action.Push(Menu({
+	local items = {
 		Heading({
-			label = "Gamepad options",
+			label = tr("Gamepad options"),
 			center = true,
 			font = "caption",
 		}),
 		Spacer(32),
 		Link({
-			label = "Rumble: ",
+			label = tr("Rumble: "),
 			value = Value.Toggle(SETTINGS.gamepad.rumble),
 			action = function(item)
 				SETTINGS.gamepad.rumble = item:toggle()
@@ -134,100 +333,335 @@ function gamepad_menu()
 			end,
 		}),
 		Spacer(16),
-		Link({
-			label = "Back",
-			action = Action.Pop,
-		}),
+	}
+
+	local gamepads = get_gamepads()
+	if #gamepads == 0 then
+		table.insert(items, Heading({
+			label = tr("No gamepads connected"),
+			center = true,
+		}))
+	end
+
+	for _, pad in ipairs(gamepads) do
+		table.insert(items, Link({
+			label = pad.name,
+			action = function() return gamepad_profile_menu(pad) end,
+		}))
+	end
+
+	table.insert(items, Spacer(16))
+	table.insert(items, Link({
+		label = tr("Back"),
+		action = Action.Pop,
 	}))
+
+	return Action.Push(Menu(items))
 end
 
-function keyboard_menu()
+function gamepad_profile_menu(pad)
+	local profile = SETTINGS.gamepad.profiles[pad.guid]
+	if not profile then
+		profile = get_default_gamepad_profile()
+	end
+	local reset = false
+
 	return Action.Push(Menu({
 		Heading({
-			label = "Keyboard controls",
+			label = pad.name,
 			center = true,
 			font = "caption",
 		}),
 		Spacer(32),
-		
+
 		Link({
-			label = "Keyboard 1",
-			action = function() return keymap_menu(1) end,
+			label = tr("Fire 1: "),
+			value = Value.PadGrab(profile.fire1),
+			action = Action.KeyGrab,
 		}),
 		Link({
-			label = "Keyboard 2",
-			action = function() return keymap_menu(2) end,
+			label = tr("Fire 2: "),
+			value = Value.PadGrab(profile.fire2),
+			action = Action.KeyGrab,
 		}),
 		Link({
-			label = "Keyboard 3",
-			action = function() return keymap_menu(3) end,
+			label = tr("Action: "),
+			value = Value.PadGrab(profile.fire3),
+			action = Action.KeyGrab,
 		}),
 		Link({
-			label = "Keyboard 4",
-			action = function() return keymap_menu(4) end,
+			label = tr("Jump: "),
+			value = Value.PadGrab(profile.jump),
+			action = Action.KeyGrab,
+		}),
+		Link({
+			label = tr("Eject: "),
+			value = Value.PadGrab(profile.eject),
+			action = Action.KeyGrab,
+		}),
+		Spacer(16),
+		Link({
+			label = tr("Stick deadzone: "),
+			value = Value.Slider(0.05, 0.5, 0.05, profile.stick_deadzone),
+			action = function(item)
+				profile.stick_deadzone = item:value()
+			end,
+		}),
+		Link({
+			label = tr("Trigger threshold: "),
+			value = Value.Slider(0.05, 0.95, 0.05, profile.trigger_threshold),
+			action = function(item)
+				profile.trigger_threshold = item:value()
+			end,
+		}),
+		Link({
+			label = tr("Invert thrust: "),
+			value = Value.Toggle(profile.invert_thrust),
+			action = function(item)
+				profile.invert_thrust = item:toggle()
+			end,
+		}),
+		Link({
+			label = tr("Invert turning: "),
+			value = Value.Toggle(profile.invert_turn),
+			action = function(item)
+				profile.invert_turn = item:toggle()
+			end,
+		}),
+		Link({
+			label = tr("Invert aiming: "),
+			value = Value.Toggle(profile.invert_aim),
+			action = function(item)
+				profile.invert_aim = item:toggle()
+			end,
+		}),
+		Link({
+			label = tr("Swap sticks: "),
+			value = Value.Toggle(profile.swap_sticks),
+			action = function(item)
+				profile.swap_sticks = item:toggle()
+			end,
 		}),
-		
 		Spacer(16),
 		Link({
-			label = "Back",
+			label = tr("Reset to defaults"),
+			action = function()
+				reset = true
+				return Action.Pop()
+			end,
+		}),
+		Link({
+			label = tr("Back"),
 			action = Action.Pop,
 		}),
+		on_exit = function(values)
+			if reset then
+				SETTINGS.gamepad.profiles[pad.guid] = nil
+			else
+				profile.fire1 = values[3]
+				profile.fire2 = values[4]
+				profile.fire3 = values[5]
+				profile.jump = values[6]
+				profile.eject = values[7]
+				SETTINGS.gamepad.profiles[pad.guid] = profile
+			end
+			SETTINGS_CHANGED = true
+		end,
 	}))
 end
 
+function mods_menu()
+	local items = {
+		Heading({
+			label = tr("Mods"),
+			center = true,
+			font = "caption",
+		}),
+		Spacer(32),
+	}
+
+	local mods = get_mods()
+	if #mods == 0 then
+		table.insert(items, Heading({
+			label = tr("No mods installed"),
+			center = true,
+		}))
+	end
+
+	for _, mod in ipairs(mods) do
+		table.insert(items, Link({
+			label = mod.title .. ": ",
+			value = Value.Toggle(not is_mod_disabled(mod.id)),
+			action = function(item)
+				set_mod_disabled(mod.id, not item:toggle())
+				SETTINGS_CHANGED = true
+			end,
+		}))
+	end
+
+	table.insert(items, Spacer(16))
+	table.insert(items, Heading({
+		label = tr("Restart to apply changes"),
+		center = true,
+	}))
+	table.insert(items, Spacer(16))
+	table.insert(items, Link({
+		label = tr("Back"),
+		action = Action.Pop,
+	}))
+
+	return Action.Push(Menu(items))
+end
+
+function language_menu()
+	local languages = get_languages()
+	local labels = {}
+	local selected = 1
+	for i, lang in ipairs(languages) do
+		table.insert(labels, lang.name)
+		if lang.code == SETTINGS.game.language then
+			selected = i
+		end
+	end
+
+	return Action.Push(Menu({
+		Heading({
+			label = tr("Language"),
+			center = true,
+			font = "caption",
+		}),
+		Spacer(32),
+		Link({
+			label = tr("Language: "),
+			value = Value.Choice(labels, selected),
+			action = function(item)
+				SETTINGS.game.language = languages[item:value()].code
+				SETTINGS_CHANGED = true
+			end,
+		}),
+		Spacer(16),
+		Heading({
+			label = tr("Restart to apply changes"),
+			center = true,
+		}),
+		Spacer(16),
+		Link({
+			label = tr("Back"),
+			action = Action.Pop,
+		}),
+	}))
+end
+
+function is_mod_disabled(id)
+	for _, d in ipairs(SETTINGS.mods.disabled) do
+		if d == id then
+			return true
+		end
+	end
+	return false
+end
+
+function set_mod_disabled(id, disabled)
+	local list = {}
+	for _, d in ipairs(SETTINGS.mods.disabled) do
+		if d ~= id then
+			table.insert(list, d)
+		end
+	end
+	if disabled then
+		table.insert(list, id)
+	end
+	SETTINGS.mods.disabled = list
+end
+
+function keyboard_menu()
+	local items = {
+		Heading({
+			label = tr("Keyboard controls"),
+			center = true,
+			font = "caption",
+		}),
+		Spacer(32),
+	}
+
+	for id = 1, MAX_KEYMAPS do
+		table.insert(items, Link({
+			label = tr("Keyboard {1}", id),
+			action = function() return keymap_menu(id) end,
+		}))
+	end
+
+	table.insert(items, Spacer(16))
+	table.insert(items, Link({
+		label = tr("Back"),
+		action = Action.Pop,
+	}))
+
+	return Action.Push(Menu(items))
+end
+
+-- Set a keymap, filling in the preceding slots so the list stays a sequence
+function set_keymap(id, keymap)
+	local keymaps = SETTINGS.keymaps
+	for i = #keymaps + 1, id - 1 do
+		keymaps[i] = get_default_keymap(i)
+	end
+	keymaps[id] = keymap
+end
+
 function keymap_menu(id)
-	local keymap = SETTINGS["keymap" .. id]
+	local keymap = SETTINGS.keymaps[id]
 	if not keymap then
 		keymap = get_default_keymap(id)
 	end
 
 	return Action.Push(Menu({
 		Heading({
-			label = "Keyboard " .. id,
+			label = tr("Keyboard {1}", id),
 			center = true,
 			font = "caption",
 		}),
 		Spacer(32),
 		
 		Link({
-			label = "Up: ",
+			label = tr("Up: "),
 			value = Value.KeyGrab(keymap.thrust),
 			action = Action.KeyGrab,
 		}),
 		Link({
-			label = "Down: ",
+			label = tr("Down: "),
 			value = Value.KeyGrab(keymap.down),
 			action = Action.KeyGrab,
 		}),
 		Link({
-			label = "Left: ",
+			label = tr("Left: "),
 			value = Value.KeyGrab(keymap.left),
 			action = Action.KeyGrab,
 		}),
 		Link({
-			label = "Right: ",
+			label = tr("Right: "),
 			value = Value.KeyGrab(keymap.right),
 			action = Action.KeyGrab,
 		}),
 		Link({
-			label = "Fire 1: ",
+			label = tr("Fire 1: "),
 			value = Value.KeyGrab(keymap.fire1),
 			action = Action.KeyGrab,
 		}),
 		Link({
-			label = "Fire 2: ",
+			label = tr("Fire 2: "),
 			value = Value.KeyGrab(keymap.fire2),
 			action = Action.KeyGrab,
 		}),
 		Link({
-			label = "Action: ",
+			label = tr("Action: "),
 			value = Value.KeyGrab(keymap.fire3),
 			action = Action.KeyGrab,
 		}),
 		
 		Spacer(16),
 		Link({
-			label = "Back",
+			label = tr("Back"),
 			action = Action.Pop,
 		}),
 		on_exit = function(values)
@@ -238,7 +672,7 @@ function keymap_menu(id)
 			keymap.fire1 = values[7]
 			keymap.fire2 = values[8]
 			keymap.fire3 = values[9]
-			SETTINGS["keymap" .. id] = keymap
+			set_keymap(id, keymap)
 			SETTINGS_CHANGED = true
 		end,
 	}))
@@ -1,21 +1,21 @@
 function main_menu()
 	return Menu({
 		Heading({
-			label = "Paused",
+			label = tr("Paused"),
 			font = "big",
 			center = true,
 		}),
 		Spacer(32),
 		Link({
-			label = "Resume",
+			label = tr("Resume"),
 			action = function() return Action.Return("resume") end,
 		}),
 		Link({
-			label = "End round",
+			label = tr("End round"),
 			action = function() return Action.Return("endround") end,
 		}),
 		Link({
-			label = "End game",
+			label = tr("End game"),
 			action = function() return Action.Return("endgame") end,
 		}),
 	})
@@ -0,0 +1,249 @@
+-- Single player missions
+-- The mission objective is declared in the level pack's missions.toml:
+--
+-- [[mission]]
+-- name = "first"
+-- title = "Target practice"
+-- level = "demo"          # level in the same pack
+-- par = 90                # par time in seconds
+-- ship = "vwing"          # optional
+-- weapons = ["grenade"]   # optional
+-- [mission.objective]
+-- type = "turrets"
+--
+-- Objective types and their settings:
+--   turrets   destroy all turrets
+--   rescue    pilots = [[x, y], ...] or a number of randomly placed pilots
+--   exit      exit = [x, y] (level coordinates)
+--   survive   time = seconds to survive
+--
+-- The mission fails if the player dies. On success, the completion time
+-- is reported as the round score.
+
+local Scheduler = require("utils.scheduler")
+local Level = require("level")
+local Pilot = require("pilot")
+local Turrets = require("turrets")
+
+local Mission = {
+	enabled = false,
+	player = 1,
+	title = "",
+	par = 0,
+	-- Seconds since the start of the mission
+	elapsed = 0,
+	-- "complete" or "failed" once the mission is over
+	result = nil,
+	objective = nil,
+}
+
+local function format_time(seconds)
+	return string.format("%d:%02d", seconds // 60, seconds % 60)
+end
+
+local function announce(text, color)
+	game.player_effect("hud_overlay", Mission.player, {
+		text = textures.font("menu_big", text),
+		pos = Vec2(0.5, 0.3),
+		color = color,
+		lifetime = 3,
+		fadein = 0.3,
+		fadeout = 1,
+	})
+end
+
+local function update_status()
+	local text = tr("{1} - {2} (par {3})",
+		Mission.objective.status(),
+		format_time(Mission.elapsed),
+		format_time(Mission.par)
+	)
+	game.player_effect("hud_status", Mission.player, textures.font("menu", text))
+end
+
+local function end_mission()
+	Scheduler.add_global(2, check_round_end_condition)
+end
+
+function Mission.complete()
+	if Mission.result ~= nil then
+		return
+	end
+	Mission.result = "complete"
+	game.effect("SetScore", Mission.elapsed)
+	announce(tr("Mission complete!"), 0xff80ff80)
+	end_mission()
+end
+
+function Mission.fail(reason)
+	if Mission.result ~= nil then
+		return
+	end
+	Mission.result = "failed"
+	announce(reason or tr("Mission failed"), 0xffff4040)
+	end_mission()
+end
+
+-- Objective types.
+-- init(config) is called when the round starts and status() returns
+-- the objective progress shown on the HUD.
+local OBJECTIVES = {}
+
+OBJECTIVES.turrets = {
+	destroyed = 0,
+	init = function(config)
+		local objective = OBJECTIVES.turrets
+		-- Level turrets are placed before the mission starts. Without any,
+		-- there is nothing to destroy and the mission is won right away.
+		if Turrets.count == 0 then
+			Mission.complete()
+			return
+		end
+		game.on("fixedobject_destroyed", function(obj)
+			if obj.state ~= nil and obj.state.is_turret then
+				objective.destroyed = objective.destroyed + 1
+				if objective.destroyed >= Turrets.count then
+					Mission.complete()
+				end
+				update_status()
+			end
+		end)
+	end,
+	status = function()
+		return tr("Turrets left: {1}", Turrets.count - OBJECTIVES.turrets.destroyed)
+	end,
+}
+
+OBJECTIVES.rescue = {
+	total = 0,
+	rescued = 0,
+	init = function(config)
+		local objective = OBJECTIVES.rescue
+		local positions = {}
+		if type(config.pilots) == "table" then
+			for _, pos in ipairs(config.pilots) do
+				local w = Level.to_world_coordinates(pos)
+				table.insert(positions, Vec2(w[1], w[2]))
+			end
+		else
+			for _ = 1, config.pilots or 3 do
+				table.insert(positions, game.find_spawnpoint(nil, false))
+			end
+		end
+
+		local function on_rescue()
+			objective.rescued = objective.rescued + 1
+			if objective.rescued >= objective.total then
+				Mission.complete()
+			end
+			update_status()
+		end
+
+		local function on_killed()
+			Mission.fail(tr("A pilot was killed!"))
+		end
+
+		objective.total = #positions
+		for _, pos in ipairs(positions) do
+			Pilot.create_stranded(pos, on_rescue, on_killed)
+		end
+	end,
+	status = function()
+		local objective = OBJECTIVES.rescue
+		return tr("Pilots rescued: {1}/{2}", objective.rescued, objective.total)
+	end,
+}
+
+OBJECTIVES.exit = {
+	init = function(config)
+		local w = Level.to_world_coordinates(config.exit)
+		game.effect("AddFixedObject", {
+			pos = Vec2(w[1], w[2]),
+			texture = textures.get("portal"),
+			color = 0xff5fcde4,
+			radius = 24,
+			light = { radius = 120, color = 0xc05fcde4 },
+			id = 0,
+			state = {
+				on_object_hit = function(exit, ship)
+					if ship.player == Mission.player and ship.controller ~= 0 then
+						Mission.complete()
+					end
+				end,
+				-- Let bullets fly through
+				on_bullet_hit = function()
+					return true
+				end,
+			},
+		})
+	end,
+	status = function()
+		return tr("Reach the exit")
+	end,
+}
+
+OBJECTIVES.survive = {
+	time = 60,
+	init = function(config)
+		OBJECTIVES.survive.time = config.time or OBJECTIVES.survive.time
+	end,
+	status = function()
+		local left = math.max(0, OBJECTIVES.survive.time - Mission.elapsed)
+		return tr("Survive: {1}", format_time(left))
+	end,
+	tick = function()
+		if Mission.elapsed >= OBJECTIVES.survive.time then
+			Mission.complete()
+		end
+	end,
+}
+
+local function mission_timer()
+	if Mission.result ~= nil then
+		return nil
+	end
+
+	Mission.elapsed = Mission.elapsed + 1
+	if Mission.objective.tick ~= nil then
+		Mission.objective.tick()
+	end
+	update_status()
+	return 1
+end
+
+function Mission.init(settings)
+	if settings.mission == nil then
+		return
+	end
+
+	local config = settings.mission.objective
+	Mission.objective = OBJECTIVES[config.type]
+	if Mission.objective == nil then
+		error("Unknown mission objective type: " .. tostring(config.type))
+	end
+
+	Mission.enabled = true
+	Mission.title = settings.mission.title
+	Mission.par = settings.mission.par
+	Mission.player = settings.players[1].player
+
+	Mission.objective.init(config)
+
+	announce(Mission.title, 0xffffd700)
+	update_status()
+	Scheduler.add_global(1, mission_timer)
+end
+
+-- Get the round winner in mission mode.
+-- The player "wins" when the mission is complete and the round is
+-- a draw if the mission failed or the player died.
+function Mission.round_winner(players_alive)
+	if Mission.result == "complete" then
+		return Mission.player
+	elseif Mission.result == "failed" or players_alive == 0 then
+		return 0
+	end
+	return nil
+end
+
+return Mission
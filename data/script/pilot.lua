@@ -3,7 +3,17 @@ local Scheduler = require("utils.scheduler")
 local Level = require("level")
 local Portals = require("portals")
 
-local Pilot = {}
+local Pilot = {
+	-- Can pilots be shot by other players (disabled in co-op modes)
+	friendly_fire = true,
+}
+
+-- Landing speed (pixels per second) at which a fall is lethal.
+-- Fall damage starts at game.pilot_danger_speed.
+local DEADLY_LANDING_SPEED = 900
+
+-- Minimum downward speed for squashing a critter by landing on it
+local STOMP_SPEED = 150
 
 local function on_shoot(pilot)
 	pilot.weapon_cooldown = 0.4
@@ -57,27 +67,64 @@ local function on_ninjarope_swing(pilot, dir)
 	})
 end
 
+local function kill_pilot(pilot, effect)
+	Scheduler.add_global(1, check_round_end_condition)
+
+	pilot:destroy()
+	game.effect("Emit", { preset = effect, pos = pilot.pos })
+
+	if pilot.state.on_killed ~= nil then
+		pilot.state.on_killed(pilot)
+	end
+end
+
+-- Hurt a pilot (e.g. by a biting critter) and kill them if health runs out
+function Pilot.hurt(pilot, damage)
+	pilot:damage(damage)
+	if pilot.health <= 0 then
+		kill_pilot(pilot, "blood_splatter")
+	else
+		game.effect("Emit", { preset = "blood_spray", pos = pilot.pos + Vec2(0, -8) })
+	end
+end
+
 local function on_bullet_hit(pilot, bullet, damage)
 	if damage <= 0 then
 		return
 	end
 
-	Scheduler.add_global(1, check_round_end_condition)
+	if not Pilot.friendly_fire and bullet.owner > 0 then
+		-- all players are on the same side
+		return
+	end
 
-	pilot:destroy()
-	-- blood splatter
-	for a = 0, 360, (360 / 16) do
-		game.effect("AddTerrainParticle", {
-			pos = pilot.pos,
-			vel = Vec2_for_angle(a + math.random(-30, 30), 300.0),
-			color = 0x80ff0000,
-			imass = 1,
-			drag = 0.002,
-			stain = true,
-		})
+	kill_pilot(pilot, "blood_splatter")
+end
+
+local function on_hard_landing(pilot, speed)
+	local safe_speed = game.pilot_danger_speed
+	local severity = (speed - safe_speed) / (DEADLY_LANDING_SPEED - safe_speed)
+	pilot:damage(pilot.max_health * severity)
+
+	if pilot.health <= 0 then
+		kill_pilot(pilot, "splat")
+	else
+		game.effect("Emit", { preset = "blood_spray", pos = pilot.pos, direction = -90 })
 	end
 end
 
+local function on_touch_critter(pilot, critter)
+	-- Landing on top of a small critter squashes it.
+	-- Critters that can be stomped on have an on_stomp method.
+	-- Returns true if stomped, so the critter doesn't also bite the pilot.
+	if critter.state.on_stomp and pilot.vel.y > STOMP_SPEED and pilot.pos.y < critter.pos.y then
+		critter.state.on_stomp(critter, pilot)
+		pilot:impulse(Vec2(0, -30000))
+		return true
+	end
+	return false
+end
+
 local function on_ship_recall(pilot, terrain)
 	-- First see if the ship still exists somewhere in the level
 	local found = nil
@@ -110,31 +157,70 @@ local function on_touch_ship(pilot, ship)
 	end
 end
 
+local function on_touch_ship_stranded(pilot, ship)
+	-- Stranded pilots are rescued by any ship that has a pilot of its own
+	if ship.controller ~= 0 and not pilot.state.rescued then
+		pilot.state.rescued = true
+		pilot:destroy()
+		game.effect("AddParticle", {
+			pos = pilot.pos,
+			texture = textures.get("sparkle"),
+			lifetime = 0.5,
+		})
+		if pilot.state.on_rescue ~= nil then
+			pilot.state.on_rescue(pilot, ship)
+		end
+	end
+end
+
+local function add_pilot(props)
+	props.light = { radius = 60, color = 0x80ffffe0 }
+	props.walk_texture = textures.get("pilot_walk")
+	props.swim_texture = textures.get("pilot_swim")
+	props.jetpack_texture = textures.get("pilot_jetpack")
+	props.stand_texture = textures.get("pilot_stand")
+	props.parachute_texture = textures.get("pilot_parachuting")
+	game.effect("AddPilot", props)
+end
+
 function Pilot.create(pos, player, controller)
-	game.effect("AddPilot",
-		{
-			pos = pos,
-			controller = controller,
-			player = player,
-			state = {
-				on_shoot = on_shoot,
-				on_jetpack = on_jetpack,
-				on_ninjarope_swing = on_ninjarope_swing,
-				on_bullet_hit = on_bullet_hit,
-				on_ship_recall = on_ship_recall,
-				scheduler = Scheduler:new():add(2, function(pilot)
-					-- We don't want to immediately get back into a ship we just exited
-					pilot.state.on_touch_ship = on_touch_ship
-				end),
-			},
-			timer = 2,
-			walk_texture = textures.get("pilot_walk"),
-			swim_texture = textures.get("pilot_swim"),
-			jetpack_texture = textures.get("pilot_jetpack"),
-			stand_texture = textures.get("pilot_stand"),
-			parachute_texture = textures.get("pilot_parachuting"),
-		}
-	)
+	add_pilot({
+		pos = pos,
+		controller = controller,
+		player = player,
+		state = {
+			on_shoot = on_shoot,
+			on_jetpack = on_jetpack,
+			on_ninjarope_swing = on_ninjarope_swing,
+			on_bullet_hit = on_bullet_hit,
+			on_hard_landing = on_hard_landing,
+			on_touch_critter = on_touch_critter,
+			on_ship_recall = on_ship_recall,
+			scheduler = Scheduler:new():add(2, function(pilot)
+				-- We don't want to immediately get back into a ship we just exited
+				pilot.state.on_touch_ship = on_touch_ship
+			end),
+		},
+		timer = 2,
+	})
+end
+
+-- Create an uncontrolled pilot waiting to be picked up.
+-- on_rescue(pilot, ship) is called when a ship picks the pilot up
+-- and on_killed(pilot) if the pilot dies before that.
+function Pilot.create_stranded(pos, on_rescue, on_killed)
+	add_pilot({
+		pos = pos,
+		controller = 0,
+		player = 0,
+		state = {
+			on_bullet_hit = on_bullet_hit,
+			on_hard_landing = on_hard_landing,
+			on_touch_ship = on_touch_ship_stranded,
+			on_rescue = on_rescue,
+			on_killed = on_killed,
+		},
+	})
 end
 
 return Pilot
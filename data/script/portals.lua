@@ -16,6 +16,7 @@ function Portals.create_portal_pair(source, destination)
 		texture = textures.get("portal"),
 		color = 0xfff0a422,
 		radius = 16, 
+		light = { radius = 80, color = 0xc0f0a422 },
 		id = 0,
 		state = {
 			on_object_hit = teleport_object,
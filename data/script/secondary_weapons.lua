@@ -127,7 +127,7 @@ function weapons.drone(ship)
 			})
 		else
 			game.player_effect("hud_overlay", ship.player, {
-				text = textures.font("menu", "Cannot deploy more drones here!"),
+				text = textures.font("menu", tr("Cannot deploy more drones here!")),
 				pos = Vec2(0.5, 0.1),
 				color = 0xffff0000,
 				lifetime = 2,
@@ -150,7 +150,7 @@ function weapons.tank(ship)
 			})
 		else
 			game.player_effect("hud_overlay", ship.player, {
-				text = textures.font("menu", "Cannot deploy more tanks here!"),
+				text = textures.font("menu", tr("Cannot deploy more tanks here!")),
 				pos = Vec2(0.5, 0.1),
 				color = 0xffff0000,
 				lifetime = 2,
@@ -167,14 +167,14 @@ function weapons.cloaking_device(ship, trigger)
 		ship.cloaked = false
 	elseif ship.ammo >= 0.5 then
 		ship.cloaked = true
+		-- Keep draining the cloaking device even if the player switches weapons
+		local slot = ship.weapon
 		Scheduler.add_to_object(ship, 0.1, function(ship)
 			if ship.cloaked then
-				local ammo = ship.ammo - 0.5
-				if ammo < 0 then
+				if not ship:drain_ammo(slot, 0.5) then
 					ship.cloaked = false
 					return
 				end
-				ship.ammo = ammo
 				return 0.1
 			end
 		end)
@@ -201,14 +201,13 @@ function weapons.ghostship(ship, trigger)
 		ship.ghostmode = false
 	elseif ship.ammo > 0.9 then
 		ship.ghostmode = true
+		local slot = ship.weapon
 		Scheduler.add_to_object(ship, 0.1, function(ship)
 			if ship.ghostmode then
-				local ammo = ship.ammo - 0.9
-				if ammo < 0 then
+				if not ship:drain_ammo(slot, 0.9) then
 					ship.ghostmode = false
 					return
 				end
-				ship.ammo = ammo
 				game.player_effect("rumble", ship.controller, {
 					low = 0.01,
 					duration = 0.1,
@@ -422,6 +421,7 @@ function weapons.autorepair(ship, trigger)
 		ship.state.autorepair = false
 	elseif ship.ammo >= 1 then
 		ship.state.autorepair = true
+		local slot = ship.weapon
 		Scheduler.add_to_object(ship, 0.1, function(ship)
 			if ship.state.autorepair and ship.health < ship.max_health then
 				ship:damage(-0.25)
@@ -436,9 +436,7 @@ function weapons.autorepair(ship, trigger)
 					lifetime = 1,
 				})
 
-				local ammo = ship.ammo - 0.5
-				if ammo > 0 then
-					ship.ammo = ammo
+				if ship:drain_ammo(slot, 0.5) then
 					return 0.1
 				end
 			end
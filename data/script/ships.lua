@@ -3,6 +3,8 @@ local weapons = require("primary_weapons")
 local Impacts = require("weapons.impacts")
 local Pilot = require("pilot")
 local tableutils = require("utils.table")
+local Economy = require("economy")
+local Horde = require("horde")
 
 local function vwing_thrust_effect(ship, uw, thrust)
 	if uw then
@@ -85,7 +87,21 @@ local function ship_on_base(ship, timestep)
 			lifetime = 1,
 		})
 	end
-	ship.ammo = ship.ammo + timestep * 10
+	ship:resupply(timestep * 10)
+	if ship.max_fuel > 0 then
+		ship.fuel = ship.fuel + ship.max_fuel * timestep * 0.2
+	end
+end
+
+local function ship_overheat(ship)
+	game.player_effect("hud_overlay", ship.player, {
+		text = textures.font("menu", tr("Overheated!")),
+		pos = Vec2(0.5, 0.1),
+		color = 0xffff6600,
+		lifetime = 1,
+		fadeout = 0.5,
+	})
+	game.effect("Emit", { preset = "wreck_smoke", pos = ship.pos })
 end
 
 local function on_ship_destroyed(ship)
@@ -100,14 +116,11 @@ local function on_ship_destroyed(ship)
 		duration = 0.6,
 	})
 
+	Economy.kill(ship.state.last_attacker, ship.player)
+
 	game.effect("MakeBigHole", { pos = ship.pos, r = 16 })
-	for i = 0, 2 do
-		game.effect("AddParticle", {
-			pos = ship.pos + Vec2(math.random(-30, 30), math.random(-30, 30)),
-			texture = textures.get("bigboom"),
-			reveal_in = i / 6,
-		})
-	end
+	game.effect("Emit", { preset = "ship_explosion", pos = ship.pos })
+	game.effect("Emit", { preset = "wreck_smoke", pos = ship.pos })
 
 	Impacts.make_shrapnell(36, ship.pos, {
 		color = 0xffff6666,
@@ -118,6 +131,25 @@ local function on_ship_destroyed(ship)
 	})
 end
 
+-- Switch to the special weapon in the given loadout slot
+local function ship_switch_weapon(ship, slot)
+	local weapon = ship.state.weapons[slot]
+	if weapon == nil then
+		return
+	end
+
+	ship.state.weapon = weapon
+	ship.state.on_fire_secondary = luola_weapons[weapon].fire_func
+
+	game.player_effect("hud_overlay", ship.player, {
+		text = textures.font("menu", tr(luola_weapons[weapon].title)),
+		pos = Vec2(0.5, 0.9),
+		color = game.player_color(ship.player),
+		lifetime = 1,
+		fadeout = 0.5,
+	})
+end
+
 local function on_ship_eject(ship)
 	Pilot.create(ship.pos, ship.player, ship.controller)
 	ship.controller = 0
@@ -129,7 +161,7 @@ local function ship_touch_greygoo(ship)
 		-- afflicted ship takes constant damage for a while and will also radiate short lived grey goo particles
 		Scheduler.add_to_object(ship, 0.1, function(ship)
 			game.player_effect("hud_overlay", ship.player, {
-					text = textures.font("menu", "Nanite intrusion detected!"),
+					text = textures.font("menu", tr("Nanite intrusion detected!")),
 					pos = Vec2(0.5, 0.1),
 					color = 0xffff0000,
 					lifetime = 0.5,
@@ -164,8 +196,39 @@ local function ship_touch_greygoo(ship)
 	end
 end
 
-local function ship_bullet_hit(ship, bullet, damage)
+-- Shield charges bought from the shop absorb damage until depleted.
+-- Returns the damage that got through.
+local function shield_absorb(ship, damage)
+	local shield = ship.state.shield
+	if shield == nil or shield <= 0 or damage <= 0 then
+		return damage
+	end
+
+	local absorbed = math.min(shield, damage)
+	ship.state.shield = shield - absorbed
+	game.effect("Emit", { preset = "shield_hit", pos = ship.pos })
+	return damage - absorbed
+end
+
+-- Take damage from another player's weapon or ship
+local function ship_attacked(ship, attacker, damage)
+	if Horde.enabled and attacker > 0 then
+		-- all players are on the same side
+		return
+	end
+	damage = shield_absorb(ship, damage)
+	if ship.health > 0 then
+		-- no credits for beating a dead horse
+		Economy.damage_dealt(attacker, ship.player, math.min(damage, ship.health))
+	end
 	ship:damage(damage)
+	if attacker > 0 then
+		ship.state.last_attacker = attacker
+	end
+end
+
+local function ship_bullet_hit(ship, bullet, damage)
+	ship_attacked(ship, bullet.owner, damage)
 	game.player_effect("rumble", ship.controller, {
 		low = 0.25,
 		duration = 0.1,
@@ -181,6 +244,22 @@ local function ship_terrain_damage(ship, damage, terrain)
 	})
 end
 
+-- Rammed by (or rammed into) another ship or a critter
+local function ship_collision(ship, other, damage)
+	local attacker = 0
+	if other.is_ship then
+		attacker = other.player
+	elseif other.is_critter then
+		attacker = other.owner
+	end
+	ship_attacked(ship, attacker, damage)
+	game.player_effect("rumble", ship.controller, {
+		low = math.min(0.3 + damage / 30, 1.0),
+		high = math.min(damage / 30, 1.0),
+		duration = 0.3,
+	})
+end
+
 local ships = {
 	vwing = {
 		title = "V-Wing",
@@ -193,6 +272,13 @@ local ships = {
 			thrust = 40,
 			turn_speed = 260,
 			hitpoints = 100,
+			armour = 0,
+			ram_damage = 1.0,
+			fuel = 30,
+			engine_heat = 0.3,
+			weapon_heat = 0.05,
+			cooling = 0.2,
+			light = { radius = 140, color = 0x90ffffff },
 			state = {
 				on_fire_primary = weapons.cannon,
 				on_destroyed = on_ship_destroyed,
@@ -202,6 +288,9 @@ local ships = {
 				on_eject = on_ship_eject,
 				on_bullet_hit = ship_bullet_hit,
 				on_terrain_damage = ship_terrain_damage,
+				on_collision = ship_collision,
+				on_overheat = ship_overheat,
+				on_switch_weapon = ship_switch_weapon,
 			}
 		},
 	},
@@ -217,6 +306,13 @@ local ships = {
 			thrust = 30,
 			turn_speed = 220,
 			hitpoints = 200,
+			armour = 0.3,
+			ram_damage = 1.5,
+			fuel = 45,
+			engine_heat = 0.25,
+			weapon_heat = 0.04,
+			cooling = 0.2,
+			light = { radius = 140, color = 0x90ffffff },
 			state = {
 				on_fire_primary = weapons.delta_cannon,
 				on_destroyed = on_ship_destroyed,
@@ -226,6 +322,9 @@ local ships = {
 				on_eject = on_ship_eject,
 				on_bullet_hit = ship_bullet_hit,
 				on_terrain_damage = ship_terrain_damage,
+				on_collision = ship_collision,
+				on_overheat = ship_overheat,
+				on_switch_weapon = ship_switch_weapon,
 			}
 		},
 	},
@@ -4,7 +4,10 @@ local Impacts = require("weapons.impacts")
 local Maths = require("utils.maths")
 local Scheduler = require("utils.scheduler")
 
-local Turrets = {}
+local Turrets = {
+	-- Number of turrets added to the level
+	count = 0,
+}
 
 local function deathray_turret_target(turret)
 	local target = game.ships_nearest_pos(turret.pos, turret.state.range, 0)
@@ -31,6 +34,7 @@ local function turret_hit_bullet(turret, bullet)
 	game.effect("AddParticle", {
 		pos = turret.pos,
 		texture = textures.get("bigboom"),
+		light = Impacts.EXPLOSION_LIGHT,
 	})
 	game.effect("MakeBigHole", { pos = turret.pos, r = 8 })
 end
@@ -75,13 +79,16 @@ local function gun_turret_target(turret)
 end
 
 function Turrets.add_deathray(pos, range)
+	Turrets.count = Turrets.count + 1
 	game.effect("AddFixedObject", {
 		pos = pos,
 		id = 0,
 		texture = textures.get("turret_deathray"),
 		action_texture = textures.get("turret_deathray_shoot"),
 		radius = 8,
+		light = { radius = 40, color = 0x80ff3030 },
 		state = {
+			is_turret = true,
 			range = range,
 			scheduler = deathray_turret_target,
 			on_bullet_hit = turret_hit_bullet,
@@ -91,6 +98,7 @@ function Turrets.add_deathray(pos, range)
 end
 
 function Turrets.add_gun(pos, range, initial_angle)
+	Turrets.count = Turrets.count + 1
 	game.effect("AddFixedObject", {
 		pos = pos,
 		id = 0,
@@ -98,6 +106,7 @@ function Turrets.add_gun(pos, range, initial_angle)
 		radius = 8,
 		angle = initial_angle,
 		state = {
+			is_turret = true,
 			turn_dir = 15,
 			range = range,
 			scheduler = gun_turret_target,
@@ -0,0 +1,19 @@
+local Targeting = {}
+
+-- Get the position of the nearest enemy ship or pilot.
+-- Returns nil if there are none in range.
+function Targeting.nearest_enemy_pos(pos, range, other_than)
+	local ship = game.ships_nearest_pos(pos, range, other_than)
+	local pilot = game.pilots_nearest_pos(pos, range, other_than)
+
+	if ship and pilot then
+		if pilot:dist_squared(pos) < ship:dist_squared(pos) then
+			return pilot
+		end
+		return ship
+	end
+
+	return ship or pilot
+end
+
+return Targeting
@@ -61,7 +61,12 @@ end
 function Grav.activate_shield(ship)
 	ship.state.forcefield = UniqID.new()
 	Scheduler.add_to_object(ship, 0, Grav._update_shield)
-	Scheduler.add_to_object(ship, 0.1, Grav._consume_shield_energy)
+
+	-- The shield keeps drawing from the weapon slot it was activated from
+	local slot = ship.weapon
+	Scheduler.add_to_object(ship, 0.1, function(ship)
+		return Grav._consume_shield_energy(ship, slot)
+	end)
 end
 
 function Grav.deactivate_shield(ship)
@@ -69,10 +74,9 @@ function Grav.deactivate_shield(ship)
 	ship.state.forcefield = nil
 end
 
-function Grav._consume_shield_energy(ship)
+function Grav._consume_shield_energy(ship, slot)
 	if ship.state.forcefield ~= nil then
-		ship.ammo = ship.ammo - 1
-		if ship.ammo > 0 then
+		if ship:drain_ammo(slot, 1) then
 			game.player_effect("rumble", ship.controller, {
 				low = 0.01,
 				duration = 0.1,
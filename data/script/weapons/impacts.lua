@@ -4,6 +4,10 @@ local Scheduler = require("utils.scheduler")
 local Level = require("level")
 local impacts = {}
 
+-- Light sources used in levels with dynamic lighting
+impacts.EXPLOSION_LIGHT = { radius = 160, color = 0xffffc060 }
+impacts.ROCKET_LIGHT = { radius = 60, color = 0xc0ffa040 }
+
 -- generic function for explosions
 -- count is the number of bullets generated
 -- pos is the center of the explosion
@@ -98,6 +102,7 @@ function impacts.grenade(this, terrain, obj)
 	game.effect("AddParticle", {
 		pos = this.pos,
 		texture = textures.get("bigboom"),
+		light = impacts.EXPLOSION_LIGHT,
 	})
 	impacts.make_shrapnell(36, this.pos, {
 		color = 0xffff6666,
@@ -120,6 +125,7 @@ function impacts.megabomb(this, terrain, obj)
 	game.effect("AddParticle", {
 		pos = this.pos,
 		texture = textures.get("bigboom"),
+		light = impacts.EXPLOSION_LIGHT,
 	})
 
 	impacts.make_shrapnell(10, this.pos, {
@@ -143,6 +149,7 @@ function impacts.rocket(this, terrain, obj)
 	game.effect("AddParticle", {
 		pos = this.pos,
 		texture = textures.get("bigboom"),
+		light = impacts.EXPLOSION_LIGHT,
 	})
 
 	impacts.make_shrapnell(4, this.pos, {
@@ -165,6 +172,7 @@ function impacts.missile(this, terrain, obj)
 	game.effect("AddParticle", {
 		pos = this.pos,
 		texture = textures.get("bigboom"),
+		light = impacts.EXPLOSION_LIGHT,
 	})
 
 	impacts.make_shrapnell(20, this.pos, {
@@ -189,6 +197,7 @@ function impacts.minimissile(this, terrain, obj)
 	game.effect("AddParticle", {
 		pos = this.pos,
 		texture = textures.get("bigboom"),
+		light = impacts.EXPLOSION_LIGHT,
 	})
 
 	impacts.make_firestarters(3, this.pos)
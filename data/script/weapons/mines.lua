@@ -37,9 +37,10 @@ local function magmine_attract_timer(this)
 
 	game.ships_iter(function(ship)
 		if ship.player ~= this.owner then
-			local dist2 = ship.pos:dist_squared(this.pos)
+			local delta = game.wrapped_delta(this.pos, ship.pos)
+			local dist2 = delta.x * delta.x + delta.y * delta.y
 			if dist2 < nearest_enemy_dist2 then
-				nearest_enemy_pos = ship.pos
+				nearest_enemy_pos = this.pos + delta
 				nearest_enemy_dist2 = dist2
 			end
 		end
@@ -88,6 +89,7 @@ local function detonate_landmine(mine)
 	game.effect("AddParticle", {
 		pos = mine.pos,
 		texture = textures.get("bigboom"),
+		light = Impacts.EXPLOSION_LIGHT,
 	})
 
 	local tex = textures.get("pewpew")
@@ -25,6 +25,7 @@ function Rockets.rocket(pos, vel, angle, owner)
 		radius = 5,
 		owner = owner,
 		texture = textures.get("rocket"),
+		light = Impacts.ROCKET_LIGHT,
 		state = {
 			on_impact = Impacts.rocket,
 			impulse = Vec2_for_angle(angle, 8000.0),
@@ -74,6 +75,7 @@ function Rockets.homing_missile(pos, vel, launch_angle, owner)
 		radius = 5,
 		owner = owner,
 		texture = textures.get("rocket"),
+		light = Impacts.ROCKET_LIGHT,
 		state = {
 			on_impact = Impacts.missile,
 			angle = launch_angle,
@@ -92,6 +94,7 @@ function Rockets.mini_homing_missile(pos, vel, launch_angle, owner)
 		drag = 0.0025,
 		owner = owner,
 		texture = textures.get("rocket"),
+		light = Impacts.ROCKET_LIGHT,
 		state = {
 			on_impact = Impacts.minimissile,
 			angle = launch_angle,
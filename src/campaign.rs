@@ -0,0 +1,145 @@
+// This file is part of Luola2
+// Copyright (C) 2025 Calle Laakkonen
+//
+// Luola2 is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Luola2 is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Luola2.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::{
+    collections::HashMap,
+    fs::{read_to_string, write},
+};
+
+use log::{error, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::fs::get_savefile_path;
+
+const PROGRESS_FILE: &str = "campaign.toml";
+
+/// Maximum star rating of a mission
+pub const MAX_STARS: i32 = 3;
+
+/// Best result of a completed mission
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct MissionRecord {
+    /// Completion time in seconds
+    pub time: i32,
+    pub stars: i32,
+}
+
+/// Completed missions, keyed by mission ID
+#[derive(Serialize, Deserialize, Default)]
+pub struct CampaignProgress {
+    #[serde(default)]
+    missions: HashMap<String, MissionRecord>,
+}
+
+impl CampaignProgress {
+    pub fn load() -> Self {
+        let filename = get_savefile_path(PROGRESS_FILE);
+        let content = match read_to_string(&filename) {
+            Ok(c) => c,
+            Err(e) => {
+                warn!("Couldn't read campaign progress ({:?}): {}", filename, e);
+                return Self::default();
+            }
+        };
+
+        toml::from_str(&content).unwrap_or_else(|e| {
+            error!("Couldn't parse campaign progress ({:?}): {}", filename, e);
+            Self::default()
+        })
+    }
+
+    pub fn save(&self) {
+        let filename = get_savefile_path(PROGRESS_FILE);
+        let content = match toml::to_string(self) {
+            Ok(c) => c,
+            Err(err) => {
+                error!("Failed to serialize campaign progress! {err}");
+                return;
+            }
+        };
+
+        if let Err(e) = write(&filename, content) {
+            error!("Failed to write campaign progress {:?}: {e}", filename);
+        }
+    }
+
+    pub fn get(&self, mission: &str) -> Option<&MissionRecord> {
+        self.missions.get(mission)
+    }
+
+    /// A mission is unlocked when it's the first in its level pack
+    /// or the previous one has been completed
+    pub fn is_unlocked(&self, previous: Option<&str>) -> bool {
+        previous.is_none_or(|p| self.missions.contains_key(p))
+    }
+
+    /// Record a mission completion. Returns true if this was a new best time.
+    pub fn record(&mut self, mission: &str, time: i32, stars: i32) -> bool {
+        match self.missions.get_mut(mission) {
+            Some(best) if best.time <= time => false,
+            Some(best) => {
+                best.time = time;
+                best.stars = best.stars.max(stars);
+                true
+            }
+            None => {
+                self.missions
+                    .insert(mission.to_owned(), MissionRecord { time, stars });
+                true
+            }
+        }
+    }
+}
+
+/// Get the star rating for a mission completion time.
+/// Beating the par time is worth full stars and each
+/// half par time over it loses one.
+pub fn star_rating(time: i32, par: i32) -> i32 {
+    let over = (time - par).max(0) as u32;
+    let lost = (over * 2).div_ceil(par.max(1) as u32) as i32;
+    (MAX_STARS - lost).max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_star_rating() {
+        assert_eq!(star_rating(10, 60), 3);
+        assert_eq!(star_rating(60, 60), 3);
+        assert_eq!(star_rating(61, 60), 2);
+        assert_eq!(star_rating(90, 60), 2);
+        assert_eq!(star_rating(91, 60), 1);
+        assert_eq!(star_rating(1000, 60), 1);
+    }
+
+    #[test]
+    fn test_campaign_progress() {
+        let mut progress = CampaignProgress::default();
+        assert!(progress.is_unlocked(None));
+        assert!(!progress.is_unlocked(Some("demos/first")));
+
+        assert!(progress.record("demos/first", 80, 2));
+        assert!(progress.is_unlocked(Some("demos/first")));
+        assert!(!progress.record("demos/first", 90, 1));
+        assert!(progress.record("demos/first", 50, 3));
+        assert_eq!(
+            progress.get("demos/first"),
+            Some(&MissionRecord { time: 50, stars: 3 })
+        );
+    }
+}
@@ -15,6 +15,7 @@
 // along with Luola2.  If not, see <https://www.gnu.org/licenses/>.
 
 use std::{
+    collections::HashMap,
     fs::{read_to_string, write},
     sync::{LazyLock, RwLock},
 };
@@ -22,7 +23,13 @@ use std::{
 use log::{error, info, warn};
 use serde::{Deserialize, Serialize, de::value::Error as SerdeError, de::value::MapDeserializer};
 
-use crate::{events::push_config_changed_event, fs::get_savefile_path, game::PlayerKeymap};
+use crate::{
+    events::push_config_changed_event,
+    fs::get_savefile_path,
+    game::{GameControllerSet, GamepadProfile, PlayerKeymap},
+    gfx::{Color, FullscreenMode, PlayerPalette, ScalingMode},
+    lang::{DEFAULT_LANGUAGE, tr_fmt},
+};
 
 macro_rules! default_from_serde {
     ($typ:ident) => {
@@ -42,24 +49,109 @@ macro_rules! default_from_serde {
 pub struct VideoConfig {
     #[serde(default)]
     pub fullscreen: bool,
+    #[serde(default)]
+    pub fullscreen_mode: FullscreenMode,
+    /// Index of the display to open the window on
+    #[serde(default)]
+    pub display: usize,
+    /// Initial window size (width, height)
+    #[serde(default = "default_window_size")]
+    pub window_size: (u32, u32),
+    #[serde(default = "default_true")]
+    pub vsync: bool,
+    /// Frames per second. The game world is always simulated at 60 steps per second,
+    /// so the frame rate is rounded to a multiple of that (or 30.)
+    #[serde(default = "default_frame_rate")]
+    pub frame_rate: u32,
+    /// How the logical resolution is fitted to the window
+    #[serde(default)]
+    pub scaling: ScalingMode,
+    /// Resolution the game is rendered at when scaling is enabled
+    #[serde(default = "default_window_size")]
+    pub logical_size: (u32, u32),
+    /// Scale factor for fonts, menus and the HUD
+    #[serde(default = "default_ui_scale")]
+    pub ui_scale: f32,
+    /// Save every Nth frame in frame capture mode
+    #[serde(default = "default_capture_interval")]
+    pub capture_interval: u32,
 }
 
 fn default_true() -> bool {
     true
 }
 
+fn default_max_players() -> usize {
+    4
+}
+
+fn default_language() -> String {
+    DEFAULT_LANGUAGE.to_owned()
+}
+
+fn default_frame_rate() -> u32 {
+    60
+}
+
+fn default_ui_scale() -> f32 {
+    1.0
+}
+
+fn default_capture_interval() -> u32 {
+    2
+}
+
+fn default_window_size() -> (u32, u32) {
+    (1280, 720)
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct GameOptions {
     #[serde(default = "default_true")]
     pub minimap: bool,
     #[serde(default = "default_true")]
     pub baseregen: bool,
+    /// Maximum number of players that can join a game
+    #[serde(default = "default_max_players")]
+    pub max_players: usize,
+    /// Player color palette
+    #[serde(default)]
+    pub palette: PlayerPalette,
+    /// Show player number badges on ships and pilots and distinct minimap marker shapes
+    #[serde(default)]
+    pub player_badges: bool,
+    /// Custom player names. An empty name means the default "Player N" is used.
+    #[serde(default)]
+    pub player_names: Vec<String>,
+    /// Language code of the string table to use (data/lang/<code>.toml)
+    #[serde(default = "default_language")]
+    pub language: String,
+}
+
+impl GameOptions {
+    pub fn player_name(&self, player: i32) -> String {
+        self.player_names
+            .get((player - 1) as usize)
+            .filter(|n| !n.is_empty())
+            .cloned()
+            .unwrap_or_else(|| tr_fmt("Player {1}", &[&player]))
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct GamepadOptions {
     #[serde(default = "default_true")]
     pub rumble: bool,
+    /// Input mapping profiles keyed by gamepad GUID
+    #[serde(default)]
+    pub profiles: HashMap<String, GamepadProfile>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ModOptions {
+    /// IDs of installed mods that should not be loaded
+    #[serde(default)]
+    pub disabled: Vec<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -70,15 +162,49 @@ pub struct UserConfig {
     pub game: GameOptions,
     #[serde(default)]
     pub gamepad: GamepadOptions,
-    pub keymap1: Option<PlayerKeymap>,
-    pub keymap2: Option<PlayerKeymap>,
-    pub keymap3: Option<PlayerKeymap>,
-    pub keymap4: Option<PlayerKeymap>,
+    #[serde(default)]
+    pub mods: ModOptions,
+    /// Keyboard keymaps. The default keymaps are used for keyboards not in the list.
+    #[serde(default)]
+    pub keymaps: Vec<PlayerKeymap>,
+
+    // Fixed keymap slots used by older versions.
+    // These are moved to the keymaps list when the config is loaded.
+    #[serde(default, skip_serializing)]
+    keymap1: Option<PlayerKeymap>,
+    #[serde(default, skip_serializing)]
+    keymap2: Option<PlayerKeymap>,
+    #[serde(default, skip_serializing)]
+    keymap3: Option<PlayerKeymap>,
+    #[serde(default, skip_serializing)]
+    keymap4: Option<PlayerKeymap>,
+}
+
+impl UserConfig {
+    fn migrate_legacy_keymaps(&mut self) {
+        let legacy = [
+            self.keymap1.take(),
+            self.keymap2.take(),
+            self.keymap3.take(),
+            self.keymap4.take(),
+        ];
+
+        if !self.keymaps.is_empty() || legacy.iter().all(Option::is_none) {
+            return;
+        }
+
+        self.keymaps = legacy
+            .into_iter()
+            .zip(GameControllerSet::DEFAULT_KEYMAP)
+            .map(|(keymap, default)| keymap.unwrap_or(default))
+            .collect();
+    }
 }
 
 default_from_serde!(VideoConfig);
 default_from_serde!(GameOptions);
 default_from_serde!(GamepadOptions);
+default_from_serde!(ModOptions);
 default_from_serde!(UserConfig);
 
 pub static GAME_CONFIG: LazyLock<RwLock<UserConfig>> =
@@ -94,7 +220,7 @@ pub fn load_user_config() {
         }
     };
 
-    let config = match toml::from_str(&content) {
+    let mut config: UserConfig = match toml::from_str(&content) {
         Ok(c) => c,
         Err(e) => {
             error!("Couldn't parse user config file ({:?}: {}", filename, e);
@@ -102,6 +228,9 @@ pub fn load_user_config() {
         }
     };
 
+    config.migrate_legacy_keymaps();
+    Color::set_player_palette(config.game.palette);
+
     let mut w = GAME_CONFIG.write().unwrap();
     *w = config;
 }
@@ -121,6 +250,8 @@ pub fn save_user_config(config: UserConfig) {
         return;
     }
 
+    Color::set_player_palette(config.game.palette);
+
     let mut w = GAME_CONFIG.write().unwrap();
     *w = config;
     drop(w);
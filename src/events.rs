@@ -26,21 +26,32 @@ pub struct CustomEvents {
     pub grabkey: u32,
     pub config_changed: u32,
     pub menu_button: u32,
+    pub text_input: u32,
 }
 
 pub static CUSTOM_EVENTS: LazyLock<CustomEvents> = LazyLock::new(|| {
-    let id = unsafe { SDL_RegisterEvents(3) };
+    let id = unsafe { SDL_RegisterEvents(4) };
 
     CustomEvents {
         grabkey: id,
         config_changed: id + 1,
         menu_button: id + 2,
+        text_input: id + 3,
     }
 });
 
-pub fn push_grabkey_event() {
+/// Start grabbing the next keyboard key or, if `gamepad` is set, gamepad input combination
+pub fn push_grabkey_event(gamepad: bool) {
     let mut ev = SDL_Event {
-        r#type: CUSTOM_EVENTS.grabkey,
+        user: SDL_UserEvent {
+            r#type: CUSTOM_EVENTS.grabkey,
+            reserved: 0,
+            timestamp: 0,
+            windowID: SDL_WindowID(0),
+            code: gamepad as i32,
+            data1: null_mut(),
+            data2: null_mut(),
+        },
     };
     unsafe {
         SDL_PushEvent(&mut ev);
@@ -73,3 +84,21 @@ pub fn push_menu_button_event(button: MenuButton) {
         SDL_PushEvent(&mut ev);
     }
 }
+
+/// Enable or disable text input mode
+pub fn push_text_input_event(enabled: bool) {
+    let mut ev = SDL_Event {
+        user: SDL_UserEvent {
+            r#type: CUSTOM_EVENTS.text_input,
+            reserved: 0,
+            timestamp: 0,
+            windowID: SDL_WindowID(0),
+            code: enabled as i32,
+            data1: null_mut(),
+            data2: null_mut(),
+        },
+    };
+    unsafe {
+        SDL_PushEvent(&mut ev);
+    }
+}
@@ -14,6 +14,8 @@
 // You should have received a copy of the GNU General Public License
 // along with Luola2.  If not, see <https://www.gnu.org/licenses/>.
 
+mod mods;
 mod paths;
 
+pub use mods::*;
 pub use paths::*;
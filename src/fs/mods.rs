@@ -0,0 +1,186 @@
+// This file is part of Luola2
+// Copyright (C) 2025 Calle Laakkonen
+//
+// Luola2 is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Luola2 is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Luola2.  If not, see <https://www.gnu.org/licenses/>.
+
+use anyhow::Result;
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+use std::fs::{read_dir, read_to_string};
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, RwLock};
+
+use super::paths::{BASEPATH, USERPATH};
+
+/// Mod manifest file (mods/<id>/mod.toml)
+#[derive(Deserialize)]
+struct ModManifest {
+    title: String,
+    #[serde(default)]
+    description: String,
+    /// Mods are loaded in ascending load order. Mods loaded later
+    /// override the textures, ships and weapons of earlier ones.
+    #[serde(default)]
+    load_order: i32,
+    /// Name of the Lua module to run after the main script has been loaded
+    script: Option<String>,
+}
+
+#[derive(Clone, Serialize)]
+pub struct ModInfo {
+    /// Name of the mod's directory
+    pub id: String,
+    pub title: String,
+    pub description: String,
+    pub load_order: i32,
+    pub enabled: bool,
+    #[serde(skip)]
+    pub script: Option<String>,
+    #[serde(skip)]
+    root: PathBuf,
+}
+
+static MODS: LazyLock<RwLock<Vec<ModInfo>>> = LazyLock::new(|| RwLock::new(Vec::new()));
+
+impl ModInfo {
+    fn load(id: String, root: PathBuf) -> Result<Self> {
+        let manifest: ModManifest = toml::from_str(&read_to_string(root.join("mod.toml"))?)?;
+
+        Ok(Self {
+            id,
+            title: manifest.title,
+            description: manifest.description,
+            load_order: manifest.load_order,
+            enabled: true,
+            script: manifest.script,
+            root,
+        })
+    }
+
+    /// The mod's own data directory
+    pub fn data_path(&self) -> PathBuf {
+        self.root.join("data")
+    }
+}
+
+/**
+ * Find all installed mods.
+ *
+ * Mods are searched from the "mods" directory in user data and next to the executable.
+ * A mod in user data hides a mod with the same ID in the executable directory.
+ * Mods whose ID is in the disabled list are marked as not enabled.
+ */
+pub fn load_mods(disabled: &[String]) {
+    let mut mods: Vec<ModInfo> = Vec::new();
+
+    for dir in [
+        [&USERPATH, "mods"].iter().collect::<PathBuf>(),
+        [&BASEPATH, "mods"].iter().collect::<PathBuf>(),
+    ] {
+        let Ok(entries) = read_dir(&dir) else {
+            continue;
+        };
+
+        for entry in entries.flatten() {
+            let root = entry.path();
+            if !root.is_dir() {
+                continue;
+            }
+
+            let Some(id) = root.file_name().and_then(|f| f.to_str()).map(str::to_owned) else {
+                continue;
+            };
+
+            if mods.iter().any(|m| m.id == id) {
+                continue;
+            }
+
+            match ModInfo::load(id.clone(), root) {
+                Ok(mut m) => {
+                    m.enabled = !disabled.contains(&m.id);
+                    mods.push(m);
+                }
+                Err(err) => error!("Mod \"{id}\": couldn't load manifest: {err}"),
+            }
+        }
+    }
+
+    mods.sort_by(|a, b| a.load_order.cmp(&b.load_order).then(a.id.cmp(&b.id)));
+
+    for m in mods.iter() {
+        info!(
+            "Mod \"{}\" ({}): {}",
+            m.title,
+            m.id,
+            if m.enabled { "enabled" } else { "disabled" }
+        );
+    }
+
+    *MODS.write().unwrap() = mods;
+}
+
+/**
+ * Get a list of all installed mods (including disabled ones) in load order.
+ */
+pub fn installed_mods() -> Vec<ModInfo> {
+    MODS.read().unwrap().clone()
+}
+
+/**
+ * Get a list of enabled mods in load order.
+ */
+pub fn enabled_mods() -> Vec<ModInfo> {
+    MODS.read()
+        .unwrap()
+        .iter()
+        .filter(|m| m.enabled)
+        .cloned()
+        .collect()
+}
+
+/**
+ * Find the named file in the data trees of all enabled mods.
+ *
+ * Returns the full paths to the files found, in load order.
+ */
+pub fn find_mod_datafiles(path: &str) -> Vec<PathBuf> {
+    enabled_mods()
+        .into_iter()
+        .map(|m| m.data_path().join(path))
+        .filter(|p| p.exists())
+        .collect()
+}
+
+/**
+ * Find the mod the given data file belongs to.
+ *
+ * Returns the title of the mod or None if the file is not part of any mod.
+ */
+pub fn mod_for_path(path: &Path) -> Option<String> {
+    MODS.read()
+        .unwrap()
+        .iter()
+        .find(|m| path.starts_with(&m.root))
+        .map(|m| m.title.clone())
+}
+
+/**
+ * Describe where a data file came from for error messages.
+ */
+pub fn datafile_origin(path: &Path) -> String {
+    match mod_for_path(path) {
+        Some(title) => format!("mod \"{title}\" ({})", path.display()),
+        None => path.display().to_string(),
+    }
+}
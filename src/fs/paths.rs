@@ -25,9 +25,10 @@ use std::ffi::{CStr, CString, c_char, c_int, c_void};
 use std::path::{Path, PathBuf};
 use std::sync::LazyLock;
 
+use super::mods::enabled_mods;
 use crate::gfx::SdlError;
 
-static BASEPATH: LazyLock<String> = LazyLock::new(|| {
+pub(super) static BASEPATH: LazyLock<String> = LazyLock::new(|| {
     let bp = unsafe { SDL_GetBasePath() };
     if bp.is_null() {
         // shouldn't happen
@@ -39,7 +40,7 @@ static BASEPATH: LazyLock<String> = LazyLock::new(|| {
     bp.into()
 });
 
-static USERPATH: LazyLock<String> = LazyLock::new(|| {
+pub(super) static USERPATH: LazyLock<String> = LazyLock::new(|| {
     let bp = unsafe { SDL_GetPrefPath(c"io.github.callaa.luola2".as_ptr(), c"luola2".as_ptr()) };
     if bp.is_null() {
         // shouldn't happen
@@ -77,6 +78,9 @@ fn if_exists(p: PathBuf) -> Option<PathBuf> {
  *   4. /usr/share/luola2/ (Linux only)
  *
  * Returns the full path to the first file or directory found.
+ *
+ * Note: mod data directories are not searched. Mod content is merged
+ * with the base data instead (see find_mod_datafiles.)
  */
 pub fn find_datafile_path(path: &str) -> Result<PathBuf> {
     let p = if_exists([&USERPATH, "data", path].iter().collect())
@@ -138,16 +142,27 @@ fn do_glob(
 /**
  * Return a list of files or directories in the given directory that match the glob pattern.
  *
- * Files from all available data directories (see find_datafile_path) are searched.
+ * Files from all available data directories (see find_datafile_path) are searched,
+ * as well as the data directories of enabled mods.
  * Files from higher priority directories will hide files from low priority ones,
  * so that if "settings.toml" is found in both user data and /usr/share, only the user data
- * one will be returned.
+ * one will be returned. Mods have the highest priority, with mods later in the load
+ * order taking precedence.
  */
 pub fn glob_datafiles(path: &str, pattern: &str) -> Result<Vec<PathBuf>> {
     let pattern = CString::new(pattern)?;
     let mut paths: Vec<PathBuf> = Vec::new();
     let mut filenames: HashSet<String> = HashSet::new();
 
+    for m in enabled_mods().iter().rev() {
+        do_glob(
+            &m.data_path().join(path),
+            &pattern,
+            &mut filenames,
+            &mut paths,
+        )?;
+    }
+
     do_glob(
         &[&USERPATH, "data", path].iter().collect(),
         &pattern,
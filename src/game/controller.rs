@@ -16,7 +16,7 @@
 
 use std::{
     collections::HashMap,
-    ffi::{CStr, c_void},
+    ffi::{CStr, CString, c_char, c_void},
     ptr::{self, null_mut},
 };
 
@@ -30,21 +30,27 @@ use sdl3_sys::{
         SDL_GAMEPAD_BUTTON_EAST, SDL_GAMEPAD_BUTTON_LEFT_SHOULDER, SDL_GAMEPAD_BUTTON_NORTH,
         SDL_GAMEPAD_BUTTON_RIGHT_SHOULDER, SDL_GAMEPAD_BUTTON_SOUTH, SDL_GAMEPAD_BUTTON_START,
         SDL_GAMEPAD_BUTTON_WEST, SDL_Gamepad, SDL_GamepadAxis, SDL_GamepadButton, SDL_GamepadType,
-        SDL_GetGamepadAxis, SDL_GetGamepadButton, SDL_GetGamepadGUIDForID,
-        SDL_GetGamepadStringForType, SDL_GetGamepadTypeForID, SDL_OpenGamepad, SDL_RumbleGamepad,
-        SDL_SetGamepadLED, SDL_SetGamepadPlayerIndex,
+        SDL_GetGamepadAxis, SDL_GetGamepadAxisFromString, SDL_GetGamepadButton,
+        SDL_GetGamepadButtonFromString, SDL_GetGamepadGUIDForID, SDL_GetGamepadNameForID,
+        SDL_GetGamepadStringForAxis, SDL_GetGamepadStringForButton, SDL_GetGamepadStringForType,
+        SDL_GetGamepadTypeForID, SDL_OpenGamepad, SDL_RumbleGamepad, SDL_SetGamepadLED,
+        SDL_SetGamepadPlayerIndex,
     },
-    guid::SDL_GUID,
+    guid::{SDL_GUID, SDL_GUIDToString},
     joystick::{SDL_JOYSTICK_AXIS_MAX, SDL_JoystickID},
     keycode::*,
+    scancode::SDL_SCANCODE_GRAVE,
 };
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de::Error as _};
 
-use crate::{configfile::GAME_CONFIG, events::push_menu_button_event, game::PlayerId, gfx::Color};
+use crate::{
+    configfile::GAME_CONFIG, events::push_menu_button_event, game::PlayerId, gfx::Color,
+    lang::tr_fmt,
+};
 
-/// How many controllers are reserved for keyboard use.
+/// How many controllers are reserved for keyboard use (the maximum number of keymaps.)
 /// All other controllers are gamepads.
-pub const KEYBOARDS: usize = 4;
+pub const KEYBOARDS: usize = 8;
 
 /**
  * The state of a single player's game controller.
@@ -64,6 +70,7 @@ pub struct GameController {
     guid: SDL_GUID,
     joystick_id: SDL_JoystickID,
     gamepad: *mut SDL_Gamepad,
+    profile: GamepadProfile,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -77,6 +84,251 @@ pub struct PlayerKeymap {
     pub fire3: u32,
 }
 
+/**
+ * A gamepad button or trigger that can be bound to an action.
+ *
+ * Inputs are stored in the settings file using SDL's gamepad mapping
+ * names (e.g. "a", "rightshoulder", "lefttrigger".)
+ */
+#[derive(Clone, Copy, PartialEq)]
+pub enum GamepadInput {
+    Button(SDL_GamepadButton),
+    Trigger(SDL_GamepadAxis),
+}
+
+impl GamepadInput {
+    pub fn name(self) -> String {
+        let name = unsafe {
+            match self {
+                Self::Button(b) => SDL_GetGamepadStringForButton(b),
+                Self::Trigger(a) => SDL_GetGamepadStringForAxis(a),
+            }
+        };
+        if name.is_null() {
+            return String::new();
+        }
+        unsafe { CStr::from_ptr(name) }
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        let cname = CString::new(name).ok()?;
+        let button = unsafe { SDL_GetGamepadButtonFromString(cname.as_ptr()) };
+        if button != SDL_GamepadButton::INVALID {
+            return Some(Self::Button(button));
+        }
+
+        let axis = unsafe { SDL_GetGamepadAxisFromString(cname.as_ptr()) };
+        if axis == SDL_GAMEPAD_AXIS_LEFT_TRIGGER || axis == SDL_GAMEPAD_AXIS_RIGHT_TRIGGER {
+            return Some(Self::Trigger(axis));
+        }
+
+        None
+    }
+
+    /// Encode as an integer for passing in a custom SDL event
+    fn to_code(self) -> usize {
+        match self {
+            Self::Button(b) => b.0 as usize,
+            Self::Trigger(a) => 0x100 | a.0 as usize,
+        }
+    }
+
+    fn from_code(code: usize) -> Self {
+        if code & 0x100 != 0 {
+            Self::Trigger(SDL_GamepadAxis((code & 0xff) as _))
+        } else {
+            Self::Button(SDL_GamepadButton(code as _))
+        }
+    }
+}
+
+/// Maximum number of gamepad inputs in a grabbed combination
+pub const MAX_GRABBED_INPUTS: usize = 3;
+
+/**
+ * Gamepad inputs held down together in pad grab mode.
+ *
+ * This is a fixed size list so it can be passed in a custom SDL event.
+ */
+#[derive(Clone, Copy, PartialEq, Default)]
+pub struct GrabbedInputs([Option<GamepadInput>; MAX_GRABBED_INPUTS]);
+
+impl GrabbedInputs {
+    /// Add an input to the combination, unless it's already there or the list is full
+    fn push(&mut self, input: GamepadInput) {
+        if self.contains(input) {
+            return;
+        }
+        if let Some(slot) = self.0.iter_mut().find(|i| i.is_none()) {
+            *slot = Some(input);
+        }
+    }
+
+    fn contains(&self, input: GamepadInput) -> bool {
+        self.0.contains(&Some(input))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0[0].is_none()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = GamepadInput> + '_ {
+        self.0.iter().map_while(|i| *i)
+    }
+
+    /// Encode as an integer for passing in a custom SDL event.
+    /// Each input takes 10 bits, so the result fits in 32 bits.
+    fn to_code(self) -> usize {
+        self.0.iter().rev().fold(0, |code, i| {
+            (code << 10) | i.map(|i| i.to_code() + 1).unwrap_or(0)
+        })
+    }
+
+    fn from_code(mut code: usize) -> Self {
+        let mut inputs = Self::default();
+        for slot in inputs.0.iter_mut() {
+            let c = code & 0x3ff;
+            if c == 0 {
+                break;
+            }
+            *slot = Some(GamepadInput::from_code(c - 1));
+            code >>= 10;
+        }
+        inputs
+    }
+}
+
+impl Serialize for GamepadInput {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.name())
+    }
+}
+
+impl<'de> Deserialize<'de> for GamepadInput {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        Self::from_name(&name)
+            .ok_or_else(|| D::Error::custom(format!("unknown gamepad input \"{name}\"")))
+    }
+}
+
+fn default_fire1_binding() -> Vec<GamepadInput> {
+    vec![
+        GamepadInput::Button(SDL_GAMEPAD_BUTTON_EAST),
+        GamepadInput::Trigger(SDL_GAMEPAD_AXIS_RIGHT_TRIGGER),
+    ]
+}
+
+fn default_fire2_binding() -> Vec<GamepadInput> {
+    vec![
+        GamepadInput::Button(SDL_GAMEPAD_BUTTON_SOUTH),
+        GamepadInput::Trigger(SDL_GAMEPAD_AXIS_LEFT_TRIGGER),
+    ]
+}
+
+fn default_fire3_binding() -> Vec<GamepadInput> {
+    vec![GamepadInput::Button(SDL_GAMEPAD_BUTTON_WEST)]
+}
+
+fn default_jump_binding() -> Vec<GamepadInput> {
+    vec![GamepadInput::Button(SDL_GAMEPAD_BUTTON_NORTH)]
+}
+
+fn default_eject_binding() -> Vec<GamepadInput> {
+    vec![
+        GamepadInput::Button(SDL_GAMEPAD_BUTTON_LEFT_SHOULDER),
+        GamepadInput::Button(SDL_GAMEPAD_BUTTON_RIGHT_SHOULDER),
+    ]
+}
+
+fn default_stick_deadzone() -> f32 {
+    0.25
+}
+
+fn default_trigger_threshold() -> f32 {
+    0.25
+}
+
+/**
+ * Input mapping for a gamepad.
+ *
+ * Profiles are stored per device type (SDL GUID) in the settings file.
+ * An action is active when any of its bound inputs is pressed, except for
+ * eject, which requires all its inputs to be held at once so it can't be
+ * triggered by accident.
+ *
+ * Menu navigation (D-pad, east button, start and back) is not remappable.
+ */
+#[derive(Serialize, Deserialize, Clone)]
+pub struct GamepadProfile {
+    #[serde(default = "default_fire1_binding")]
+    pub fire1: Vec<GamepadInput>,
+    #[serde(default = "default_fire2_binding")]
+    pub fire2: Vec<GamepadInput>,
+    #[serde(default = "default_fire3_binding")]
+    pub fire3: Vec<GamepadInput>,
+    #[serde(default = "default_jump_binding")]
+    pub jump: Vec<GamepadInput>,
+    #[serde(default = "default_eject_binding")]
+    pub eject: Vec<GamepadInput>,
+
+    /// Analog stick deadzone as a fraction of the full axis range
+    #[serde(default = "default_stick_deadzone")]
+    pub stick_deadzone: f32,
+
+    /// How far a trigger must be pressed to count as a button press
+    #[serde(default = "default_trigger_threshold")]
+    pub trigger_threshold: f32,
+
+    #[serde(default)]
+    pub invert_thrust: bool,
+    #[serde(default)]
+    pub invert_turn: bool,
+    #[serde(default)]
+    pub invert_aim: bool,
+
+    /// Swap left and right sticks (for left handed play)
+    #[serde(default)]
+    pub swap_sticks: bool,
+}
+
+impl Default for GamepadProfile {
+    fn default() -> Self {
+        Self {
+            fire1: default_fire1_binding(),
+            fire2: default_fire2_binding(),
+            fire3: default_fire3_binding(),
+            jump: default_jump_binding(),
+            eject: default_eject_binding(),
+            stick_deadzone: default_stick_deadzone(),
+            trigger_threshold: default_trigger_threshold(),
+            invert_thrust: false,
+            invert_turn: false,
+            invert_aim: false,
+            swap_sticks: false,
+        }
+    }
+}
+
+/// Information about a connected gamepad for the settings menu
+#[derive(Serialize)]
+pub struct GamepadInfo {
+    pub controller: i32,
+    pub guid: String,
+    pub name: String,
+}
+
+/// Get the string form of a gamepad GUID (used as the profile key)
+pub fn guid_to_string(guid: SDL_GUID) -> String {
+    let mut buf = [0 as c_char; 33];
+    unsafe {
+        SDL_GUIDToString(guid, buf.as_mut_ptr(), buf.len() as _);
+        CStr::from_ptr(buf.as_ptr()).to_string_lossy().into_owned()
+    }
+}
+
 /**
  * Button events for controlling menus.
  *
@@ -94,7 +346,17 @@ pub enum MenuButton {
     Back,
     Debug,
     Screenshot,
+    /// Toggle frame capture mode
+    Capture,
+    Console,
     GrabbedKey(SDL_Keycode),
+
+    /// Key press in text input mode
+    TextEdit(SDL_Keycode),
+
+    /// Gamepad buttons and triggers held down together in pad grab mode.
+    /// Empty if pad grab mode was cancelled.
+    GrabbedPadInput(GrabbedInputs),
 }
 
 impl MenuButton {
@@ -116,6 +378,10 @@ impl MenuButton {
             Self::Debug => (8, null_mut()),
             Self::Screenshot => (9, null_mut()),
             Self::GrabbedKey(k) => (10, ptr::without_provenance_mut(k.0 as usize)),
+            Self::Console => (11, null_mut()),
+            Self::TextEdit(k) => (12, ptr::without_provenance_mut(k.0 as usize)),
+            Self::GrabbedPadInput(i) => (13, ptr::without_provenance_mut(i.to_code())),
+            Self::Capture => (14, null_mut()),
         }
     }
 
@@ -131,6 +397,10 @@ impl MenuButton {
             8 => Self::Debug,
             9 => Self::Screenshot,
             10 => Self::GrabbedKey(SDL_Keycode(data1 as u32)),
+            11 => Self::Console,
+            12 => Self::TextEdit(SDL_Keycode(data1 as u32)),
+            13 => Self::GrabbedPadInput(GrabbedInputs::from_code(data1 as usize)),
+            14 => Self::Capture,
             _ => Self::None,
         }
     }
@@ -150,10 +420,41 @@ impl GameController {
             guid: SDL_GUID { data: [0; 16] },
             joystick_id: SDL_JoystickID(0),
             gamepad: null_mut(),
+            profile: GamepadProfile::default(),
         }
     }
 }
 
+impl GameController {
+    fn is_input_pressed(&self, input: GamepadInput) -> bool {
+        match input {
+            GamepadInput::Button(b) => unsafe { SDL_GetGamepadButton(self.gamepad, b) },
+            GamepadInput::Trigger(a) => {
+                GameControllerSet::axis_value(unsafe { SDL_GetGamepadAxis(self.gamepad, a) }, 0.0)
+                    >= self.profile.trigger_threshold
+            }
+        }
+    }
+
+    /// Update the button actions from the current gamepad state
+    fn update_actions(&mut self) {
+        let any = |inputs: &[GamepadInput]| inputs.iter().any(|&i| self.is_input_pressed(i));
+
+        let fire1 = any(&self.profile.fire1);
+        let fire2 = any(&self.profile.fire2);
+        let fire3 = any(&self.profile.fire3);
+        let jump = any(&self.profile.jump);
+        let eject = !self.profile.eject.is_empty()
+            && self.profile.eject.iter().all(|&i| self.is_input_pressed(i));
+
+        self.fire1 = fire1;
+        self.fire2 = fire2;
+        self.fire3 = fire3;
+        self.jump = jump;
+        self.eject = eject;
+    }
+}
+
 #[derive(PartialEq, Debug)]
 pub enum MappedKey {
     Up,
@@ -173,9 +474,25 @@ pub struct GameControllerSet {
     /// so the indices will remain stable.
     pub states: Vec<GameController>,
 
-    /// In key grab mode, the next button press will emit a GrabbedKey menu button event
+    /// In key grab mode, the next key press will emit a GrabbedKey menu button event
     key_grabbing: bool,
 
+    /// In pad grab mode, the gamepad inputs held down together are collected
+    /// and emitted as a GrabbedPadInput menu button event when one is released
+    pad_grabbing: bool,
+
+    /// The gamepad whose inputs are being grabbed and the inputs grabbed so far
+    pad_grab_source: Option<SDL_JoystickID>,
+    grabbed_inputs: GrabbedInputs,
+
+    /// In text input mode, key presses are emitted as TextEdit menu button events
+    /// instead of being mapped to player controls
+    text_input: bool,
+
+    /// The console toggle key is being held down.
+    /// The key is matched by position (under Esc), since its symbol depends on the keyboard layout.
+    console_key_down: bool,
+
     keymap: HashMap<u32, (MappedKey, usize)>,
 }
 
@@ -185,6 +502,11 @@ impl GameControllerSet {
             states: vec![GameController::new(); KEYBOARDS],
             keymap: HashMap::new(),
             key_grabbing: false,
+            pad_grabbing: false,
+            pad_grab_source: None,
+            grabbed_inputs: GrabbedInputs::default(),
+            text_input: false,
+            console_key_down: false,
         }
     }
 
@@ -196,6 +518,8 @@ impl GameControllerSet {
             return;
         }
 
+        let profile = Self::find_profile(guid);
+
         let ctrl = self
             .states
             .iter_mut()
@@ -216,12 +540,14 @@ impl GameControllerSet {
             );
             ctrl.joystick_id = id;
             ctrl.gamepad = gamepad;
+            ctrl.profile = profile;
         } else {
             // Add a new controller otherwise.
             self.states.push(GameController {
                 guid,
                 joystick_id: id,
                 gamepad,
+                profile,
                 ..GameController::new()
             });
             log::info!(
@@ -251,6 +577,41 @@ impl GameControllerSet {
         }
     }
 
+    fn find_profile(guid: SDL_GUID) -> GamepadProfile {
+        GAME_CONFIG
+            .read()
+            .unwrap()
+            .gamepad
+            .profiles
+            .get(&guid_to_string(guid))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Get a list of currently connected gamepads
+    pub fn gamepads(&self) -> Vec<GamepadInfo> {
+        self.states
+            .iter()
+            .enumerate()
+            .skip(KEYBOARDS)
+            .filter(|(_, c)| !c.gamepad.is_null())
+            .map(|(idx, c)| {
+                let name = unsafe { SDL_GetGamepadNameForID(c.joystick_id) };
+                GamepadInfo {
+                    controller: idx as i32 + 1,
+                    guid: guid_to_string(c.guid),
+                    name: if name.is_null() {
+                        tr_fmt("Gamepad {1}", &[&(idx + 1 - KEYBOARDS)])
+                    } else {
+                        unsafe { CStr::from_ptr(name) }
+                            .to_string_lossy()
+                            .into_owned()
+                    },
+                }
+            })
+            .collect()
+    }
+
     pub fn get_gamepad_type(&self, controller: i32) -> SDL_GamepadType {
         let ctrl = &self.states[controller as usize - 1];
         if ctrl.gamepad.is_null() {
@@ -265,26 +626,72 @@ impl GameControllerSet {
         log::debug!("Started keygrab mode");
     }
 
+    pub fn start_padgrab(&mut self) {
+        self.pad_grabbing = true;
+        self.pad_grab_source = None;
+        self.grabbed_inputs = GrabbedInputs::default();
+        log::debug!("Started padgrab mode");
+    }
+
+    /// Collect a gamepad input pressed or released in pad grab mode.
+    /// The combination is complete when one of the held inputs is released.
+    fn grab_pad_input(&mut self, id: SDL_JoystickID, input: GamepadInput, down: bool) {
+        if self.pad_grab_source.is_some_and(|source| source != id) {
+            return;
+        }
+
+        if down {
+            self.pad_grab_source = Some(id);
+            self.grabbed_inputs.push(input);
+        } else if self.grabbed_inputs.contains(input) {
+            self.finish_padgrab();
+        }
+    }
+
+    fn finish_padgrab(&mut self) {
+        self.pad_grabbing = false;
+        self.pad_grab_source = None;
+        push_menu_button_event(MenuButton::GrabbedPadInput(self.grabbed_inputs));
+    }
+
+    pub fn set_text_input(&mut self, enabled: bool) {
+        self.text_input = enabled;
+
+        // Release all keyboard controls so no keys are left stuck
+        for state in self.states.iter_mut().take(KEYBOARDS) {
+            *state = GameController::new();
+        }
+    }
+
+    /// Is the console toggle key held down? (Text typed by it should be ignored)
+    pub fn console_key_down(&self) -> bool {
+        self.console_key_down
+    }
+
     pub fn reload_keymaps(&mut self) {
         let config = GAME_CONFIG.read().unwrap();
 
+        for ctrl in self.states.iter_mut().skip(KEYBOARDS) {
+            ctrl.profile = config
+                .gamepad
+                .profiles
+                .get(&guid_to_string(ctrl.guid))
+                .cloned()
+                .unwrap_or_default();
+        }
+
         self.keymap = HashMap::new();
-        self.set_keymap(
-            0,
-            config.keymap1.as_ref().unwrap_or(&Self::DEFAULT_KEYMAP[0]),
-        );
-        self.set_keymap(
-            1,
-            config.keymap2.as_ref().unwrap_or(&Self::DEFAULT_KEYMAP[1]),
-        );
-        self.set_keymap(
-            2,
-            config.keymap3.as_ref().unwrap_or(&Self::DEFAULT_KEYMAP[2]),
-        );
-        self.set_keymap(
-            3,
-            config.keymap4.as_ref().unwrap_or(&Self::DEFAULT_KEYMAP[3]),
-        );
+
+        // Keyboards without a configured keymap use the default one (if there is one)
+        for idx in 0..KEYBOARDS {
+            if let Some(keymap) = config
+                .keymaps
+                .get(idx)
+                .or_else(|| Self::DEFAULT_KEYMAP.get(idx))
+            {
+                self.set_keymap(idx, keymap);
+            }
+        }
     }
 
     pub fn get_keymap(&self, controller: usize) -> PlayerKeymap {
@@ -311,20 +718,20 @@ impl GameControllerSet {
     }
 
     fn set_keymap(&mut self, controller: usize, keymap: &PlayerKeymap) {
-        self.keymap
-            .insert(keymap.thrust, (MappedKey::Up, controller));
-        self.keymap
-            .insert(keymap.down, (MappedKey::Down, controller));
-        self.keymap
-            .insert(keymap.left, (MappedKey::Left, controller));
-        self.keymap
-            .insert(keymap.right, (MappedKey::Right, controller));
-        self.keymap
-            .insert(keymap.fire1, (MappedKey::Fire1, controller));
-        self.keymap
-            .insert(keymap.fire2, (MappedKey::Fire2, controller));
-        self.keymap
-            .insert(keymap.fire3, (MappedKey::Fire3, controller));
+        for (key, mapping) in [
+            (keymap.thrust, MappedKey::Up),
+            (keymap.down, MappedKey::Down),
+            (keymap.left, MappedKey::Left),
+            (keymap.right, MappedKey::Right),
+            (keymap.fire1, MappedKey::Fire1),
+            (keymap.fire2, MappedKey::Fire2),
+            (keymap.fire3, MappedKey::Fire3),
+        ] {
+            // Zero means the key is unbound
+            if key != 0 {
+                self.keymap.insert(key, (mapping, controller));
+            }
+        }
     }
 
     pub fn handle_sdl_key_event(&mut self, key: &SDL_KeyboardEvent) {
@@ -335,6 +742,33 @@ impl GameControllerSet {
             return;
         }
 
+        if self.pad_grabbing && !key.down && key.key == SDLK_ESCAPE {
+            // Cancel pad grab mode
+            self.grabbed_inputs = GrabbedInputs::default();
+            self.finish_padgrab();
+            return;
+        }
+
+        let is_console_key = key.scancode == SDL_SCANCODE_GRAVE;
+        if is_console_key {
+            self.console_key_down = key.down;
+        }
+
+        if self.text_input {
+            // The console toggle key is handled on release, like the other global keys,
+            // so that the release event doesn't reopen the console after it was closed.
+            if key.down {
+                if !is_console_key {
+                    push_menu_button_event(MenuButton::TextEdit(key.key));
+                }
+            } else if is_console_key {
+                push_menu_button_event(MenuButton::Console);
+            } else if key.key == SDLK_F12 {
+                push_menu_button_event(MenuButton::Screenshot);
+            }
+            return;
+        }
+
         // Player key mappings
         let mut menubtn = MenuButton::None;
 
@@ -384,7 +818,9 @@ impl GameControllerSet {
         }
 
         // Global "menu" keys (and key grabbing, which is also a menu thing)
-        if !key.down && menubtn.is_none() {
+        if !key.down && menubtn.is_none() && is_console_key {
+            menubtn = MenuButton::Console;
+        } else if !key.down && menubtn.is_none() {
             menubtn = match key.key {
                 SDLK_UP => MenuButton::Up(0),
                 SDLK_DOWN => MenuButton::Down(0),
@@ -392,6 +828,7 @@ impl GameControllerSet {
                 SDLK_RIGHT => MenuButton::Right(0),
                 SDLK_RETURN => MenuButton::Start,
                 SDLK_ESCAPE => MenuButton::Back,
+                SDLK_F10 => MenuButton::Capture,
                 SDLK_F11 => MenuButton::Debug,
                 SDLK_F12 => MenuButton::Screenshot,
                 _ => MenuButton::None,
@@ -417,39 +854,51 @@ impl GameControllerSet {
             }
         };
 
-        let value = Self::axis_value(value);
+        let profile = &state.profile;
+
+        if axis == SDL_GAMEPAD_AXIS_LEFT_TRIGGER || axis == SDL_GAMEPAD_AXIS_RIGHT_TRIGGER {
+            let down = Self::axis_value(value, 0.0) >= profile.trigger_threshold;
+            state.update_actions();
+            if self.pad_grabbing {
+                self.grab_pad_input(id, GamepadInput::Trigger(axis), down);
+            }
+            return;
+        }
+
+        let value = Self::axis_value(value, profile.stick_deadzone);
+        let invert = |inverted: bool| if inverted { -1.0 } else { 1.0 };
+
+        // Left stick moves, right stick turns and aims, unless swapped
+        let axis = if profile.swap_sticks {
+            match axis {
+                SDL_GAMEPAD_AXIS_LEFTX => SDL_GAMEPAD_AXIS_RIGHTX,
+                SDL_GAMEPAD_AXIS_LEFTY => SDL_GAMEPAD_AXIS_RIGHTY,
+                SDL_GAMEPAD_AXIS_RIGHTX => SDL_GAMEPAD_AXIS_LEFTX,
+                SDL_GAMEPAD_AXIS_RIGHTY => SDL_GAMEPAD_AXIS_LEFTY,
+                a => a,
+            }
+        } else {
+            axis
+        };
 
         if axis == SDL_GAMEPAD_AXIS_RIGHTX {
             // Buff turning speed for gamepad users, since the thumb stick requires a bigger
             // motion compared to a key press
-            state.turn = value * -1.15;
+            state.turn = value * -1.15 * invert(profile.invert_turn);
         } else if axis == SDL_GAMEPAD_AXIS_RIGHTY {
             // No equivalent for this on keyboard.
             // Only aim-mode + thrust combo available there.
-            state.aim = -value;
+            state.aim = -value * invert(profile.invert_aim);
         } else if axis == SDL_GAMEPAD_AXIS_LEFTY {
-            state.thrust = -value;
+            state.thrust = -value * invert(profile.invert_thrust);
         } else if axis == SDL_GAMEPAD_AXIS_LEFTX {
             state.walk = -value;
-        } else if axis == SDL_GAMEPAD_AXIS_RIGHT_TRIGGER {
-            // primary fire: also east button
-            let firebtn = unsafe { SDL_GetGamepadButton(state.gamepad, SDL_GAMEPAD_BUTTON_EAST) };
-            state.fire1 = firebtn || value > 0.0;
-        } else if axis == SDL_GAMEPAD_AXIS_LEFT_TRIGGER {
-            // secondary fire: also south button
-            let firebtn = unsafe { SDL_GetGamepadButton(state.gamepad, SDL_GAMEPAD_BUTTON_SOUTH) };
-            state.fire2 = firebtn || value > 0.0;
         }
     }
 
-    fn axis_value(val: i16) -> f32 {
-        // TODO deadzone should be configurable
+    fn axis_value(val: i16, deadzone: f32) -> f32 {
         let value = val as f32 / SDL_JOYSTICK_AXIS_MAX as f32;
-        if value.abs() < 8000.0 / SDL_JOYSTICK_AXIS_MAX as f32 {
-            0.0
-        } else {
-            value
-        }
+        if value.abs() < deadzone { 0.0 } else { value }
     }
 
     pub fn handle_gamepad_button(
@@ -472,70 +921,29 @@ impl GameControllerSet {
             }
         };
 
-        let mut menubtn = MenuButton::None;
+        state.update_actions();
 
-        match button {
-            SDL_GAMEPAD_BUTTON_DPAD_UP => {
-                if down {
-                    menubtn = MenuButton::Up(ctrl_id);
-                }
-            }
-            SDL_GAMEPAD_BUTTON_DPAD_RIGHT => {
-                if down {
-                    menubtn = MenuButton::Right(ctrl_id);
-                }
-            }
-            SDL_GAMEPAD_BUTTON_DPAD_DOWN => {
-                if down {
-                    menubtn = MenuButton::Down(ctrl_id);
-                }
-            }
-            SDL_GAMEPAD_BUTTON_DPAD_LEFT => {
-                if down {
-                    menubtn = MenuButton::Left(ctrl_id);
-                }
-            }
-            SDL_GAMEPAD_BUTTON_EAST => {
-                if down {
-                    menubtn = MenuButton::Select(ctrl_id);
-                }
-                let axis = Self::axis_value(unsafe {
-                    SDL_GetGamepadAxis(state.gamepad, SDL_GAMEPAD_AXIS_RIGHT_TRIGGER)
-                });
-                state.fire1 = down || axis > 0.0;
-            }
-            SDL_GAMEPAD_BUTTON_SOUTH => {
-                state.fire2 = down;
-                let axis = Self::axis_value(unsafe {
-                    SDL_GetGamepadAxis(state.gamepad, SDL_GAMEPAD_AXIS_LEFT_TRIGGER)
-                });
-                state.fire2 = down || axis > 0.0;
-            }
-            SDL_GAMEPAD_BUTTON_WEST => {
-                state.fire3 = down;
-            }
-            SDL_GAMEPAD_BUTTON_NORTH => {
-                state.jump = down;
-            }
-            SDL_GAMEPAD_BUTTON_START => {
-                if down {
-                    menubtn = MenuButton::Start;
-                }
-            }
-            SDL_GAMEPAD_BUTTON_BACK => {
-                if down {
-                    menubtn = MenuButton::Back;
-                }
-            }
-            SDL_GAMEPAD_BUTTON_LEFT_SHOULDER | SDL_GAMEPAD_BUTTON_RIGHT_SHOULDER => {
-                state.eject = unsafe {
-                    SDL_GetGamepadButton(state.gamepad, SDL_GAMEPAD_BUTTON_LEFT_SHOULDER)
-                        & SDL_GetGamepadButton(state.gamepad, SDL_GAMEPAD_BUTTON_RIGHT_SHOULDER)
-                };
-            }
-            _ => {}
+        if self.pad_grabbing {
+            self.grab_pad_input(id, GamepadInput::Button(button), down);
+            return;
         }
 
+        // Menu navigation buttons are fixed
+        let menubtn = if down {
+            match button {
+                SDL_GAMEPAD_BUTTON_DPAD_UP => MenuButton::Up(ctrl_id),
+                SDL_GAMEPAD_BUTTON_DPAD_RIGHT => MenuButton::Right(ctrl_id),
+                SDL_GAMEPAD_BUTTON_DPAD_DOWN => MenuButton::Down(ctrl_id),
+                SDL_GAMEPAD_BUTTON_DPAD_LEFT => MenuButton::Left(ctrl_id),
+                SDL_GAMEPAD_BUTTON_EAST => MenuButton::Select(ctrl_id),
+                SDL_GAMEPAD_BUTTON_START => MenuButton::Start,
+                SDL_GAMEPAD_BUTTON_BACK => MenuButton::Back,
+                _ => MenuButton::None,
+            }
+        } else {
+            MenuButton::None
+        };
+
         if !menubtn.is_none() {
             push_menu_button_event(menubtn);
         }
@@ -14,7 +14,11 @@
 // You should have received a copy of the GNU General Public License
 // along with Luola2.  If not, see <https://www.gnu.org/licenses/>.
 
+use sdl3_sys::rect::SDL_FPoint;
+use smallvec::SmallVec;
+
 use crate::{
+    game::{PlayerId, objects::MAX_SECONDARY_WEAPONS},
     gfx::{
         Color, RenderDest, RenderMode, RenderOptions, RenderTextDest, RenderTextOptions, Renderer,
         Text, TextOutline, Texture, TextureId,
@@ -29,8 +33,15 @@ pub enum PlayerHud {
         health: f32,
         ammo: f32,
         cooling_down: bool,
+        fuel: Option<f32>,
+        heat: Option<f32>,
+        overheated: bool,
+        weapon_icons: [Option<TextureId>; MAX_SECONDARY_WEAPONS],
+        weapon_count: usize,
+        selected_weapon: usize,
     },
     Pilot {
+        health: f32,
         jetpack: f32,
         target: Option<Vec2>,
     },
@@ -206,26 +217,102 @@ impl HudOverlay {
     }
 }
 
-pub fn draw_hud(renderer: &Renderer, hud: PlayerHud, overlays: &[HudOverlay], camera_pos: Vec2) {
+pub fn draw_hud(
+    renderer: &Renderer,
+    hud: PlayerHud,
+    status: Option<&Text>,
+    overlays: &[HudOverlay],
+    camera_pos: Vec2,
+) {
     match hud {
         PlayerHud::Ship {
             health,
             ammo,
             cooling_down,
-            ..
-        } => draw_ship_hud(renderer, health, ammo, cooling_down),
-        PlayerHud::Pilot { jetpack, target } => {
-            draw_pilot_hud(renderer, jetpack, target.map(|t| t - camera_pos))
+            fuel,
+            heat,
+            overheated,
+            weapon_icons,
+            weapon_count,
+            selected_weapon,
+        } => {
+            let bars = draw_ship_hud(renderer, health, ammo, cooling_down, fuel, heat, overheated);
+            draw_weapon_icons(
+                renderer,
+                bars,
+                &weapon_icons[..weapon_count],
+                selected_weapon,
+            );
         }
+        PlayerHud::Pilot {
+            health,
+            jetpack,
+            target,
+        } => draw_pilot_hud(renderer, health, jetpack, target.map(|t| t - camera_pos)),
         PlayerHud::None => {}
     }
 
+    if let Some(status) = status {
+        let margin = 10.0 * renderer.ui_scale();
+        status.render(&RenderTextOptions {
+            dest: RenderTextDest::TopLeft(Vec2(margin, margin)),
+            outline: TextOutline::Outline,
+            ..Default::default()
+        });
+    }
+
     for overlay in overlays {
         overlay.draw(renderer);
     }
 }
 
-pub fn draw_minimap(renderer: &Renderer, minimap: &Texture, pointers: &[(Color, Vec2)]) {
+/// Minimap marker shapes (7x7 bitmaps) used to tell players apart without relying on color
+const MINIMAP_MARKER_SHAPES: [[u8; 7]; 8] = [
+    // circle
+    [
+        0b0011100, 0b0111110, 0b1111111, 0b1111111, 0b1111111, 0b0111110, 0b0011100,
+    ],
+    // square
+    [
+        0b0000000, 0b0111110, 0b0111110, 0b0111110, 0b0111110, 0b0111110, 0b0000000,
+    ],
+    // triangle
+    [
+        0b0001000, 0b0001000, 0b0011100, 0b0011100, 0b0111110, 0b0111110, 0b1111111,
+    ],
+    // diamond
+    [
+        0b0001000, 0b0011100, 0b0111110, 0b1111111, 0b0111110, 0b0011100, 0b0001000,
+    ],
+    // plus
+    [
+        0b0011100, 0b0011100, 0b1111111, 0b1111111, 0b1111111, 0b0011100, 0b0011100,
+    ],
+    // cross
+    [
+        0b1100011, 0b1110111, 0b0111110, 0b0011100, 0b0111110, 0b1110111, 0b1100011,
+    ],
+    // upside down triangle
+    [
+        0b1111111, 0b0111110, 0b0111110, 0b0011100, 0b0011100, 0b0001000, 0b0001000,
+    ],
+    // ring
+    [
+        0b1111111, 0b1111111, 0b1100011, 0b1100011, 0b1100011, 0b1111111, 0b1111111,
+    ],
+];
+
+/**
+ * Draw the minimap and player position markers.
+ *
+ * If `shapes` is set, each player gets a distinctly shaped marker.
+ */
+pub fn draw_minimap(
+    renderer: &Renderer,
+    minimap: &Texture,
+    pointers: &[(PlayerId, Vec2)],
+    shapes: bool,
+) {
     let w = minimap.width();
     let h = minimap.height();
     let x = renderer.width() as f32 - 10.0 - w;
@@ -245,22 +332,63 @@ pub fn draw_minimap(renderer: &Renderer, minimap: &Texture, pointers: &[(Color,
             .find_texture(b"minimap_pointer")
             .expect("minimap_pointer texture should exist"),
     );
-    for (color, pointer) in pointers {
-        tex.render(
-            renderer,
-            &RenderOptions {
-                dest: RenderDest::Centered(Vec2(
-                    x + (pointer.0 * w).round(),
-                    y + (pointer.1 * h).round(),
-                )),
-                color: *color,
-                ..Default::default()
-            },
-        );
+    for &(player, pointer) in pointers {
+        let center = Vec2(x + (pointer.0 * w).round(), y + (pointer.1 * h).round());
+        let color = Color::player_color(player);
+
+        if shapes && player > 0 {
+            let shape = &MINIMAP_MARKER_SHAPES[(player as usize - 1) % MINIMAP_MARKER_SHAPES.len()];
+            let points: SmallVec<[SDL_FPoint; 64]> = shape
+                .iter()
+                .enumerate()
+                .flat_map(|(row, bits)| {
+                    (0..7)
+                        .filter(move |col| bits & (0b1000000 >> col) != 0)
+                        .map(move |col| SDL_FPoint {
+                            x: center.0 + col as f32 - 3.0,
+                            y: center.1 + row as f32 - 3.0,
+                        })
+                })
+                .collect();
+            renderer.draw_points(&points, &color);
+        } else {
+            tex.render(
+                renderer,
+                &RenderOptions {
+                    dest: RenderDest::Centered(center),
+                    color,
+                    ..Default::default()
+                },
+            );
+        }
     }
 }
 
-fn draw_ship_hud(renderer: &Renderer, health: f32, ammo: f32, cooling_down: bool) {
+/**
+ * Draw a player number badge above a ship or pilot.
+ *
+ * The badge texts are created once per round and indexed by player ID - 1.
+ */
+pub fn draw_player_badge(badges: &[Text], player: PlayerId, pos: Vec2) {
+    if let Some(badge) = badges.get((player - 1) as usize) {
+        badge.render(&RenderTextOptions {
+            dest: RenderTextDest::BottomCenter(pos),
+            color: Some(Color::player_color(player)),
+            outline: TextOutline::Shadow,
+            ..Default::default()
+        });
+    }
+}
+
+fn draw_ship_hud(
+    renderer: &Renderer,
+    health: f32,
+    ammo: f32,
+    cooling_down: bool,
+    fuel: Option<f32>,
+    heat: Option<f32>,
+    overheated: bool,
+) -> RectF {
     let barfill = renderer.texture_store().get_texture(
         renderer
             .texture_store()
@@ -275,9 +403,11 @@ fn draw_ship_hud(renderer: &Renderer, health: f32, ammo: f32, cooling_down: bool
     );
 
     let w = ((renderer.width() - 20) as f32 * 0.61).ceil();
-    let h = barbg.height();
-    let x = 10.0;
-    let y = renderer.height() as f32 - (h * 2.0) - 10.0;
+    let scale = renderer.ui_scale();
+    let h = (barbg.height() * scale).round();
+    let x = 10.0 * scale;
+    let rows = 2 + fuel.is_some() as i32 + heat.is_some() as i32;
+    let y = renderer.height() as f32 - (h * rows as f32) - 10.0 * scale;
 
     let mut opts = RenderOptions {
         dest: RenderDest::Rect(RectF::new(x, y, w, h)),
@@ -315,9 +445,108 @@ fn draw_ship_hud(renderer: &Renderer, health: f32, ammo: f32, cooling_down: bool
         };
         barfill.render(renderer, &opts);
     }
+
+    let mut y = y + h * 2.0;
+
+    // Fuel gauge
+    if let Some(fuel) = fuel {
+        opts.dest = RenderDest::Rect(RectF::new(x, y, w, h));
+        opts.color = Color::WHITE;
+        barbg.render(renderer, &opts);
+
+        if fuel > 0.0 {
+            opts.dest = RenderDest::Rect(RectF::new(x, y, w * fuel, h));
+            opts.color = if fuel > 0.2 {
+                Color::new(0.2, 0.65, 0.3)
+            } else {
+                Color::new(0.78, 0.0, 0.0)
+            };
+            barfill.render(renderer, &opts);
+        }
+        y += h;
+    }
+
+    // Heat gauge
+    if let Some(heat) = heat {
+        opts.dest = RenderDest::Rect(RectF::new(x, y, w, h));
+        opts.color = Color::WHITE;
+        barbg.render(renderer, &opts);
+
+        if heat > 0.0 {
+            opts.dest = RenderDest::Rect(RectF::new(x, y, w * heat, h));
+            opts.color = if overheated {
+                Color::new(0.9, 0.1, 0.1)
+            } else {
+                Color::new(0.9, 0.5 - heat * 0.3, 0.1)
+            };
+            barfill.render(renderer, &opts);
+        }
+    }
+
+    RectF::new(
+        x,
+        renderer.height() as f32 - 10.0 * scale - h * rows as f32,
+        w,
+        h * rows as f32,
+    )
+}
+
+/**
+ * Draw the icons of the special weapons next to the status bars.
+ *
+ * The selected weapon is highlighted. Nothing is drawn if the ship has only one weapon
+ * and it has no icon.
+ */
+fn draw_weapon_icons(
+    renderer: &Renderer,
+    bars: RectF,
+    icons: &[Option<TextureId>],
+    selected: usize,
+) {
+    if icons.len() < 2 && icons.iter().all(|i| i.is_none()) {
+        return;
+    }
+
+    let size = bars.h().max(24.0 * renderer.ui_scale());
+    let spacing = 4.0 * renderer.ui_scale();
+    let mut x = bars.right() + spacing * 2.0;
+    let y = bars.bottom() - size;
+
+    for (idx, icon) in icons.iter().enumerate() {
+        let rect = RectF::new(x, y, size, size);
+        let alpha = if idx == selected { 1.0 } else { 0.4 };
+
+        let background = if idx == selected {
+            Color::new_rgba(0.31, 0.38, 0.72, 0.8)
+        } else {
+            Color::new_rgba(0.1, 0.1, 0.2, 0.5)
+        };
+        renderer.draw_filled_rectangle(rect, &background);
+
+        if let Some(icon) = icon {
+            let tex = renderer.texture_store().get_texture(*icon);
+            let scale = (size / tex.width()).min(size / tex.height()).min(1.0);
+            let (w, h) = (tex.width() * scale, tex.height() * scale);
+            tex.render(
+                renderer,
+                &RenderOptions {
+                    dest: RenderDest::Rect(RectF::new(
+                        rect.x() + (size - w) / 2.0,
+                        rect.y() + (size - h) / 2.0,
+                        w,
+                        h,
+                    )),
+                    color: Color::WHITE.with_alpha(alpha),
+                    ..Default::default()
+                },
+            );
+        }
+
+        x += size + spacing;
+    }
 }
 
-fn draw_pilot_hud(renderer: &Renderer, jetpack: f32, target: Option<Vec2>) {
+fn draw_pilot_hud(renderer: &Renderer, health: f32, jetpack: f32, target: Option<Vec2>) {
     let barfill = renderer.texture_store().get_texture(
         renderer
             .texture_store()
@@ -332,9 +561,10 @@ fn draw_pilot_hud(renderer: &Renderer, jetpack: f32, target: Option<Vec2>) {
     );
 
     let w = ((renderer.width() - 20) as f32 * 0.1).ceil();
-    let h = barbg.height();
-    let x = 10.0;
-    let y = renderer.height() as f32 - (h * 2.0) - 10.0;
+    let scale = renderer.ui_scale();
+    let h = (barbg.height() * scale).round();
+    let x = 10.0 * scale;
+    let y = renderer.height() as f32 - (h * 2.0) - 10.0 * scale;
 
     let mut opts = RenderOptions {
         dest: RenderDest::Rect(RectF::new(x, y, w, h)),
@@ -342,11 +572,28 @@ fn draw_pilot_hud(renderer: &Renderer, jetpack: f32, target: Option<Vec2>) {
         ..Default::default()
     };
 
+    // Health bar
+    barbg.render(renderer, &opts);
+
+    if health > 0.0 {
+        opts.dest = RenderDest::Rect(RectF::new(x, y, w * health, h));
+        opts.color = if health > 0.7 {
+            Color::new(0.31, 0.38, 0.72)
+        } else if health > 0.4 {
+            Color::new(0.78, 0.78, 0.0)
+        } else {
+            Color::new(0.78, 0.0, 0.0)
+        };
+        barfill.render(renderer, &opts);
+    }
+
     // Jetpack charge bar
+    opts.dest = RenderDest::Rect(RectF::new(x, y + h, w, h));
+    opts.color = Color::WHITE;
     barbg.render(renderer, &opts);
 
     if jetpack > 0.0 {
-        opts.dest = RenderDest::Rect(RectF::new(x, y, w * jetpack, h));
+        opts.dest = RenderDest::Rect(RectF::new(x, y + h, w * jetpack, h));
         barfill.render(renderer, &opts);
     }
 
@@ -361,7 +608,7 @@ fn draw_pilot_hud(renderer: &Renderer, jetpack: f32, target: Option<Vec2>) {
         tex.render(
             renderer,
             &RenderOptions {
-                dest: RenderDest::CenterScaled(t, 2.0),
+                dest: RenderDest::CenterScaled(t, 2.0 * renderer.ui_scale()),
                 ..Default::default()
             },
         );
@@ -0,0 +1,264 @@
+// This file is part of Luola2
+// Copyright (C) 2025 Calle Laakkonen
+//
+// Luola2 is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Luola2 is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Luola2.  If not, see <https://www.gnu.org/licenses/>.
+
+use anyhow::Result;
+
+use super::{LEVEL_SCALE, LevelInfo, wrap};
+use crate::{
+    game::objects::GameObject,
+    gfx::{AnimatedTexture, Color, Light, RenderDest, RenderOptions, Renderer},
+    math::{RectF, Vec2},
+};
+
+const ENTRY_COLOR: u32 = 0xff5fcde4;
+const EXIT_COLOR: u32 = 0xfff0a422;
+const GATE_LIGHT: Light = Light::new(100.0, 0xc05fcde4);
+
+/**
+ * A game object that can pass through jump gates
+ */
+pub trait Jumper: GameObject {
+    fn vel(&self) -> Vec2;
+
+    /// Move the object to its new position.
+    /// Rotation is the change in direction of travel (in degrees, same direction as Vec2::for_angle)
+    fn jump(&mut self, pos: Vec2, vel: Vec2, rotation: f32);
+}
+
+struct JumpGate {
+    a: Vec2,
+    b: Vec2,
+    radius: f32,
+    rotation: f32,
+    one_way: bool,
+    cooldown: f32,
+
+    /// Time until the gates are usable again
+    cooldown_left: f32,
+}
+
+impl JumpGate {
+    /// Check if the object is entering the gate at the given position.
+    ///
+    /// Only objects moving towards the center of the gate are taken.
+    /// They come out of the opposite side of the other gate moving away from its center,
+    /// so they won't immediately bounce back.
+    fn entering(&self, gate: Vec2, pos: Vec2, vel: Vec2) -> bool {
+        let offset = wrap::wrapped_delta(gate, pos);
+        offset.magnitude_squared() < self.radius * self.radius && offset.dot(vel) < 0.0
+    }
+
+    /// Get the new position, velocity and rotation of an object entering either gate
+    fn destination(&self, pos: Vec2, vel: Vec2) -> Option<(Vec2, Vec2, f32)> {
+        let (from, to, rotation) = if self.entering(self.a, pos, vel) {
+            (self.a, self.b, self.rotation)
+        } else if !self.one_way && self.entering(self.b, pos, vel) {
+            (self.b, self.a, -self.rotation)
+        } else {
+            return None;
+        };
+
+        Some((
+            to - wrap::wrapped_delta(from, pos).rotated(rotation),
+            vel.rotated(rotation),
+            rotation,
+        ))
+    }
+}
+
+/**
+ * Permanent jump gate pairs defined in the level file.
+ *
+ * An object passing through a gate keeps its speed. Its position relative
+ * to the gate, direction of travel and orientation are rotated by the pair's
+ * rotation angle.
+ */
+pub struct JumpGates {
+    gates: Vec<JumpGate>,
+    texture: AnimatedTexture,
+}
+
+impl JumpGates {
+    /// Create the level's jump gates. Returns None if the level has none.
+    pub fn load(renderer: &Renderer, info: &LevelInfo) -> Result<Option<Self>> {
+        if info.jumpgates().is_empty() {
+            return Ok(None);
+        }
+
+        let gates = info
+            .jumpgates()
+            .iter()
+            .map(|g| JumpGate {
+                a: Vec2(g.a.0 as f32, g.a.1 as f32) * LEVEL_SCALE,
+                b: Vec2(g.b.0 as f32, g.b.1 as f32) * LEVEL_SCALE,
+                radius: g.radius.max(1) as f32 * LEVEL_SCALE,
+                rotation: g.rotation,
+                one_way: g.one_way,
+                cooldown: g.cooldown.max(0.0),
+                cooldown_left: 0.0,
+            })
+            .collect();
+
+        Ok(Some(Self {
+            gates,
+            texture: AnimatedTexture::new(renderer.texture_store().find_texture(b"portal")?),
+        }))
+    }
+
+    pub fn step(&mut self, timestep: f32) {
+        for gate in self.gates.iter_mut() {
+            gate.cooldown_left = (gate.cooldown_left - timestep).max(0.0);
+        }
+        self.texture.step(timestep);
+    }
+
+    /// Move the object through a gate if it is entering one.
+    /// Returns true if the object jumped.
+    pub fn pass_through<T: Jumper>(&mut self, obj: &mut T) -> bool {
+        let pos = obj.pos();
+        let vel = obj.vel();
+
+        for gate in self.gates.iter_mut() {
+            if gate.cooldown_left > 0.0 {
+                continue;
+            }
+
+            if let Some((pos, vel, rotation)) = gate.destination(pos, vel) {
+                obj.jump(pos, vel, rotation);
+                gate.cooldown_left = gate.cooldown;
+                return true;
+            }
+        }
+
+        false
+    }
+
+    pub fn lights(&self) -> impl Iterator<Item = (Vec2, Light)> {
+        self.gates
+            .iter()
+            .flat_map(|g| [(g.a, GATE_LIGHT), (g.b, GATE_LIGHT)])
+    }
+
+    pub fn render(&self, renderer: &Renderer, camera: RectF) {
+        let camera_pos = camera.topleft();
+        for gate in &self.gates {
+            // Gates fade in as the cooldown runs out
+            let alpha = if gate.cooldown > 0.0 {
+                1.0 - gate.cooldown_left / gate.cooldown * 0.8
+            } else {
+                1.0
+            };
+
+            for (pos, color) in [
+                (gate.a, ENTRY_COLOR),
+                (
+                    gate.b,
+                    if gate.one_way {
+                        EXIT_COLOR
+                    } else {
+                        ENTRY_COLOR
+                    },
+                ),
+            ] {
+                let rect = RectF::new(
+                    pos.0 - gate.radius,
+                    pos.1 - gate.radius,
+                    gate.radius * 2.0,
+                    gate.radius * 2.0,
+                );
+
+                if rect.right() < camera.x()
+                    || rect.x() > camera.right()
+                    || rect.bottom() < camera.y()
+                    || rect.y() > camera.bottom()
+                {
+                    continue;
+                }
+
+                self.texture.render(
+                    renderer,
+                    &RenderOptions {
+                        dest: RenderDest::Rect(rect.offset(-camera_pos.0, -camera_pos.1)),
+                        color: Color::from_argb_u32(color).with_alpha(alpha),
+                        ..Default::default()
+                    },
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gate(rotation: f32, one_way: bool) -> JumpGate {
+        JumpGate {
+            a: Vec2(100.0, 100.0),
+            b: Vec2(500.0, 100.0),
+            radius: 20.0,
+            rotation,
+            one_way,
+            cooldown: 0.0,
+            cooldown_left: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_jumpgate_destination() {
+        let g = gate(0.0, false);
+
+        // Entering gate A from the left comes out of the right side of gate B
+        let (pos, vel, _) = g.destination(Vec2(90.0, 100.0), Vec2(10.0, 0.0)).unwrap();
+        assert_eq!(pos, Vec2(510.0, 100.0));
+        assert_eq!(vel, Vec2(10.0, 0.0));
+
+        // ...and is then moving away from the gate, so it doesn't bounce back
+        assert!(g.destination(pos, vel).is_none());
+
+        // Objects moving away from a gate or outside of it are not taken
+        assert!(g.destination(Vec2(90.0, 100.0), Vec2(-10.0, 0.0)).is_none());
+        assert!(g.destination(Vec2(50.0, 100.0), Vec2(10.0, 0.0)).is_none());
+
+        // Two-way gate
+        let (pos, _, _) = g.destination(Vec2(510.0, 100.0), Vec2(-10.0, 0.0)).unwrap();
+        assert_eq!(pos, Vec2(90.0, 100.0));
+
+        // One-way gate
+        assert!(
+            gate(0.0, true)
+                .destination(Vec2(510.0, 100.0), Vec2(-10.0, 0.0))
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_jumpgate_rotation() {
+        let g = gate(90.0, false);
+
+        // Moving right into A: come out of B moving down
+        let (pos, vel, rotation) = g.destination(Vec2(90.0, 100.0), Vec2(10.0, 0.0)).unwrap();
+        assert_eq!(pos, Vec2(500.0, 110.0));
+        assert_eq!(vel, Vec2(0.0, 10.0));
+        assert_eq!(rotation, 90.0);
+
+        // And back again
+        let (pos, vel, rotation) = g.destination(Vec2(500.0, 90.0), Vec2(0.0, 10.0)).unwrap();
+        assert_eq!(pos, Vec2(110.0, 100.0));
+        assert_eq!(vel, Vec2(10.0, 0.0));
+        assert_eq!(rotation, -90.0);
+    }
+}
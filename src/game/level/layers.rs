@@ -0,0 +1,233 @@
+// This file is part of Luola2
+// Copyright (C) 2025 Calle Laakkonen
+//
+// Luola2 is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Luola2 is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Luola2.  If not, see <https://www.gnu.org/licenses/>.
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+use super::LevelInfo;
+use crate::{
+    gfx::{Color, RenderDest, RenderMode, RenderOptions, Renderer, Texture, TextureScaleMode},
+    math::{RectF, Vec2},
+};
+
+/// How a layer image is repeated
+#[derive(Deserialize, Clone, Copy, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum LayerTiling {
+    /// The image is drawn once
+    #[default]
+    None,
+    /// The image is repeated horizontally
+    Horizontal,
+    /// The image is repeated in both directions
+    Both,
+}
+
+/**
+ * A decorative image layer as given in the level file ([[layers]] array)
+ */
+#[derive(Deserialize, Clone, Debug)]
+pub struct LayerConfig {
+    /// Image file (relative to the level file)
+    pub image: String,
+
+    /// How fast the layer moves relative to the level.
+    /// 0.0 is fixed to the screen, 1.0 moves with the level.
+    #[serde(default = "default_parallax")]
+    pub parallax: f32,
+
+    #[serde(default)]
+    pub tile: LayerTiling,
+
+    /// Position of the image's top-left corner (in world coordinates at parallax factor 1.0)
+    #[serde(default)]
+    pub offset: (f32, f32),
+
+    /// Automatic scrolling speed in pixels per second
+    #[serde(default)]
+    pub scroll: (f32, f32),
+
+    /// Image scaling factor
+    #[serde(default = "default_scale")]
+    pub scale: f32,
+
+    /// Color modulation (ARGB)
+    #[serde(default = "default_color")]
+    pub color: u32,
+
+    /// Draw this layer over the game objects
+    #[serde(default)]
+    pub foreground: bool,
+}
+
+fn default_parallax() -> f32 {
+    0.5
+}
+
+fn default_scale() -> f32 {
+    1.0
+}
+
+fn default_color() -> u32 {
+    0xffffffff
+}
+
+struct ParallaxLayer {
+    texture: Texture,
+    parallax: f32,
+    tile: LayerTiling,
+    offset: Vec2,
+    scroll: Vec2,
+    scale: f32,
+    color: Color,
+
+    /// Accumulated auto-scroll offset
+    scrolled: Vec2,
+}
+
+impl ParallaxLayer {
+    fn step(&mut self, timestep: f32) {
+        if self.scroll.0 == 0.0 && self.scroll.1 == 0.0 {
+            return;
+        }
+
+        let w = self.texture.width() * self.scale;
+        let h = self.texture.height() * self.scale;
+        let s = self.scrolled + self.scroll * timestep;
+
+        // Tiled layers loop, so the offset can be kept small
+        self.scrolled = match self.tile {
+            LayerTiling::None => s,
+            LayerTiling::Horizontal => Vec2(s.0.rem_euclid(w), s.1),
+            LayerTiling::Both => Vec2(s.0.rem_euclid(w), s.1.rem_euclid(h)),
+        };
+    }
+
+    fn render(&self, renderer: &Renderer, camera: RectF) {
+        let w = self.texture.width() * self.scale;
+        let h = self.texture.height() * self.scale;
+        let pos = self.offset + self.scrolled - camera.topleft() * self.parallax;
+
+        let (dest, mode) = match self.tile {
+            LayerTiling::None => (RectF::new(pos.0, pos.1, w, h), RenderMode::Normal),
+            LayerTiling::Horizontal => (
+                RectF::new(pos.0.rem_euclid(w) - w, pos.1, camera.w() + w * 2.0, h),
+                RenderMode::Tiled(self.scale),
+            ),
+            LayerTiling::Both => (
+                RectF::new(
+                    pos.0.rem_euclid(w) - w,
+                    pos.1.rem_euclid(h) - h,
+                    camera.w() + w * 2.0,
+                    camera.h() + h * 2.0,
+                ),
+                RenderMode::Tiled(self.scale),
+            ),
+        };
+
+        if dest.right() < 0.0
+            || dest.bottom() < 0.0
+            || dest.x() > camera.w()
+            || dest.y() > camera.h()
+        {
+            return;
+        }
+
+        self.texture.render(
+            renderer,
+            &RenderOptions {
+                dest: RenderDest::Rect(dest),
+                mode,
+                color: self.color,
+                ..Default::default()
+            },
+        );
+    }
+}
+
+/**
+ * Parallax scrolling decoration layers.
+ *
+ * Background layers are drawn between the level's background image
+ * and the terrain. Foreground layers are drawn over the game objects.
+ * Layers are drawn in the order they are listed in the level file.
+ */
+pub struct ParallaxLayers {
+    background: Vec<ParallaxLayer>,
+    foreground: Vec<ParallaxLayer>,
+}
+
+impl ParallaxLayers {
+    /// Load the level's layers. Returns None if the level has none.
+    pub fn load(renderer: &Renderer, info: &LevelInfo) -> Result<Option<Self>> {
+        if info.layers().is_empty() {
+            return Ok(None);
+        }
+
+        let mut background = Vec::new();
+        let mut foreground = Vec::new();
+
+        for config in info.layers() {
+            let path = info.layer_path(config);
+            let mut texture = Texture::from_file(renderer, path.clone())
+                .with_context(|| format!("Couldn't load layer image {path:?}"))?;
+            if config.scale != 1.0 {
+                texture.set_scalemode(TextureScaleMode::Linear);
+            }
+
+            let layer = ParallaxLayer {
+                texture,
+                parallax: config.parallax,
+                tile: config.tile,
+                offset: Vec2(config.offset.0, config.offset.1),
+                scroll: Vec2(config.scroll.0, config.scroll.1),
+                scale: config.scale.max(0.01),
+                color: Color::from_argb_u32(config.color),
+                scrolled: Vec2::ZERO,
+            };
+
+            if config.foreground {
+                foreground.push(layer);
+            } else {
+                background.push(layer);
+            }
+        }
+
+        Ok(Some(Self {
+            background,
+            foreground,
+        }))
+    }
+
+    /// Advance auto-scrolling layers
+    pub fn step(&mut self, timestep: f32) {
+        for layer in self.background.iter_mut().chain(self.foreground.iter_mut()) {
+            layer.step(timestep);
+        }
+    }
+
+    pub fn render_background(&self, renderer: &Renderer, camera: RectF) {
+        for layer in &self.background {
+            layer.render(renderer, camera);
+        }
+    }
+
+    pub fn render_foreground(&self, renderer: &Renderer, camera: RectF) {
+        for layer in &self.foreground {
+            layer.render(renderer, camera);
+        }
+    }
+}
@@ -14,19 +14,20 @@
 // You should have received a copy of the GNU General Public License
 // along with Luola2.  If not, see <https://www.gnu.org/licenses/>.
 
-use std::{cell::Cell, ops::Add};
+use std::{cell::Cell, collections::HashMap, ops::Add};
 
 use super::{
-    LevelInfo,
+    DynamicTerrainCell, LevelInfo, LevelWrap,
     dynter::DynamicTerrainMap,
     terrain,
     terrain::TER_BIT_WATER,
     tileiterator::{MutableTileIterator, TileIterator},
+    wrap::{set_wraparound, wrap_offsets, wrap_position, wrap_size, wrapped_line_segments},
 };
 use crate::{
     configfile::GAME_CONFIG,
     game::level::terrain::Terrain,
-    gfx::{Color, Image, Renderer, Texture, TextureScaleMode},
+    gfx::{Color, Image, Light, Renderer, Texture, TextureScaleMode},
     math::{Line, LineF, Rect, RectF, Vec2},
 };
 
@@ -41,6 +42,10 @@ pub const LEVEL_SCALE: f32 = 3.0; // Scaling factor: 1 level pixel equals this m
 pub const TILE_SIZE: i32 = 64;
 pub const TILE_LENGTH: usize = (TILE_SIZE * TILE_SIZE) as usize;
 
+/// Burning cells this close to each other are merged into a single light source
+const FIRE_LIGHT_GRID: f32 = 48.0;
+const FIRE_LIGHT: Light = Light::new(40.0, 0xb0ff8c30);
+
 /// A point in unscaled level coordinates
 #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq, Deserialize)]
 pub struct LevelCoordinate(pub i32, pub i32);
@@ -53,6 +58,16 @@ impl LevelCoordinate {
     pub fn as_world_coordinate(&self) -> Vec2 {
         Vec2(self.0 as f32 * LEVEL_SCALE, self.1 as f32 * LEVEL_SCALE)
     }
+
+    /// Move the coordinate inside the level along the wrapping axes
+    pub fn wrapped(self) -> Self {
+        let size = wrap_size();
+        let wrap_axis = |v: i32, size: f32| {
+            let size = (size / LEVEL_SCALE).round() as i32;
+            if size > 0 { v.rem_euclid(size) } else { v }
+        };
+        Self(wrap_axis(self.0, size.0), wrap_axis(self.1, size.1))
+    }
 }
 
 impl Add for LevelCoordinate {
@@ -134,6 +149,7 @@ pub struct Level {
 
     windspeed: f32, // Wind speed (horizontal)
     nospawnzones: Vec<RectF>,
+    wrap: LevelWrap,
 
     pub forcefields: Vec<Forcefield>,
     pub water_color: u32, // pixel value used when creating water
@@ -321,6 +337,8 @@ impl Level {
 
         artwork.set_scalemode(TextureScaleMode::Nearest);
 
+        set_wraparound(info.wrap(), width, height);
+
         Ok(Level {
             tiles,
             artwork,
@@ -338,6 +356,7 @@ impl Level {
             water_color,
             snow_color,
             nospawnzones: info.nospawnzones().clone(),
+            wrap: info.wrap(),
         })
     }
 
@@ -355,6 +374,11 @@ impl Level {
         self.size_scale
     }
 
+    /// Which level edges wrap around
+    pub fn wrap(&self) -> LevelWrap {
+        self.wrap
+    }
+
     /// Get level minimap texture
     pub fn minimap(&self) -> Option<&Texture> {
         self.minimap.as_ref()
@@ -370,6 +394,7 @@ impl Level {
 
     /// Return the color at the given point
     pub fn pixel_at_lc(&self, pos: LevelCoordinate) -> u32 {
+        let pos = pos.wrapped();
         let xq = pos.0 / TILE_SIZE;
         let yq = pos.1 / TILE_SIZE;
 
@@ -385,6 +410,7 @@ impl Level {
 
     /// Return the terrain type at the given point
     pub fn terrain_at_lc(&self, pos: LevelCoordinate) -> terrain::Terrain {
+        let pos = pos.wrapped();
         let xq = pos.0 / TILE_SIZE;
         let yq = pos.1 / TILE_SIZE;
 
@@ -401,6 +427,7 @@ impl Level {
     /// Return the terrain type at the given point in world coordinates
     /// If out of bounds, will return the special "level bounds" terrain type
     pub fn terrain_at(&self, pos: Vec2) -> terrain::Terrain {
+        let pos = wrap_position(pos);
         let (x, y) = (pos.0, pos.1);
         if x < 0.0 || y < 0.0 || x >= self.width || y >= self.height {
             return terrain::TER_LEVELBOUND;
@@ -422,8 +449,31 @@ impl Level {
      *
      * Returns either the first point in which solid terrain was found,
      * or just the terrain type at the end if it was non-solid.
+     *
+     * In wrapping levels, the line continues from the opposite edge.
+     * The hit position is given relative to the original line, so it may
+     * be outside the level.
      */
     pub fn terrain_line(&self, line: LineF) -> TerrainLineHit {
+        if self.wrap == LevelWrap::None {
+            return self.terrain_line_bounded(line);
+        }
+
+        let segments = wrapped_line_segments(line, Vec2(self.width, self.height), wrap_size());
+        let mut result = TerrainLineHit::Miss(0);
+        for (segment, offset) in segments {
+            // Segments before the last one end at a wrapping edge, so any hit stops the line
+            result = match self.terrain_line_bounded(segment) {
+                TerrainLineHit::Hit(t, pos) => return TerrainLineHit::Hit(t, pos + offset),
+                miss => miss,
+            };
+        }
+
+        result
+    }
+
+    /// Terrain line check that treats level edges as solid
+    fn terrain_line_bounded(&self, line: LineF) -> TerrainLineHit {
         if line.0.0 < 0.0 || line.0.1 < 0.0 || line.0.0 >= self.width || line.0.1 >= self.height {
             return TerrainLineHit::Hit(terrain::TER_LEVELBOUND, Vec2(line.0.0, line.0.1));
         }
@@ -531,11 +581,22 @@ impl Level {
         self.forcefields.push(ff.clone());
     }
 
-    /// Return a rectangle centered on the given point and clamped to the level bounds
+    /// Return a rectangle centered on the given point and clamped to the level bounds.
+    /// Wrapping edges don't clamp the rectangle.
     pub fn camera_rect(&self, center: Vec2, width: f32, height: f32) -> RectF {
+        let x = center.0 - width / 2.0;
+        let y = center.1 - height / 2.0;
         RectF::new(
-            (center.0 - width / 2.0).clamp(0.0, self.width - width),
-            (center.1 - height / 2.0).clamp(0.0, self.height - height),
+            if self.wrap == LevelWrap::None {
+                x.clamp(0.0, self.width - width)
+            } else {
+                x
+            },
+            if self.wrap == LevelWrap::Both {
+                y
+            } else {
+                y.clamp(0.0, self.height - height)
+            },
             width,
             height,
         )
@@ -604,6 +665,7 @@ impl Level {
         &mut self,
         pos: LevelCoordinate,
     ) -> Option<(&mut TerrainTile, usize, (i32, i32))> {
+        let pos = pos.wrapped();
         let tx = pos.0 / TILE_SIZE;
         let ty = pos.1 / TILE_SIZE;
         if tx < 0 || ty < 0 || tx >= self.tiles_wide || ty >= self.tiles_high {
@@ -662,29 +724,90 @@ impl Level {
         Err(anyhow!("Couldn't find a spawnpoint after 100 tries!"))
     }
 
-    /// Render the level using the given camera rectangle
-    pub fn render(&self, renderer: &Renderer, camera: RectF) {
-        let source = RectF::new(
-            camera.x() / LEVEL_SCALE,
-            camera.y() / LEVEL_SCALE,
-            camera.w() / LEVEL_SCALE,
-            camera.h() / LEVEL_SCALE,
-        );
-
+    /// Render the level's background image (if any) using the given camera rectangle
+    pub fn render_background(&self, renderer: &Renderer, camera: RectF) {
         if let Some(bg) = self.background.as_ref() {
             bg.render_simple(
                 renderer,
                 Some(RectF::new(
-                    (camera.x() / (self.width - camera.w())) * (bg.width() - camera.w()),
-                    (camera.y() / (self.height - camera.h())) * (bg.height() - camera.h()),
+                    (camera.x() / (self.width - camera.w())).clamp(0.0, 1.0)
+                        * (bg.width() - camera.w()),
+                    (camera.y() / (self.height - camera.h())).clamp(0.0, 1.0)
+                        * (bg.height() - camera.h()),
                     camera.w(),
                     camera.h(),
                 )),
                 None,
             );
         }
+    }
+
+    /// Render the level terrain using the given camera rectangle
+    pub fn render(&self, renderer: &Renderer, camera: RectF) {
+        // In a wrapping level, the camera may see parts of several copies of the level
+        for offset in wrap_offsets(camera) {
+            let left = camera.x().max(offset.0);
+            let top = camera.y().max(offset.1);
+            let right = camera.right().min(offset.0 + self.width);
+            let bottom = camera.bottom().min(offset.1 + self.height);
+            if right <= left || bottom <= top {
+                continue;
+            }
+
+            let visible = RectF::new(left, top, right - left, bottom - top);
+            let source = RectF::new(
+                (visible.x() - offset.0) / LEVEL_SCALE,
+                (visible.y() - offset.1) / LEVEL_SCALE,
+                visible.w() / LEVEL_SCALE,
+                visible.h() / LEVEL_SCALE,
+            );
+
+            self.artwork.render_simple(
+                renderer,
+                Some(source),
+                Some(visible.offset(-camera.x(), -camera.y())),
+            );
+        }
+    }
 
-        self.artwork.render_simple(renderer, Some(source), None);
+    /**
+     * Get light sources for burning terrain inside the given area.
+     *
+     * Fires spread cell by cell, so nearby cells are merged into one
+     * bigger light to keep the number of lights reasonable.
+     */
+    pub fn fire_lights(&self, area: RectF) -> Vec<(Vec2, Light)> {
+        let cells = self.dynterrain.take();
+        let mut buckets: HashMap<(i32, i32), (Vec2, i32)> = HashMap::new();
+
+        for (lc, cell) in cells.iter() {
+            if !matches!(cell, DynamicTerrainCell::Fire { .. }) {
+                continue;
+            }
+
+            let pos = lc.as_world_coordinate();
+            if area.contains(pos) {
+                let bucket = buckets
+                    .entry((
+                        (pos.0 / FIRE_LIGHT_GRID) as i32,
+                        (pos.1 / FIRE_LIGHT_GRID) as i32,
+                    ))
+                    .or_insert((Vec2::ZERO, 0));
+                bucket.0 = bucket.0 + pos;
+                bucket.1 += 1;
+            }
+        }
+
+        self.dynterrain.set(cells);
+
+        buckets
+            .into_values()
+            .map(|(sum, count)| {
+                let mut light = FIRE_LIGHT;
+                light.radius = (light.radius + count as f32 * 4.0).min(FIRE_LIGHT_GRID * 3.0);
+                (sum / count as f32, light)
+            })
+            .collect()
     }
 
     pub fn debug_render_tilehints(&self, renderer: &Renderer, camera: RectF) {
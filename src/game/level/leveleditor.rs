@@ -28,13 +28,14 @@ use crate::{
                 TER_TYPE_DAMAGE, TER_TYPE_GREYGOO, TER_TYPE_GROUND, TER_TYPE_HIGH_EXPLOSIVE,
                 TER_TYPE_ICE, Terrain,
             },
+            wrap,
         },
         objects::TerrainParticle,
         scripting::ScriptEnvironment,
         world::WorldEffect,
     },
     gfx::Color,
-    math::{Rect, Vec2},
+    math::{Rect, RectF, Vec2},
 };
 
 use super::level::Level;
@@ -58,6 +59,10 @@ impl<'a> LevelEditor<'a> {
         }
     }
 
+    pub fn level(&self) -> &Level {
+        self.level
+    }
+
     pub fn update_forcefield(&mut self, ff: &Forcefield) {
         self.level.update_forcefield(ff);
     }
@@ -91,6 +96,26 @@ impl<'a> LevelEditor<'a> {
             return;
         }
 
+        // In a wrapping level, a hole crossing the edge continues on the other side
+        let pos = wrap::wrap_position(pos);
+        let reach = r as f32 * LEVEL_SCALE;
+        for offset in wrap::wrap_offsets(RectF::new(
+            pos.0 - reach,
+            pos.1 - reach,
+            reach * 2.0,
+            reach * 2.0,
+        )) {
+            self.make_hole_unwrapped(pos - offset, r, dust_chance, scripting);
+        }
+    }
+
+    fn make_hole_unwrapped(
+        &mut self,
+        pos: Vec2,
+        r: i32,
+        dust_chance: f32,
+        scripting: &mut ScriptEnvironment,
+    ) {
         let center_x = (pos.0 / LEVEL_SCALE) as i32;
         let center_y = (pos.1 / LEVEL_SCALE) as i32;
         let rr = r * r;
@@ -101,10 +126,10 @@ impl<'a> LevelEditor<'a> {
             if let TileContentHint::Destructible = tile.content_hint {
                 let mut dirty = false;
                 let tile_rect = Rect::new(i * TILE_SIZE, j * TILE_SIZE, TILE_SIZE, TILE_SIZE);
-                let rect_in_tile = hole_rect
-                    .intersected(tile_rect)
-                    .unwrap()
-                    .offset(-tile_rect.x(), -tile_rect.y());
+                let Some(rect_in_tile) = hole_rect.intersected(tile_rect) else {
+                    continue;
+                };
+                let rect_in_tile = rect_in_tile.offset(-tile_rect.x(), -tile_rect.y());
 
                 let terrain_iter = MutableRectIterator::from_rect(
                     &mut tile.terrain,
@@ -203,6 +228,7 @@ impl<'a> LevelEditor<'a> {
 
     /// Change the color of an artwork pixel without changing the terrain type
     pub fn color_point(&mut self, pos: Vec2, color: Color) {
+        let pos = wrap::wrap_position(pos);
         if pos.0 < 0.0 || pos.1 < 0.0 || pos.0 >= self.level.width() || pos.1 >= self.level.height()
         {
             return;
@@ -260,6 +286,7 @@ impl<'a> LevelEditor<'a> {
      * already a solid pixel in the given position.
      */
     pub fn add_point(&mut self, pos: Vec2, ter: Terrain, color: Color) {
+        let pos = wrap::wrap_position(pos);
         if pos.0 < 0.0 || pos.1 < 0.0 || pos.0 >= self.level.width() || pos.1 >= self.level.height()
         {
             return;
@@ -290,7 +317,7 @@ impl<'a> LevelEditor<'a> {
         if !dter.destroys_ground() || !terrain::is_indestructible_solid(self.level.terrain_at(pos))
         {
             let mut cells = self.level.dynterrain.take();
-            cells.insert(LevelCoordinate::from_world_coordinate(pos), dter);
+            cells.insert(LevelCoordinate::from_world_coordinate(pos).wrapped(), dter);
 
             self.level.dynterrain.replace(cells);
         }
@@ -301,7 +328,7 @@ impl<'a> LevelEditor<'a> {
         n: &[LevelCoordinate],
         pos: LevelCoordinate,
     ) -> impl Iterator<Item = LevelCoordinate> {
-        n.iter().map(move |&p| p + pos)
+        n.iter().map(move |&p| (p + pos).wrapped())
     }
 
     /// Perform a dynamic terrain simulation step
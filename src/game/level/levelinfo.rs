@@ -27,11 +27,11 @@ use toml;
 
 use anyhow::{Result, anyhow};
 
-use super::terrain::*;
+use super::{LayerConfig, LevelWrap, terrain::*};
 use crate::{
-    fs::glob_datafiles,
+    fs::{datafile_origin, glob_datafiles},
     game::level::LEVEL_SCALE,
-    gfx::{Renderer, Texture},
+    gfx::{Color, Renderer, Texture},
     math::RectF,
 };
 
@@ -51,6 +51,10 @@ pub struct LevelInfo {
     transparent_color_index: Option<u8>,
     script_settings: toml::Table,
     starfield: bool,
+    lighting: Option<LightingConfig>,
+    layers: Vec<LayerConfig>,
+    jumpgates: Vec<JumpGateConfig>,
+    wrap: LevelWrap,
     nospawnzones: Vec<RectF>,
 }
 
@@ -68,6 +72,17 @@ struct LevelInfoToml {
     #[serde(default)]
     starfield: bool,
 
+    lighting: Option<LightingConfig>,
+
+    #[serde(default)]
+    layers: Vec<LayerConfig>,
+
+    #[serde(default)]
+    jumpgates: Vec<JumpGateConfig>,
+
+    #[serde(default)]
+    wrap: LevelWrap,
+
     #[serde(default)]
     nospawnzones: Vec<NoSpawnZoneToml>,
 
@@ -81,6 +96,74 @@ struct LevelInfoToml {
     script_settings: Option<toml::Table>,
 }
 
+/**
+ * Dynamic lighting settings.
+ *
+ * Lighting is enabled for levels that have a [lighting] section.
+ */
+#[derive(serde::Deserialize, Clone, Debug)]
+pub struct LightingConfig {
+    /// Ambient light level (0.0 is pitch black, 1.0 is fully lit)
+    #[serde(default = "default_ambient_light")]
+    pub ambient: f32,
+
+    /// Tint of the ambient light (0xRRGGBB)
+    #[serde(default = "default_ambient_color")]
+    pub color: u32,
+}
+
+fn default_ambient_light() -> f32 {
+    0.2
+}
+
+fn default_ambient_color() -> u32 {
+    0xffffff
+}
+
+impl LightingConfig {
+    /// Get the color of unlit areas
+    pub fn ambient_color(&self) -> Color {
+        let c = Color::from_argb_u32(self.color);
+        let a = self.ambient.clamp(0.0, 1.0);
+        Color::new(c.r * a, c.g * a, c.b * a)
+    }
+}
+
+/**
+ * A pair of permanent jump gates ([[jumpgates]] array)
+ *
+ * Positions and radius are given in level (terrain pixel) coordinates.
+ */
+#[derive(serde::Deserialize, Clone, Debug)]
+pub struct JumpGateConfig {
+    /// Position of the first gate
+    pub a: (i32, i32),
+
+    /// Position of the second gate
+    pub b: (i32, i32),
+
+    #[serde(default = "default_jumpgate_radius")]
+    pub radius: i32,
+
+    /// Objects passing from A to B are rotated by this many degrees
+    /// (in the same direction as Vec2_for_angle.) The reverse rotation
+    /// is applied when passing from B to A.
+    #[serde(default)]
+    pub rotation: f32,
+
+    /// Gate B is an exit only
+    #[serde(default)]
+    pub one_way: bool,
+
+    /// Time in seconds the gates are inactive after each jump
+    #[serde(default)]
+    pub cooldown: f32,
+}
+
+fn default_jumpgate_radius() -> i32 {
+    10
+}
+
 #[derive(serde::Deserialize, Clone, Debug)]
 struct NoSpawnZoneToml {
     rect: (i32, i32, i32, i32),
@@ -152,6 +235,10 @@ impl LevelInfo {
             transparent_color_index,
             script_settings: info.script_settings.unwrap_or_default(),
             starfield: info.starfield,
+            lighting: info.lighting,
+            layers: info.layers,
+            jumpgates: info.jumpgates,
+            wrap: info.wrap,
             colors: info.colors,
             nospawnzones,
         })
@@ -162,16 +249,23 @@ impl LevelInfo {
 
         Ok(files
             .iter()
+            // Level packs may list their missions alongside the levels
+            .filter(|f| f.file_name().is_none_or(|n| n != "missions.toml"))
             .filter_map(|f| match LevelInfo::load(f, renderer) {
                 Ok(l) => Some(l),
                 Err(err) => {
-                    error!("Couldn't load level info: {}", err);
+                    error!("Couldn't load level info {}: {}", datafile_origin(f), err);
                     None
                 }
             })
             .collect())
     }
 
+    /// Unique ID of the level (level names are only unique within a level pack)
+    pub fn id(&self) -> String {
+        format!("{}/{}", self.levelpack, self.name)
+    }
+
     pub fn levelpack(&self) -> &str {
         &self.levelpack
     }
@@ -212,10 +306,32 @@ impl LevelInfo {
         self.script_file.as_ref().map(|f| self.root.join(f))
     }
 
+    pub fn wrap(&self) -> LevelWrap {
+        self.wrap
+    }
+
+    pub fn jumpgates(&self) -> &[JumpGateConfig] {
+        &self.jumpgates
+    }
+
     pub fn use_starfield(&self) -> bool {
         self.starfield
     }
 
+    /// Get the decorative parallax layers in drawing order
+    pub fn layers(&self) -> &[LayerConfig] {
+        &self.layers
+    }
+
+    pub fn layer_path(&self, layer: &LayerConfig) -> PathBuf {
+        self.root.join(&layer.image)
+    }
+
+    /// Get the lighting settings, if dynamic lighting is enabled for this level
+    pub fn lighting(&self) -> Option<&LightingConfig> {
+        self.lighting.as_ref()
+    }
+
     pub fn nospawnzones(&self) -> &Vec<RectF> {
         &self.nospawnzones
     }
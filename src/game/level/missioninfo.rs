@@ -0,0 +1,150 @@
+// This file is part of Luola2
+// Copyright (C) 2025 Calle Laakkonen
+//
+// Luola2 is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Luola2 is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Luola2.  If not, see <https://www.gnu.org/licenses/>.
+
+use anyhow::Result;
+use log::error;
+use serde;
+use std::{fs, path::Path};
+use toml;
+
+use crate::fs::{datafile_origin, glob_datafiles};
+
+/**
+ * A single player mission declared by a level pack.
+ *
+ * Missions are listed in the level pack's missions.toml file in
+ * campaign order. The objective table is passed as is to the
+ * mission script, which decides what needs to be done.
+ */
+#[derive(Clone, Debug)]
+pub struct MissionInfo {
+    levelpack: String,
+    name: String,
+    title: String,
+    level: String,
+    par: i32,
+    ship: Option<String>,
+    weapons: Vec<String>,
+    objective: toml::Table,
+}
+
+#[derive(serde::Deserialize, Debug)]
+struct MissionPackToml {
+    #[serde(default)]
+    mission: Vec<MissionToml>,
+}
+
+#[derive(serde::Deserialize, Debug)]
+struct MissionToml {
+    name: String,
+    title: String,
+    level: String,
+
+    /// Par time in seconds
+    par: i32,
+
+    ship: Option<String>,
+
+    #[serde(default)]
+    weapons: Vec<String>,
+
+    objective: toml::Table,
+}
+
+impl MissionInfo {
+    pub fn load(path: &Path) -> Result<Vec<MissionInfo>> {
+        let missions: MissionPackToml = toml::from_str(&fs::read_to_string(path)?)?;
+        let levelpack = path
+            .parent()
+            .unwrap()
+            .file_name()
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .to_owned();
+
+        Ok(missions
+            .mission
+            .into_iter()
+            .map(|m| MissionInfo {
+                levelpack: levelpack.clone(),
+                name: m.name,
+                title: m.title,
+                level: m.level,
+                par: m.par.max(1),
+                ship: m.ship,
+                weapons: m.weapons,
+                objective: m.objective,
+            })
+            .collect())
+    }
+
+    /// Load the missions of all level packs, in campaign order
+    pub fn load_mission_packs() -> Result<Vec<MissionInfo>> {
+        let files = glob_datafiles("levels", "*/missions.toml")?;
+
+        let mut missions: Vec<_> = files
+            .iter()
+            .flat_map(|f| match MissionInfo::load(f) {
+                Ok(m) => m,
+                Err(err) => {
+                    error!("Couldn't load missions {}: {}", datafile_origin(f), err);
+                    Vec::new()
+                }
+            })
+            .collect();
+
+        // Stable sort keeps the missions of each pack in the declared order
+        missions.sort_by(|a, b| a.levelpack.cmp(&b.levelpack));
+
+        Ok(missions)
+    }
+
+    /// Unique ID of the mission for saving progress
+    pub fn id(&self) -> String {
+        format!("{}/{}", self.levelpack, self.name)
+    }
+
+    pub fn levelpack(&self) -> &str {
+        &self.levelpack
+    }
+
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// Name of the level (in the same level pack) the mission is played on
+    pub fn level(&self) -> &str {
+        &self.level
+    }
+
+    /// Par time in seconds
+    pub fn par(&self) -> i32 {
+        self.par
+    }
+
+    pub fn ship(&self) -> Option<&str> {
+        self.ship.as_deref()
+    }
+
+    pub fn weapons(&self) -> &[String] {
+        &self.weapons
+    }
+
+    pub fn objective(&self) -> &toml::Table {
+        &self.objective
+    }
+}
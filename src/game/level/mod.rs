@@ -15,16 +15,24 @@
 // along with Luola2.  If not, see <https://www.gnu.org/licenses/>.
 
 mod dynter;
+mod jumpgate;
+mod layers;
 mod level;
 mod leveleditor;
 mod levelinfo;
+mod missioninfo;
 mod rectiter;
 mod starfield;
 pub mod terrain;
 mod tileiterator;
+pub mod wrap;
 
 pub use dynter::DynamicTerrainCell;
+pub use jumpgate::*;
+pub use layers::*;
 pub use level::*;
 pub use leveleditor::*;
 pub use levelinfo::*;
+pub use missioninfo::*;
 pub use starfield::Starfield;
+pub use wrap::LevelWrap;
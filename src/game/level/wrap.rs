@@ -0,0 +1,306 @@
+// This file is part of Luola2
+// Copyright (C) 2025 Calle Laakkonen
+//
+// Luola2 is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Luola2 is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Luola2.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Level wraparound.
+//!
+//! In a wrapping level, objects leaving from one edge come back from the opposite one.
+//! Object positions are kept inside the level, but things that only exist for a moment
+//! (lines, hitscans, decorative particles) may extend over the edge.
+//!
+//! The wrap size is a global setting (like the game config), since object collision
+//! checks need it but don't otherwise have access to the level.
+
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use serde::Deserialize;
+use smallvec::SmallVec;
+
+use crate::math::{LineF, RectF, Vec2};
+
+/// Which edges of the level wrap around
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum LevelWrap {
+    /// The level is bounded on all sides
+    #[default]
+    None,
+    /// The left and right edges are connected
+    Horizontal,
+    /// Both the left and right and top and bottom edges are connected
+    Both,
+}
+
+/// Wraparound width and height as f32 bits. Zero means the axis doesn't wrap.
+static WRAP_WIDTH: AtomicU32 = AtomicU32::new(0);
+static WRAP_HEIGHT: AtomicU32 = AtomicU32::new(0);
+
+/// Set the wraparound mode of the current level
+pub fn set_wraparound(wrap: LevelWrap, width: f32, height: f32) {
+    let (w, h) = match wrap {
+        LevelWrap::None => (0.0, 0.0),
+        LevelWrap::Horizontal => (width, 0.0),
+        LevelWrap::Both => (width, height),
+    };
+    WRAP_WIDTH.store(f32::to_bits(w), Ordering::Relaxed);
+    WRAP_HEIGHT.store(f32::to_bits(h), Ordering::Relaxed);
+}
+
+/// Get the wraparound size. An axis that doesn't wrap has the size 0.
+pub fn wrap_size() -> Vec2 {
+    Vec2(
+        f32::from_bits(WRAP_WIDTH.load(Ordering::Relaxed)),
+        f32::from_bits(WRAP_HEIGHT.load(Ordering::Relaxed)),
+    )
+}
+
+fn wrap_axis(v: f32, size: f32) -> f32 {
+    if size > 0.0 {
+        // rem_euclid can return size itself due to rounding
+        let v = v.rem_euclid(size);
+        if v >= size { 0.0 } else { v }
+    } else {
+        v
+    }
+}
+
+fn shortest_axis(d: f32, size: f32) -> f32 {
+    if size > 0.0 {
+        d - (d / size).round() * size
+    } else {
+        d
+    }
+}
+
+/// Move the position inside the level along the wrapping axes
+pub fn wrap_position(pos: Vec2) -> Vec2 {
+    let size = wrap_size();
+    Vec2(wrap_axis(pos.0, size.0), wrap_axis(pos.1, size.1))
+}
+
+/// Get the shortest vector from one point to another, taking wraparound into account
+pub fn wrapped_delta(from: Vec2, to: Vec2) -> Vec2 {
+    let size = wrap_size();
+    let d = to - from;
+    Vec2(shortest_axis(d.0, size.0), shortest_axis(d.1, size.1))
+}
+
+/**
+ * Get the offsets of the copies of the level that are visible in the given area.
+ *
+ * Something at position `pos` inside the level appears at `pos + offset` for each offset.
+ * When the level doesn't wrap, or the area is inside the level, the only offset is zero.
+ */
+pub fn wrap_offsets(area: RectF) -> SmallVec<[Vec2; 4]> {
+    let size = wrap_size();
+
+    let axis_offsets = |start: f32, end: f32, size: f32| -> SmallVec<[f32; 2]> {
+        if size > 0.0 {
+            let first = (start / size).floor() as i32;
+            let last = (end / size).floor() as i32;
+            (first..=last.max(first))
+                .take(3)
+                .map(|k| k as f32 * size)
+                .collect()
+        } else {
+            SmallVec::from_slice(&[0.0])
+        }
+    };
+
+    let xs = axis_offsets(area.x(), area.right(), size.0);
+    let ys = axis_offsets(area.y(), area.bottom(), size.1);
+
+    ys.iter()
+        .flat_map(|&y| xs.iter().map(move |&x| Vec2(x, y)))
+        .collect()
+}
+
+/**
+ * Get the ranges of positions inside the level that overlap the given horizontal range.
+ *
+ * Each range is returned along with the offset to add to positions in it to bring them
+ * to the original range.
+ */
+pub fn wrapped_ranges(left: f32, right: f32) -> SmallVec<[(f32, f32, f32); 3]> {
+    let w = wrap_size().0;
+    let mut ranges = SmallVec::new();
+    ranges.push((left, right, 0.0));
+
+    // Ranges wider than the level would produce duplicates
+    if w > 0.0 && right - left < w {
+        if left < 0.0 {
+            ranges.push((left + w, right + w, -w));
+        }
+        if right >= w {
+            ranges.push((left - w, right - w, w));
+        }
+    }
+
+    ranges
+}
+
+/**
+ * Split a line into segments inside the level, following it across the wrapping edges.
+ *
+ * Each segment is returned along with the offset to add to positions in it to get
+ * the position on the original line. Axes that don't wrap are not split, so the last
+ * segment may extend outside the level. Very long lines are not followed all the way.
+ */
+pub fn wrapped_line_segments(
+    line: LineF,
+    level_size: Vec2,
+    wrap: Vec2,
+) -> SmallVec<[(LineF, Vec2); 4]> {
+    // Keep positions on the wrapping axes inside the level
+    let clamp_axis = |v: f32, size: f32, wrap: f32| {
+        if wrap > 0.0 {
+            v.clamp(0.0, size - 0.01)
+        } else {
+            v
+        }
+    };
+    let clamp = |v: Vec2| {
+        Vec2(
+            clamp_axis(v.0, level_size.0, wrap.0),
+            clamp_axis(v.1, level_size.1, wrap.1),
+        )
+    };
+
+    // Fraction of the segment after which it leaves the level on a wrapping axis
+    let exit_at = |start: f32, dir: f32, size: f32, wrap: f32| {
+        if wrap > 0.0 && dir > 0.0 && start + dir >= size {
+            (size - start) / dir
+        } else if wrap > 0.0 && dir < 0.0 && start + dir < 0.0 {
+            -start / dir
+        } else {
+            f32::INFINITY
+        }
+    };
+
+    let start = Vec2(wrap_axis(line.0.0, wrap.0), wrap_axis(line.0.1, wrap.1));
+    let mut offset = line.0 - start;
+    let mut segment = LineF(start, line.1 - offset);
+    let mut segments = SmallVec::new();
+
+    for _ in 0..4 {
+        let dir = segment.1 - segment.0;
+        let exit_x = exit_at(segment.0.0, dir.0, level_size.0, wrap.0);
+        let exit_y = exit_at(segment.0.1, dir.1, level_size.1, wrap.1);
+        let exit = exit_x.min(exit_y);
+
+        if exit.is_infinite() {
+            segments.push((segment, offset));
+            break;
+        }
+
+        let mut shift = Vec2::ZERO;
+        if exit_x <= exit {
+            shift.0 = if dir.0 < 0.0 { wrap.0 } else { -wrap.0 };
+        }
+        if exit_y <= exit {
+            shift.1 = if dir.1 < 0.0 { wrap.1 } else { -wrap.1 };
+        }
+
+        let exit_pos = segment.0 + dir * exit;
+        segments.push((LineF(segment.0, clamp(exit_pos)), offset));
+        segment = LineF(clamp(exit_pos + shift), segment.1 + shift);
+        offset = offset - shift;
+    }
+
+    segments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wrap_math() {
+        assert_eq!(wrap_axis(-10.0, 100.0), 90.0);
+        assert_eq!(wrap_axis(110.0, 100.0), 10.0);
+        assert_eq!(wrap_axis(-10.0, 0.0), -10.0);
+
+        assert_eq!(shortest_axis(90.0, 100.0), -10.0);
+        assert_eq!(shortest_axis(-90.0, 100.0), 10.0);
+        assert_eq!(shortest_axis(30.0, 100.0), 30.0);
+        assert_eq!(shortest_axis(90.0, 0.0), 90.0);
+    }
+
+    #[test]
+    fn test_wrapped_line_segments() {
+        let level = Vec2(100.0, 50.0);
+        let segments = |line: LineF, wrap: Vec2| -> Vec<(Vec2, Vec2, Vec2)> {
+            wrapped_line_segments(line, level, wrap)
+                .into_iter()
+                .map(|(l, offset)| (l.0, l.1, offset))
+                .collect()
+        };
+
+        // Line inside the level is not split
+        let line = LineF(Vec2(10.0, 10.0), Vec2(60.0, 20.0));
+        assert_eq!(
+            segments(line, Vec2(100.0, 0.0)),
+            [(line.0, line.1, Vec2::ZERO)]
+        );
+
+        // Crossing the right edge continues from the left edge
+        assert_eq!(
+            segments(LineF(Vec2(90.0, 10.0), Vec2(120.0, 10.0)), Vec2(100.0, 0.0)),
+            [
+                (Vec2(90.0, 10.0), Vec2(99.99, 10.0), Vec2::ZERO),
+                (Vec2(0.0, 10.0), Vec2(20.0, 10.0), Vec2(100.0, 0.0)),
+            ]
+        );
+
+        // Crossing the left edge continues from the right edge
+        assert_eq!(
+            segments(LineF(Vec2(10.0, 10.0), Vec2(-20.0, 40.0)), Vec2(100.0, 0.0)),
+            [
+                (Vec2(10.0, 10.0), Vec2(0.0, 20.0), Vec2::ZERO),
+                (Vec2(99.99, 20.0), Vec2(80.0, 40.0), Vec2(-100.0, 0.0)),
+            ]
+        );
+
+        // A line starting outside the level is moved inside first
+        assert_eq!(
+            segments(
+                LineF(Vec2(110.0, 10.0), Vec2(120.0, 10.0)),
+                Vec2(100.0, 0.0)
+            ),
+            [(Vec2(10.0, 10.0), Vec2(20.0, 10.0), Vec2(100.0, 0.0))]
+        );
+
+        // Crossing both wrapping edges
+        assert_eq!(
+            segments(
+                LineF(Vec2(90.0, 20.0), Vec2(110.0, 60.0)),
+                Vec2(100.0, 50.0)
+            ),
+            [
+                (Vec2(90.0, 20.0), Vec2(99.99, 40.0), Vec2::ZERO),
+                (Vec2(0.0, 40.0), Vec2(5.0, 49.99), Vec2(100.0, 0.0)),
+                (Vec2(5.0, 0.0), Vec2(10.0, 10.0), Vec2(100.0, 50.0)),
+            ]
+        );
+
+        // Edges that don't wrap are left for the terrain check
+        let line = LineF(Vec2(10.0, 40.0), Vec2(20.0, 60.0));
+        assert_eq!(
+            segments(line, Vec2(100.0, 0.0)),
+            [(line.0, line.1, Vec2::ZERO)]
+        );
+        assert_eq!(segments(line, Vec2::ZERO), [(line.0, line.1, Vec2::ZERO)]);
+    }
+}
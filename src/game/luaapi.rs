@@ -0,0 +1,1160 @@
+// This file is part of Luola2
+// Copyright (C) 2025 Calle Laakkonen
+//
+// Luola2 is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Luola2 is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Luola2.  If not, see <https://www.gnu.org/licenses/>.
+
+//! A declarative description of the Lua scripting API.
+//!
+//! The actual bindings live next to the types they expose (see `scripting.rs`
+//! and the UserData impls of the game objects.) This registry mirrors them so
+//! that type definition files can be generated for the Lua language server.
+//! Remember to update this when changing the bindings!
+
+use std::fmt::Write;
+use std::path::Path;
+
+use anyhow::Result;
+
+pub struct LuaParam {
+    pub name: &'static str,
+    /// LuaLS type expression
+    pub ty: &'static str,
+}
+
+pub struct LuaFunction {
+    pub name: &'static str,
+    pub doc: &'static str,
+    pub params: &'static [LuaParam],
+    pub returns: &'static [&'static str],
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum FieldAccess {
+    Read,
+    Write,
+    ReadWrite,
+}
+
+pub struct LuaField {
+    /// Field name. Optional table fields are suffixed with '?'
+    pub name: &'static str,
+    pub ty: &'static str,
+    pub access: FieldAccess,
+    pub doc: &'static str,
+}
+
+pub struct LuaClass {
+    pub name: &'static str,
+    pub doc: &'static str,
+    pub fields: &'static [LuaField],
+    pub methods: &'static [LuaFunction],
+}
+
+pub enum EffectProps {
+    /// The effect takes no parameters
+    None,
+    /// A single value of the given type
+    Value(&'static str),
+    /// A property table with the given fields
+    Table(&'static [LuaField]),
+}
+
+pub struct LuaEffect {
+    pub name: &'static str,
+    pub doc: &'static str,
+    pub props: EffectProps,
+}
+
+const fn param(name: &'static str, ty: &'static str) -> LuaParam {
+    LuaParam { name, ty }
+}
+
+const fn func(
+    name: &'static str,
+    params: &'static [LuaParam],
+    returns: &'static [&'static str],
+    doc: &'static str,
+) -> LuaFunction {
+    LuaFunction {
+        name,
+        doc,
+        params,
+        returns,
+    }
+}
+
+const fn ro(name: &'static str, ty: &'static str, doc: &'static str) -> LuaField {
+    LuaField {
+        name,
+        ty,
+        access: FieldAccess::Read,
+        doc,
+    }
+}
+
+const fn wo(name: &'static str, ty: &'static str, doc: &'static str) -> LuaField {
+    LuaField {
+        name,
+        ty,
+        access: FieldAccess::Write,
+        doc,
+    }
+}
+
+const fn rw(name: &'static str, ty: &'static str, doc: &'static str) -> LuaField {
+    LuaField {
+        name,
+        ty,
+        access: FieldAccess::ReadWrite,
+        doc,
+    }
+}
+
+/// Type aliases for values that are opaque or plain numbers on the Lua side
+pub const ALIASES: &[(&str, &str, &str)] = &[
+    (
+        "PlayerId",
+        "integer",
+        "Player number (1-based, 0 means no player)",
+    ),
+    ("Terrain", "integer", "Terrain type bitfield"),
+    ("Color", "integer", "Color in ARGB format"),
+    ("TextureId", "userdata", "Handle to a texture"),
+    ("Text", "userdata", "A prerendered text object"),
+];
+
+/// Plain table types used in object properties
+pub const TABLE_TYPES: &[(&str, &str, &[LuaField])] = &[(
+    "Light",
+    "A light source. Only used in levels with dynamic lighting enabled.",
+    &[
+        ro("radius", "number", "Radius of the lit area"),
+        ro("color?", "Color", "Light color. Alpha is the intensity."),
+    ],
+)];
+
+/// Global functions outside the game table
+pub const GLOBAL_FUNCTIONS: &[LuaFunction] = &[
+    func(
+        "Vec2",
+        &[param("x", "number"), param("y", "number")],
+        &["Vec2"],
+        "Construct a vector",
+    ),
+    func(
+        "Vec2_for_angle",
+        &[param("angle", "number"), param("magnitude", "number")],
+        &["Vec2"],
+        "Construct a vector pointing in the given direction (in degrees)",
+    ),
+    func(
+        "RectF",
+        &[
+            param("x", "number"),
+            param("y", "number"),
+            param("w", "number"),
+            param("h", "number"),
+        ],
+        &["RectF"],
+        "Construct a rectangle",
+    ),
+    func(
+        "tr",
+        &[param("text", "string"), param("...", "any")],
+        &["string"],
+        "Translate a string to the current language. Placeholders {1}, {2}, etc. are replaced with the extra arguments",
+    ),
+];
+
+/// Functions in the global textures table
+pub const TEXTURE_FUNCTIONS: &[LuaFunction] = &[
+    func(
+        "get",
+        &[param("name", "string")],
+        &["TextureId"],
+        "Find a texture by name",
+    ),
+    func(
+        "font",
+        &[
+            param("font", "\"menu\"|\"menu_big\"|\"flavortext\""),
+            param("text", "string"),
+        ],
+        &["Text"],
+        "Render a text using one of the game fonts",
+    ),
+];
+
+/// Constant values in the game table
+pub const GAME_FIELDS: &[LuaField] = &[
+    ro(
+        "level_width",
+        "number",
+        "Width of the level in world coordinates",
+    ),
+    ro(
+        "level_height",
+        "number",
+        "Height of the level in world coordinates",
+    ),
+    ro("snow_color", "Color", "Color of snow in this level"),
+    ro("water_color", "Color", "Color of water in this level"),
+    ro(
+        "pilot_danger_speed",
+        "number",
+        "Pilots landing faster than this get hurt (on_hard_landing)",
+    ),
+];
+
+/// Functions in the game table
+pub const GAME_FUNCTIONS: &[LuaFunction] = &[
+    func(
+        "find_spawnpoint",
+        &[param("area", "RectF?"), param("allow_water", "boolean?")],
+        &["Vec2"],
+        "Find a free spot for a ship, optionally inside the given area",
+    ),
+    func(
+        "on",
+        &[param("event", "GameEvent"), param("handler", "function")],
+        &[],
+        "Subscribe to a game event. Multiple handlers can be subscribed to the same event.",
+    ),
+    func(
+        "player_color",
+        &[param("player", "PlayerId")],
+        &["Color"],
+        "Get the color of the given player",
+    ),
+    func(
+        "terrain_at",
+        &[param("pos", "Vec2")],
+        &["Terrain"],
+        "Get the terrain type at the given position",
+    ),
+    func(
+        "wrapped_delta",
+        &[param("from", "Vec2"), param("to", "Vec2")],
+        &["Vec2"],
+        "Get the shortest vector from one point to another. \
+        In a wrapping level, this may go over the level edge.",
+    ),
+    func(
+        "terrain_line",
+        &[param("start", "Vec2"), param("stop", "Vec2")],
+        &["Vec2", "Terrain", "boolean"],
+        "Check for solid terrain along a line. Returns the hit position (or the end point), \
+        terrain type and true if solid terrain was hit.",
+    ),
+    func(
+        "ships_iter",
+        &[param("callback", "fun(ship: Ship): boolean?")],
+        &[],
+        "Iterate through all ships. The callback can return false to stop iteration.",
+    ),
+    func(
+        "ships_nearest_pos",
+        &[
+            param("pos", "Vec2"),
+            param("range", "number"),
+            param("other_than", "PlayerId"),
+        ],
+        &["Vec2?"],
+        "Get the position of the nearest visible and intact ship not owned by the given player. \
+        In a wrapping level, the position may be outside the level, in the direction of the ship.",
+    ),
+    func(
+        "pilots_iter",
+        &[param("callback", "fun(pilot: Pilot): boolean?")],
+        &[],
+        "Iterate through all pilots. The callback can return false to stop iteration.",
+    ),
+    func(
+        "pilots_nearest_pos",
+        &[
+            param("pos", "Vec2"),
+            param("range", "number"),
+            param("other_than", "PlayerId"),
+        ],
+        &["Vec2?"],
+        "Get the position of the nearest pilot not owned by the given player. \
+        In a wrapping level, the position may be outside the level, in the direction of the pilot.",
+    ),
+    func(
+        "critters_iter",
+        &[
+            param("pos", "Vec2"),
+            param("radius", "number"),
+            param("except_this_id", "integer"),
+            param("callback", "fun(critter: Critter): boolean?"),
+        ],
+        &[],
+        "Iterate through critters within the given radius. The callback can return false to stop iteration.",
+    ),
+    func(
+        "mines_iter_mut",
+        &[
+            param("owner", "PlayerId?"),
+            param("callback", "fun(mine: Projectile): boolean?"),
+        ],
+        &[],
+        "Iterate through mines, optionally only those of the given owner. \
+        Cannot be called from mine callbacks (except timers.)",
+    ),
+    func(
+        "fixedobjs_iter_mut",
+        &[param("callback", "fun(obj: FixedObject): boolean?")],
+        &[],
+        "Iterate through fixed objects. Cannot be called from fixed object callbacks.",
+    ),
+    func(
+        "effect",
+        &[param("effect", "EffectType"), param("props", "any")],
+        &[],
+        "Change the world. Effects are applied at the end of the animation step.",
+    ),
+    func(
+        "player_effect",
+        &[
+            param(
+                "effect",
+                "\"hud_overlay\"|\"hud_status\"|\"rumble\"|\"credits\"",
+            ),
+            param("player", "integer"),
+            param("props", "HudOverlayProps|RumbleProps|Text|number|nil"),
+        ],
+        &[],
+        "Apply a player specific effect. For rumble, the player parameter is the controller ID. \
+        The credits effect gives the player credits to spend in the shop. \
+        The hud_status effect sets (or clears with nil) the status line at the top of the HUD.",
+    ),
+    func(
+        "set_global_timer",
+        &[param("timeout", "number")],
+        &[],
+        "Call luola_on_global_timer after the given number of seconds",
+    ),
+];
+
+/// Events that can be subscribed to with game.on
+pub const EVENTS: &[LuaFunction] = &[
+    func(
+        "round_start",
+        &[param("settings", "table")],
+        &[],
+        "After the round has been initialized",
+    ),
+    func(
+        "round_end",
+        &[param("winner", "PlayerId")],
+        &[],
+        "When the round end condition has been reached",
+    ),
+    func(
+        "ship_destroyed",
+        &[param("ship", "Ship")],
+        &[],
+        "A ship has been destroyed",
+    ),
+    func(
+        "ship_landed",
+        &[param("ship", "Ship")],
+        &[],
+        "A ship has landed on a base",
+    ),
+    func(
+        "pilot_created",
+        &[param("pilot", "Pilot")],
+        &[],
+        "A pilot has spawned or ejected from a ship",
+    ),
+    func(
+        "critter_destroyed",
+        &[param("critter", "Critter")],
+        &[],
+        "A critter has been destroyed",
+    ),
+    func(
+        "fixedobject_destroyed",
+        &[param("object", "FixedObject")],
+        &[],
+        "A fixed object (such as a turret) has been destroyed",
+    ),
+];
+
+/// Userdata types exposed to scripts
+pub const CLASSES: &[LuaClass] = &[
+    LuaClass {
+        name: "Vec2",
+        doc: "A 2D vector. Supports +, - and multiplication and division by a scalar.",
+        fields: &[ro("x", "number", ""), ro("y", "number", "")],
+        methods: &[
+            func("normalized", &[], &["Vec2"], "Get a unit vector"),
+            func("dist", &[param("other", "Vec2")], &["number"], ""),
+            func("dist_squared", &[param("other", "Vec2")], &["number"], ""),
+            func("angle", &[], &["number"], "Angle of the vector in degrees"),
+            func("magnitude", &[], &["number"], ""),
+        ],
+    },
+    LuaClass {
+        name: "RectF",
+        doc: "A rectangle",
+        fields: &[
+            ro("x", "number", ""),
+            ro("y", "number", ""),
+            ro("w", "number", ""),
+            ro("h", "number", ""),
+        ],
+        methods: &[],
+    },
+    LuaClass {
+        name: "Ship",
+        doc: "A player's ship. Each special weapon slot has its own ammunition: ammo and consume_ammo use the selected slot, drain_ammo the given slot and resupply refills every slot.",
+        fields: &[
+            ro("is_ship", "true", ""),
+            ro("texture", "TextureId", ""),
+            rw("pos", "Vec2", ""),
+            ro("vel", "Vec2", ""),
+            ro("radius", "number", ""),
+            ro("angle", "number", "Facing direction in degrees"),
+            rw("player", "PlayerId", "Owning player"),
+            rw("controller", "integer", "Controller ID"),
+            ro("health", "number", ""),
+            ro("max_health", "number", ""),
+            ro("armour", "number", "Fraction of collision damage absorbed"),
+            ro(
+                "ram_damage",
+                "number",
+                "Multiplier for collision damage inflicted on others",
+            ),
+            rw(
+                "ammo",
+                "number",
+                "Remaining ammunition of the selected special weapon",
+            ),
+            ro("weapon", "integer", "Selected special weapon slot"),
+            ro("weapon_count", "integer", "Number of special weapon slots"),
+            rw("fuel", "number", "Remaining fuel (seconds of full thrust)"),
+            ro(
+                "max_fuel",
+                "number",
+                "Fuel capacity. Zero if the ship doesn't use fuel",
+            ),
+            rw("heat", "number", "Engine and weapon heat level (0-1)"),
+            ro(
+                "overheated",
+                "boolean",
+                "Engine and weapons are disabled until the ship cools down",
+            ),
+            rw("cloaked", "boolean", ""),
+            rw("ghostmode", "boolean", "Ship doesn't collide with terrain"),
+            rw("frozen", "boolean", ""),
+            rw("timer", "number?", "Time until the on_timer callback"),
+            ro("state", "table", "Scripting state"),
+            rw("light", "Light?", "Attached light source"),
+            wo("primary_weapon_cooldown", "number", ""),
+            wo("secondary_weapon_cooldown", "number", ""),
+        ],
+        methods: &[
+            func(
+                "damage",
+                &[param("hp", "number")],
+                &[],
+                "Apply damage to the ship with side effects",
+            ),
+            func(
+                "consume_ammo",
+                &[param("amount", "number"), param("cooldown", "number")],
+                &["boolean"],
+                "Consume ammo of the selected special weapon if there is enough and set the secondary weapon cooldown",
+            ),
+            func(
+                "drain_ammo",
+                &[param("slot", "integer"), param("amount", "number")],
+                &["boolean"],
+                "Consume ammo of the given special weapon slot if there is enough",
+            ),
+            func(
+                "resupply",
+                &[param("amount", "number")],
+                &[],
+                "Add ammo to all special weapon slots",
+            ),
+        ],
+    },
+    LuaClass {
+        name: "Pilot",
+        doc: "An ejected pilot",
+        fields: &[
+            ro("is_pilot", "true", ""),
+            ro("pos", "Vec2", ""),
+            ro("vel", "Vec2", ""),
+            ro("facing", "integer", "-1 or 1"),
+            ro("player", "PlayerId", ""),
+            ro("controller", "integer", ""),
+            ro("health", "number", ""),
+            ro("max_health", "number", ""),
+            ro("state", "table", "Scripting state"),
+            rw("light", "Light?", "Attached light source"),
+            wo("weapon_cooldown", "number", ""),
+            ro(
+                "rope_tangent",
+                "Vec2",
+                "Tangent of the ninjarope. Error if the rope is not attached.",
+            ),
+            rw("timer", "number?", "Time until the on_timer callback"),
+        ],
+        methods: &[
+            func(
+                "aim_vector",
+                &[param("magnitude", "number")],
+                &["Vec2"],
+                "Get the aiming direction",
+            ),
+            func("destroy", &[], &[], ""),
+            func("impulse", &[param("v", "Vec2")], &[], ""),
+            func(
+                "damage",
+                &[param("hp", "number")],
+                &[],
+                "Reduce health. The pilot is not destroyed automatically.",
+            ),
+        ],
+    },
+    LuaClass {
+        name: "Critter",
+        doc: "A scripted critter",
+        fields: &[
+            ro("is_critter", "true", ""),
+            ro("pos", "Vec2", ""),
+            rw("vel", "Vec2", ""),
+            wo("drag", "number", ""),
+            rw("action", "boolean", "Play the action animation"),
+            rw("walking", "integer", "Walking direction (-1, 0 or 1)"),
+            rw("facing", "integer", "-1 or 1"),
+            ro("rope_attached", "boolean", ""),
+            ro("rope_length", "number", ""),
+            rw("texture", "TextureId", ""),
+            ro("id", "integer", ""),
+            ro("owner", "PlayerId", ""),
+            rw("timer", "number?", "Time until the on_timer callback"),
+            ro("state", "table", "Scripting state"),
+        ],
+        methods: &[
+            func("destroy", &[], &[], ""),
+            func("impulse", &[param("v", "Vec2")], &[], ""),
+            func(
+                "attach_rope",
+                &[param("pos", "Vec2")],
+                &[],
+                "Attach a rope to the given point",
+            ),
+            func("detach_rope", &[], &["boolean"], ""),
+            func(
+                "climb_rope",
+                &[param("d", "number")],
+                &[],
+                "Change rope length",
+            ),
+        ],
+    },
+    LuaClass {
+        name: "Projectile",
+        doc: "A bullet or a mine",
+        fields: &[
+            ro("is_projectile", "true", ""),
+            rw("pos", "Vec2", ""),
+            rw("vel", "Vec2", ""),
+            ro("owner", "PlayerId", ""),
+            ro("state", "table", "Scripting state"),
+            wo("texture", "TextureId", ""),
+            wo("color", "Color", ""),
+            rw("light", "Light?", "Attached light source"),
+            rw("timer", "number?", "Time until the on_timer callback"),
+        ],
+        methods: &[
+            func("impulse", &[param("v", "Vec2")], &[], ""),
+            func("destroy", &[], &[], ""),
+            func("disown", &[], &[], "Remove the owner of this projectile"),
+        ],
+    },
+    LuaClass {
+        name: "Hitscan",
+        doc: "An instant hit weapon",
+        fields: &[
+            ro("is_hitscan", "true", ""),
+            ro("start", "Vec2", ""),
+            ro("stop", "Vec2", ""),
+            ro("owner", "PlayerId", ""),
+            ro("terrain", "Terrain", "Type of terrain that was hit"),
+            ro("state", "table", "Scripting state"),
+            ro("vel", "Vec2", "Vector from start to stop"),
+        ],
+        methods: &[],
+    },
+    LuaClass {
+        name: "FixedObject",
+        doc: "A static scripted object, such as a turret",
+        fields: &[
+            rw("pos", "Vec2", ""),
+            rw("angle", "number", ""),
+            rw("texture", "TextureId?", ""),
+            rw("action_texture", "TextureId?", ""),
+            ro("id", "integer", ""),
+            rw("light", "Light?", "Attached light source"),
+            rw("timer", "number?", "Time until the on_timer callback"),
+            ro("state", "table", "Scripting state"),
+        ],
+        methods: &[
+            func("destroy", &[], &[], ""),
+            func("action", &[], &[], "Play the action animation"),
+        ],
+    },
+];
+
+const PROJECTILE_PROPS: &[LuaField] = &[
+    ro("pos", "Vec2", ""),
+    ro("vel", "Vec2", ""),
+    ro("mass?", "number", ""),
+    ro("radius?", "number", ""),
+    ro("drag?", "number", ""),
+    ro(
+        "terrain_collision?",
+        "\"exact\"|\"simple\"|\"passthrough\"",
+        "",
+    ),
+    ro("texture?", "TextureId", ""),
+    ro("owner?", "PlayerId", ""),
+    ro("color?", "Color", ""),
+    ro("waterproof?", "boolean", ""),
+    ro("wind?", "boolean", "Affected by wind"),
+    ro("light?", "Light", ""),
+    ro("state?", "table", "Scripting state"),
+    ro("timer?", "number", ""),
+];
+
+/// Effect types accepted by game.effect
+pub const EFFECTS: &[LuaEffect] = &[
+    LuaEffect {
+        name: "AddBullet",
+        doc: "Add a bullet",
+        props: EffectProps::Table(PROJECTILE_PROPS),
+    },
+    LuaEffect {
+        name: "AddMine",
+        doc: "Add a mine",
+        props: EffectProps::Table(PROJECTILE_PROPS),
+    },
+    LuaEffect {
+        name: "MakeBulletHole",
+        doc: "Destroy a single pixel of terrain",
+        props: EffectProps::Value("Vec2"),
+    },
+    LuaEffect {
+        name: "MakeBigHole",
+        doc: "Destroy a circle of terrain",
+        props: EffectProps::Table(&[
+            ro("pos", "Vec2", ""),
+            ro("r", "integer", "Radius (1-999)"),
+            ro("dust?", "number", "Chance of creating dust particles"),
+        ]),
+    },
+    LuaEffect {
+        name: "AddParticle",
+        doc: "Add a decorative particle",
+        props: EffectProps::Table(&[
+            ro("pos", "Vec2", ""),
+            ro("vel?", "Vec2", ""),
+            ro("a?", "Vec2", "Acceleration"),
+            ro("angle?", "number", ""),
+            ro("color?", "Color", ""),
+            ro("target_color?", "Color", "Color at the end of the lifetime"),
+            ro("texture?", "TextureId", ""),
+            ro("lifetime?", "number", ""),
+            ro(
+                "reveal_in?",
+                "number",
+                "Delay before the particle becomes visible",
+            ),
+            ro("wind?", "boolean", "Affected by wind"),
+            ro("light?", "Light", "Fades out with the particle color"),
+        ]),
+    },
+    LuaEffect {
+        name: "AddTerrainParticle",
+        doc: "Add a particle that turns into terrain",
+        props: EffectProps::Table(&[
+            ro("pos", "Vec2", ""),
+            ro("vel", "Vec2", ""),
+            ro("drag?", "number", ""),
+            ro("texture?", "TextureId", ""),
+            ro(
+                "terrain?",
+                "Terrain",
+                "If zero, the particle won't turn into terrain",
+            ),
+            ro("color?", "Color", ""),
+            ro("wind?", "boolean", "Affected by wind"),
+            ro("stain?", "boolean", "Recolor an existing pixel instead"),
+        ]),
+    },
+    LuaEffect {
+        name: "Emit",
+        doc: "Spawn particles using a preset from particles.toml",
+        props: EffectProps::Table(&[
+            ro("preset", "string", "Name of the particle emitter preset"),
+            ro("pos", "Vec2", ""),
+            ro("vel?", "Vec2", "Velocity added to every particle"),
+            ro(
+                "direction?",
+                "number",
+                "Emission direction in degrees (overrides the preset's)",
+            ),
+            ro("scale?", "number", "Particle count multiplier"),
+            ro("color?", "Color", "Overrides the preset's initial color"),
+        ]),
+    },
+    LuaEffect {
+        name: "AddHitscan",
+        doc: "Fire an instant hit weapon",
+        props: EffectProps::Table(&[
+            ro("start", "Vec2", ""),
+            ro("stop", "Vec2", ""),
+            ro("owner?", "PlayerId", ""),
+            ro("hit_terrain?", "boolean", ""),
+            ro("hit_multiple?", "boolean", ""),
+            ro("state?", "table", "Scripting state"),
+        ]),
+    },
+    LuaEffect {
+        name: "AddDynamicTerrain",
+        doc: "Add a dynamic terrain cell",
+        props: EffectProps::Table(&[
+            ro("pos", "Vec2", ""),
+            ro(
+                "type",
+                "\"Foam\"|\"GreyGoo\"|\"Freezer\"|\"Nitro\"|\"Fire\"|\"Toxin\"",
+                "",
+            ),
+            ro("limit?", "integer", "Spreading limit"),
+            ro("counter?", "integer", ""),
+        ]),
+    },
+    LuaEffect {
+        name: "AddShip",
+        doc: "Add a ship",
+        props: EffectProps::Table(&[
+            ro("pos", "Vec2", ""),
+            ro("vel?", "Vec2", ""),
+            ro("mass?", "number", ""),
+            ro("radius?", "number", ""),
+            ro("drag?", "number", ""),
+            ro("angle?", "number", ""),
+            ro("thrust?", "number", ""),
+            ro("turn_speed?", "number", ""),
+            ro("player?", "PlayerId", ""),
+            ro("controller?", "integer", ""),
+            ro("hitpoints?", "number", ""),
+            ro(
+                "armour?",
+                "number",
+                "Fraction (0-1) of collision damage absorbed (default 0)",
+            ),
+            ro(
+                "ram_damage?",
+                "number",
+                "Multiplier for collision damage inflicted on others (default 1)",
+            ),
+            ro("ammo?", "number", ""),
+            ro(
+                "weapons?",
+                "table",
+                "List of up to 3 special weapon slots: {ammo?, icon?}. Extra slots are ignored",
+            ),
+            ro(
+                "fuel?",
+                "number",
+                "Fuel capacity in seconds of full thrust (default 0: unlimited)",
+            ),
+            ro(
+                "engine_heat?",
+                "number",
+                "Heat generated per second of full thrust (default 0)",
+            ),
+            ro(
+                "weapon_heat?",
+                "number",
+                "Heat generated per primary weapon shot (default 0)",
+            ),
+            ro(
+                "cooling?",
+                "number",
+                "Heat dissipated per second (default 0.25)",
+            ),
+            ro("texture", "TextureId", ""),
+            ro("light?", "Light", ""),
+            ro("state?", "table", "Scripting state"),
+            ro("timer?", "number", ""),
+        ]),
+    },
+    LuaEffect {
+        name: "AddPilot",
+        doc: "Add a pilot",
+        props: EffectProps::Table(&[
+            ro("pos", "Vec2", ""),
+            ro("vel?", "Vec2", ""),
+            ro("radius?", "number", ""),
+            ro("player", "PlayerId", ""),
+            ro("controller", "integer", ""),
+            ro("health?", "number", "Maximum health (default 100)"),
+            ro("stand_texture", "TextureId", ""),
+            ro("jetpack_texture", "TextureId", ""),
+            ro("walk_texture", "TextureId", ""),
+            ro("swim_texture", "TextureId", ""),
+            ro("parachute_texture", "TextureId", ""),
+            ro("light?", "Light", ""),
+            ro("state?", "table", "Scripting state"),
+            ro("timer?", "number", ""),
+        ]),
+    },
+    LuaEffect {
+        name: "AddCritter",
+        doc: "Add a critter",
+        props: EffectProps::Table(&[
+            ro("pos", "Vec2", ""),
+            ro("vel?", "Vec2", ""),
+            ro("mass?", "number", ""),
+            ro("radius?", "number", ""),
+            ro("drag?", "number", ""),
+            ro("id?", "integer", ""),
+            ro("owner?", "PlayerId", ""),
+            ro("waterproof?", "boolean", ""),
+            ro("walking?", "integer", ""),
+            ro("walkspeed?", "number", ""),
+            ro("texture", "TextureId", ""),
+            ro("action_texture?", "TextureId", ""),
+            ro("state?", "table", "Scripting state"),
+            ro("timer?", "number", ""),
+        ]),
+    },
+    LuaEffect {
+        name: "UpdateForcefield",
+        doc: "Add or update a forcefield",
+        props: EffectProps::Table(&[
+            ro("id", "integer", ""),
+            ro("bounds", "RectF", ""),
+            ro("uniform?", "Vec2", "Uniform force"),
+            ro(
+                "point?",
+                "number",
+                "Force towards (or away from) the center",
+            ),
+        ]),
+    },
+    LuaEffect {
+        name: "RemoveForcefield",
+        doc: "Remove the forcefield with the given ID",
+        props: EffectProps::Value("integer"),
+    },
+    LuaEffect {
+        name: "AddFixedObject",
+        doc: "Add a fixed object",
+        props: EffectProps::Table(&[
+            ro("id", "integer", ""),
+            ro("pos", "Vec2", ""),
+            ro("radius?", "number", ""),
+            ro("texture?", "TextureId", ""),
+            ro("action_texture?", "TextureId", ""),
+            ro("color?", "Color", ""),
+            ro("angle?", "number", ""),
+            ro("light?", "Light", ""),
+            ro("state?", "table", "Scripting state"),
+            ro("timer?", "number", ""),
+        ]),
+    },
+    LuaEffect {
+        name: "SetWindspeed",
+        doc: "Change the wind speed",
+        props: EffectProps::Value("number"),
+    },
+    LuaEffect {
+        name: "RegenerateTerrain",
+        doc: "Restore the level's original terrain",
+        props: EffectProps::None,
+    },
+    LuaEffect {
+        name: "EndRound",
+        doc: "End the round with the given winner",
+        props: EffectProps::Value("PlayerId"),
+    },
+    LuaEffect {
+        name: "SetScore",
+        doc: "Set the round's score. The best score of each level is saved as a high score.",
+        props: EffectProps::Value("integer"),
+    },
+];
+
+/// Property tables of player effects
+pub const PLAYER_EFFECT_PROPS: &[(&str, &[LuaField])] = &[
+    (
+        "HudOverlayProps",
+        &[
+            ro("pos", "Vec2", ""),
+            ro("text?", "Text", ""),
+            ro("texture?", "TextureId", ""),
+            ro("align?", "\"topleft\"|\"center\"|\"status\"", ""),
+            ro("scale?", "number", ""),
+            ro("angle?", "number", ""),
+            ro("color?", "Color", ""),
+            ro("lifetime?", "number", ""),
+            ro("fadein?", "number", ""),
+            ro("fadeout?", "number", ""),
+        ],
+    ),
+    (
+        "RumbleProps",
+        &[
+            ro("low?", "number", "Low frequency motor strength"),
+            ro("high?", "number", "High frequency motor strength"),
+            ro("duration", "number", "Duration in seconds"),
+        ],
+    ),
+];
+
+fn write_doc(out: &mut String, doc: &str) {
+    if !doc.is_empty() {
+        writeln!(out, "---{doc}").unwrap();
+    }
+}
+
+fn field_doc(field: &LuaField) -> String {
+    let access = match field.access {
+        FieldAccess::Read | FieldAccess::ReadWrite => "",
+        FieldAccess::Write => "(write only) ",
+    };
+    format!("{access}{}", field.doc)
+}
+
+fn write_fields(out: &mut String, fields: &[LuaField]) {
+    for field in fields {
+        writeln!(
+            out,
+            "---@field {} {} {}",
+            field.name,
+            field.ty,
+            field_doc(field)
+        )
+        .unwrap();
+    }
+}
+
+fn write_function(out: &mut String, prefix: &str, func: &LuaFunction) {
+    write_doc(out, func.doc);
+    for p in func.params {
+        writeln!(out, "---@param {} {}", p.name, p.ty).unwrap();
+    }
+    for r in func.returns {
+        writeln!(out, "---@return {r}").unwrap();
+    }
+    let params: Vec<&str> = func.params.iter().map(|p| p.name).collect();
+    writeln!(
+        out,
+        "function {prefix}{}({}) end\n",
+        func.name,
+        params.join(", ")
+    )
+    .unwrap();
+}
+
+fn quoted_names<'a>(names: impl Iterator<Item = &'a str>) -> String {
+    names
+        .map(|n| format!("\"{n}\""))
+        .collect::<Vec<_>>()
+        .join("|")
+}
+
+/**
+ * Generate LuaLS (EmmyLua) annotations for the whole scripting API
+ */
+pub fn generate_lua_api() -> String {
+    let mut out = String::new();
+
+    out.push_str("---@meta\n");
+    out.push_str("-- Luola2 scripting API definitions.\n");
+    out.push_str("-- This file is generated by `luola2 --lua-api <file>`. Do not edit.\n\n");
+
+    for (name, ty, doc) in ALIASES {
+        write_doc(&mut out, doc);
+        writeln!(out, "---@alias {name} {ty}\n").unwrap();
+    }
+
+    for (name, doc, fields) in TABLE_TYPES {
+        write_doc(&mut out, doc);
+        writeln!(out, "---@class {name}").unwrap();
+        write_fields(&mut out, fields);
+        out.push('\n');
+    }
+
+    // Userdata types
+    for class in CLASSES {
+        write_doc(&mut out, class.doc);
+        writeln!(out, "---@class {}", class.name).unwrap();
+        write_fields(&mut out, class.fields);
+        if class.name == "Vec2" {
+            out.push_str("---@operator add(Vec2): Vec2\n");
+            out.push_str("---@operator sub(Vec2): Vec2\n");
+            out.push_str("---@operator mul(number): Vec2\n");
+            out.push_str("---@operator div(number): Vec2\n");
+        }
+        writeln!(out, "local {} = {{}}\n", class.name).unwrap();
+
+        for method in class.methods {
+            write_function(&mut out, &format!("{}:", class.name), method);
+        }
+    }
+
+    for func in GLOBAL_FUNCTIONS {
+        write_function(&mut out, "", func);
+    }
+
+    out.push_str("textures = {}\n\n");
+    for func in TEXTURE_FUNCTIONS {
+        write_function(&mut out, "textures.", func);
+    }
+
+    // Effect property tables
+    for effect in EFFECTS {
+        if let EffectProps::Table(fields) = effect.props {
+            writeln!(out, "---@class {}Props", effect.name).unwrap();
+            write_fields(&mut out, fields);
+            out.push('\n');
+        }
+    }
+
+    for (name, fields) in PLAYER_EFFECT_PROPS {
+        writeln!(out, "---@class {name}").unwrap();
+        write_fields(&mut out, fields);
+        out.push('\n');
+    }
+
+    writeln!(
+        out,
+        "---@alias EffectType {}\n",
+        quoted_names(EFFECTS.iter().map(|e| e.name))
+    )
+    .unwrap();
+
+    writeln!(
+        out,
+        "---@alias GameEvent {}\n",
+        quoted_names(EVENTS.iter().map(|e| e.name))
+    )
+    .unwrap();
+
+    // The game table
+    out.push_str("---@class GameApi\n");
+    write_fields(&mut out, GAME_FIELDS);
+    out.push_str("game = {}\n\n");
+
+    for func in GAME_FUNCTIONS {
+        match func.name {
+            "effect" => {
+                for effect in EFFECTS {
+                    let props = match effect.props {
+                        EffectProps::None => String::new(),
+                        EffectProps::Value(ty) => format!(", props: {ty}"),
+                        EffectProps::Table(_) => format!(", props: {}Props", effect.name),
+                    };
+                    writeln!(
+                        out,
+                        "---@overload fun(effect: \"{}\"{props}) {}",
+                        effect.name, effect.doc
+                    )
+                    .unwrap();
+                }
+            }
+            "on" => {
+                for event in EVENTS {
+                    let params: Vec<String> = event
+                        .params
+                        .iter()
+                        .map(|p| format!("{}: {}", p.name, p.ty))
+                        .collect();
+                    writeln!(
+                        out,
+                        "---@overload fun(event: \"{}\", handler: fun({})) {}",
+                        event.name,
+                        params.join(", "),
+                        event.doc
+                    )
+                    .unwrap();
+                }
+            }
+            _ => {}
+        }
+        write_function(&mut out, "game.", func);
+    }
+
+    out
+}
+
+/**
+ * Write the API definitions to a file
+ */
+pub fn write_lua_api(path: &Path) -> Result<()> {
+    std::fs::write(path, generate_lua_api())?;
+    Ok(())
+}
+
+/**
+ * Get the names of the members of the game table that are missing from this registry
+ */
+pub fn undocumented_game_members<'a>(names: impl Iterator<Item = &'a str>) -> Vec<&'a str> {
+    names
+        .filter(|name| {
+            !GAME_FUNCTIONS.iter().any(|f| f.name == *name)
+                && !GAME_FIELDS.iter().any(|f| f.name == *name)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_lua_api() {
+        let api = generate_lua_api();
+        assert!(api.starts_with("---@meta\n"));
+        for class in CLASSES {
+            assert!(api.contains(&format!("---@class {}\n", class.name)));
+        }
+        for (name, _, _) in TABLE_TYPES {
+            assert!(api.contains(&format!("---@class {name}\n")));
+        }
+        for effect in EFFECTS {
+            assert!(api.contains(&format!("fun(effect: \"{}\"", effect.name)));
+        }
+        assert!(api.contains("function game.terrain_line(start, stop) end"));
+        assert!(api.contains("function Ship:consume_ammo(amount, cooldown) end"));
+    }
+
+    #[test]
+    fn test_undocumented_game_members() {
+        assert_eq!(
+            undocumented_game_members(["effect", "level_width", "nonexistent"].into_iter()),
+            vec!["nonexistent"]
+        );
+    }
+}
@@ -17,6 +17,7 @@
 pub mod controller;
 mod hud;
 pub mod level;
+pub mod luaapi;
 pub mod objects;
 mod player;
 pub mod script_macros;
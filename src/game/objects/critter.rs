@@ -1,10 +1,10 @@
 use mlua::UserData;
 
 use crate::{
-    call_state_method,
+    call_state_method, emit_event,
     game::{
         PlayerId,
-        level::{LEVEL_SCALE, Level, terrain},
+        level::{Jumper, LEVEL_SCALE, Level, terrain},
         objects::{GameObject, PhysicalObject, Rope, TerrainCollisionMode},
     },
     gameobject_timer, get_state_method,
@@ -215,6 +215,9 @@ impl Critter {
         if !self.destroyed {
             self.destroyed = true;
             call_state_method!(*self, lua, "on_destroy");
+            emit_event!(lua, "critter_destroyed", (scope) => {
+                scope.create_userdata_ref_mut(&mut *self)?
+            });
         }
     }
 
@@ -337,8 +340,9 @@ impl Critter {
     }
 
     /// Execute non-bullet object collision callback.
-    /// Called for collisions with ships and other critters
-    pub fn object_collision<T>(&mut self, obj: &mut T, lua: &mlua::Lua) -> bool
+    /// Called for collisions with ships, pilots and other critters.
+    /// Damage is the impact damage this critter took from the collision.
+    pub fn object_collision<T>(&mut self, obj: &mut T, damage: f32, lua: &mlua::Lua) -> bool
     where
         T: UserData + 'static,
     {
@@ -346,6 +350,7 @@ impl Critter {
             f.call::<Option<bool>>((
                 scope.create_userdata_ref_mut(self)?,
                 scope.create_userdata_ref_mut(obj)?,
+                damage,
             ))
         })
         .unwrap_or(true)
@@ -365,3 +370,15 @@ impl GameObject for Critter {
         self.phys.radius
     }
 }
+
+impl Jumper for Critter {
+    fn vel(&self) -> Vec2 {
+        self.phys.vel
+    }
+
+    fn jump(&mut self, pos: Vec2, vel: Vec2, _rotation: f32) {
+        self.phys.pos = pos;
+        self.phys.vel = vel;
+        self.rope = None;
+    }
+}
@@ -0,0 +1,404 @@
+// This file is part of Luola2
+// Copyright (C) 2025 Calle Laakkonen
+//
+// Luola2 is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Luola2 is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Luola2.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::{
+    collections::HashMap,
+    fs::read_to_string,
+    path::PathBuf,
+    sync::{LazyLock, RwLock},
+};
+
+use anyhow::{Context, Result, anyhow};
+use serde::Deserialize;
+
+use super::{GameObjectArray, Particle, TerrainParticle};
+use crate::{
+    fs::datafile_origin,
+    game::level::terrain::Terrain,
+    gfx::{AnimatedTexture, Color, Light, Renderer, TextureId},
+    math::Vec2,
+};
+
+/// A value that is either fixed or picked randomly from a range
+#[derive(Deserialize, Clone, Copy, Debug)]
+#[serde(untagged)]
+enum ValueRange {
+    Fixed(f32),
+    Range(f32, f32),
+}
+
+impl ValueRange {
+    fn sample(&self) -> f32 {
+        match *self {
+            ValueRange::Fixed(v) => v,
+            ValueRange::Range(min, max) => min + fastrand::f32() * (max - min),
+        }
+    }
+}
+
+/// Type of particles an emitter spawns
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+enum ParticleKind {
+    /// Decorative particles
+    #[default]
+    Decorative,
+    /// Terrain particles that fall down and (optionally) turn into terrain
+    Terrain,
+    /// Terrain particles that recolor the terrain they hit
+    Stain,
+}
+
+#[derive(Deserialize, Clone, Copy, Debug)]
+struct LightToml {
+    radius: f32,
+    #[serde(default = "default_color")]
+    color: u32,
+}
+
+/**
+ * A particle emitter preset as given in particles.toml
+ */
+#[derive(Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+struct EmitterPresetToml {
+    #[serde(default)]
+    kind: ParticleKind,
+
+    /// Number of particles emitted in a burst (or per second for continuous emitters)
+    count: ValueRange,
+
+    /// Emission direction in degrees (same as Vec2_for_angle)
+    #[serde(default)]
+    direction: f32,
+
+    /// Width of the emission arc in degrees
+    #[serde(default = "default_spread")]
+    spread: f32,
+
+    /// Particles are spawned randomly inside a circle of this radius
+    #[serde(default)]
+    radius: f32,
+
+    #[serde(default = "default_zero")]
+    speed: ValueRange,
+
+    /// Particle lifetime. Defaults to the length of the texture animation.
+    lifetime: Option<ValueRange>,
+
+    /// Constant acceleration (e.g. gravity)
+    #[serde(default)]
+    acceleration: (f32, f32),
+
+    /// Initial color (ARGB)
+    #[serde(default = "default_color")]
+    color: u32,
+
+    /// Color at the end of the lifetime
+    target_color: Option<u32>,
+
+    /// Texture name. Particles without a texture are drawn as small squares.
+    texture: Option<String>,
+
+    /// Decorative particles are affected by wind (terrain particles always are)
+    #[serde(default)]
+    wind: bool,
+
+    /// Air resistance (terrain particles only)
+    #[serde(default = "default_drag")]
+    drag: f32,
+
+    /// Terrain type the particles turn into (terrain particles only)
+    #[serde(default)]
+    terrain: Terrain,
+
+    /// Maximum random delay before each particle appears
+    #[serde(default)]
+    delay: f32,
+
+    /// Emit continuously for this many seconds instead of a single burst
+    #[serde(default)]
+    duration: f32,
+
+    light: Option<LightToml>,
+}
+
+fn default_spread() -> f32 {
+    360.0
+}
+
+fn default_zero() -> ValueRange {
+    ValueRange::Fixed(0.0)
+}
+
+fn default_drag() -> f32 {
+    0.002
+}
+
+fn default_color() -> u32 {
+    0xffffffff
+}
+
+/**
+ * A particle emitter preset with its texture resolved
+ */
+#[derive(Clone, Debug)]
+pub struct EmitterPreset {
+    config: EmitterPresetToml,
+    texture: Option<TextureId>,
+}
+
+static PRESETS: LazyLock<RwLock<HashMap<String, EmitterPreset>>> =
+    LazyLock::new(|| RwLock::new(HashMap::new()));
+
+fn read_presets(path: &PathBuf) -> Result<HashMap<String, EmitterPresetToml>> {
+    let content = read_to_string(path).with_context(|| datafile_origin(path))?;
+    toml::from_str(&content).with_context(|| datafile_origin(path))
+}
+
+/**
+ * Load particle emitter presets.
+ *
+ * Presets in files loaded later (i.e. mods) replace earlier ones with the same name.
+ */
+pub fn load_particle_presets(files: &[PathBuf], renderer: &Renderer) -> Result<()> {
+    let mut presets = HashMap::new();
+
+    for file in files {
+        for (name, config) in read_presets(file)? {
+            let texture = match config.texture.as_ref() {
+                Some(t) => Some(
+                    renderer
+                        .texture_store()
+                        .find_texture(t.as_bytes())
+                        .with_context(|| format!("particle preset \"{name}\""))?,
+                ),
+                None => None,
+            };
+
+            presets.insert(name, EmitterPreset { config, texture });
+        }
+    }
+
+    *PRESETS.write().unwrap() = presets;
+    Ok(())
+}
+
+/// Get a particle emitter preset by name
+pub fn find_particle_preset(name: &str) -> Result<EmitterPreset> {
+    PRESETS
+        .read()
+        .unwrap()
+        .get(name)
+        .cloned()
+        .ok_or_else(|| anyhow!("Particle preset \"{name}\" not found"))
+}
+
+/**
+ * A particle emitter.
+ *
+ * Burst emitters spawn all their particles at once and are then done.
+ * Continuous emitters spawn particles at a steady rate until their
+ * duration runs out.
+ */
+#[derive(Clone, Debug)]
+pub struct Emitter {
+    preset: EmitterPreset,
+    pos: Vec2,
+
+    /// Velocity added to every particle
+    vel: Vec2,
+
+    /// Emission direction
+    direction: f32,
+
+    /// Particle count multiplier
+    scale: f32,
+
+    /// Initial color override
+    color: Option<Color>,
+
+    /// Remaining emission time
+    remaining: f32,
+
+    /// Fractional particles carried over to the next step
+    accumulator: f32,
+}
+
+impl Emitter {
+    /// Emit particles for one step. Returns false when the emitter is done.
+    pub fn step(
+        &mut self,
+        timestep: f32,
+        particles: &mut GameObjectArray<Particle>,
+        terrainparticles: &mut GameObjectArray<TerrainParticle>,
+    ) -> bool {
+        let config = &self.preset.config;
+
+        let count = if config.duration > 0.0 {
+            self.accumulator += config.count.sample() * self.scale * timestep;
+            self.remaining -= timestep;
+            let n = self.accumulator.floor();
+            self.accumulator -= n;
+            n as i32
+        } else {
+            self.remaining = 0.0;
+            (config.count.sample() * self.scale).round() as i32
+        };
+
+        for _ in 0..count {
+            let (pos, vel) = self.make_motion();
+            match config.kind {
+                ParticleKind::Decorative => particles.push(self.make_particle(pos, vel)),
+                ParticleKind::Terrain | ParticleKind::Stain => {
+                    terrainparticles.push(TerrainParticle::new_thrown(
+                        pos,
+                        vel,
+                        config.drag,
+                        config.terrain,
+                        config.kind == ParticleKind::Stain,
+                        self.color.unwrap_or(Color::from_argb_u32(config.color)),
+                        self.preset.texture,
+                    ))
+                }
+            }
+        }
+
+        self.remaining > 0.0
+    }
+
+    /// Pick a random starting position and velocity for a particle
+    fn make_motion(&self) -> (Vec2, Vec2) {
+        let config = &self.preset.config;
+
+        let angle = self.direction + (fastrand::f32() - 0.5) * config.spread;
+        let vel = self.vel + Vec2::for_angle(angle, config.speed.sample());
+
+        let pos = if config.radius > 0.0 {
+            self.pos
+                + Vec2::for_angle(
+                    fastrand::f32() * 360.0,
+                    fastrand::f32().sqrt() * config.radius,
+                )
+        } else {
+            self.pos
+        };
+
+        (pos, vel)
+    }
+
+    fn make_particle(&self, pos: Vec2, vel: Vec2) -> Particle {
+        let config = &self.preset.config;
+
+        let lifetime = match (config.lifetime, self.preset.texture) {
+            (Some(l), _) => l.sample().max(0.01),
+            (None, Some(tex)) => tex.frame_duration() * tex.frames().max(1) as f32,
+            (None, None) => 1.0,
+        };
+        let color = self
+            .color
+            .unwrap_or_else(|| Color::from_argb_u32(config.color));
+        let target_color = config
+            .target_color
+            .map(Color::from_argb_u32)
+            .unwrap_or(color);
+
+        Particle {
+            pos,
+            vel,
+            a: Vec2(config.acceleration.0, config.acceleration.1),
+            angle: vel.angle(),
+            reveal_in: fastrand::f32() * config.delay,
+            lifetime,
+            texture: self.preset.texture.map(AnimatedTexture::new),
+            color,
+            dcolor: (target_color - color) / lifetime,
+            wind: config.wind,
+            light: config.light.map(|l| Light {
+                radius: l.radius,
+                color: Color::from_argb_u32(l.color),
+            }),
+        }
+    }
+}
+
+impl mlua::FromLua for Emitter {
+    fn from_lua(value: mlua::Value, _lua: &mlua::Lua) -> mlua::Result<Self> {
+        if let mlua::Value::Table(table) = value {
+            let preset = find_particle_preset(&table.get::<String>("preset")?)?;
+            Ok(Emitter {
+                pos: table.get("pos")?,
+                vel: table.get::<Option<Vec2>>("vel")?.unwrap_or_default(),
+                direction: table
+                    .get::<Option<f32>>("direction")?
+                    .unwrap_or(preset.config.direction),
+                scale: table.get::<Option<f32>>("scale")?.unwrap_or(1.0).max(0.0),
+                color: table.get::<Option<u32>>("color")?.map(Color::from_argb_u32),
+                remaining: preset.config.duration,
+                accumulator: 0.0,
+                preset,
+            })
+        } else {
+            Err(mlua::Error::FromLuaConversionError {
+                from: value.type_name(),
+                to: "Emitter".to_owned(),
+                message: Some("expected a table describing a particle emitter".to_string()),
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preset_parsing() {
+        let presets: HashMap<String, EmitterPresetToml> = toml::from_str(
+            r#"
+            [sparks]
+            count = [8, 12]
+            speed = 300
+            color = 0xffffff00
+            target_color = 0x00ff0000
+
+            [smoke]
+            count = 20
+            duration = 2.0
+            light = { radius = 50 }
+            "#,
+        )
+        .unwrap();
+
+        let sparks = &presets["sparks"];
+        for _ in 0..10 {
+            let c = sparks.count.sample();
+            assert!((8.0..=12.0).contains(&c));
+        }
+        assert_eq!(sparks.speed.sample(), 300.0);
+        assert_eq!(sparks.spread, 360.0);
+
+        let smoke = &presets["smoke"];
+        assert_eq!(smoke.duration, 2.0);
+        assert_eq!(smoke.kind, ParticleKind::Decorative);
+        assert!(smoke.lifetime.is_none());
+        assert_eq!(smoke.light.unwrap().color, 0xffffffff);
+
+        assert!(
+            toml::from_str::<HashMap<String, EmitterPresetToml>>("[x]\ncount=1\ncolour=1").is_err()
+        );
+    }
+}
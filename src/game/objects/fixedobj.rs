@@ -1,10 +1,12 @@
 use mlua::UserData;
 
 use crate::{
-    call_state_method,
+    call_state_method, emit_event,
     game::objects::PhysicalObject,
     gameobject_timer, get_state_method,
-    gfx::{AnimatedTexture, Color, RenderDest, RenderMode, RenderOptions, Renderer, TextureId},
+    gfx::{
+        AnimatedTexture, Color, Light, RenderDest, RenderMode, RenderOptions, Renderer, TextureId,
+    },
     math::Vec2,
 };
 
@@ -26,6 +28,9 @@ pub struct FixedObject {
     action: bool,
     angle: f32,
 
+    /// Light emitted by this object (used in levels with lighting enabled)
+    light: Option<Light>,
+
     /// Flag that is set when the object is moved via scripting
     moved: bool,
 
@@ -54,6 +59,7 @@ impl mlua::FromLua for FixedObject {
                     table.get::<Option<u32>>("color")?.unwrap_or(0xffffffff),
                 ),
                 angle: table.get::<Option<f32>>("angle")?.unwrap_or_default(),
+                light: table.get("light")?,
                 destroyed: false,
                 moved: false,
                 state: table.get("state")?,
@@ -100,6 +106,11 @@ impl mlua::UserData for FixedObject {
             Ok(())
         });
         fields.add_field_method_get("id", |_, this| Ok(this.id));
+        fields.add_field_method_get("light", |_, this| Ok(this.light));
+        fields.add_field_method_set("light", |_, this, light: Option<Light>| {
+            this.light = light;
+            Ok(())
+        });
         fields.add_field_method_get("timer", |_, this| Ok(this.timer));
         fields.add_field_method_set("timer", |_, this, timeout: Option<f32>| {
             this.timer = timeout;
@@ -126,10 +137,17 @@ impl FixedObject {
         self.id
     }
 
+    pub fn light(&self) -> Option<Light> {
+        self.light
+    }
+
     pub fn destroy(&mut self, lua: &mlua::Lua) {
         if !self.destroyed {
             self.destroyed = true;
             call_state_method!(*self, lua, "on_destroy");
+            emit_event!(lua, "fixedobject_destroyed", (scope) => {
+                scope.create_userdata_ref_mut(&mut *self)?
+            });
         }
     }
 
@@ -2,7 +2,7 @@ use crate::{
     call_state_method,
     game::{
         PlayerId,
-        level::{Level, TerrainLineHit, terrain::Terrain},
+        level::{Level, TerrainLineHit, terrain::Terrain, wrap},
         objects::GameObject,
     },
     get_state_method,
@@ -137,7 +137,10 @@ impl HitscanProjectile {
     {
         // Note: normal object collisions involve two radiuses, whereas the hitscan
         // beam has a radius of zero. To compensate for the difference in feel, we buff up the target radius.
-        Self::circle_linesegment(self.start, self.stop, obj.pos(), obj.radius() * 2.0)
+        // In a wrapping level, check against the copy of the object nearest to the beam
+        let mid = (self.start + self.stop) * 0.5;
+        let pos = mid + wrap::wrapped_delta(mid, obj.pos());
+        Self::circle_linesegment(self.start, self.stop, pos, obj.radius() * 2.0)
     }
 
     /// Check for a hit against this object and execute callback if hit_multiple is true
@@ -15,6 +15,7 @@
 // along with Luola2.  If not, see <https://www.gnu.org/licenses/>.
 
 mod critter;
+mod emitter;
 mod fixedobj;
 mod hitscan;
 mod objarray;
@@ -27,6 +28,7 @@ mod ship;
 mod terpart;
 
 pub use critter::*;
+pub use emitter::*;
 pub use fixedobj::*;
 pub use hitscan::*;
 pub use objarray::*;
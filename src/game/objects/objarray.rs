@@ -14,8 +14,11 @@
 // You should have received a copy of the GNU General Public License
 // along with Luola2.  If not, see <https://www.gnu.org/licenses/>.
 
-use std::{marker::PhantomData, ptr::NonNull, slice};
+use std::{iter, marker::PhantomData, ptr::NonNull, slice};
 
+use smallvec::SmallVec;
+
+use crate::game::level::wrap;
 use crate::math::Vec2;
 
 /**
@@ -23,6 +26,9 @@ use crate::math::Vec2;
  */
 pub struct GameObjectArray<T>(Vec<T>);
 
+/// Iterator over the objects in a (possibly wrapped) horizontal range
+pub type RangeIterMut<'a, T> = iter::Flatten<smallvec::IntoIter<[&'a mut [T]; 3]>>;
+
 pub trait GameObject {
     /// Return the center of this object
     fn pos(&self) -> Vec2;
@@ -68,8 +74,58 @@ impl<T: GameObject> GameObjectArray<T> {
         &mut self.0[start..end]
     }
 
-    pub fn collider_slice_mut(&mut self, obj: &impl GameObject) -> &mut [T] {
-        self.range_slice_mut(obj.pos().0 - obj.radius(), obj.pos().0 + obj.radius())
+    /// Find the index ranges of the objects in the given horizontal range,
+    /// including the ones on the other side of a wraparound edge.
+    /// The returned ranges are sorted and do not overlap.
+    fn find_wrapped_slices(&self, left: f32, right: f32) -> SmallVec<[(usize, usize); 3]> {
+        let mut ranges: SmallVec<[(usize, usize); 3]> = wrap::wrapped_ranges(left, right)
+            .iter()
+            .map(|&(l, r, _)| self.find_potential_collider_slice(l, r))
+            .filter(|(start, end)| start < end)
+            .collect();
+        ranges.sort_unstable();
+
+        let mut merged: SmallVec<[(usize, usize); 3]> = SmallVec::new();
+        for (start, end) in ranges {
+            if let Some(last) = merged.last_mut()
+                && start <= last.1
+            {
+                last.1 = last.1.max(end);
+            } else {
+                merged.push((start, end));
+            }
+        }
+        merged
+    }
+
+    /// Like range_slice, but also returns objects that are in range across a wraparound edge
+    pub fn range_iter(&self, left: f32, right: f32) -> impl Iterator<Item = &T> {
+        self.find_wrapped_slices(left, right)
+            .into_iter()
+            .flat_map(|(start, end)| &self.0[start..end])
+    }
+
+    /// Like range_slice_mut, but also returns objects that are in range across a wraparound edge
+    pub fn range_iter_mut(&mut self, left: f32, right: f32) -> RangeIterMut<'_, T> {
+        let ranges = self.find_wrapped_slices(left, right);
+
+        let mut slices: SmallVec<[&mut [T]; 3]> = SmallVec::new();
+        let mut rest = &mut self.0[..];
+        let mut consumed = 0;
+        for (start, end) in ranges {
+            let (_, tail) = std::mem::take(&mut rest).split_at_mut(start - consumed);
+            let (slice, tail) = tail.split_at_mut(end - start);
+            slices.push(slice);
+            rest = tail;
+            consumed = end;
+        }
+
+        slices.into_iter().flatten()
+    }
+
+    /// Get the objects potentially colliding with the given object
+    pub fn colliders_mut(&mut self, obj: &impl GameObject) -> RangeIterMut<'_, T> {
+        self.range_iter_mut(obj.pos().0 - obj.radius(), obj.pos().0 + obj.radius())
     }
 
     pub fn iter(&self) -> std::slice::Iter<'_, T> {
@@ -104,8 +160,8 @@ impl<T: GameObject> GameObjectArray<T> {
     ) -> Option<&T> {
         let mut nearest_dist2 = range * range;
         let mut nearest: Option<&T> = None;
-        for item in self.range_slice(pos.0 - range, pos.0 + range) {
-            let dd = item.pos().dist_squared(pos);
+        for item in self.range_iter(pos.0 - range, pos.0 + range) {
+            let dd = wrap::wrapped_delta(pos, item.pos()).magnitude_squared();
             if dd < nearest_dist2 && predicate(item) {
                 nearest_dist2 = dd;
                 nearest = Some(item);
@@ -141,8 +197,11 @@ impl<T: GameObject> GameObjectArray<T> {
 }
 
 pub struct GameObjectTailedIterMut<'a, T> {
+    start: NonNull<T>,
     ptr: NonNull<T>,
     remaining: usize,
+    len: usize,
+    wrap_width: f32,
     _marker: PhantomData<&'a T>,
 }
 
@@ -151,17 +210,24 @@ impl<'a, T> GameObjectTailedIterMut<'a, T> {
         let len = objects.len();
         let ptr = NonNull::from_ref(objects).cast();
         Self {
+            start: ptr,
             ptr,
             remaining: len,
+            len,
+            wrap_width: wrap::wrap_size().0,
             _marker: PhantomData,
         }
     }
 }
 
 impl<'a, T: GameObject> Iterator for GameObjectTailedIterMut<'a, T> {
-    type Item = (&'a mut T, &'a mut [T]);
+    type Item = (
+        &'a mut T,
+        iter::Chain<slice::IterMut<'a, T>, slice::IterMut<'a, T>>,
+    );
     fn next(&mut self) -> Option<Self::Item> {
         if self.remaining > 0 {
+            let head_index = self.len - self.remaining;
             let head = unsafe { self.ptr.as_mut() };
 
             let right_edge = head.pos().0 + head.radius();
@@ -178,9 +244,100 @@ impl<'a, T: GameObject> Iterator for GameObjectTailedIterMut<'a, T> {
                 .count();
 
             let tail = &mut tail[0..potentials];
-            Some((head, tail))
+
+            // In a wrapping level, objects at the start of the array may also
+            // be colliding with objects at the right edge of the level.
+            // Only objects before the head are included, so no pair is repeated.
+            let wrapped = if self.wrap_width > 0.0 {
+                let head_slice =
+                    unsafe { slice::from_raw_parts_mut(self.start.as_ptr(), head_index) };
+                let potentials = head_slice
+                    .iter()
+                    .take_while(|obj| obj.pos().0 + self.wrap_width - obj.radius() <= right_edge)
+                    .count();
+                &mut head_slice[0..potentials]
+            } else {
+                &mut []
+            };
+
+            Some((head, tail.iter_mut().chain(wrapped.iter_mut())))
         } else {
             None
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::level::{LevelWrap, wrap::set_wraparound};
+
+    struct TestObject {
+        x: f32,
+        hits: i32,
+    }
+
+    impl GameObject for TestObject {
+        fn pos(&self) -> Vec2 {
+            Vec2(self.x, 0.0)
+        }
+
+        fn radius(&self) -> f32 {
+            5.0
+        }
+
+        fn is_destroyed(&self) -> bool {
+            false
+        }
+    }
+
+    fn make_array(xs: &[f32]) -> GameObjectArray<TestObject> {
+        let mut array = GameObjectArray::new();
+        for &x in xs {
+            array.push(TestObject { x, hits: 0 });
+        }
+        array.sort();
+        array
+    }
+
+    fn range_xs(array: &GameObjectArray<TestObject>, left: f32, right: f32) -> Vec<f32> {
+        array.range_iter(left, right).map(|o| o.x).collect()
+    }
+
+    #[test]
+    fn test_range_iter_wraparound() {
+        let mut array = make_array(&[990.0, 10.0, 500.0, 50.0, 950.0]);
+
+        // The wrap size is global, so all wrapping checks are in this one test
+        set_wraparound(LevelWrap::Horizontal, 1000.0, 500.0);
+
+        // Ranges crossing the seam from either side
+        assert_eq!(range_xs(&array, -20.0, 20.0), [10.0, 990.0]);
+        assert_eq!(range_xs(&array, 980.0, 1020.0), [10.0, 990.0]);
+        assert_eq!(range_xs(&array, 400.0, 600.0), [500.0]);
+
+        // Overlapping ranges don't return an object twice
+        assert_eq!(
+            range_xs(&array, -550.0, 50.0),
+            [10.0, 50.0, 500.0, 950.0, 990.0]
+        );
+        assert_eq!(
+            range_xs(&array, -100.0, 1100.0),
+            [10.0, 50.0, 500.0, 950.0, 990.0]
+        );
+
+        for obj in array.range_iter_mut(-20.0, 20.0) {
+            obj.hits += 1;
+        }
+        for obj in array.range_iter_mut(-550.0, 50.0) {
+            obj.hits += 1;
+        }
+        let hits: Vec<i32> = array.iter().map(|o| o.hits).collect();
+        assert_eq!(hits, [2, 1, 1, 1, 2]);
+
+        set_wraparound(LevelWrap::None, 1000.0, 500.0);
+
+        assert_eq!(range_xs(&array, -20.0, 20.0), [10.0]);
+        assert_eq!(array.range_iter_mut(980.0, 1020.0).count(), 1);
+    }
+}
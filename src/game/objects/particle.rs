@@ -17,7 +17,7 @@
 use crate::{
     game::objects::GameObject,
     gfx::{
-        AnimatedTexture, Color, ColorDiff, RenderDest, RenderMode, RenderOptions, Renderer,
+        AnimatedTexture, Color, ColorDiff, Light, RenderDest, RenderMode, RenderOptions, Renderer,
         TextureId,
     },
     math::{RectF, Vec2},
@@ -40,6 +40,7 @@ pub struct Particle {
     pub color: Color,
     pub dcolor: ColorDiff,
     pub wind: bool,
+    pub light: Option<Light>,
 }
 
 impl Particle {
@@ -62,6 +63,14 @@ impl Particle {
         self.color = self.color + self.dcolor * timestep;
     }
 
+    /// Get the light emitted by this particle. The light fades out with the particle.
+    pub fn light(&self) -> Option<Light> {
+        if self.reveal_in > 0.0 {
+            return None;
+        }
+        self.light.map(|l| l.dimmed(self.color.a))
+    }
+
     pub fn render(&self, renderer: &Renderer, camera_pos: Vec2) {
         if self.reveal_in <= 0.0 {
             if let Some(tex) = &self.texture {
@@ -117,6 +126,7 @@ impl mlua::FromLua for Particle {
                 color,
                 dcolor,
                 wind: table.get::<Option<bool>>("wind")?.unwrap_or(false),
+                light: table.get("light")?,
             })
         } else {
             Err(mlua::Error::FromLuaConversionError {
@@ -14,11 +14,24 @@
 // You should have received a copy of the GNU General Public License
 // along with Luola2.  If not, see <https://www.gnu.org/licenses/>.
 
-use crate::game::{level::Level, level::TerrainLineHit, level::terrain};
+use crate::game::{level::Level, level::TerrainLineHit, level::terrain, level::wrap};
 use crate::math::{LineF, Vec2};
 
 pub const SCALE_FACTOR: f32 = 50.0;
 
+/// Change of velocity below which impacts cause no damage
+const IMPACT_DAMAGE_THRESHOLD: f32 = 300.0;
+
+/// Damage per unit of velocity change above the threshold
+const IMPACT_DAMAGE_SCALE: f32 = 0.1;
+
+/**
+ * Calculate the damage caused by a sudden change of velocity (e.g. ramming another ship)
+ */
+fn impact_damage(delta_v: f32) -> f32 {
+    ((delta_v - IMPACT_DAMAGE_THRESHOLD) * IMPACT_DAMAGE_SCALE).max(0.0)
+}
+
 #[derive(Clone, Debug)]
 pub enum TerrainCollisionMode {
     Exact,       // check every pixel on the line from old to new position
@@ -102,7 +115,7 @@ impl PhysicalObject {
         let new_pos = self.pos + self.vel * timestep;
 
         // Object already embedded in ground?
-        if terrain::is_solid(old_terrain) && !self.terrain_collision_mode.is_none() {
+        let terrain = if terrain::is_solid(old_terrain) && !self.terrain_collision_mode.is_none() {
             self.vel = Vec2::ZERO;
             (old_terrain, old_terrain)
         } else {
@@ -170,7 +183,12 @@ impl PhysicalObject {
                     }
                 },
             )
-        }
+        };
+
+        // Objects that went over a wrapping edge come back from the other side
+        self.pos = wrap::wrap_position(self.pos);
+
+        terrain
     }
 
     pub fn add_impulse(&mut self, impulse: Vec2) {
@@ -185,7 +203,7 @@ impl PhysicalObject {
      * to balance the forces.
      */
     pub fn check_collision(&self, other: &PhysicalObject) -> Option<Vec2> {
-        let distv = self.pos - other.pos;
+        let distv = wrap::wrapped_delta(other.pos, self.pos);
         let dd = distv.dot(distv);
         let r = self.radius + other.radius;
 
@@ -214,11 +232,21 @@ impl PhysicalObject {
         Some(normal * j)
     }
 
+    /**
+     * Calculate the damage this object takes from a collision impulse.
+     *
+     * Damage is based on the change of velocity, so of two colliding objects,
+     * the lighter one takes more damage.
+     */
+    pub fn collision_damage(&self, impulse: Vec2) -> f32 {
+        impact_damage(impulse.magnitude() * self.imass)
+    }
+
     /**
      * Collision check without impulse calculation
      */
     pub fn check_overlap(&self, other: &PhysicalObject) -> bool {
-        let distv = self.pos - other.pos;
+        let distv = wrap::wrapped_delta(other.pos, self.pos);
         let dd = distv.dot(distv);
         let r = self.radius + other.radius;
 
@@ -4,11 +4,11 @@ use crate::{
     call_state_method,
     game::{
         GameController, PlayerId,
-        level::{LEVEL_SCALE, Level, TerrainLineHit, terrain},
-        objects::{GameObject, PhysicalObject, Rope, Ship, TerrainCollisionMode},
+        level::{Jumper, LEVEL_SCALE, Level, TerrainLineHit, terrain},
+        objects::{Critter, GameObject, PhysicalObject, Rope, Ship, TerrainCollisionMode},
     },
     gameobject_timer, get_state_method,
-    gfx::{AnimatedTexture, Color, RenderDest, RenderMode, RenderOptions, Renderer, TexAlt},
+    gfx::{AnimatedTexture, Color, Light, RenderDest, RenderMode, RenderOptions, Renderer, TexAlt},
     math::{LineF, Vec2},
 };
 
@@ -18,6 +18,9 @@ pub struct Pilot {
     player_id: PlayerId,
     controller: i32,
     destroyed: bool,
+    /// Remaining health. Scripts decide what happens when it runs out.
+    health: f32,
+    max_health: f32,
     jetpack_charge: f32,
     state: Option<mlua::Table>,
     stand_texture: AnimatedTexture,
@@ -35,6 +38,7 @@ pub struct Pilot {
     /// for ninjarope activation.
     fire3_down: bool,
     weapon_cooldown: f32,
+    light: Option<Light>,
     timer: Option<f32>,
     timer_accumulator: f32,
 }
@@ -64,7 +68,14 @@ impl mlua::UserData for Pilot {
         fields.add_field_method_get("facing", |_, this| Ok(this.facing));
         fields.add_field_method_get("player", |_, this| Ok(this.player_id));
         fields.add_field_method_get("controller", |_, this| Ok(this.controller));
+        fields.add_field_method_get("health", |_, this| Ok(this.health));
+        fields.add_field_method_get("max_health", |_, this| Ok(this.max_health));
         fields.add_field_method_get("state", |_, this| Ok(this.state.clone()));
+        fields.add_field_method_get("light", |_, this| Ok(this.light));
+        fields.add_field_method_set("light", |_, this, light: Option<Light>| {
+            this.light = light;
+            Ok(())
+        });
 
         fields.add_field_method_set("weapon_cooldown", |_, this, cooldown| {
             this.weapon_cooldown = cooldown;
@@ -98,12 +109,17 @@ impl mlua::UserData for Pilot {
             this.phys.add_impulse(v);
             Ok(())
         });
+        methods.add_method_mut("damage", |_, this, hp: f32| {
+            this.damage(hp);
+            Ok(())
+        });
     }
 }
 
 const NORMAL_DRAG: f32 = 0.015;
 const PARACHUTE_DRAG: f32 = 0.7;
-const DANGER_SPEED: f32 = 500.0;
+/// Landing faster than this hurts (exposed to scripts as game.pilot_danger_speed)
+pub const PILOT_DANGER_SPEED: f32 = 500.0;
 const NINJAROPE_SPEED: f32 = 800.0;
 const NINJAROPE_MAX_LEN: f32 = 300.0;
 const NINJAROPE_CLIMB_SPEED: f32 = 200.0;
@@ -111,6 +127,7 @@ const NINJAROPE_CLIMB_SPEED: f32 = 200.0;
 impl mlua::FromLua for Pilot {
     fn from_lua(value: mlua::Value, _lua: &mlua::Lua) -> mlua::Result<Self> {
         if let mlua::Value::Table(table) = value {
+            let health = table.get::<Option<f32>>("health")?.unwrap_or(100.0);
             Ok(Pilot {
                 phys: PhysicalObject {
                     pos: table.get("pos")?,
@@ -124,6 +141,8 @@ impl mlua::FromLua for Pilot {
                 player_id: table.get("player")?,
                 controller: table.get("controller")?,
                 destroyed: false,
+                health,
+                max_health: health,
                 jetpack_charge: 1.0,
                 state: table.get("state")?,
                 stand_texture: AnimatedTexture::new(table.get("stand_texture")?),
@@ -139,6 +158,7 @@ impl mlua::FromLua for Pilot {
                 ninjarope: NinjaRope::Stowed,
                 fire3_down: false,
                 weapon_cooldown: 0.0,
+                light: table.get("light")?,
                 timer: table.get("timer")?,
                 timer_accumulator: 0.0,
             })
@@ -157,6 +177,14 @@ impl Pilot {
         &self.phys
     }
 
+    pub fn physics_mut(&mut self) -> &mut PhysicalObject {
+        &mut self.phys
+    }
+
+    pub fn light(&self) -> Option<Light> {
+        self.light
+    }
+
     pub fn player_id(&self) -> PlayerId {
         self.player_id
     }
@@ -173,6 +201,19 @@ impl Pilot {
         self.jetpack_charge
     }
 
+    /// Get health as a fraction of the maximum
+    pub fn health(&self) -> f32 {
+        if self.max_health <= 0.0 {
+            // A pilot created with zero health has no health to show
+            return 0.0;
+        }
+        self.health.max(0.0) / self.max_health
+    }
+
+    pub fn damage(&mut self, hp: f32) {
+        self.health = (self.health - hp).min(self.max_health);
+    }
+
     const GUN_OFFSET: Vec2 = Vec2(0.0, -16.0);
 
     pub fn aim_vector(&self, mag: f32, force_manual: bool) -> Vec2 {
@@ -249,7 +290,9 @@ impl Pilot {
         lua: &mlua::Lua,
         timestep: f32,
     ) {
-        let (_old_ter, ter) = self.phys.step(level, timestep);
+        let fall_speed = self.phys.vel.1;
+        let old_pos = self.phys.pos;
+        let (old_ter, ter) = self.phys.step(level, timestep);
 
         if let NinjaRope::Attached(rope) = &self.ninjarope {
             debug_assert!(matches!(self.mode, MotionMode::Ninjaroping));
@@ -259,9 +302,18 @@ impl Pilot {
         self.walk_texture.step(timestep);
         self.swim_texture.step(timestep);
 
-        if self.phys.vel.1 > DANGER_SPEED {
-            // TODO call script
-            println!("Falling dangerously fast: {}", self.phys.vel);
+        // Hitting the ground too fast hurts. A parachute or water breaks the fall.
+        // Only ground under the pilot's feet counts: the fall alone must have reached it,
+        // so being thrown into a wall isn't a landing.
+        if fall_speed > PILOT_DANGER_SPEED
+            && terrain::is_solid(ter)
+            && !terrain::is_solid(old_ter)
+            && terrain::is_solid(
+                level.terrain_at(old_pos + Vec2(0.0, fall_speed * timestep + LEVEL_SCALE)),
+            )
+            && !matches!(self.mode, MotionMode::Parachuting)
+        {
+            call_state_method!(*self, lua, "on_hard_landing", fall_speed);
         }
 
         // Hitting the ground ends parachute mode
@@ -465,6 +517,19 @@ impl Pilot {
         });
     }
 
+    /// Execute critter collision callback.
+    /// Returns true if the pilot stomped on the critter, in which case
+    /// the critter doesn't get to react to the collision.
+    pub fn touch_critter(&mut self, critter: &mut Critter, lua: &mlua::Lua) -> bool {
+        get_state_method!(self, lua, "on_touch_critter", (f, scope) => {
+            f.call::<Option<bool>>((
+                scope.create_userdata_ref_mut(self)?,
+                scope.create_userdata_ref_mut(critter)?,
+            ))
+        })
+        .unwrap_or(false)
+    }
+
     pub fn render(&self, renderer: &Renderer, camera_pos: Vec2) {
         let tex = match self.mode {
             MotionMode::Standing => &self.stand_texture,
@@ -520,3 +585,19 @@ impl GameObject for Pilot {
         self.phys.radius
     }
 }
+
+impl Jumper for Pilot {
+    fn vel(&self) -> Vec2 {
+        self.phys.vel
+    }
+
+    fn jump(&mut self, pos: Vec2, vel: Vec2, _rotation: f32) {
+        if vel.0.signum() != self.phys.vel.0.signum() {
+            self.facing = -self.facing;
+        }
+        self.phys.pos = pos;
+        self.phys.vel = vel;
+        // The rope can't follow through a gate
+        self.ninjarope = NinjaRope::Stowed;
+    }
+}
@@ -20,13 +20,15 @@ use mlua::{self, Table};
 use crate::{
     game::{
         level::{
-            Level,
+            Jumper, Level,
             terrain::{self, Terrain},
         },
         objects::{GameObject, TerrainCollisionMode},
     },
     gameobject_timer, get_state_method,
-    gfx::{AnimatedTexture, Color, RenderDest, RenderMode, RenderOptions, Renderer, TextureId},
+    gfx::{
+        AnimatedTexture, Color, Light, RenderDest, RenderMode, RenderOptions, Renderer, TextureId,
+    },
     math::Vec2,
 };
 
@@ -54,6 +56,7 @@ pub struct Projectile {
     destroyed: bool,
     hit_water: bool,
     wind: bool,
+    light: Option<Light>,
     state: Option<Table>,
     timer: Option<f32>,
     timer_accumulator: f32,
@@ -100,6 +103,7 @@ impl mlua::FromLua for Projectile {
                 destroyed: false,
                 hit_water: !table.get::<Option<bool>>("waterproof")?.unwrap_or(true),
                 wind: table.get::<Option<bool>>("wind")?.unwrap_or(false),
+                light: table.get("light")?,
                 state: table.get("state")?,
                 timer: table.get("timer")?,
                 timer_accumulator: 0.0,
@@ -138,6 +142,11 @@ impl mlua::UserData for Projectile {
             this.color = Color::from_argb_u32(c);
             Ok(())
         });
+        fields.add_field_method_get("light", |_, this| Ok(this.light));
+        fields.add_field_method_set("light", |_, this, light: Option<Light>| {
+            this.light = light;
+            Ok(())
+        });
         fields.add_field_method_get("timer", |_, this| Ok(this.timer));
         fields.add_field_method_set("timer", |_, this, timeout: Option<f32>| {
             this.timer = timeout;
@@ -168,6 +177,10 @@ impl Projectile {
         &self.phys
     }
 
+    pub fn light(&self) -> Option<Light> {
+        self.light
+    }
+
     pub fn owner(&self) -> i32 {
         self.owner
     }
@@ -252,3 +265,14 @@ impl GameObject for Projectile {
         self.destroyed
     }
 }
+
+impl Jumper for Projectile {
+    fn vel(&self) -> Vec2 {
+        self.phys.vel
+    }
+
+    fn jump(&mut self, pos: Vec2, vel: Vec2, _rotation: f32) {
+        self.phys.pos = pos;
+        self.phys.vel = vel;
+    }
+}
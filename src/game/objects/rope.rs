@@ -1,5 +1,5 @@
 use crate::{
-    game::objects::PhysicalObject,
+    game::{level::wrap, objects::PhysicalObject},
     gfx::{Color, Renderer},
     math::{LineF, Vec2},
 };
@@ -17,7 +17,7 @@ impl Rope {
     pub fn new(startpoint: Vec2, endpoint: Vec2) -> Self {
         Self {
             endpoint,
-            length: startpoint.dist(endpoint),
+            length: wrap::wrapped_delta(startpoint, endpoint).magnitude(),
         }
     }
 
@@ -40,7 +40,9 @@ impl Rope {
         );
     }
     pub fn render(&self, other_end: Vec2, renderer: &Renderer, camera_pos: Vec2) {
-        Self::render_rope(other_end, self.endpoint, renderer, camera_pos);
+        // The endpoint may be on the other side of a wraparound edge
+        let end = other_end + wrap::wrapped_delta(other_end, self.endpoint);
+        Self::render_rope(other_end, end, renderer, camera_pos);
     }
 
     /**
@@ -52,7 +54,7 @@ impl Rope {
         // and the coefficients are all 1, so the spring has infinite
         // stiffness.
 
-        let dist = phys.pos - self.endpoint;
+        let dist = wrap::wrapped_delta(self.endpoint, phys.pos);
         let unit = dist.normalized();
 
         let d_err = unit.dot(dist) - self.length;
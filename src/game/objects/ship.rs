@@ -16,14 +16,53 @@
 
 use log::error;
 use mlua::{Function, Lua, Table, UserData};
+use smallvec::SmallVec;
 
 use super::{GameObject, PhysicalObject, SCALE_FACTOR, TerrainCollisionMode};
 use crate::game::PlayerId;
 use crate::game::controller::GameController;
-use crate::game::level::{Level, terrain};
-use crate::gfx::{Color, RenderDest, RenderMode, RenderOptions, Renderer, TexAlt, TextureId};
+use crate::game::level::{Jumper, Level, terrain};
+use crate::gfx::{
+    Color, Light, RenderDest, RenderMode, RenderOptions, Renderer, TexAlt, TextureId,
+};
 use crate::math::Vec2;
-use crate::{call_state_method, gameobject_timer};
+use crate::{call_state_method, emit_event, gameobject_timer, get_state_method};
+
+/// Maximum number of special weapons a ship can carry
+pub const MAX_SECONDARY_WEAPONS: usize = 3;
+
+/**
+ * A special weapon slot.
+ *
+ * The weapon itself is implemented by the on_fire_secondary script callback,
+ * which is swapped when switching weapons. The slot keeps track of the
+ * weapon's ammo and cooldown.
+ */
+#[derive(Clone, Debug)]
+pub struct SecondaryWeapon {
+    /// Number of ammo units remaining
+    ammo: f32,
+
+    /// Maximum ammo
+    max_ammo: f32,
+
+    /// Number of seconds the weapon is still on cooldown
+    cooldown: f32,
+
+    /// Icon shown in the HUD
+    icon: Option<TextureId>,
+}
+
+impl SecondaryWeapon {
+    fn new(ammo: f32, icon: Option<TextureId>) -> Self {
+        Self {
+            ammo,
+            max_ammo: ammo,
+            cooldown: 0.0,
+            icon,
+        }
+    }
+}
 
 /**
  * A ship that can be piloted by a player.
@@ -68,17 +107,41 @@ pub struct Ship {
     /// Maximum health
     max_hitpoints: f32,
 
+    /// Fraction (0-1) of collision damage absorbed by armour plating
+    armour: f32,
+
+    /// Multiplier for the collision damage this ship inflicts on others
+    ram_damage: f32,
+
     /// Number of seconds the primary weapon is still on cooldown
     primary_weapon_cooldown: f32,
 
-    /// Number of seconds the secondary weapon is still on cooldown
-    secondary_weapon_cooldown: f32,
+    /// Special weapon slots. There is always at least one
+    weapons: SmallVec<[SecondaryWeapon; MAX_SECONDARY_WEAPONS]>,
 
-    /// Number of ammo units remaining
-    ammo_remaining: f32,
+    /// Index of the selected special weapon
+    selected_weapon: usize,
 
-    /// Maximum ammo
-    max_ammo: f32,
+    /// Remaining fuel (in seconds of full thrust)
+    fuel: f32,
+
+    /// Fuel tank capacity. Zero if the ship doesn't use fuel.
+    max_fuel: f32,
+
+    /// Heat level between 0..1
+    heat: f32,
+
+    /// Heat generated per second of full thrust
+    engine_heat: f32,
+
+    /// Heat generated per primary weapon shot
+    weapon_heat: f32,
+
+    /// Heat dissipated per second
+    cooling: f32,
+
+    /// Heat reached maximum: engine and weapons are disabled until the ship has cooled down
+    overheated: bool,
 
     /// Timer for damage effect
     damage_effect: f32,
@@ -96,10 +159,16 @@ pub struct Ship {
     /// Ship is frozen. Special rendering mode is used and controls are locked
     frozen: bool,
 
+    /// Ship was on a base during the previous step. Used to detect landing.
+    landed: bool,
+
     /// Is the fire2 button being held down? Used to detect leading-edge input event
     /// for weapons that should fire only once per trigger pull.
     fire2_down: bool,
 
+    /// Is the fire3 (switch weapon) button being held down?
+    fire3_down: bool,
+
     /// Object scheduler
     timer: Option<f32>,
     timer_accumulator: f32,
@@ -109,6 +178,9 @@ pub struct Ship {
 
     /// Texture to draw the ship with
     texture: TextureId,
+
+    /// Light source attached to the ship (used in levels with lighting enabled)
+    light: Option<Light>,
 }
 
 impl UserData for Ship {
@@ -135,11 +207,28 @@ impl UserData for Ship {
         });
         fields.add_field_method_get("health", |_, this| Ok(this.hitpoints));
         fields.add_field_method_get("max_health", |_, this| Ok(this.max_hitpoints));
-        fields.add_field_method_get("ammo", |_, this| Ok(this.ammo_remaining));
+        fields.add_field_method_get("armour", |_, this| Ok(this.armour));
+        fields.add_field_method_get("ram_damage", |_, this| Ok(this.ram_damage));
+        fields.add_field_method_get("ammo", |_, this| Ok(this.weapon().ammo));
         fields.add_field_method_set("ammo", |_, this, ammo: f32| {
-            this.ammo_remaining = ammo.clamp(0.0, this.max_ammo);
+            let weapon = this.weapon_mut();
+            weapon.ammo = ammo.clamp(0.0, weapon.max_ammo);
+            Ok(())
+        });
+        fields.add_field_method_get("weapon", |_, this| Ok(this.selected_weapon + 1));
+        fields.add_field_method_get("weapon_count", |_, this| Ok(this.weapons.len()));
+        fields.add_field_method_get("fuel", |_, this| Ok(this.fuel));
+        fields.add_field_method_set("fuel", |_, this, fuel: f32| {
+            this.fuel = fuel.clamp(0.0, this.max_fuel);
             Ok(())
         });
+        fields.add_field_method_get("max_fuel", |_, this| Ok(this.max_fuel));
+        fields.add_field_method_get("heat", |_, this| Ok(this.heat));
+        fields.add_field_method_set("heat", |_, this, heat: f32| {
+            this.heat = heat.clamp(0.0, 1.0);
+            Ok(())
+        });
+        fields.add_field_method_get("overheated", |_, this| Ok(this.overheated));
         fields.add_field_method_get("cloaked", |_, this| Ok(this.cloaked));
         fields.add_field_method_set("cloaked", |_, this, c: bool| {
             this.cloaked = c;
@@ -162,12 +251,17 @@ impl UserData for Ship {
             Ok(())
         });
         fields.add_field_method_get("state", |_, this| Ok(this.state.clone()));
+        fields.add_field_method_get("light", |_, this| Ok(this.light));
+        fields.add_field_method_set("light", |_, this, light: Option<Light>| {
+            this.light = light;
+            Ok(())
+        });
         fields.add_field_method_set("primary_weapon_cooldown", |_, this, cooldown| {
             this.primary_weapon_cooldown = cooldown;
             Ok(())
         });
         fields.add_field_method_set("secondary_weapon_cooldown", |_, this, cooldown| {
-            this.secondary_weapon_cooldown = cooldown;
+            this.weapon_mut().cooldown = cooldown;
             Ok(())
         });
     }
@@ -180,15 +274,39 @@ impl UserData for Ship {
         });
         // Consume ammo if there is enough and set secondary weapon cooldown..
         methods.add_method_mut("consume_ammo", |_, this, (amount, cooldown): (f32, f32)| {
-            let a = this.ammo_remaining - amount;
+            let weapon = this.weapon_mut();
+            let a = weapon.ammo - amount;
             Ok(if a < 0.0 {
                 false
             } else {
-                this.ammo_remaining = a;
-                this.secondary_weapon_cooldown = cooldown;
+                weapon.ammo = a;
+                weapon.cooldown = cooldown;
                 true
             })
         });
+        // Consume ammo of the given special weapon slot (not necessarily the selected one)
+        // if there is enough. Weapons with a lasting effect use this to drain the slot
+        // they were activated from, even if the player has since switched weapons.
+        methods.add_method_mut("drain_ammo", |_, this, (slot, amount): (usize, f32)| {
+            let weapon = slot
+                .checked_sub(1)
+                .and_then(|idx| this.weapons.get_mut(idx))
+                .ok_or_else(|| mlua::Error::runtime(format!("invalid weapon slot {slot}")))?;
+            let a = weapon.ammo - amount;
+            Ok(if a < 0.0 {
+                false
+            } else {
+                weapon.ammo = a;
+                true
+            })
+        });
+        // Add ammo to all special weapons
+        methods.add_method_mut("resupply", |_, this, amount: f32| {
+            for weapon in this.weapons.iter_mut() {
+                weapon.ammo = (weapon.ammo + amount).min(weapon.max_ammo);
+            }
+            Ok(())
+        });
     }
 }
 
@@ -197,6 +315,31 @@ impl mlua::FromLua for Ship {
         if let mlua::Value::Table(table) = value {
             let hitpoints = table.get::<Option<f32>>("hitpoints")?.unwrap_or(100.0);
             let ammo = table.get::<Option<f32>>("ammo")?.unwrap_or(100.0);
+            let weapon_slots = table
+                .get::<Option<Vec<Table>>>("weapons")?
+                .unwrap_or_default();
+            if weapon_slots.len() > MAX_SECONDARY_WEAPONS {
+                error!(
+                    "Ship has {} special weapons, only the first {MAX_SECONDARY_WEAPONS} can be used",
+                    weapon_slots.len()
+                );
+            }
+            let weapons: SmallVec<[SecondaryWeapon; MAX_SECONDARY_WEAPONS]> =
+                if weapon_slots.is_empty() {
+                    smallvec::smallvec![SecondaryWeapon::new(ammo, None)]
+                } else {
+                    weapon_slots
+                        .iter()
+                        .take(MAX_SECONDARY_WEAPONS)
+                        .map(|w| {
+                            Ok(SecondaryWeapon::new(
+                                w.get::<Option<f32>>("ammo")?.unwrap_or(ammo),
+                                w.get("icon")?,
+                            ))
+                        })
+                        .collect::<mlua::Result<_>>()?
+                };
+            let fuel = table.get::<Option<f32>>("fuel")?.unwrap_or(0.0).max(0.0);
             Ok(Ship {
                 phys: PhysicalObject {
                     pos: table.get("pos")?,
@@ -214,18 +357,32 @@ impl mlua::FromLua for Ship {
                 controller: table.get::<Option<i32>>("controller")?.unwrap_or(0),
                 hitpoints,
                 max_hitpoints: hitpoints,
+                armour: table
+                    .get::<Option<f32>>("armour")?
+                    .unwrap_or(0.0)
+                    .clamp(0.0, 1.0),
+                ram_damage: table.get::<Option<f32>>("ram_damage")?.unwrap_or(1.0),
                 primary_weapon_cooldown: 0.0,
-                secondary_weapon_cooldown: 0.0,
-                ammo_remaining: ammo,
-                max_ammo: ammo,
+                weapons,
+                selected_weapon: 0,
+                fuel,
+                max_fuel: fuel,
+                heat: 0.0,
+                engine_heat: table.get::<Option<f32>>("engine_heat")?.unwrap_or(0.0),
+                weapon_heat: table.get::<Option<f32>>("weapon_heat")?.unwrap_or(0.0),
+                cooling: table.get::<Option<f32>>("cooling")?.unwrap_or(0.25),
+                overheated: false,
                 damage_effect: 0.0,
                 state: table.get("state")?,
                 texture: table.get("texture")?,
+                light: table.get("light")?,
                 destroyed: false,
                 cloaked: false,
                 ghostmode: false,
                 frozen: false,
+                landed: false,
                 fire2_down: false,
+                fire3_down: false,
                 timer: table.get("timer")?,
                 timer_accumulator: 0.0,
             })
@@ -248,6 +405,11 @@ impl Ship {
         &mut self.phys
     }
 
+    /// Get the light source attached to this ship. Cloaked ships do not emit light.
+    pub fn light(&self) -> Option<Light> {
+        if self.cloaked { None } else { self.light }
+    }
+
     pub fn player_id(&self) -> PlayerId {
         self.player_id
     }
@@ -260,12 +422,59 @@ impl Ship {
         self.hitpoints.max(0.0) / self.max_hitpoints
     }
 
+    /// Initialize the landed state from the spawn position,
+    /// so a ship spawned on a base doesn't count as having just landed
+    pub fn init_landed(&mut self, level: &Level) {
+        self.landed = terrain::is_effective_base(level.terrain_at(self.pos()));
+    }
+
+    fn weapon(&self) -> &SecondaryWeapon {
+        &self.weapons[self.selected_weapon]
+    }
+
+    fn weapon_mut(&mut self) -> &mut SecondaryWeapon {
+        &mut self.weapons[self.selected_weapon]
+    }
+
+    /// Get the ammo fraction of the selected special weapon
     pub fn ammo(&self) -> f32 {
-        self.ammo_remaining / self.max_ammo
+        let weapon = self.weapon();
+        weapon.ammo / weapon.max_ammo
     }
 
     pub fn secondary_weapon_cooldown(&self) -> f32 {
-        self.secondary_weapon_cooldown
+        self.weapon().cooldown
+    }
+
+    /// Get the HUD icons of the special weapons and the index of the selected one
+    pub fn weapon_icons(&self) -> ([Option<TextureId>; MAX_SECONDARY_WEAPONS], usize, usize) {
+        let mut icons = [None; MAX_SECONDARY_WEAPONS];
+        for (icon, weapon) in icons.iter_mut().zip(&self.weapons) {
+            *icon = weapon.icon;
+        }
+        (icons, self.weapons.len(), self.selected_weapon)
+    }
+
+    /// Get the remaining fuel fraction, or None if this ship doesn't use fuel
+    pub fn fuel(&self) -> Option<f32> {
+        if self.max_fuel > 0.0 {
+            Some(self.fuel / self.max_fuel)
+        } else {
+            None
+        }
+    }
+
+    /// Get the heat level, or None if this ship doesn't heat up
+    pub fn heat(&self) -> Option<f32> {
+        if self.engine_heat > 0.0 || self.weapon_heat > 0.0 {
+            Some(self.heat)
+        } else {
+            None
+        }
+    }
+
+    pub fn is_overheated(&self) -> bool {
+        self.overheated
     }
 
     pub fn is_wrecked(&self) -> bool {
@@ -276,6 +485,34 @@ impl Ship {
         self.cloaked
     }
 
+    pub fn ram_damage(&self) -> f32 {
+        self.ram_damage
+    }
+
+    /**
+     * Handle a collision with another object.
+     *
+     * The impulse is the collision impulse returned by check_collision and the multiplier
+     * is the other party's ram damage multiplier. If the impact was hard enough to cause
+     * damage, the on_collision callback is called with the other object and the damage.
+     * Applying the damage is up to the script.
+     */
+    pub fn collide<T>(&mut self, other: &mut T, impulse: Vec2, multiplier: f32, lua: &Lua)
+    where
+        T: UserData + 'static,
+    {
+        let damage = self.phys.collision_damage(impulse) * multiplier * (1.0 - self.armour);
+        if damage > 0.0 {
+            get_state_method!(self, lua, "on_collision", (f, scope) => {
+                f.call::<Option<bool>>((
+                    scope.create_userdata_ref_mut(self)?,
+                    scope.create_userdata_ref_mut(other)?,
+                    damage,
+                ))
+            });
+        }
+    }
+
     /**
      * Inflict damage on this ship.
      *
@@ -299,6 +536,9 @@ impl Ship {
         if !self.destroyed {
             self.destroyed = true;
             call_state_method!(*self, lua, "on_destroyed");
+            emit_event!(lua, "ship_destroyed", (scope) => {
+                scope.create_userdata_ref_mut(&mut *self)?
+            });
         }
     }
 
@@ -329,7 +569,14 @@ impl Ship {
         {
             ship.angle += ship.turn_speed * controller.turn * timestep;
 
-            if controller.thrust > 0.0 {
+            let has_fuel = self.max_fuel <= 0.0 || self.fuel > 0.0;
+
+            if controller.thrust > 0.0 && has_fuel && !self.overheated {
+                if ship.max_fuel > 0.0 {
+                    ship.fuel = (ship.fuel - controller.thrust * timestep).max(0.0);
+                }
+                ship.heat += ship.engine_heat * controller.thrust * timestep;
+
                 ship.phys.vel = ship.phys.vel
                     + Vec2::for_angle(
                         -ship.angle,
@@ -357,6 +604,18 @@ impl Ship {
             ship.damage_effect -= timestep;
         }
 
+        // Heat builds up faster than it dissipates when the engine or weapon is in use.
+        // Once the maximum is reached, the ship must cool down completely before it can
+        // thrust or shoot again.
+        if ship.heat >= 1.0 && !ship.overheated {
+            ship.overheated = true;
+            call_state_method!(ship, lua, "on_overheat");
+        }
+        ship.heat = (ship.heat - ship.cooling * timestep).clamp(0.0, 1.0);
+        if ship.overheated && ship.heat <= 0.0 {
+            ship.overheated = false;
+        }
+
         if terrain::is_underwater(prev_ter) != is_underwater {
             // Water/air transition
             match lua.globals().get::<Function>("luola_splash") {
@@ -372,7 +631,10 @@ impl Ship {
             }
         }
 
-        if terrain::is_effective_base(ter) {
+        let was_landed = ship.landed;
+        ship.landed = terrain::is_effective_base(ter);
+
+        if ship.landed {
             // Bases forcibly orient the ship
             if terrain::is_underwater(ter) {
                 ship.angle = 270.0;
@@ -383,6 +645,12 @@ impl Ship {
             // Repair/resupply logic is implemented in scripts to allow
             // for differences between ship types and so we can do special effects.
             call_state_method!(ship, lua, "on_base", timestep, is_underwater);
+
+            if !was_landed {
+                emit_event!(lua, "ship_landed", (scope) => {
+                    scope.create_userdata_ref_mut(&mut ship)?
+                });
+            }
         } else if terrain::is_greygoo(ter) {
             call_state_method!(ship, lua, "on_touch_greygoo");
         } else if terrain::is_damaging(ter) {
@@ -424,15 +692,18 @@ impl Ship {
                     ship.primary_weapon_cooldown -= timestep;
                 }
 
-                if ship.secondary_weapon_cooldown > 0.0 {
-                    ship.secondary_weapon_cooldown -= timestep;
+                for weapon in ship.weapons.iter_mut() {
+                    if weapon.cooldown > 0.0 {
+                        weapon.cooldown -= timestep;
+                    }
                 }
 
-                if controller.fire1 && ship.primary_weapon_cooldown <= 0.0 {
+                if controller.fire1 && ship.primary_weapon_cooldown <= 0.0 && !ship.overheated {
                     call_state_method!(ship, lua, "on_fire_primary");
+                    ship.heat += ship.weapon_heat;
                 }
 
-                if controller.fire2 && ship.secondary_weapon_cooldown <= 0.0 {
+                if controller.fire2 && ship.weapon().cooldown <= 0.0 && !ship.overheated {
                     // second parameter is true on leading edge of trigger pull
                     call_state_method!(
                         ship,
@@ -442,7 +713,15 @@ impl Ship {
                     );
                 }
 
+                if controller.fire3 && !self.fire3_down && ship.weapons.len() > 1 {
+                    ship.selected_weapon = (ship.selected_weapon + 1) % ship.weapons.len();
+                    // Script is responsible for swapping the on_fire_secondary function
+                    let slot = ship.selected_weapon + 1;
+                    call_state_method!(ship, lua, "on_switch_weapon", slot);
+                }
+
                 ship.fire2_down = fire2_down;
+                ship.fire3_down = controller.fire3;
             }
         }
 
@@ -510,3 +789,16 @@ impl GameObject for Ship {
         self.destroyed
     }
 }
+
+impl Jumper for Ship {
+    fn vel(&self) -> Vec2 {
+        self.phys.vel
+    }
+
+    fn jump(&mut self, pos: Vec2, vel: Vec2, rotation: f32) {
+        self.phys.pos = pos;
+        self.phys.vel = vel;
+        // Ship angle is measured in the opposite direction
+        self.angle -= rotation;
+    }
+}
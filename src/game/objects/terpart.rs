@@ -101,6 +101,26 @@ impl TerrainParticle {
         }
     }
 
+    /// Create a terrain particle flying in the given direction
+    pub fn new_thrown(
+        pos: Vec2,
+        vel: Vec2,
+        drag: f32,
+        terrain: Terrain,
+        stain: bool,
+        color: Color,
+        texture: Option<TextureId>,
+    ) -> Self {
+        let mut p = Self::new(pos, terrain, texture, color);
+        p.phys.vel = vel;
+        p.phys.drag = drag;
+        p.stain = stain;
+        if stain {
+            p.phys.terrain_collision_mode = TerrainCollisionMode::Passthrough;
+        }
+        p
+    }
+
     pub fn physics(&self) -> &PhysicalObject {
         &self.phys
     }
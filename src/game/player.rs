@@ -14,10 +14,15 @@
 // You should have received a copy of the GNU General Public License
 // along with Luola2.  If not, see <https://www.gnu.org/licenses/>.
 
-use serde::Deserialize;
+use std::collections::HashMap;
 
+use serde::{Deserialize, Deserializer, de::Error as _};
+
+use crate::game::KEYBOARDS;
 use crate::game::hud::{HudOverlay, PlayerHud};
 use crate::game::level::LevelCoordinate;
+use crate::gfx::Text;
+use crate::lang::tr;
 use crate::math::{Rect, Vec2};
 
 #[derive(Deserialize)]
@@ -31,18 +36,101 @@ pub struct GameInitConfig {
 
     #[serde(default)]
     pub winners: Vec<PlayerId>,
+
+    #[serde(default)]
+    pub rules: GameRules,
+}
+
+/**
+ * Optional game rules that apply to all rounds of a game.
+ *
+ * The rules are passed to the game init script, which decides how to apply them.
+ */
+#[derive(Deserialize, Clone, Copy, Default, Debug)]
+pub struct GameRules {
+    /// Ships have a limited amount of fuel that is refilled on bases
+    #[serde(default)]
+    pub fuel: bool,
+
+    /// Ship engines and weapons heat up and must cool down when overheated
+    #[serde(default)]
+    pub heat: bool,
+
+    /// Players earn credits during rounds and can spend them in the shop between rounds
+    #[serde(default)]
+    pub economy: bool,
+
+    /// All players play together against waves of critters
+    #[serde(default)]
+    pub horde: bool,
+}
+
+impl GameRules {
+    fn from_bits(bits: u32) -> Self {
+        Self {
+            fuel: bits & 1 != 0,
+            heat: bits & 2 != 0,
+            economy: bits & 4 != 0,
+            horde: bits & 8 != 0,
+        }
+    }
+
+    fn bits(&self) -> u32 {
+        self.fuel as u32
+            | (self.heat as u32) << 1
+            | (self.economy as u32) << 2
+            | (self.horde as u32) << 3
+    }
+
+    /// Cycle through the combinations of rules
+    pub fn next(self) -> Self {
+        Self::from_bits((self.bits() + 1) % 16)
+    }
+
+    /// Cycle through the combinations of rules in reverse order
+    pub fn prev(self) -> Self {
+        Self::from_bits((self.bits() + 15) % 16)
+    }
+
+    /// A human readable (and translated) description of the active rules
+    pub fn description(&self) -> String {
+        let mut rules = Vec::new();
+        if self.fuel {
+            rules.push(tr("LIMITED FUEL"));
+        }
+        if self.heat {
+            rules.push(tr("OVERHEATING"));
+        }
+        if self.economy {
+            rules.push(tr("SHOP"));
+        }
+        if self.horde {
+            rules.push(tr("HORDE"));
+        }
+
+        if rules.is_empty() {
+            tr("STANDARD RULES")
+        } else {
+            rules.join(", ")
+        }
+    }
 }
 
 #[derive(Deserialize, Clone)]
 pub struct Player {
-    /// Controller ID
+    /// Controller ID.
+    /// In config files, the controller is given as "keyboardN" or "gamepadN".
+    /// Plain numbers are read using the numbering of older versions.
+    #[serde(deserialize_with = "deserialize_controller")]
     pub controller: i32,
 
     /// Ship name passed to init script
     pub ship: String,
 
-    /// Special weapon name passed to init script
-    pub weapon: String,
+    /// Special weapon loadout passed to init script.
+    /// The old single `weapon` setting is accepted as a one-weapon loadout.
+    #[serde(default, alias = "weapon", deserialize_with = "deserialize_loadout")]
+    pub weapons: Vec<String>,
 
     /// Player spawn point in level coordinates (if unspecified, a random spawn point will be used)
     pub spawn: Option<LevelCoordinate>,
@@ -50,6 +138,14 @@ pub struct Player {
     /// Pilot spawn point in level coordinates (if specified, pilot will start outside the ship)
     pub pilot_spawn: Option<LevelCoordinate>,
 
+    /// Credits available to spend in the shop
+    #[serde(default)]
+    pub credits: i32,
+
+    /// Shop items bought by this player and their counts
+    #[serde(default)]
+    pub purchases: HashMap<String, i32>,
+
     /// Number of rounds won by this player
     #[serde(skip)]
     pub wins: i32,
@@ -59,16 +155,75 @@ pub struct Player {
     pub viewport: Rect,
 }
 
+/// Deserialize a weapon loadout from a list of weapon names or a single name
+fn deserialize_loadout<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<String>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Loadout {
+        Single(String),
+        List(Vec<String>),
+    }
+
+    Ok(match Loadout::deserialize(deserializer)? {
+        Loadout::Single(weapon) => vec![weapon],
+        Loadout::List(weapons) => weapons,
+    })
+}
+
+/// Number of keyboard controllers in older versions.
+/// Gamepad controller IDs used to start right after these.
+const LEGACY_KEYBOARDS: i32 = 4;
+
+/// Deserialize a controller ID from a "keyboardN"/"gamepadN" name
+/// or from a number using the old controller numbering
+fn deserialize_controller<'de, D: Deserializer<'de>>(deserializer: D) -> Result<i32, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum ControllerId {
+        Legacy(i32),
+        Named(String),
+    }
+
+    match ControllerId::deserialize(deserializer)? {
+        ControllerId::Legacy(id) if id < 1 => Err(D::Error::custom("invalid controller ID")),
+        ControllerId::Legacy(id) if id <= LEGACY_KEYBOARDS => Ok(id),
+        ControllerId::Legacy(id) => Ok(id - LEGACY_KEYBOARDS + KEYBOARDS as i32),
+        ControllerId::Named(name) => {
+            let parse = |prefix: &str| {
+                name.strip_prefix(prefix)
+                    .and_then(|n| n.parse::<i32>().ok())
+                    .filter(|&n| n > 0)
+            };
+            if let Some(n) = parse("keyboard")
+                && n <= KEYBOARDS as i32
+            {
+                Ok(n)
+            } else if let Some(n) = parse("gamepad") {
+                Ok(KEYBOARDS as i32 + n)
+            } else {
+                Err(D::Error::custom(format!("invalid controller: {name}")))
+            }
+        }
+    }
+}
+
 pub type PlayerId = i32;
 
+/// Maximum number of players in a game
+pub const MAX_PLAYERS: usize = 8;
+
 impl Player {
     pub fn new(controller: i32) -> Self {
         Self {
             controller,
             ship: String::new(),
-            weapon: String::new(),
+            weapons: Vec::new(),
             spawn: None,
             pilot_spawn: None,
+            credits: 0,
+            purchases: HashMap::new(),
             wins: 0,
             viewport: Rect::new(0, 0, 1, 1),
         }
@@ -81,6 +236,12 @@ pub struct PlayerState {
     pub hud: PlayerHud,
     pub overlays: Vec<HudOverlay>,
 
+    /// Status line shown at the top of the HUD (set by scripts)
+    pub status: Option<Text>,
+
+    /// Credits earned during the current round
+    pub credits: f32,
+
     /// Draw fadeout between 0..1
     pub fadeout: f32,
 }
@@ -91,7 +252,78 @@ impl PlayerState {
             camera_pos: Vec2::ZERO,
             hud: PlayerHud::None,
             overlays: Vec::new(),
+            status: None,
+            credits: 0.0,
             fadeout: 0.0,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rule_cycle() {
+        let mut rules = GameRules::default();
+        let mut seen = Vec::new();
+        for _ in 0..16 {
+            seen.push(rules.bits());
+            assert_eq!(rules.next().prev().bits(), rules.bits());
+            rules = rules.next();
+        }
+        seen.sort();
+        seen.dedup();
+        assert_eq!(seen.len(), 16);
+        assert_eq!(rules.bits(), GameRules::default().bits());
+        assert_eq!(GameRules::default().description(), "STANDARD RULES");
+    }
+
+    #[test]
+    fn test_player_loadout() {
+        let player: Player =
+            toml::from_str("controller = 1\nship = \"vwing\"\nweapons = [\"laser\", \"mine\"]")
+                .unwrap();
+        assert_eq!(player.weapons, ["laser", "mine"]);
+
+        let player: Player =
+            toml::from_str("controller = 1\nship = \"vwing\"\nweapon = \"rocket\"").unwrap();
+        assert_eq!(player.weapons, ["rocket"]);
+
+        let player: Player = toml::from_str("controller = 1\nship = \"vwing\"").unwrap();
+        assert!(player.weapons.is_empty());
+    }
+
+    #[test]
+    fn test_player_controller() {
+        let controller = |value: &str| {
+            toml::from_str::<Player>(&format!("controller = {value}\nship = \"vwing\""))
+                .map(|p| p.controller)
+        };
+
+        assert_eq!(controller("\"keyboard1\"").unwrap(), 1);
+        assert_eq!(controller("\"keyboard8\"").unwrap(), KEYBOARDS as i32);
+        assert_eq!(controller("\"gamepad1\"").unwrap(), KEYBOARDS as i32 + 1);
+        assert!(controller("\"keyboard9\"").is_err());
+        assert!(controller("\"gamepad0\"").is_err());
+        assert!(controller("\"joystick1\"").is_err());
+        assert!(controller("0").is_err());
+    }
+
+    #[test]
+    fn test_legacy_launch_config() {
+        // Launch file written when there were four keyboard controllers
+        // and the first gamepad was controller 5
+        let config: GameInitConfig = toml::from_str(
+            "level = \"demo\"\n\
+             [[player]]\ncontroller = 4\nship = \"vwing\"\n\
+             [[player]]\ncontroller = 5\nship = \"vwing\"\n\
+             [[player]]\ncontroller = 6\nship = \"vwing\"\n",
+        )
+        .unwrap();
+
+        let controllers: Vec<i32> = config.players.iter().map(|p| p.controller).collect();
+        let gamepad = KEYBOARDS as i32;
+        assert_eq!(controllers, [4, gamepad + 1, gamepad + 2]);
+    }
+}
@@ -72,3 +72,23 @@ macro_rules! gameobject_timer {
         }
     };
 }
+
+/// Call all handlers subscribed to a game event (see `game.on`.)
+/// Errors are logged and do not prevent the remaining handlers from being called.
+#[macro_export]
+macro_rules! emit_event {
+    ($lua:expr, $event:literal, ($scope:ident) => $args:expr) => {
+        for handler in $crate::game::scripting::event_handlers($lua, $event) {
+            if let Err(err) = $lua.scope(|$scope| handler.call::<()>($args)) {
+                log::error!(concat!($event, " event handler: {}"), err);
+            }
+        }
+    };
+    ($lua:expr, $event:literal, $args:expr) => {
+        for handler in $crate::game::scripting::event_handlers($lua, $event) {
+            if let Err(err) = handler.call::<()>($args) {
+                log::error!(concat!($event, " event handler: {}"), err);
+            }
+        }
+    };
+}
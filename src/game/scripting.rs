@@ -16,30 +16,153 @@
 
 use core::ops::Deref;
 use std::fs::read_to_string;
-use std::{cell::RefCell, path::Path, rc::Rc};
+use std::{
+    cell::{Cell, RefCell},
+    path::Path,
+    rc::Rc,
+};
 
 use anyhow::{Result, anyhow};
 use log::error;
-use mlua::{FromLua, Function, Lua, Result as LuaResult, String as LuaString, Table, Value};
+use mlua::{
+    ChunkMode, ErrorContext, FromLua, Function, HookTriggers, Lua, Result as LuaResult,
+    String as LuaString, Table, Value, VmState,
+};
 
 use crate::configfile::GAME_CONFIG;
-use crate::fs::find_datafile_path;
+use crate::fs::{enabled_mods, find_datafile_path};
 use crate::game::hud::HudOverlay;
-use crate::game::level::{DynamicTerrainCell, Forcefield, Level, TerrainLineHit};
+use crate::game::level::{DynamicTerrainCell, Forcefield, Level, TerrainLineHit, wrap};
+use crate::game::luaapi;
 use crate::game::objects::{
-    Critter, FixedObject, GameObject, GameObjectArray, HitscanProjectile, Particle, Pilot,
-    Projectile, Ship, TerrainParticle,
+    Critter, Emitter, FixedObject, GameObject, GameObjectArray, HitscanProjectile,
+    PILOT_DANGER_SPEED, Particle, Pilot, Projectile, Ship, TerrainParticle,
 };
 use crate::game::world::WorldEffect;
 use crate::game::{GameControllerSet, PlayerId, PlayerState};
-use crate::gfx::{Color, Renderer};
+use crate::gfx::{Color, Renderer, Text};
+use crate::lang;
 use crate::math::{LineF, RectF, Vec2};
 
+/// Maximum number of Lua instructions that may be executed during a single game tick.
+/// If exceeded, the script is assumed to be stuck in an infinite loop.
+const INSTRUCTION_LIMIT: u32 = 50_000_000;
+
+/// How often the instruction limit hook is called
+const INSTRUCTION_CHECK_INTERVAL: u32 = 10_000;
+
+/// Standard library functions available to level and mod scripts
+const SANDBOX_FUNCTIONS: &[&str] = &[
+    "_VERSION",
+    "assert",
+    "error",
+    "ipairs",
+    "next",
+    "pairs",
+    "rawequal",
+    "rawlen",
+    "select",
+    "setmetatable",
+    "tonumber",
+    "tostring",
+    "type",
+];
+
+/// Standard libraries that level and mod scripts get their own copies of
+const SANDBOX_LIBRARIES: &[&str] = &["coroutine", "math", "string", "table", "utf8"];
+
+/// Functions of the os module that level scripts are allowed to use
+const SANDBOX_OS_FUNCTIONS: &[&str] = &["clock", "date", "difftime", "time"];
+
+/// Standard library globals that the sandbox never looks up from the engine's globals.
+/// (print is not listed, so the developer console can redirect it.)
+const LUA_STANDARD_GLOBALS: &[&str] = &[
+    "_G",
+    "_VERSION",
+    "assert",
+    "collectgarbage",
+    "coroutine",
+    "debug",
+    "dofile",
+    "error",
+    "getmetatable",
+    "io",
+    "ipairs",
+    "load",
+    "loadfile",
+    "math",
+    "next",
+    "os",
+    "package",
+    "pairs",
+    "pcall",
+    "rawequal",
+    "rawget",
+    "rawlen",
+    "rawset",
+    "require",
+    "select",
+    "setmetatable",
+    "string",
+    "table",
+    "tonumber",
+    "tostring",
+    "type",
+    "utf8",
+    "warn",
+    "xpcall",
+];
+
+/// Protected call functions for the sandbox.
+/// Errors caught after the instruction limit has been exceeded are raised again,
+/// so a script can't keep itself running by catching the instruction limit error.
+const SANDBOX_PROTECTED_CALLS: &str = r#"
+local pcall, xpcall, resume, close, error, over_limit = ...
+local function check(ok, ...)
+    if not ok and over_limit() then
+        error((...), 0)
+    end
+    return ok, ...
+end
+return function(...) return check(pcall(...)) end,
+    function(...) return check(xpcall(...)) end,
+    function(...) return check(resume(...)) end,
+    function(...) return check(close(...)) end
+"#;
+
+/// Registry key of the game event handler table
+const EVENT_HANDLERS_KEY: &str = "luola_event_handlers";
+
+/// Get the list of handlers subscribed to the named game event
+pub fn event_handlers(lua: &Lua, event: &str) -> Vec<Function> {
+    let handlers = match lua.named_registry_value::<Option<Table>>(EVENT_HANDLERS_KEY) {
+        Ok(Some(h)) => h,
+        Ok(None) => return Vec::new(),
+        Err(err) => {
+            error!("Couldn't get event handler table: {err}");
+            return Vec::new();
+        }
+    };
+
+    match handlers.get::<Option<Table>>(event) {
+        Ok(Some(list)) => list.sequence_values::<Function>().flatten().collect(),
+        Ok(None) => Vec::new(),
+        Err(err) => {
+            error!("Couldn't get {event} event handlers: {err}");
+            Vec::new()
+        }
+    }
+}
+
+/// Instruction counter shared with the sandboxed protected call functions
+struct InstructionCount(Rc<Cell<u32>>);
+
 pub struct ScriptEnvironment {
     lua: Lua,
     effect_accumulator: Rc<RefCell<Vec<WorldEffect>>>,
     global_timer: Rc<RefCell<Option<f32>>>,
     global_timer_accumulator: f32,
+    instruction_count: Rc<Cell<u32>>,
 }
 
 impl ScriptEnvironment {
@@ -48,7 +171,8 @@ impl ScriptEnvironment {
 
         let script_path = find_datafile_path("script")?;
 
-        // Load modules from the script path only
+        // Load modules from the script path only. Mod scripts are loaded
+        // from their own directories through the sandboxed require()
         lua.globals()
             .get::<Table>("package")?
             .set("path", format!("{}/?.lua", script_path.to_str().unwrap()))?;
@@ -82,6 +206,7 @@ impl ScriptEnvironment {
         )?;
 
         lua.globals().set("textures", texapi)?;
+        lang::register_lua_functions(&lua)?;
         Ok(lua)
     }
 
@@ -90,32 +215,277 @@ impl ScriptEnvironment {
 
         let effect_accumulator = Rc::new(RefCell::new(Vec::new()));
 
+        let instruction_count = Self::install_instruction_limit(&lua)?;
+
         Ok(Self {
             lua,
             effect_accumulator,
             global_timer: Rc::new(RefCell::new(None)),
             global_timer_accumulator: 0.0,
+            instruction_count,
         })
     }
 
+    /// Abort runaway scripts.
+    ///
+    /// Once the limit is exceeded, the hook keeps raising errors until the counter is reset.
+    fn install_instruction_limit(lua: &Lua) -> LuaResult<Rc<Cell<u32>>> {
+        let instruction_count = Rc::new(Cell::new(0u32));
+        lua.set_app_data(InstructionCount(instruction_count.clone()));
+
+        let counter = instruction_count.clone();
+        lua.set_global_hook(
+            HookTriggers::new().every_nth_instruction(INSTRUCTION_CHECK_INTERVAL),
+            move |_, _| {
+                let count = counter.get().saturating_add(INSTRUCTION_CHECK_INTERVAL);
+                counter.set(count);
+                if count > INSTRUCTION_LIMIT {
+                    Err(mlua::Error::runtime(
+                        "script instruction limit exceeded (infinite loop?)",
+                    ))
+                } else {
+                    Ok(VmState::Continue)
+                }
+            },
+        )?;
+
+        Ok(instruction_count)
+    }
+
+    /// Reset the instruction counter. This should be called at the start of each game tick.
+    pub fn reset_instruction_count(&self) {
+        self.instruction_count.set(0);
+    }
+
+    /// Get a handle to the instruction counter, for resetting it outside the game tick
+    pub fn instruction_counter(&self) -> Rc<Cell<u32>> {
+        self.instruction_count.clone()
+    }
+
+    /// Return an error if the scripts have exceeded their instruction budget
+    pub fn check_instruction_limit(&self) -> Result<()> {
+        if self.instruction_count.get() > INSTRUCTION_LIMIT {
+            Err(anyhow!(
+                "A script exceeded the instruction limit and was aborted. Is there an infinite loop?"
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    /**
+     * Load and run a level specific script.
+     *
+     * Level scripts are not trusted and are run in a sandboxed environment
+     * with no access to files or other processes.
+     */
     pub fn load_level_specific_script(&mut self, path: &Path) -> LuaResult<()> {
         let script_content = read_to_string(path)?;
+        let script_dir = path.parent().expect("script path should have a parent");
 
-        // Add script root to search path so we can require additional level specific modules
-        let package = self.lua.globals().get::<Table>("package")?;
+        let env = Self::create_sandbox(&self.lua, script_dir)?;
 
-        let old_searchpath = package.get::<String>("path")?;
-        let script_path = path.parent().expect("script path should have a parent");
-        package.set(
-            "path",
-            format!("{};{}/?.lua", old_searchpath, script_path.to_str().unwrap()),
-        )?;
-
-        // Load new script
-        self.lua.load(script_content).exec()?;
+        self.lua
+            .load(script_content)
+            .set_name(format!("@{}", path.display()))
+            .set_mode(ChunkMode::Text)
+            .set_environment(env)
+            .exec()?;
         Ok(())
     }
 
+    /**
+     * Create a global environment table for level and mod scripts.
+     *
+     * The environment is built from a whitelist of safe standard library functions
+     * and private copies of the string, table, math, utf8 and coroutine libraries.
+     * The game API and other globals defined by the engine are inherited, but
+     * standard library globals (such as io, debug, rawset and collectgarbage) are not.
+     * Global variables written by the level script are stored in the
+     * environment table itself, so they can't replace the engine's globals.
+     * Engine hooks are installed through the game.on event API.
+     *
+     * Modules can be required from the engine's script directory and
+     * the level's (or mod's) own script directory. Only source code can be loaded.
+     */
+    fn create_sandbox(lua: &Lua, script_dir: &Path) -> LuaResult<Table> {
+        let globals = lua.globals();
+        let env = lua.create_table()?;
+
+        for name in SANDBOX_FUNCTIONS {
+            env.raw_set(*name, globals.raw_get::<Value>(*name)?)?;
+        }
+
+        for name in SANDBOX_LIBRARIES {
+            let library = lua.create_table()?;
+            for pair in globals.raw_get::<Table>(*name)?.pairs::<Value, Value>() {
+                let (key, value) = pair?;
+                library.raw_set(key, value)?;
+            }
+            env.raw_set(*name, library)?;
+        }
+
+        let os = globals.raw_get::<Table>("os")?;
+        let safe_os = lua.create_table()?;
+        for name in SANDBOX_OS_FUNCTIONS {
+            safe_os.raw_set(*name, os.raw_get::<Value>(*name)?)?;
+        }
+        env.raw_set("os", safe_os)?;
+
+        // getmetatable() for tables only, so the shared string metatable can't be reached
+        {
+            let getmetatable = globals.raw_get::<Function>("getmetatable")?;
+            env.raw_set(
+                "getmetatable",
+                lua.create_function(move |_, value: Value| match value {
+                    Value::Table(_) => getmetatable.call::<Value>(value),
+                    _ => Ok(Value::Nil),
+                })?,
+            )?;
+        }
+
+        // Protected calls that can't catch the instruction limit error
+        {
+            let instruction_count = lua
+                .app_data_ref::<InstructionCount>()
+                .map(|count| count.0.clone())
+                .unwrap_or_default();
+            let coroutine = env.raw_get::<Table>("coroutine")?;
+
+            let (pcall, xpcall, resume, close) = lua
+                .load(SANDBOX_PROTECTED_CALLS)
+                .set_name("=sandbox")
+                .call::<(Function, Function, Function, Function)>((
+                    globals.raw_get::<Function>("pcall")?,
+                    globals.raw_get::<Function>("xpcall")?,
+                    coroutine.raw_get::<Function>("resume")?,
+                    coroutine.raw_get::<Function>("close")?,
+                    globals.raw_get::<Function>("error")?,
+                    lua.create_function(move |_, ()| {
+                        Ok(instruction_count.get() > INSTRUCTION_LIMIT)
+                    })?,
+                ))?;
+
+            env.raw_set("pcall", pcall)?;
+            env.raw_set("xpcall", xpcall)?;
+            coroutine.raw_set("resume", resume)?;
+            coroutine.raw_set("close", close)?;
+        }
+
+        // load() that accepts only source code and defaults to the sandbox environment
+        {
+            let sandbox = env.clone();
+            env.set(
+                "load",
+                lua.create_function(
+                    move |lua,
+                          (chunk, name, _mode, chunk_env): (
+                        LuaString,
+                        Option<String>,
+                        Option<LuaString>,
+                        Option<Table>,
+                    )| {
+                        let result = lua
+                            .load(chunk.as_bytes().to_vec())
+                            .set_name(name.unwrap_or_else(|| "=(load)".to_string()))
+                            .set_mode(ChunkMode::Text)
+                            .set_environment(chunk_env.unwrap_or_else(|| sandbox.clone()))
+                            .into_function();
+
+                        Ok(match result {
+                            Ok(f) => (Some(f), None),
+                            Err(err) => (None, Some(err.to_string())),
+                        })
+                    },
+                )?,
+            )?;
+        }
+
+        // require() limited to engine modules and modules in the level directory
+        {
+            let package = globals.get::<Table>("package")?;
+            let engine_path = package.get::<String>("path")?;
+            let searchpath = package.get::<Function>("searchpath")?;
+            let engine_require = globals.get::<Function>("require")?;
+            let level_modules = lua.create_table()?;
+            let script_dir = script_dir.to_path_buf();
+            let sandbox = env.clone();
+
+            env.set(
+                "require",
+                lua.create_function(move |lua, name: String| {
+                    if name.is_empty()
+                        || name.contains("..")
+                        || !name
+                            .chars()
+                            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+                    {
+                        return Err(mlua::Error::runtime(format!(
+                            "invalid module name \"{name}\""
+                        )));
+                    }
+
+                    // Engine modules are shared with the rest of the game
+                    if searchpath
+                        .call::<Option<String>>((name.as_str(), engine_path.as_str()))?
+                        .is_some()
+                    {
+                        return engine_require.call::<Value>(name.as_str());
+                    }
+
+                    let module = level_modules.get::<Value>(name.as_str())?;
+                    if !module.is_nil() {
+                        return Ok(module);
+                    }
+
+                    let path = script_dir.join(format!("{}.lua", name.replace('.', "/")));
+                    let content = read_to_string(&path).map_err(|err| {
+                        mlua::Error::runtime(format!("module \"{name}\" not found: {err}"))
+                    })?;
+
+                    let module = lua
+                        .load(content)
+                        .set_name(format!("@{}", path.display()))
+                        .set_mode(ChunkMode::Text)
+                        .set_environment(sandbox.clone())
+                        .call::<Value>(name.as_str())?;
+
+                    let module = if module.is_nil() {
+                        Value::Boolean(true)
+                    } else {
+                        module
+                    };
+                    level_modules.set(name.as_str(), module.clone())?;
+                    Ok(module)
+                })?,
+            )?;
+        }
+
+        env.set("_G", env.clone())?;
+
+        let meta = lua.create_table()?;
+        {
+            let globals = globals.clone();
+            meta.set(
+                "__index",
+                lua.create_function(move |_, (_, key): (Table, Value)| {
+                    if let Value::String(name) = &key
+                        && LUA_STANDARD_GLOBALS
+                            .iter()
+                            .any(|b| name.as_bytes().deref() == b.as_bytes())
+                    {
+                        return Ok(Value::Nil);
+                    }
+                    globals.raw_get::<Value>(key)
+                })?,
+            )?;
+        }
+        meta.set("__metatable", false)?;
+        env.set_metatable(Some(meta))?;
+
+        Ok(env)
+    }
+
     pub fn init_game(
         &mut self,
         players: Rc<RefCell<Vec<PlayerState>>>,
@@ -144,11 +514,42 @@ impl ScriptEnvironment {
             )?;
         }
 
+        // Subscribe to a game event
+        // function on(event, handler)
+        // Multiple handlers can be subscribed to the same event.
+        // Events:
+        //  round_start(level_settings) - after the round has been initialized
+        //  round_end(winner) - when the round end condition has been reached
+        //  ship_destroyed(ship) - a ship has been destroyed
+        //  ship_landed(ship) - a ship has landed on a base
+        //  pilot_created(pilot) - a pilot has spawned or ejected from a ship
+        {
+            let handlers = self.lua.create_table()?;
+            self.lua
+                .set_named_registry_value(EVENT_HANDLERS_KEY, handlers.clone())?;
+            api.set(
+                "on",
+                self.lua
+                    .create_function(move |lua, (event, handler): (LuaString, Function)| {
+                        let list = match handlers.get::<Option<Table>>(&event)? {
+                            Some(list) => list,
+                            None => {
+                                let list = lua.create_table()?;
+                                handlers.set(&event, &list)?;
+                                list
+                            }
+                        };
+                        list.push(handler)
+                    })?,
+            )?;
+        }
+
         // Level size and colors
         api.set("level_width", level.borrow().width())?;
         api.set("level_height", level.borrow().height())?;
         api.set("snow_color", level.borrow().snow_color)?;
         api.set("water_color", level.borrow().water_color)?;
+        api.set("pilot_danger_speed", PILOT_DANGER_SPEED)?;
 
         api.set(
             "player_color",
@@ -167,6 +568,14 @@ impl ScriptEnvironment {
             )?;
         }
 
+        // Shortest vector between two points, taking level wraparound into account
+        // function wrapped_delta(from, to) -> Vec2
+        api.set(
+            "wrapped_delta",
+            self.lua
+                .create_function(|_, (from, to): (Vec2, Vec2)| Ok(wrap::wrapped_delta(from, to)))?,
+        )?;
+
         // Terrain line intersection check
         // function terrain_line(start, end) -> (Vec2, Terrain, bool), where bool is true if intersected with solid terrain
         {
@@ -211,6 +620,8 @@ impl ScriptEnvironment {
         // Get the position of the nearest ship
         // This is used by target seeking scripts.
         // Note: returns only non-cloaked, non-wrecked ships!
+        // In a wrapping level, the position may be outside the level
+        // so that it is in the right direction as seen from `pos`.
         api.set(
             "ships_nearest_pos",
             self.lua.create_function(
@@ -219,27 +630,43 @@ impl ScriptEnvironment {
                     let nearest = ships.find_nearest(pos, range, |s| {
                         s.player_id() != other_than && !s.is_cloaked() && !s.is_wrecked()
                     });
-                    Ok(nearest.map(|s| s.pos()))
+                    Ok(nearest.map(|s| pos + wrap::wrapped_delta(pos, s.pos())))
                 },
             )?,
         )?;
 
         // Iterate through a read-only list of pilots
-        api.set(
-            "pilots_iter",
-            self.lua.create_function(move |lua, callback: Function| {
-                let pilots = pilot_list.borrow();
-                lua.scope(|scope| {
-                    for pilot in pilots.iter() {
-                        let res =
-                            callback.call::<Option<bool>>(scope.create_userdata_ref(pilot))?;
-                        if let Some(false) = res {
-                            break;
+        {
+            let pilot_list = pilot_list.clone();
+            api.set(
+                "pilots_iter",
+                self.lua.create_function(move |lua, callback: Function| {
+                    let pilots = pilot_list.borrow();
+                    lua.scope(|scope| {
+                        for pilot in pilots.iter() {
+                            let res =
+                                callback.call::<Option<bool>>(scope.create_userdata_ref(pilot))?;
+                            if let Some(false) = res {
+                                break;
+                            }
                         }
-                    }
-                    Ok(())
-                })
-            })?,
+                        Ok(())
+                    })
+                })?,
+            )?;
+        }
+
+        // Get the position of the nearest pilot
+        // Like ships_nearest_pos, but for hostile critters hunting pilots.
+        api.set(
+            "pilots_nearest_pos",
+            self.lua.create_function(
+                move |_, (pos, range, other_than): (Vec2, f32, PlayerId)| {
+                    let pilots = pilot_list.borrow();
+                    let nearest = pilots.find_nearest(pos, range, |p| p.player_id() != other_than);
+                    Ok(nearest.map(|p| pos + wrap::wrapped_delta(pos, p.pos())))
+                },
+            )?,
         )?;
 
         // Iterate through a read-only list of critters within the given position and radius
@@ -251,8 +678,10 @@ impl ScriptEnvironment {
                     let critters = critter_list.borrow();
                     let rr = rad * rad;
                     lua.scope(|scope| {
-                        for critter in critters.range_slice(pos.0 - rad, pos.0 + rad) {
-                            if critter.id() != except_this && critter.pos().dist_squared(pos) < rr {
+                        for critter in critters.range_iter(pos.0 - rad, pos.0 + rad) {
+                            if critter.id() != except_this
+                                && wrap::wrapped_delta(pos, critter.pos()).magnitude_squared() < rr
+                            {
                                 let res = callback
                                     .call::<Option<bool>>(scope.create_userdata_ref(critter))?;
                                 if let Some(false) = res {
@@ -341,6 +770,7 @@ impl ScriptEnvironment {
                             }
                         }
                         b"AddParticle" => WorldEffect::AddParticle(Particle::from_lua(props, lua)?),
+                        b"Emit" => WorldEffect::Emit(Emitter::from_lua(props, lua)?),
                         b"AddTerrainParticle" => {
                             WorldEffect::AddTerrainParticle(TerrainParticle::from_lua(props, lua)?)
                         }
@@ -369,6 +799,7 @@ impl ScriptEnvironment {
                         b"SetWindspeed" => WorldEffect::SetWindspeed(f32::from_lua(props, lua)?),
                         b"RegenerateTerrain" => WorldEffect::RegenerateTerrain,
                         b"EndRound" => WorldEffect::EndRound(i32::from_lua(props, lua)?),
+                        b"SetScore" => WorldEffect::SetScore(i32::from_lua(props, lua)?),
                         unknown => {
                             return Err(anyhow!(
                                 "Unknown effect type: {}",
@@ -403,6 +834,26 @@ impl ScriptEnvironment {
                                 );
                             }
                         }
+                        b"hud_status" => {
+                            if let Some(plr) =
+                                players.borrow_mut().get_mut((player_id - 1) as usize)
+                            {
+                                plr.status = Option::<Text>::from_lua(props, lua)?;
+                            } else {
+                                return Err(
+                                    anyhow!("hud_status requires a valid player index").into()
+                                );
+                            }
+                        }
+                        b"credits" => {
+                            if let Some(plr) =
+                                players.borrow_mut().get_mut((player_id - 1) as usize)
+                            {
+                                plr.credits += f32::from_lua(props, lua)?;
+                            } else {
+                                return Err(anyhow!("credits requires a valid player index").into());
+                            }
+                        }
                         b"rumble" => {
                             if rumble_enabled {
                                 // Note: player_id is actually controller_id in this case
@@ -443,6 +894,17 @@ impl ScriptEnvironment {
             })?,
         )?;
 
+        // Keep the API type definitions in sync with the actual bindings
+        if cfg!(debug_assertions) {
+            let names = api
+                .pairs::<String, Value>()
+                .map(|pair| pair.map(|(name, _)| name))
+                .collect::<LuaResult<Vec<_>>>()?;
+            for name in luaapi::undocumented_game_members(names.iter().map(String::as_str)) {
+                log::warn!("game.{name} is missing from the Lua API registry");
+            }
+        }
+
         // Access to game objects via the "game" table
         let globals = self.lua.globals();
         globals.set("game", api)?;
@@ -469,7 +931,29 @@ impl ScriptEnvironment {
         )?;
 
         // Load main entrypoint file
-        self.lua.load(r#"require "luola_main""#).exec()?;
+        Self::load_main_script(&self.lua)?;
+
+        Ok(())
+    }
+
+    /**
+     * Load the main entrypoint script, followed by the scripts of enabled mods.
+     *
+     * Mod scripts are run in load order and can add their own entries
+     * to the luola_weapons and luola_ships tables. Like level scripts,
+     * they are not trusted and each runs in its own sandboxed environment.
+     */
+    pub fn load_main_script(lua: &Lua) -> LuaResult<()> {
+        lua.load(r#"require "luola_main""#).exec()?;
+
+        for m in enabled_mods() {
+            if let Some(script) = &m.script {
+                let env = Self::create_sandbox(lua, &m.data_path().join("script"))?;
+                env.get::<Function>("require")?
+                    .call::<()>(script.as_str())
+                    .context(format!("mod \"{}\"", m.title))?;
+            }
+        }
 
         Ok(())
     }
@@ -518,3 +1002,68 @@ impl ScriptEnvironment {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run_sandboxed(lua: &Lua, code: &str) -> LuaResult<()> {
+        let env = ScriptEnvironment::create_sandbox(lua, Path::new("."))?;
+        lua.load(code).set_environment(env).exec()
+    }
+
+    #[test]
+    fn test_sandbox_globals() {
+        let lua = Lua::new();
+        lua.globals().set("engine_value", 1).unwrap();
+
+        run_sandboxed(
+            &lua,
+            r#"
+            assert(engine_value == 1)
+            assert(rawset == nil and rawget == nil and collectgarbage == nil)
+            assert(io == nil and debug == nil and package == nil and os.execute == nil)
+            assert(getmetatable("") == nil)
+
+            -- Library tables are private copies
+            string.upper = nil
+            assert(("a"):upper() == "A")
+
+            engine_value = 2
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(lua.globals().get::<i32>("engine_value").unwrap(), 1);
+        assert!(
+            lua.globals()
+                .get::<Table>("string")
+                .unwrap()
+                .contains_key("upper")
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_sandbox_instruction_limit() {
+        let lua = Lua::new();
+        let instruction_count = ScriptEnvironment::install_instruction_limit(&lua).unwrap();
+
+        let result = run_sandboxed(
+            &lua,
+            r#"
+            local function spin()
+                while true do end
+            end
+            while true do
+                pcall(spin)
+                xpcall(spin, function(err) return err end)
+                coroutine.resume(coroutine.create(spin))
+            end
+            "#,
+        );
+
+        assert!(result.is_err());
+        assert!(instruction_count.get() > INSTRUCTION_LIMIT);
+    }
+}
@@ -21,19 +21,27 @@ use log::error;
 use smallvec::SmallVec;
 
 use crate::{
+    configfile::GAME_CONFIG,
+    emit_event,
     game::{
-        GameControllerSet, Player, PlayerId, PlayerState,
-        hud::{PlayerHud, draw_hud, draw_minimap},
+        GameControllerSet, MAX_PLAYERS, Player, PlayerId, PlayerState,
+        hud::{PlayerHud, draw_hud, draw_minimap, draw_player_badge},
         level::{
-            DynamicTerrainCell, LEVEL_SCALE, LevelInfo, Starfield,
+            DynamicTerrainCell, JumpGates, Jumper, LEVEL_SCALE, LevelInfo, ParallaxLayers,
+            Starfield,
             terrain::{self, Terrain},
+            wrap::{wrap_offsets, wrap_position, wrapped_delta},
         },
         objects::{
-            Critter, FixedObject, GameObjectArray, HitscanProjectile, Pilot, TerrainParticle,
+            Critter, Emitter, FixedObject, GameObjectArray, HitscanProjectile, Pilot,
+            TerrainParticle,
         },
     },
-    gfx::{AnimatedTexture, Color, RenderMode, RenderOptions, Renderer},
-    math::{Rect, Vec2},
+    gfx::{
+        AnimatedTexture, Color, Light, Lightmap, MAX_LIGHT_RADIUS, RenderMode, RenderOptions,
+        Renderer, Text,
+    },
+    math::{Rect, RectF, Vec2},
 };
 
 use super::{
@@ -43,6 +51,9 @@ use super::{
     scripting::ScriptEnvironment,
 };
 
+/// If the camera is further than this from the followed object, it jumps straight to it
+const CAMERA_SNAP_DISTANCE: f32 = 600.0;
+
 #[derive(Clone)]
 pub enum WorldEffect {
     AddShip(Ship),
@@ -51,6 +62,7 @@ pub enum WorldEffect {
     AddMine(Projectile),
     AddParticle(Particle),
     AddTerrainParticle(TerrainParticle),
+    Emit(Emitter),
     AddDynamicTerrain(Vec2, DynamicTerrainCell),
     AddFixedObject(FixedObject),
     AddHitscan(HitscanProjectile),
@@ -68,6 +80,7 @@ pub enum WorldEffect {
     SetWindspeed(f32),
     RegenerateTerrain,
     EndRound(PlayerId),
+    SetScore(i32),
 }
 
 impl mlua::UserData for WorldEffect {}
@@ -122,6 +135,9 @@ pub struct World {
     /// No need to double buffer these, since they don't do anything except get drawn on screen.
     particles: GameObjectArray<Particle>, // decorative particles with no interactions at all
 
+    /// Active continuous particle emitters
+    emitters: Vec<Emitter>,
+
     /// Game objects that are fixed in place (or move via script actions only)
     fixedobjects: Rc<RefCell<GameObjectArray<FixedObject>>>,
 
@@ -131,9 +147,24 @@ pub struct World {
     /// Starfield background
     starfield: Option<Starfield>,
 
+    /// Decorative parallax background and foreground layers
+    layers: Option<ParallaxLayers>,
+
+    /// Lightmap for levels with dynamic lighting
+    lightmap: Option<Lightmap>,
+
+    /// Permanent jump gates defined in the level file
+    jumpgates: Option<JumpGates>,
+
+    /// Player number badges (if enabled)
+    player_badges: Vec<Text>,
+
     /// This will be set to the winner of the round when decided
     winner: Option<PlayerId>,
 
+    /// Score of the round (set by scripts in co-op modes)
+    score: Option<i32>,
+
     /// Debug helper
     debug_mode: DebugMode,
 }
@@ -195,6 +226,7 @@ impl World {
             critters_work: Rc::new(RefCell::new(GameObjectArray::new())),
             terrainparticles: GameObjectArray::new(),
             particles: GameObjectArray::new(),
+            emitters: Vec::new(),
             fixedobjects,
             noise_texture: AnimatedTexture::new(
                 renderer.borrow().texture_store().find_texture(b"noise")?,
@@ -204,7 +236,24 @@ impl World {
             } else {
                 None
             },
+            layers: ParallaxLayers::load(&renderer.borrow(), levelinfo)?,
+            lightmap: match levelinfo.lighting() {
+                Some(lighting) => {
+                    Some(Lightmap::new(&renderer.borrow(), lighting.ambient_color())?)
+                }
+                None => None,
+            },
+            jumpgates: JumpGates::load(&renderer.borrow(), levelinfo)?,
+            player_badges: if GAME_CONFIG.read().unwrap().game.player_badges {
+                let r = renderer.borrow();
+                (1..=MAX_PLAYERS)
+                    .map(|p| r.fontset().menu.create_text(&r, &p.to_string()))
+                    .collect::<Result<_>>()?
+            } else {
+                Vec::new()
+            },
             winner: None,
+            score: None,
             debug_mode: DebugMode::None,
         })
     }
@@ -231,6 +280,11 @@ impl World {
         let mut level = self.level.borrow_mut();
         let mut level_editor = LevelEditor::new(&mut level);
         let mut sort_particles = false;
+
+        // Objects whose creation emits an event are added after the level editor
+        // is released, so event handlers can freely use the game API.
+        let mut new_pilots = Vec::new();
+        let mut round_ended = false;
         for fx in effects {
             match fx {
                 WorldEffect::AddBullet(b) => self.bullets.push(b),
@@ -240,6 +294,13 @@ impl World {
                     sort_particles = true;
                 }
                 WorldEffect::AddTerrainParticle(p) => self.terrainparticles.push(p),
+                WorldEffect::Emit(mut e) => {
+                    // Bursts are emitted immediately. Continuous emitters start on the next step.
+                    if e.step(0.0, &mut self.particles, &mut self.terrainparticles) {
+                        self.emitters.push(e);
+                    }
+                    sort_particles = true;
+                }
                 WorldEffect::AddDynamicTerrain(pos, t) => level_editor.add_dynterrain(pos, t),
                 WorldEffect::AddFixedObject(o) => {
                     let mut objs = self.fixedobjects.borrow_mut();
@@ -275,22 +336,38 @@ impl World {
                 WorldEffect::RegenerateTerrain => {
                     level_editor.regenerate_terrain();
                 }
-                WorldEffect::AddShip(s) => {
+                WorldEffect::AddShip(mut s) => {
+                    s.init_landed(level_editor.level());
                     if s.player_id() > 0 && s.controller() > 0 {
                         self.players.borrow_mut()[s.player_id() as usize - 1].camera_pos = s.pos();
                     }
                     self.ships.borrow_mut().push(s);
                 }
-                WorldEffect::AddPilot(p) => {
-                    if p.player_id() > 0 && p.controller() > 0 {
-                        self.players.borrow_mut()[p.player_id() as usize - 1].camera_pos = p.pos();
-                    }
-                    self.pilots.borrow_mut().push(p);
+                WorldEffect::AddPilot(p) => new_pilots.push(p),
+                WorldEffect::EndRound(winner) => {
+                    round_ended |= self.winner.is_none();
+                    self.winner = Some(winner);
                 }
-                WorldEffect::EndRound(winner) => self.winner = Some(winner),
+                WorldEffect::SetScore(score) => self.score = Some(score),
             }
         }
         level_editor.step_dynterrain();
+        drop(level_editor);
+        drop(level);
+
+        for mut p in new_pilots {
+            if p.player_id() > 0 && p.controller() > 0 {
+                self.players.borrow_mut()[p.player_id() as usize - 1].camera_pos = p.pos();
+            }
+            emit_event!(self.scripting.lua(), "pilot_created", (scope) => {
+                scope.create_userdata_ref_mut(&mut p)?
+            });
+            self.pilots.borrow_mut().push(p);
+        }
+
+        if round_ended && let Some(winner) = self.winner {
+            emit_event!(self.scripting.lua(), "round_end", winner);
+        }
 
         if sort_particles {
             // Hitscan weapons (laser) add a large number of short lived particles.
@@ -313,6 +390,8 @@ impl World {
      * Returns the ID of the winning player, if a win was decided in this step.
      */
     pub fn step(&mut self, controllers: &[GameController], timestep: f32) -> Option<PlayerId> {
+        self.scripting.reset_instruction_count();
+
         // Player state reset
         for ps in self.players.borrow_mut().iter_mut() {
             if ps.fadeout < 1.0 {
@@ -349,14 +428,20 @@ impl World {
                 if ship.player_id() > 0 && ship.controller() > 0 {
                     let ps = &mut self.players.borrow_mut()[ship.player_id() as usize - 1];
                     let ship = work.last_mut();
+                    let (weapon_icons, weapon_count, selected_weapon) = ship.weapon_icons();
                     ps.hud = PlayerHud::Ship {
                         health: ship.health(),
                         ammo: ship.ammo(),
                         cooling_down: ship.secondary_weapon_cooldown() > 0.0,
+                        fuel: ship.fuel(),
+                        heat: ship.heat(),
+                        overheated: ship.is_overheated(),
+                        weapon_icons,
+                        weapon_count,
+                        selected_weapon,
                     };
-                    // camera inertia for an enhanced feeling of motion
                     // TODO rather than trailing behind the ship, the camera should look ahead?
-                    ps.camera_pos = ps.camera_pos + (ship.pos() - ps.camera_pos) / 5.0;
+                    ps.camera_pos = follow_camera(ps.camera_pos, ship.pos());
                     ps.fadeout = -1.0;
                 }
             }
@@ -388,11 +473,11 @@ impl World {
             if pilot.player_id() > 0 && pilot.controller() > 0 {
                 let ps = &mut self.players.borrow_mut()[pilot.player_id() as usize - 1];
                 ps.hud = PlayerHud::Pilot {
+                    health: pilot.health(),
                     jetpack: pilot.jetpack_charge(),
                     target: pilot.aim_target(),
                 };
-                // camera inertia for an enhanced feeling of motion
-                ps.camera_pos = ps.camera_pos + (pilot.pos() - ps.camera_pos) / 5.0;
+                ps.camera_pos = follow_camera(ps.camera_pos, pilot.pos());
                 ps.fadeout = -1.0;
             }
         }
@@ -425,6 +510,11 @@ impl World {
             work.sort();
         }
 
+        // Particle emitter step. Emitters add both decorative and terrain
+        // particles, so this must be done before the particle arrays are sorted.
+        self.emitters
+            .retain_mut(|e| e.step(timestep, &mut self.particles, &mut self.terrainparticles));
+
         // Terrain particle simulation step
         for tp in self.terrainparticles.iter_mut() {
             let e = tp.step_mut(&level, timestep);
@@ -458,6 +548,10 @@ impl World {
 
         self.particles.sort();
 
+        if let Some(layers) = self.layers.as_mut() {
+            layers.step(timestep);
+        }
+
         // Fixed object simulation step
         {
             let mut any_moved = false;
@@ -470,6 +564,47 @@ impl World {
             }
         }
 
+        // Jump gates
+        if let Some(gates) = self.jumpgates.as_mut() {
+            gates.step(timestep);
+
+            // Cameras snap to the new position instead of panning across the level
+            let mut players = self.players.borrow_mut();
+            let mut snap_camera = |player: PlayerId, controller: i32, pos: Vec2| {
+                if player > 0 && controller > 0 {
+                    players[player as usize - 1].camera_pos = pos;
+                }
+            };
+
+            let mut ships = self.ships_work.borrow_mut();
+            let mut jumped = false;
+            for ship in ships.iter_mut() {
+                if gates.pass_through(ship) {
+                    snap_camera(ship.player_id(), ship.controller(), ship.pos());
+                    jumped = true;
+                }
+            }
+            if jumped {
+                ships.sort();
+            }
+
+            let mut pilots = self.pilots.borrow_mut();
+            let mut jumped = false;
+            for pilot in pilots.iter_mut() {
+                if gates.pass_through(pilot) {
+                    snap_camera(pilot.player_id(), pilot.controller(), pilot.pos());
+                    jumped = true;
+                }
+            }
+            if jumped {
+                pilots.sort();
+            }
+
+            pass_through_all(gates, &mut self.bullets);
+            pass_through_all(gates, &mut self.mines.borrow_mut());
+            pass_through_all(gates, &mut self.critters_work.borrow_mut());
+        }
+
         //
         // Collision check phase
         //
@@ -480,16 +615,20 @@ impl World {
             let mut minework = self.mines.borrow_mut();
             let mut critterwork = self.critters_work.borrow_mut();
             for (ship, rest) in work.self_collision_iter_mut() {
-                // Ship self collisions
+                // Ship self collisions. Ramming damage is attributed to the other ship.
                 for other in rest {
                     if let Some(impulse) = ship.physics().check_collision(other.physics()) {
                         ship.physics_mut().add_impulse(impulse);
                         other.physics_mut().add_impulse(impulse * -1.0);
+
+                        let (ship_ram, other_ram) = (ship.ram_damage(), other.ram_damage());
+                        ship.collide(other, impulse, other_ram, self.scripting.lua());
+                        other.collide(ship, impulse, ship_ram, self.scripting.lua());
                     }
                 }
 
                 // Ship to bullet checks.
-                for bullet in self.bullets.collider_slice_mut(ship) {
+                for bullet in self.bullets.colliders_mut(ship) {
                     if bullet.owner() != ship.player_id()
                         && let Some(impulse) = ship.physics().check_collision(bullet.physics())
                     {
@@ -499,7 +638,7 @@ impl World {
                 }
 
                 // Ship to mine checks
-                for mine in minework.collider_slice_mut(ship) {
+                for mine in minework.colliders_mut(ship) {
                     if mine.owner() != ship.player_id()
                         && let Some(impulse) = ship.physics().check_collision(mine.physics())
                     {
@@ -510,16 +649,20 @@ impl World {
                 }
 
                 // Ship to critter checks
-                for critter in critterwork.collider_slice_mut(ship) {
+                for critter in critterwork.colliders_mut(ship) {
                     if let Some(impulse) = ship.physics().check_collision(critter.physics()) {
                         ship.physics_mut().add_impulse(impulse);
                         critter.physics_mut().add_impulse(impulse * -1.0);
-                        critter.object_collision(ship, self.scripting.lua());
+
+                        let damage =
+                            critter.physics().collision_damage(impulse) * ship.ram_damage();
+                        critter.object_collision(ship, damage, self.scripting.lua());
+                        ship.collide(critter, impulse, 1.0, self.scripting.lua());
                     }
                 }
 
                 // Ship to terrain particles check. This is mainly so ship's don't get buried in snow
-                for tp in self.terrainparticles.collider_slice_mut(ship) {
+                for tp in self.terrainparticles.colliders_mut(ship) {
                     if let Some(impulse) = ship.physics().check_collision(tp.physics()) {
                         ship.physics_mut().add_impulse(impulse);
                         tp.physics_mut().add_impulse(impulse * -1.0);
@@ -542,7 +685,7 @@ impl World {
                 }
 
                 // Bullet collisions. No friendly fire here
-                for bullet in self.bullets.collider_slice_mut(mine).iter_mut() {
+                for bullet in self.bullets.colliders_mut(mine) {
                     if mine.physics().check_overlap(bullet.physics()) {
                         let terrain = self.level.borrow().terrain_at(mine.pos());
                         bullet.impact(terrain, Some(mine), self.scripting.lua());
@@ -560,11 +703,12 @@ impl World {
                     if let Some(impulse) = critter.physics().check_collision(other.physics()) {
                         critter.physics_mut().add_impulse(impulse);
                         other.physics_mut().add_impulse(impulse * -1.0);
-                        critter.object_collision(other, self.scripting.lua());
+                        let damage = critter.physics().collision_damage(impulse);
+                        critter.object_collision(other, damage, self.scripting.lua());
                     }
                 }
 
-                for bullet in self.bullets.collider_slice_mut(critter).iter_mut() {
+                for bullet in self.bullets.colliders_mut(critter) {
                     // Drones are liable to shoot each other much too easily, so
                     // friendly fire is not checked
                     if (critter.owner() == 0 || critter.owner() != bullet.owner())
@@ -576,7 +720,7 @@ impl World {
                 }
 
                 let mut minework = self.mines.borrow_mut();
-                for mine in minework.collider_slice_mut(critter).iter_mut() {
+                for mine in minework.colliders_mut(critter) {
                     if critter.physics().check_overlap(mine.physics()) {
                         let terrain = self.level.borrow().terrain_at(mine.pos());
                         mine.impact(terrain, Some(critter), self.scripting.lua());
@@ -589,7 +733,7 @@ impl World {
         {
             let mut work = self.pilots.borrow_mut();
             for pilot in work.iter_mut() {
-                for bullet in self.bullets.collider_slice_mut(pilot).iter_mut() {
+                for bullet in self.bullets.colliders_mut(pilot) {
                     if bullet.owner() != pilot.player_id()
                         && pilot.physics().check_overlap(bullet.physics())
                     {
@@ -599,18 +743,30 @@ impl World {
                 }
 
                 let mut minework = self.mines.borrow_mut();
-                for mine in minework.collider_slice_mut(pilot).iter_mut() {
+                for mine in minework.colliders_mut(pilot) {
                     if pilot.physics().check_overlap(mine.physics()) {
                         let terrain = self.level.borrow().terrain_at(mine.pos());
                         mine.impact(terrain, Some(pilot), self.scripting.lua());
                     }
                 }
 
-                // TODO critter collisions
+                // Critters can push and bite pilots, pilots can stomp on critters
+                let mut critterwork = self.critters_work.borrow_mut();
+                for critter in critterwork.colliders_mut(pilot) {
+                    if let Some(impulse) = pilot.physics().check_collision(critter.physics()) {
+                        pilot.physics_mut().add_impulse(impulse);
+                        critter.physics_mut().add_impulse(impulse * -1.0);
+                        // Stomping is resolved first so a stomped critter can't bite back
+                        if !pilot.touch_critter(critter, self.scripting.lua()) {
+                            let damage = critter.physics().collision_damage(impulse);
+                            critter.object_collision(pilot, damage, self.scripting.lua());
+                        }
+                    }
+                }
 
                 // Pilots can claim empty ships by touching them
                 let mut shipswork = self.ships_work.borrow_mut();
-                for ship in shipswork.collider_slice_mut(pilot).iter_mut() {
+                for ship in shipswork.colliders_mut(pilot) {
                     if !ship.is_wrecked() && pilot.physics().check_overlap(ship.physics()) {
                         pilot.touch_ship(ship, self.scripting.lua());
                     }
@@ -621,7 +777,7 @@ impl World {
             {
                 let mut work = self.fixedobjects.borrow_mut();
                 for fobj in work.iter_mut() {
-                    for bullet in self.bullets.collider_slice_mut(fobj).iter_mut() {
+                    for bullet in self.bullets.colliders_mut(fobj) {
                         if fobj.check_overlap(bullet.physics()) {
                             let terrain = self.level.borrow().terrain_at(bullet.pos());
                             bullet.impact(terrain, Some(fobj), self.scripting.lua());
@@ -629,7 +785,7 @@ impl World {
                     }
 
                     let mut minework = self.mines.borrow_mut();
-                    for mine in minework.collider_slice_mut(fobj).iter_mut() {
+                    for mine in minework.colliders_mut(fobj) {
                         if fobj.check_overlap(mine.physics()) {
                             let terrain = self.level.borrow().terrain_at(mine.pos());
                             mine.impact(terrain, Some(fobj), self.scripting.lua());
@@ -637,7 +793,7 @@ impl World {
                     }
 
                     let mut shipswork = self.ships_work.borrow_mut();
-                    for ship in shipswork.collider_slice_mut(fobj).iter_mut() {
+                    for ship in shipswork.colliders_mut(fobj) {
                         if fobj.check_overlap(ship.physics()) {
                             fobj.object_collision(ship, self.scripting.lua());
                         }
@@ -664,7 +820,7 @@ impl World {
             let mut nearest_object = Nearest::None;
 
             let mut ships_work = self.ships_work.borrow_mut();
-            for ship in ships_work.range_slice_mut(left, right) {
+            for ship in ships_work.range_iter_mut(left, right) {
                 if (hs.owner() != ship.player_id()) && hs.do_hit_object(self.scripting.lua(), ship)
                 {
                     nearest_object = Nearest::Ship(ship);
@@ -672,7 +828,7 @@ impl World {
             }
 
             let mut mines_work = self.mines.borrow_mut();
-            for mine in mines_work.range_slice_mut(left, right) {
+            for mine in mines_work.range_iter_mut(left, right) {
                 if (hs.owner() == 0 || hs.owner() != mine.owner())
                     && hs.do_hit_object(self.scripting.lua(), mine)
                 {
@@ -681,7 +837,7 @@ impl World {
             }
 
             let mut critters_work = self.critters_work.borrow_mut();
-            for critter in critters_work.range_slice_mut(left, right) {
+            for critter in critters_work.range_iter_mut(left, right) {
                 if (hs.owner() == 0 || hs.owner() != critter.owner())
                     && hs.do_hit_object(self.scripting.lua(), critter)
                 {
@@ -690,7 +846,7 @@ impl World {
             }
 
             let mut pilots_work = self.pilots.borrow_mut();
-            for pilot in pilots_work.range_slice_mut(left, right) {
+            for pilot in pilots_work.range_iter_mut(left, right) {
                 if (hs.owner() == 0 || hs.owner() != pilot.player_id())
                     && hs.do_hit_object(self.scripting.lua(), pilot)
                 {
@@ -699,7 +855,7 @@ impl World {
             }
 
             let mut fobjs_work = self.fixedobjects.borrow_mut();
-            for fobj in fobjs_work.range_slice_mut(left, right) {
+            for fobj in fobjs_work.range_iter_mut(left, right) {
                 if hs.do_hit_object(self.scripting.lua(), fobj) {
                     nearest_object = Nearest::FixedObj(fobj);
                 }
@@ -774,8 +930,20 @@ impl World {
                 sf.render(renderer);
             }
 
+            level.render_background(renderer, camera_rect);
+
+            if let Some(layers) = self.layers.as_ref() {
+                layers.render_background(renderer, camera_rect);
+            }
+
             level.render(renderer, camera_rect);
 
+            if let Some(gates) = self.jumpgates.as_ref() {
+                for offset in wrap_offsets(camera_rect) {
+                    gates.render(renderer, camera_rect.offset(-offset.0, -offset.1));
+                }
+            }
+
             match self.debug_mode {
                 DebugMode::None => {}
                 DebugMode::DrawTileGrid => {
@@ -785,52 +953,99 @@ impl World {
                     level.debug_render_tilehints(renderer, camera_rect)
                 }
             }
-            // World objects
-            let left = camera_rect.x();
-            let right = camera_rect.right();
+            // World objects. In a wrapping level, objects are drawn
+            // once for each copy of the level the camera can see.
+            let copies = wrap_offsets(camera_rect);
 
-            for o in self.fixedobjects.borrow().range_slice(left, right) {
-                o.render(renderer, camera_pos);
-            }
+            for &offset in &copies {
+                let camera_pos = camera_pos - offset;
+                let left = camera_rect.x() - offset.0;
+                let right = camera_rect.right() - offset.0;
 
-            for particle in self.particles.range_slice(left, right) {
-                particle.render(renderer, camera_pos);
-            }
+                for o in self.fixedobjects.borrow().range_slice(left, right) {
+                    o.render(renderer, camera_pos);
+                }
 
-            for tp in self.terrainparticles.range_slice(left, right) {
-                tp.render(renderer, camera_pos);
-            }
+                for particle in self.particles.range_slice(left, right) {
+                    particle.render(renderer, camera_pos);
+                }
 
-            for mine in self.mines.borrow().range_slice(left, right) {
-                mine.render(renderer, camera_pos);
-            }
+                for tp in self.terrainparticles.range_slice(left, right) {
+                    tp.render(renderer, camera_pos);
+                }
+
+                for mine in self.mines.borrow().range_slice(left, right) {
+                    mine.render(renderer, camera_pos);
+                }
 
-            for bullet in self.bullets.range_slice(left, right) {
-                bullet.render(renderer, camera_pos);
+                for bullet in self.bullets.range_slice(left, right) {
+                    bullet.render(renderer, camera_pos);
+                }
+
+                for ship in self.ships.borrow().range_slice(left, right) {
+                    ship.render(renderer, camera_pos);
+                }
+
+                for pilot in self.pilots.borrow().iter() {
+                    pilot.render(renderer, camera_pos);
+                }
+
+                for critter in self.critters.borrow().range_slice(left, right) {
+                    critter.render(renderer, camera_pos);
+                }
             }
 
-            for ship in self.ships.borrow().range_slice(left, right) {
-                ship.render(renderer, camera_pos);
+            if let Some(layers) = self.layers.as_ref() {
+                layers.render_foreground(renderer, camera_rect);
             }
 
-            for pilot in self.pilots.borrow().iter() {
-                pilot.render(renderer, camera_pos);
+            if let Some(lightmap) = self.lightmap.as_ref() {
+                let lights = self.collect_lights(&level, camera_rect);
+                lightmap.render(renderer, camera_rect, lights.into_iter());
             }
 
-            for critter in self.critters.borrow().range_slice(left, right) {
-                critter.render(renderer, camera_pos);
+            if !self.player_badges.is_empty() {
+                for &offset in &copies {
+                    let camera_pos = camera_pos - offset;
+                    let left = camera_rect.x() - offset.0;
+                    let right = camera_rect.right() - offset.0;
+
+                    for ship in self.ships.borrow().range_slice(left, right) {
+                        if ship.player_id() > 0 && !ship.is_cloaked() && !ship.is_wrecked() {
+                            draw_player_badge(
+                                &self.player_badges,
+                                ship.player_id(),
+                                ship.pos() - camera_pos - Vec2(0.0, 24.0),
+                            );
+                        }
+                    }
+
+                    for pilot in self.pilots.borrow().iter() {
+                        draw_player_badge(
+                            &self.player_badges,
+                            pilot.player_id(),
+                            pilot.pos() - camera_pos - Vec2(0.0, 16.0),
+                        );
+                    }
+                }
             }
 
             // Player HUD
-            draw_hud(renderer, player.hud, &player.overlays, camera_pos);
+            draw_hud(
+                renderer,
+                player.hud,
+                player.status.as_ref(),
+                &player.overlays,
+                camera_pos,
+            );
 
             if let Some(minimap) = self.level.borrow().minimap() {
-                let mut markers = SmallVec::<[(Color, Vec2); 6]>::new();
+                let mut markers = SmallVec::<[(PlayerId, Vec2); 8]>::new();
                 let levelscale = self.level.borrow().size_scale();
                 for ship in self.ships.borrow().iter() {
                     if ship.controller() > 0 && !ship.is_cloaked() {
                         markers.push((
-                            Color::player_color(ship.player_id()),
+                            ship.player_id(),
                             ship.pos().element_wise_product(levelscale),
                         ));
                     }
@@ -838,12 +1053,12 @@ impl World {
 
                 for pilot in self.pilots.borrow().iter() {
                     markers.push((
-                        Color::player_color(pilot.player_id()),
+                        pilot.player_id(),
                         pilot.pos().element_wise_product(levelscale),
                     ));
                 }
 
-                draw_minimap(renderer, minimap, &markers);
+                draw_minimap(renderer, minimap, &markers, !self.player_badges.is_empty());
             }
         }
 
@@ -859,8 +1074,125 @@ impl World {
         }
     }
 
+    /// Gather all light sources that may be visible in the camera rectangle
+    fn collect_lights(&self, level: &Level, camera_rect: RectF) -> Vec<(Vec2, Light)> {
+        let area = RectF::new(
+            camera_rect.x() - MAX_LIGHT_RADIUS,
+            camera_rect.y() - MAX_LIGHT_RADIUS,
+            camera_rect.w() + MAX_LIGHT_RADIUS * 2.0,
+            camera_rect.h() + MAX_LIGHT_RADIUS * 2.0,
+        );
+
+        let copies = wrap_offsets(area);
+        if copies.len() == 1 && copies[0] == Vec2::ZERO {
+            return self.collect_lights_in(level, area);
+        }
+
+        // In a wrapping level, lights from each visible copy of the level are included
+        let mut lights = Vec::new();
+        for offset in copies {
+            lights.extend(
+                self.collect_lights_in(level, area.offset(-offset.0, -offset.1))
+                    .into_iter()
+                    .map(|(pos, light)| (pos + offset, light)),
+            );
+        }
+        lights
+    }
+
+    fn collect_lights_in(&self, level: &Level, area: RectF) -> Vec<(Vec2, Light)> {
+        let left = area.x();
+        let right = area.right();
+
+        let mut lights = level.fire_lights(area);
+
+        lights.extend(
+            self.fixedobjects
+                .borrow()
+                .range_slice(left, right)
+                .iter()
+                .filter_map(|o| o.light().map(|l| (o.pos(), l))),
+        );
+
+        if let Some(gates) = self.jumpgates.as_ref() {
+            lights.extend(gates.lights());
+        }
+
+        lights.extend(
+            self.particles
+                .range_slice(left, right)
+                .iter()
+                .filter_map(|p| p.light().map(|l| (p.pos(), l))),
+        );
+
+        lights.extend(
+            self.mines
+                .borrow()
+                .range_slice(left, right)
+                .iter()
+                .chain(self.bullets.range_slice(left, right))
+                .filter_map(|p| p.light().map(|l| (p.pos(), l))),
+        );
+
+        lights.extend(
+            self.ships
+                .borrow()
+                .range_slice(left, right)
+                .iter()
+                .filter_map(|s| s.light().map(|l| (s.pos(), l))),
+        );
+
+        lights.extend(
+            self.pilots
+                .borrow()
+                .iter()
+                .filter_map(|p| p.light().map(|l| (p.pos(), l))),
+        );
+
+        lights
+    }
+
     pub fn level_size(&self) -> (f32, f32) {
         let level = self.level.borrow();
         (level.width(), level.height())
     }
+
+    /// Get the credits each player has earned this round
+    pub fn earned_credits(&self) -> Vec<i32> {
+        self.players
+            .borrow()
+            .iter()
+            .map(|p| p.credits.floor() as i32)
+            .collect()
+    }
+
+    /// Get the round's score, if the scripts have set one
+    pub fn score(&self) -> Option<i32> {
+        self.score
+    }
+}
+
+/// Move the camera towards the followed object.
+///
+/// The camera has some inertia for an enhanced feeling of motion,
+/// but if the object was teleported (e.g. by a script), it snaps to the new position.
+fn follow_camera(camera_pos: Vec2, target: Vec2) -> Vec2 {
+    // In a wrapping level, the camera follows the object smoothly over the edge
+    let delta = wrapped_delta(camera_pos, target);
+    if delta.magnitude_squared() > CAMERA_SNAP_DISTANCE * CAMERA_SNAP_DISTANCE {
+        target
+    } else {
+        wrap_position(camera_pos + delta / 5.0)
+    }
+}
+
+/// Move objects through jump gates and re-sort the array if any jumped
+fn pass_through_all<T: Jumper>(gates: &mut JumpGates, objects: &mut GameObjectArray<T>) {
+    let mut jumped = false;
+    for obj in objects.iter_mut() {
+        jumped |= gates.pass_through(obj);
+    }
+    if jumped {
+        objects.sort();
+    }
 }
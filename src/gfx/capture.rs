@@ -0,0 +1,84 @@
+// This file is part of Luola2
+// Copyright (C) 2025 Calle Laakkonen
+//
+// Luola2 is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Luola2 is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Luola2.  If not, see <https://www.gnu.org/licenses/>.
+
+use anyhow::Result;
+use std::{fs::create_dir_all, path::PathBuf};
+
+use super::Image;
+
+/**
+ * Frame capture state
+ *
+ * Every Nth presented frame is saved as a numbered PNG file
+ * in the capture directory. The frames can be turned into a video
+ * or an animated GIF with an external tool.
+ */
+pub struct FrameCapture {
+    dir: PathBuf,
+    /// Save every Nth frame
+    interval: u32,
+    /// Frames presented since capture started
+    frame: u32,
+    /// Number of frames saved so far
+    saved: u32,
+}
+
+impl FrameCapture {
+    pub fn new(dir: PathBuf, interval: u32) -> Result<Self> {
+        create_dir_all(&dir)?;
+        log::info!("Capturing every {interval} frame(s) to: {dir:?}");
+
+        Ok(Self {
+            dir,
+            interval: interval.max(1),
+            frame: 0,
+            saved: 0,
+        })
+    }
+
+    /// Check if the next frame should be saved
+    pub fn is_capture_frame(&self) -> bool {
+        self.frame.is_multiple_of(self.interval)
+    }
+
+    /// Advance the frame counter, saving the image if given
+    pub fn next_frame(&mut self, image: Option<Image>) -> Result<()> {
+        self.frame += 1;
+
+        if let Some(image) = image {
+            self.saved += 1;
+            let mut path = self.dir.clone();
+            path.push(format!("frame-{:06}.png", self.saved));
+            image.save_png(path)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn frame(&self) -> u32 {
+        self.frame
+    }
+
+    pub fn saved_frames(&self) -> u32 {
+        self.saved
+    }
+}
+
+impl Drop for FrameCapture {
+    fn drop(&mut self) {
+        log::info!("Captured {} frames to: {:?}", self.saved, self.dir);
+    }
+}
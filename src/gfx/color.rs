@@ -15,6 +15,9 @@
 // along with Luola2.  If not, see <https://www.gnu.org/licenses/>.
 
 use std::ops::{Add, Div, Mul, Sub};
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Copy, Debug)]
 pub struct Color {
@@ -38,6 +41,46 @@ impl Default for Color {
     }
 }
 
+/**
+ * Selectable player color palettes.
+ *
+ * The alternative palettes are chosen so that all player colors remain
+ * distinguishable with the given type of color vision deficiency.
+ */
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Default, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum PlayerPalette {
+    #[default]
+    Standard,
+    Deuteranopia,
+    Protanopia,
+    Tritanopia,
+    HighContrast,
+}
+
+impl PlayerPalette {
+    const ALL: [PlayerPalette; 5] = [
+        Self::Standard,
+        Self::Deuteranopia,
+        Self::Protanopia,
+        Self::Tritanopia,
+        Self::HighContrast,
+    ];
+
+    fn colors(self) -> &'static [Color; 8] {
+        match self {
+            Self::Standard => &Color::PLAYER_COLORS,
+            Self::Deuteranopia => &Color::PLAYER_COLORS_DEUTERANOPIA,
+            Self::Protanopia => &Color::PLAYER_COLORS_PROTANOPIA,
+            Self::Tritanopia => &Color::PLAYER_COLORS_TRITANOPIA,
+            Self::HighContrast => &Color::PLAYER_COLORS_HIGH_CONTRAST,
+        }
+    }
+}
+
+/// Currently selected player palette (index into PlayerPalette::ALL)
+static PLAYER_PALETTE: AtomicU8 = AtomicU8::new(0);
+
 impl Color {
     pub const PLAYER_COLORS: [Color; 8] = [
         Color::new(0.2, 0.35, 1.0),
@@ -50,6 +93,55 @@ impl Color {
         Color::new(51.0 / 255.0, 227.0 / 255.0, 190.0 / 255.0),
     ];
 
+    /// Okabe-Ito palette, which works for both red-green deficiencies
+    pub const PLAYER_COLORS_DEUTERANOPIA: [Color; 8] = [
+        Color::new(0.0, 0.447, 0.698),   // blue
+        Color::new(0.902, 0.624, 0.0),   // orange
+        Color::new(0.337, 0.706, 0.914), // sky blue
+        Color::new(0.941, 0.894, 0.259), // yellow
+        Color::new(0.8, 0.475, 0.655),   // reddish purple
+        Color::new(0.835, 0.369, 0.0),   // vermillion
+        Color::new(0.0, 0.62, 0.451),    // bluish green
+        Color::new(0.9, 0.9, 0.9),       // white
+    ];
+
+    /// Like the deuteranopia palette, but without vermillion, since reds appear
+    /// very dark with protanopia
+    pub const PLAYER_COLORS_PROTANOPIA: [Color; 8] = [
+        Color::new(0.0, 0.447, 0.698),   // blue
+        Color::new(0.902, 0.624, 0.0),   // orange
+        Color::new(0.337, 0.706, 0.914), // sky blue
+        Color::new(0.941, 0.894, 0.259), // yellow
+        Color::new(0.8, 0.475, 0.655),   // reddish purple
+        Color::new(0.6, 0.6, 0.6),       // grey
+        Color::new(0.0, 0.62, 0.451),    // bluish green
+        Color::new(0.9, 0.9, 0.9),       // white
+    ];
+
+    /// Avoids blue/green and yellow/violet pairs
+    pub const PLAYER_COLORS_TRITANOPIA: [Color; 8] = [
+        Color::new(0.0, 0.71, 0.72),  // teal
+        Color::new(0.89, 0.1, 0.11),  // red
+        Color::new(1.0, 0.62, 0.77),  // pink
+        Color::new(0.96, 0.96, 0.96), // white
+        Color::new(1.0, 0.5, 0.0),    // orange
+        Color::new(0.6, 0.6, 0.6),    // grey
+        Color::new(0.55, 0.27, 0.07), // brown
+        Color::new(0.45, 0.85, 0.95), // light cyan
+    ];
+
+    /// Fully saturated colors that stand out from the terrain
+    pub const PLAYER_COLORS_HIGH_CONTRAST: [Color; 8] = [
+        Color::new(0.0, 1.0, 1.0), // cyan
+        Color::new(1.0, 0.0, 1.0), // magenta
+        Color::new(1.0, 1.0, 0.0), // yellow
+        Color::new(1.0, 1.0, 1.0), // white
+        Color::new(0.0, 1.0, 0.0), // lime
+        Color::new(1.0, 0.2, 0.2), // red
+        Color::new(1.0, 0.6, 0.0), // orange
+        Color::new(0.5, 0.5, 1.0), // light blue
+    ];
+
     pub const WHITE: Color = Color::new(1.0, 1.0, 1.0);
     pub const BLACK: Color = Color::new(0.0, 0.0, 0.0);
 
@@ -125,15 +217,28 @@ impl Color {
         }
     }
 
-    /// Standard player colors (or white if not an active player)
+    /// Player color from the selected palette (or white if not an active player)
     pub fn player_color(id: i32) -> Color {
-        if id > 0 && id <= Self::PLAYER_COLORS.len() as i32 {
-            Self::PLAYER_COLORS[id as usize - 1]
+        let colors = Self::player_palette().colors();
+        if id > 0 && id <= colors.len() as i32 {
+            colors[id as usize - 1]
         } else {
             Self::WHITE
         }
     }
 
+    pub fn player_palette() -> PlayerPalette {
+        PlayerPalette::ALL[PLAYER_PALETTE.load(Ordering::Relaxed) as usize]
+    }
+
+    pub fn set_player_palette(palette: PlayerPalette) {
+        let idx = PlayerPalette::ALL
+            .iter()
+            .position(|&p| p == palette)
+            .unwrap_or(0);
+        PLAYER_PALETTE.store(idx as u8, Ordering::Relaxed);
+    }
+
     pub fn r_u8(&self) -> u8 {
         (self.r * 255.0).clamp(0.0, 255.0) as u8
     }
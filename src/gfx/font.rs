@@ -19,8 +19,8 @@ use std::{ffi::c_int, path::PathBuf, ptr::null_mut};
 use anyhow::Result;
 use sdl3_ttf_sys::ttf::{
     TTF_CloseFont, TTF_CopyFont, TTF_CreateText, TTF_DestroyText, TTF_DrawRendererText, TTF_Font,
-    TTF_GetFontOutline, TTF_GetTextFont, TTF_GetTextSize, TTF_OpenFont, TTF_SetFontOutline,
-    TTF_SetTextColorFloat, TTF_SetTextString, TTF_SetTextWrapWidth, TTF_Text,
+    TTF_GetFontOutline, TTF_GetStringSize, TTF_GetTextFont, TTF_GetTextSize, TTF_OpenFont,
+    TTF_SetFontOutline, TTF_SetTextColorFloat, TTF_SetTextString, TTF_SetTextWrapWidth, TTF_Text,
 };
 
 use crate::{
@@ -156,6 +156,26 @@ impl Font {
         Ok(Self { font, outline_font })
     }
 
+    /// Measure the size of the given string without creating a text object
+    pub fn string_size(&self, string: &str) -> Result<(f32, f32)> {
+        let mut width: c_int = 0;
+        let mut height: c_int = 0;
+
+        if !unsafe {
+            TTF_GetStringSize(
+                self.font,
+                string.as_ptr() as *const i8,
+                string.len(),
+                &mut width,
+                &mut height,
+            )
+        } {
+            return Err(SdlError::get_error("Couldn't measure text").into());
+        }
+
+        Ok((width as f32, height as f32))
+    }
+
     pub fn create_text(&self, renderer: &Renderer, string: &str) -> Result<Text> {
         // Note: the text object retains a pointer to the textengine (which points to the renderer)
         // even though we don't model the lifetime, this should be OK since the renderer
@@ -14,11 +14,13 @@
 // You should have received a copy of the GNU General Public License
 // along with Luola2.  If not, see <https://www.gnu.org/licenses/>.
 
+use std::collections::HashMap;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use super::Font;
-use anyhow::Result;
+use crate::fs::datafile_origin;
+use anyhow::{Context, Result, anyhow};
 use serde::Deserialize;
 
 pub struct FontSet {
@@ -36,38 +38,118 @@ struct FontOptions {
     outline: i32,
 }
 
+#[derive(Deserialize)]
+struct FontSetFonts {
+    menu: Option<FontOptions>,
+    menu_big: Option<FontOptions>,
+    menu_caption: Option<FontOptions>,
+    flavortext: Option<FontOptions>,
+}
+
 #[derive(Deserialize)]
 struct FontSetConfig {
-    menu: FontOptions,
-    menu_big: FontOptions,
-    menu_caption: FontOptions,
-    flavortext: FontOptions,
+    #[serde(flatten)]
+    fonts: FontSetFonts,
+
+    /// Per-language font overrides (e.g. for scripts the default font lacks glyphs for)
+    #[serde(default)]
+    lang: HashMap<String, FontSetFonts>,
 }
 
+/// Font options and the directory the font file is relative to
+type FontSource<'a> = Option<(&'a Path, FontOptions)>;
+
 impl FontSet {
-    pub fn load_from_toml(config_file: &Path) -> Result<FontSet> {
-        let content = fs::read_to_string(config_file)?;
-        let config: FontSetConfig = toml::from_str(&content)?;
+    /**
+     * Load the fontset from the given fonts.toml files.
+     *
+     * Fonts defined in later files replace the ones in earlier files,
+     * so mods only need to list the fonts they change.
+     *
+     * Fonts listed in a [lang.<code>] section for the given language
+     * are applied after all the default fonts.
+     *
+     * Font sizes are multiplied by the UI scale factor.
+     */
+    pub fn load_from_toml(
+        config_files: &[PathBuf],
+        language: &str,
+        ui_scale: f32,
+    ) -> Result<FontSet> {
+        let mut menu: FontSource = None;
+        let mut menu_caption: FontSource = None;
+        let mut menu_big: FontSource = None;
+        let mut flavortext: FontSource = None;
+
+        let mut lang_overrides = Vec::new();
+
+        for config_file in config_files {
+            let content =
+                fs::read_to_string(config_file).with_context(|| datafile_origin(config_file))?;
+            let mut config: FontSetConfig =
+                toml::from_str(&content).with_context(|| datafile_origin(config_file))?;
 
-        let root = config_file
-            .parent()
-            .expect("fonts.toml should have a parent directory");
+            let root = config_file
+                .parent()
+                .expect("fonts.toml should have a parent directory");
+
+            if let Some(fonts) = config.lang.remove(language) {
+                lang_overrides.push((root, fonts));
+            }
+
+            Self::apply_fonts(
+                root,
+                config.fonts,
+                [&mut menu, &mut menu_caption, &mut menu_big, &mut flavortext],
+            );
+        }
+
+        for (root, fonts) in lang_overrides {
+            Self::apply_fonts(
+                root,
+                fonts,
+                [&mut menu, &mut menu_caption, &mut menu_big, &mut flavortext],
+            );
+        }
 
         Ok(Self {
-            menu: config.menu.load(root)?,
-            menu_caption: config.menu_caption.load(root)?,
-            menu_big: config.menu_big.load(root)?,
-            flavotext: config.flavortext.load(root)?,
+            menu: Self::load_font("menu", menu, ui_scale)?,
+            menu_caption: Self::load_font("menu_caption", menu_caption, ui_scale)?,
+            menu_big: Self::load_font("menu_big", menu_big, ui_scale)?,
+            flavotext: Self::load_font("flavortext", flavortext, ui_scale)?,
         })
     }
+
+    fn apply_fonts<'a>(root: &'a Path, fonts: FontSetFonts, targets: [&mut FontSource<'a>; 4]) {
+        let [menu, menu_caption, menu_big, flavortext] = targets;
+        let sources = [
+            (menu, fonts.menu),
+            (menu_caption, fonts.menu_caption),
+            (menu_big, fonts.menu_big),
+            (flavortext, fonts.flavortext),
+        ];
+
+        for (source, options) in sources {
+            if let Some(options) = options {
+                *source = Some((root, options));
+            }
+        }
+    }
+
+    fn load_font(name: &str, source: FontSource, scale: f32) -> Result<Font> {
+        let (root, options) = source.ok_or_else(|| anyhow!("Font \"{name}\" not defined"))?;
+        options
+            .load(root, scale)
+            .with_context(|| format!("{}: font \"{name}\"", datafile_origin(root)))
+    }
 }
 
 impl FontOptions {
-    fn load(&self, root: &Path) -> Result<Font> {
+    fn load(&self, root: &Path, scale: f32) -> Result<Font> {
         Font::from_file(
             [root, Path::new(&self.file)].iter().collect(),
-            self.size,
-            self.outline,
+            self.size * scale,
+            (self.outline as f32 * scale).round() as i32,
         )
     }
 }
@@ -16,13 +16,13 @@
 
 use anyhow::Result;
 use core::slice;
-use sdl3_image_sys::image::IMG_Load;
+use sdl3_image_sys::image::{IMG_Load, IMG_SavePNG};
 use sdl3_sys::{
     pixels::{SDL_PIXELFORMAT_ARGB8888, SDL_PIXELFORMAT_INDEX8, SDL_Palette},
     rect::SDL_Rect,
     surface::{
         SDL_BlitSurface, SDL_ConvertSurface, SDL_DestroySurface, SDL_GetSurfacePalette,
-        SDL_SCALEMODE_LINEAR, SDL_SCALEMODE_NEAREST, SDL_SaveBMP, SDL_ScaleSurface, SDL_Surface,
+        SDL_SCALEMODE_LINEAR, SDL_SCALEMODE_NEAREST, SDL_ScaleSurface, SDL_Surface,
     },
 };
 use std::path::PathBuf;
@@ -164,12 +164,11 @@ impl Image {
         Ok(Image(surface))
     }
 
-    /// Save the image as a BMP
-    /// TODO once we update to SDL 3.4, replace this with save_png
-    pub fn save_bmp(&self, path: PathBuf) -> Result<()> {
+    /// Save the image as a PNG
+    pub fn save_png(&self, path: PathBuf) -> Result<()> {
         let path = pathbuf_to_cstring(path)?;
-        if unsafe { !SDL_SaveBMP(self.0, path.as_ptr()) } {
-            return Err(SdlError::get_error("IMG_load").into());
+        if unsafe { !IMG_SavePNG(self.0, path.as_ptr()) } {
+            return Err(SdlError::get_error("IMG_SavePNG").into());
         }
 
         Ok(())
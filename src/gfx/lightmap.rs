@@ -0,0 +1,216 @@
+// This file is part of Luola2
+// Copyright (C) 2025 Calle Laakkonen
+//
+// Luola2 is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Luola2 is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Luola2.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::cell::RefCell;
+
+use anyhow::Result;
+use log::error;
+
+use super::{BlendMode, Color, RenderDest, RenderOptions, Renderer, Texture, TextureScaleMode};
+use crate::math::{RectF, Vec2};
+
+/// Size of the generated light sprite
+const LIGHT_TEXTURE_SIZE: i32 = 64;
+
+/// Lights bigger than this are clamped. This is also the margin
+/// outside the viewport where light sources are still looked for.
+pub const MAX_LIGHT_RADIUS: f32 = 400.0;
+
+/**
+ * A point light source attached to a game object
+ */
+#[derive(Clone, Copy, Debug)]
+pub struct Light {
+    /// Radius of the lit area in world coordinates
+    pub radius: f32,
+
+    /// Color of the light. Alpha is the intensity.
+    pub color: Color,
+}
+
+impl Light {
+    pub const fn new(radius: f32, color: u32) -> Self {
+        Self {
+            radius,
+            color: Color::from_argb_u32(color),
+        }
+    }
+
+    /// Get a copy of this light with its intensity multiplied by the given factor
+    pub fn dimmed(&self, factor: f32) -> Self {
+        Self {
+            radius: self.radius,
+            color: self.color.with_alpha(self.color.a * factor.clamp(0.0, 1.0)),
+        }
+    }
+}
+
+impl mlua::FromLua for Light {
+    fn from_lua(value: mlua::Value, _lua: &mlua::Lua) -> mlua::Result<Self> {
+        if let mlua::Value::Table(table) = value {
+            Ok(Light {
+                radius: table.get::<f32>("radius")?.clamp(0.0, MAX_LIGHT_RADIUS),
+                color: Color::from_argb_u32(
+                    table.get::<Option<u32>>("color")?.unwrap_or(0xffffffff),
+                ),
+            })
+        } else {
+            Err(mlua::Error::FromLuaConversionError {
+                from: value.type_name(),
+                to: "Light".to_owned(),
+                message: Some("expected a table describing a light".to_string()),
+            })
+        }
+    }
+}
+
+impl mlua::IntoLua for Light {
+    fn into_lua(self, lua: &mlua::Lua) -> mlua::Result<mlua::Value> {
+        let table = lua.create_table()?;
+        table.set("radius", self.radius)?;
+        table.set("color", self.color.as_argb_u32())?;
+        Ok(mlua::Value::Table(table))
+    }
+}
+
+/**
+ * A lightmap composited over the level and the objects in it.
+ *
+ * The lightmap is first filled with the ambient light color. Lights are then
+ * added on top of it and the result is multiplied with the viewport content.
+ * Only render target textures and plain blend modes are used, so this works
+ * with the software renderer too.
+ */
+pub struct Lightmap {
+    /// Color of unlit areas
+    ambient: Color,
+
+    /// Radial gradient sprite used to draw the lights
+    light: Texture,
+
+    /// Lightmap buffer. Grown as needed to fit the biggest viewport.
+    target: RefCell<Option<Texture>>,
+}
+
+impl Lightmap {
+    pub fn new(renderer: &Renderer, ambient: Color) -> Result<Self> {
+        let size = LIGHT_TEXTURE_SIZE;
+        let center = size as f32 / 2.0;
+        let mut pixels = vec![0u32; (size * size) as usize];
+
+        for y in 0..size {
+            for x in 0..size {
+                let d = Vec2(x as f32 + 0.5 - center, y as f32 + 0.5 - center).magnitude() / center;
+                let i = (1.0 - d).max(0.0);
+                let alpha = (i * i * 255.0) as u32;
+                pixels[(y * size + x) as usize] = (alpha << 24) | 0x00ffffff;
+            }
+        }
+
+        let mut light = Texture::new_streaming(renderer, size, size)?;
+        light.write_pixels(&pixels, 0, 0, size, size);
+        light.set_blendmode(BlendMode::Add);
+        light.set_scalemode(TextureScaleMode::Linear);
+
+        Ok(Self {
+            ambient: ambient.with_alpha(1.0),
+            light,
+            target: RefCell::new(None),
+        })
+    }
+
+    /// Get a lightmap buffer at least as big as requested
+    fn target(&self, renderer: &Renderer, width: i32, height: i32) -> Result<Texture> {
+        let mut target = self.target.borrow_mut();
+
+        if let Some(t) = target.as_ref()
+            && t.width() as i32 >= width
+            && t.height() as i32 >= height
+        {
+            return Ok(t.clone());
+        }
+
+        let (oldw, oldh) = target
+            .as_ref()
+            .map_or((0, 0), |t| (t.width() as i32, t.height() as i32));
+
+        let mut t = Texture::new_render_target(renderer, width.max(oldw), height.max(oldh))?;
+        t.set_blendmode(BlendMode::Multiply);
+        *target = Some(t.clone());
+
+        Ok(t)
+    }
+
+    /**
+     * Draw the lightmap over the current viewport.
+     *
+     * The camera rectangle gives the part of the world visible in the viewport.
+     * Light positions are in world coordinates.
+     */
+    pub fn render<I>(&self, renderer: &Renderer, camera: RectF, lights: I)
+    where
+        I: Iterator<Item = (Vec2, Light)>,
+    {
+        let w = camera.w().ceil();
+        let h = camera.h().ceil();
+
+        let target = match self.target(renderer, w as i32, h as i32) {
+            Ok(t) => t,
+            Err(err) => {
+                error!("Couldn't create lightmap: {err}");
+                return;
+            }
+        };
+
+        if let Err(err) = renderer.set_render_target(Some(&target)) {
+            error!("{err}");
+            return;
+        }
+
+        renderer.draw_filled_rectangle(RectF::new(0.0, 0.0, w, h), &self.ambient);
+
+        let camera_pos = camera.topleft();
+        for (pos, light) in lights {
+            let p = pos - camera_pos;
+            let r = light.radius;
+            if p.0 + r < 0.0 || p.1 + r < 0.0 || p.0 - r > w || p.1 - r > h {
+                continue;
+            }
+
+            self.light.render(
+                renderer,
+                &RenderOptions {
+                    dest: RenderDest::Rect(RectF::new(p.0 - r, p.1 - r, r * 2.0, r * 2.0)),
+                    color: light.color,
+                    ..Default::default()
+                },
+            );
+        }
+
+        if let Err(err) = renderer.set_render_target(None) {
+            error!("{err}");
+            return;
+        }
+
+        target.render(
+            renderer,
+            &RenderOptions {
+                source: Some(RectF::new(0.0, 0.0, w, h)),
+                ..Default::default()
+            },
+        );
+    }
+}
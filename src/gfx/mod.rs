@@ -15,23 +15,29 @@
 // along with Luola2.  If not, see <https://www.gnu.org/licenses/>.
 
 mod animtex;
+mod capture;
 mod color;
 mod errors;
 mod font;
 mod fontset;
 mod image;
+mod lightmap;
 mod renderer;
 mod specialtextures;
+mod textinput;
 mod texture;
 mod texturestore;
 
 pub use animtex::*;
+pub use capture::*;
 pub use color::*;
 pub use errors::*;
 pub use font::*;
 pub use fontset::*;
 pub use image::*;
+pub use lightmap::*;
 pub use renderer::*;
 pub use specialtextures::*;
+pub use textinput::*;
 pub use texture::*;
 pub use texturestore::*;
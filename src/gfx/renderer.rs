@@ -16,26 +16,38 @@
 
 use anyhow::{Result, anyhow};
 use sdl3_sys::blendmode::SDL_BLENDMODE_BLEND;
+use sdl3_sys::keyboard::{SDL_StartTextInput, SDL_StopTextInput};
 use sdl3_sys::mouse::{SDL_HideCursor, SDL_ShowCursor};
 use sdl3_sys::rect::SDL_FPoint;
 use sdl3_sys::render::{
-    SDL_DestroyRenderer, SDL_RenderDebugText, SDL_RenderFillRect, SDL_RenderPoint,
+    SDL_DestroyRenderer, SDL_LOGICAL_PRESENTATION_DISABLED, SDL_LOGICAL_PRESENTATION_INTEGER_SCALE,
+    SDL_LOGICAL_PRESENTATION_LETTERBOX, SDL_RenderDebugText, SDL_RenderFillRect, SDL_RenderPoint,
     SDL_RenderPoints, SDL_RenderReadPixels, SDL_SetRenderDrawBlendMode,
-    SDL_SetRenderDrawColorFloat,
+    SDL_SetRenderDrawColorFloat, SDL_SetRenderLogicalPresentation, SDL_SetRenderTarget,
+};
+use sdl3_sys::stdinc::SDL_free;
+use sdl3_sys::video::{
+    SDL_DisplayID, SDL_DisplayMode, SDL_GetClosestFullscreenDisplayMode, SDL_GetDisplayForWindow,
+    SDL_GetDisplayName, SDL_GetDisplays, SDL_GetPrimaryDisplay, SDL_GetWindowSize,
+    SDL_SetWindowFullscreen, SDL_SetWindowFullscreenMode, SDL_SetWindowPosition, SDL_ShowWindow,
+    SDL_WINDOW_HIDDEN, SDL_WINDOW_RESIZABLE, SDL_WINDOWPOS_CENTERED_DISPLAY,
 };
-use sdl3_sys::video::{SDL_SetWindowFullscreen, SDL_WINDOW_FULLSCREEN, SDL_WINDOW_RESIZABLE};
 use sdl3_ttf_sys::ttf::{
     TTF_CreateRendererTextEngine, TTF_DestroyRendererTextEngine, TTF_Init, TTF_TextEngine,
 };
-use std::ffi::{CStr, CString};
-use std::path::Path;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::ffi::{CStr, CString, c_void};
+use std::mem::MaybeUninit;
+use std::path::PathBuf;
 use std::ptr::{null, null_mut};
 
-use crate::gfx::{FontSet, Image};
+use crate::configfile::VideoConfig;
+use crate::gfx::{FontSet, FrameCapture, Image};
 use crate::math::{LineF, Rect, RectF, Vec2};
 
 use super::texturestore::*;
-use super::{Color, SdlError, SdlResult};
+use super::{Color, SdlError, SdlResult, Texture};
 use sdl3_sys::{
     pixels::SDL_ALPHA_OPAQUE,
     rect::SDL_Rect,
@@ -56,6 +68,66 @@ pub struct Renderer {
     width: i32,
     height: i32,
     fullscreen: bool,
+    fullscreen_mode: FullscreenMode,
+    ui_scale: f32,
+    capture: RefCell<Option<FrameCapture>>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Default, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum FullscreenMode {
+    /// Fullscreen desktop window
+    #[default]
+    Borderless,
+    /// Change the display mode to the one closest to the window size
+    Exclusive,
+}
+
+/// How the logical resolution is presented in the window
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Default, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ScalingMode {
+    /// No logical resolution: render at the window's resolution
+    #[default]
+    Off,
+    /// Scale to fit the window, keeping the aspect ratio
+    Letterbox,
+    /// Scale by whole multiples only for pixel-perfect output
+    Integer,
+}
+
+pub const MIN_UI_SCALE: f32 = 0.5;
+pub const MAX_UI_SCALE: f32 = 3.0;
+
+/// Get the IDs and names of the connected displays
+pub fn displays() -> Vec<(SDL_DisplayID, String)> {
+    let mut count = 0;
+    let ids = unsafe { SDL_GetDisplays(&mut count) };
+    if ids.is_null() {
+        SdlError::log("Couldn't get displays");
+        return Vec::new();
+    }
+
+    let displays = unsafe { std::slice::from_raw_parts(ids, count as usize) }
+        .iter()
+        .map(|&id| {
+            let name = unsafe { SDL_GetDisplayName(id) };
+            let name = if name.is_null() {
+                format!("Display {}", id.0)
+            } else {
+                unsafe { CStr::from_ptr(name) }
+                    .to_string_lossy()
+                    .into_owned()
+            };
+            (id, name)
+        })
+        .collect();
+
+    unsafe {
+        SDL_free(ids as *mut c_void);
+    }
+
+    displays
 }
 
 impl Drop for Renderer {
@@ -67,22 +139,20 @@ impl Drop for Renderer {
     }
 }
 impl Renderer {
-    pub fn create(fullscreen: bool) -> SdlResult<Self> {
+    pub fn create(fullscreen: bool, video: &VideoConfig) -> SdlResult<Self> {
         let mut window: *mut SDL_Window = null_mut();
         let mut renderer: *mut SDL_Renderer = null_mut();
 
-        let mut flags = SDL_WINDOW_RESIZABLE;
-        if fullscreen {
-            flags |= SDL_WINDOW_FULLSCREEN;
-        }
+        // The window is shown once it has been placed on the right display
+        let flags = SDL_WINDOW_RESIZABLE | SDL_WINDOW_HIDDEN;
 
         if !unsafe {
             SDL_CreateWindowAndRenderer(
                 CString::new(format!("Luola II {}", env!("CARGO_PKG_VERSION")))
                     .unwrap()
                     .as_ptr(),
-                1280,
-                720,
+                video.window_size.0 as i32,
+                video.window_size.1 as i32,
                 flags,
                 &mut window,
                 &mut renderer,
@@ -91,8 +161,37 @@ impl Renderer {
             return Err(SdlError::get_error("Couldn't create renderer"));
         }
 
-        if !unsafe { SDL_SetRenderVSync(renderer, 1) } {
-            return Err(SdlError::get_error("Couldn't enable V-Sync"));
+        let display = displays()
+            .get(video.display)
+            .map(|d| d.0)
+            .unwrap_or_else(|| unsafe { SDL_GetPrimaryDisplay() });
+
+        unsafe {
+            let pos = SDL_WINDOWPOS_CENTERED_DISPLAY(display);
+            SDL_SetWindowPosition(window, pos, pos);
+        }
+
+        if !unsafe { SDL_SetRenderVSync(renderer, if video.vsync { 1 } else { 0 }) } {
+            SdlError::log("Couldn't set V-Sync");
+        }
+
+        let presentation = match video.scaling {
+            ScalingMode::Off => SDL_LOGICAL_PRESENTATION_DISABLED,
+            ScalingMode::Letterbox => SDL_LOGICAL_PRESENTATION_LETTERBOX,
+            ScalingMode::Integer => SDL_LOGICAL_PRESENTATION_INTEGER_SCALE,
+        };
+
+        if presentation != SDL_LOGICAL_PRESENTATION_DISABLED
+            && !unsafe {
+                SDL_SetRenderLogicalPresentation(
+                    renderer,
+                    video.logical_size.0 as i32,
+                    video.logical_size.1 as i32,
+                    presentation,
+                )
+            }
+        {
+            SdlError::log("Couldn't set logical presentation");
         }
 
         if !unsafe { TTF_Init() } {
@@ -108,13 +207,7 @@ impl Renderer {
             return Err(SdlError::get_error("Couldn't create text engine"));
         }
 
-        if fullscreen {
-            unsafe {
-                SDL_HideCursor();
-            }
-        }
-
-        Ok(Self {
+        let mut r = Self {
             window,
             renderer,
             texturestore: TextureStore::new(),
@@ -122,12 +215,32 @@ impl Renderer {
             textengine,
             width: 1280,
             height: 720,
-            fullscreen,
-        })
+            fullscreen: false,
+            fullscreen_mode: video.fullscreen_mode,
+            ui_scale: video.ui_scale.clamp(MIN_UI_SCALE, MAX_UI_SCALE),
+            capture: RefCell::new(None),
+        };
+
+        if fullscreen {
+            r.toggle_fullscreen();
+        }
+
+        unsafe {
+            SDL_ShowWindow(window);
+        }
+
+        r.reset_viewport()?;
+
+        Ok(r)
     }
 
     pub fn toggle_fullscreen(&mut self) {
         self.fullscreen = !self.fullscreen;
+
+        if self.fullscreen {
+            self.set_fullscreen_mode();
+        }
+
         unsafe {
             SDL_SetWindowFullscreen(self.window, self.fullscreen);
             if self.fullscreen {
@@ -138,6 +251,57 @@ impl Renderer {
         }
     }
 
+    /**
+     * Select the display mode used when the window goes fullscreen.
+     *
+     * In exclusive mode, the display mode closest to the window size is used.
+     */
+    fn set_fullscreen_mode(&self) {
+        let ok = unsafe {
+            match self.fullscreen_mode {
+                FullscreenMode::Borderless => SDL_SetWindowFullscreenMode(self.window, null()),
+                FullscreenMode::Exclusive => {
+                    let mut w = 0;
+                    let mut h = 0;
+                    SDL_GetWindowSize(self.window, &mut w, &mut h);
+
+                    let mut mode = MaybeUninit::<SDL_DisplayMode>::uninit();
+                    SDL_GetClosestFullscreenDisplayMode(
+                        SDL_GetDisplayForWindow(self.window),
+                        w,
+                        h,
+                        0.0,
+                        false,
+                        mode.as_mut_ptr(),
+                    ) && SDL_SetWindowFullscreenMode(self.window, mode.as_ptr())
+                }
+            }
+        };
+
+        if !ok {
+            SdlError::log("Couldn't set fullscreen mode");
+        }
+    }
+
+    /// Scale factor for fonts, menus and the HUD
+    pub fn ui_scale(&self) -> f32 {
+        self.ui_scale
+    }
+
+    /// Enable or disable text input events
+    pub fn set_text_input(&self, enabled: bool) {
+        let ok = unsafe {
+            if enabled {
+                SDL_StartTextInput(self.window)
+            } else {
+                SDL_StopTextInput(self.window)
+            }
+        };
+        if !ok {
+            SdlError::log("Couldn't toggle text input");
+        }
+    }
+
     pub fn size(&self) -> (i32, i32) {
         (self.width, self.height)
     }
@@ -150,21 +314,25 @@ impl Renderer {
         self.height
     }
 
-    pub fn load_textures(&mut self, texture_config: &Path) -> Result<()> {
+    pub fn load_textures(&mut self, texture_configs: &[PathBuf]) -> Result<()> {
         if self.texture_store().count() > 0 {
             return Err(anyhow!("Textures already loaded"));
         }
 
-        self.texturestore = TextureStore::load_from_toml(self, texture_config)?;
+        self.texturestore = TextureStore::load_from_toml(self, texture_configs)?;
         Ok(())
     }
 
-    pub fn load_fontset(&mut self, fontset_config: &Path) -> Result<()> {
+    pub fn load_fontset(&mut self, fontset_configs: &[PathBuf], language: &str) -> Result<()> {
         if self.fontset.is_some() {
             return Err(anyhow!("Fontset already loaded"));
         }
 
-        self.fontset = Some(FontSet::load_from_toml(fontset_config)?);
+        self.fontset = Some(FontSet::load_from_toml(
+            fontset_configs,
+            language,
+            self.ui_scale,
+        )?);
         Ok(())
     }
 
@@ -217,6 +385,22 @@ impl Renderer {
         Ok(())
     }
 
+    /**
+     * Draw into the given texture instead of the window.
+     *
+     * The texture must have been created with Texture::new_render_target.
+     * Pass None to go back to drawing into the window. The window's viewport
+     * is restored when the target is reset.
+     */
+    pub fn set_render_target(&self, target: Option<&Texture>) -> SdlResult<()> {
+        let tex = target.map_or(null_mut(), |t| t.as_ptr());
+        if !unsafe { SDL_SetRenderTarget(self.renderer, tex) } {
+            return Err(SdlError::get_error("couldn't set render target"));
+        }
+
+        Ok(())
+    }
+
     pub fn clear(&self) {
         unsafe {
             SDL_SetRenderDrawColor(self.renderer, 0, 0, 0, SDL_ALPHA_OPAQUE);
@@ -301,11 +485,62 @@ impl Renderer {
     }
 
     pub fn present(&self) {
+        let mut capture = self.capture.borrow_mut();
+        if let Some(c) = capture.as_mut() {
+            let image = if c.is_capture_frame() {
+                match self.screenshot() {
+                    Ok(img) => Some(img),
+                    Err(err) => {
+                        log::error!("Couldn't read frame: {err}");
+                        None
+                    }
+                }
+            } else {
+                None
+            };
+
+            if let Err(err) = c.next_frame(image) {
+                log::error!("Couldn't save frame, stopping capture: {err}");
+                *capture = None;
+            } else {
+                self.draw_capture_indicator(c.frame(), c.saved_frames());
+            }
+        }
+
         unsafe {
             SDL_RenderPresent(self.renderer);
         }
     }
 
+    /// Start saving presented frames to the given directory
+    pub fn start_capture(&self, dir: PathBuf, interval: u32) -> Result<()> {
+        *self.capture.borrow_mut() = Some(FrameCapture::new(dir, interval)?);
+        Ok(())
+    }
+
+    pub fn stop_capture(&self) {
+        self.capture.borrow_mut().take();
+    }
+
+    pub fn is_capturing(&self) -> bool {
+        self.capture.borrow().is_some()
+    }
+
+    /// Draw a blinking recording indicator in the top right corner.
+    /// This is drawn after the frame has been captured, so it is not included in the output.
+    fn draw_capture_indicator(&self, frame: u32, saved: u32) {
+        if (frame / 30).is_multiple_of(2) {
+            self.draw_filled_rectangle(
+                RectF::new(self.width as f32 - 28.0, 12.0, 16.0, 16.0),
+                &Color::new(0.9, 0.1, 0.1),
+            );
+        }
+
+        let text = CString::new(format!("REC {saved}")).unwrap();
+        let x = self.width as f32 - 40.0 - text.as_bytes().len() as f32 * 8.0;
+        self.draw_debug_text(&text, x, 16.0);
+    }
+
     pub fn screenshot(&self) -> Result<Image> {
         let surface = unsafe { SDL_RenderReadPixels(self.renderer, null()) };
         if surface.is_null() {
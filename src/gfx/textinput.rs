@@ -0,0 +1,155 @@
+// This file is part of Luola2
+// Copyright (C) 2025 Calle Laakkonen
+//
+// Luola2 is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Luola2 is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Luola2.  If not, see <https://www.gnu.org/licenses/>.
+
+use anyhow::Result;
+use sdl3_sys::keycode::{
+    SDL_Keycode, SDLK_BACKSPACE, SDLK_DELETE, SDLK_END, SDLK_HOME, SDLK_LEFT, SDLK_RIGHT,
+};
+
+use crate::{
+    gfx::{Color, Font, RenderTextDest, RenderTextOptions, Renderer, Text},
+    math::{RectF, Vec2},
+};
+
+/**
+ * A single line text editing widget.
+ *
+ * The widget does not receive input by itself: the owning state
+ * must feed it text input events and editing keys.
+ */
+pub struct TextInput {
+    buffer: String,
+
+    /// Cursor position as a byte offset into the buffer
+    cursor: usize,
+
+    text: Text,
+
+    /// Caret blink timer
+    blink: f32,
+}
+
+impl TextInput {
+    pub fn new(renderer: &Renderer, font: &Font) -> Result<Self> {
+        Ok(Self {
+            buffer: String::new(),
+            cursor: 0,
+            text: font.create_text(renderer, "")?,
+            blink: 0.0,
+        })
+    }
+
+    pub fn value(&self) -> &str {
+        &self.buffer
+    }
+
+    /// Replace the content and move the cursor to the end
+    pub fn set_value(&mut self, value: &str) {
+        self.buffer = value.to_string();
+        self.cursor = self.buffer.len();
+        self.changed();
+    }
+
+    /// Take the current content, leaving the input empty
+    pub fn take(&mut self) -> String {
+        let value = std::mem::take(&mut self.buffer);
+        self.cursor = 0;
+        self.changed();
+        value
+    }
+
+    /// Insert text at the cursor position
+    pub fn insert(&mut self, text: &str) {
+        self.buffer.insert_str(self.cursor, text);
+        self.cursor += text.len();
+        self.changed();
+    }
+
+    /// Handle an editing key. Returns false if the key is not an editing key.
+    pub fn handle_key(&mut self, key: SDL_Keycode) -> bool {
+        match key {
+            SDLK_BACKSPACE => {
+                if let Some(c) = self.buffer[..self.cursor].chars().next_back() {
+                    self.cursor -= c.len_utf8();
+                    self.buffer.remove(self.cursor);
+                }
+            }
+            SDLK_DELETE => {
+                if self.cursor < self.buffer.len() {
+                    self.buffer.remove(self.cursor);
+                }
+            }
+            SDLK_LEFT => {
+                if let Some(c) = self.buffer[..self.cursor].chars().next_back() {
+                    self.cursor -= c.len_utf8();
+                }
+            }
+            SDLK_RIGHT => {
+                if let Some(c) = self.buffer[self.cursor..].chars().next() {
+                    self.cursor += c.len_utf8();
+                }
+            }
+            SDLK_HOME => self.cursor = 0,
+            SDLK_END => self.cursor = self.buffer.len(),
+            _ => return false,
+        }
+        self.changed();
+        true
+    }
+
+    pub fn step(&mut self, timestep: f32) {
+        self.blink = (self.blink + timestep) % 1.0;
+    }
+
+    fn changed(&mut self) {
+        self.text.set_text(&self.buffer);
+        self.blink = 0.0;
+    }
+
+    pub fn height(&self, font: &Font) -> f32 {
+        match font.string_size(" ") {
+            Ok((_, h)) => h,
+            Err(err) => {
+                log::error!("{err}");
+                self.text.height()
+            }
+        }
+    }
+
+    /// Render the text and the caret.
+    /// The font should be the same one the widget was created with.
+    pub fn render(&self, renderer: &Renderer, font: &Font, pos: Vec2, color: Color) {
+        self.text.render(&RenderTextOptions {
+            dest: RenderTextDest::TopLeft(pos),
+            color: Some(color),
+            ..Default::default()
+        });
+
+        if self.blink < 0.5 {
+            let (x, h) = match font.string_size(&self.buffer[..self.cursor]) {
+                Ok(size) => size,
+                Err(err) => {
+                    log::error!("{err}");
+                    return;
+                }
+            };
+            renderer.draw_filled_rectangle(
+                RectF::new(pos.0 + x, pos.1, 2.0, h.max(self.height(font))),
+                &color,
+            );
+        }
+    }
+}
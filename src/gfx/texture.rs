@@ -31,7 +31,7 @@ use super::{Renderer, SdlError};
 use anyhow::Result;
 use sdl3_image_sys::image::IMG_LoadTexture;
 use sdl3_sys::{
-    blendmode::{SDL_BLENDMODE_ADD, SDL_BLENDMODE_BLEND},
+    blendmode::{SDL_BLENDMODE_ADD, SDL_BLENDMODE_BLEND, SDL_BLENDMODE_MOD},
     pixels::SDL_PIXELFORMAT_ARGB8888,
     rect::SDL_Rect,
     render::{
@@ -104,6 +104,8 @@ pub enum BlendMode {
     #[default]
     AlphaBlend,
     Add,
+    /// Multiply the destination color with the texture color
+    Multiply,
 }
 
 #[derive(Clone, Copy)]
@@ -235,15 +237,7 @@ impl Texture {
             tex.set_scalemode(*scale);
         }
 
-        unsafe {
-            SDL_SetTextureBlendMode(
-                tex.tex,
-                match config.blend {
-                    BlendMode::AlphaBlend => SDL_BLENDMODE_BLEND,
-                    BlendMode::Add => SDL_BLENDMODE_ADD,
-                },
-            );
-        }
+        tex.set_blendmode(config.blend);
         Ok(tex)
     }
 
@@ -274,6 +268,21 @@ impl Texture {
         Self::from_texture(tex)
     }
 
+    /// Create a blank texture that can be rendered to
+    pub fn new_render_target(renderer: &Renderer, width: i32, height: i32) -> Result<Texture> {
+        let tex = unsafe {
+            SDL_CreateTexture(
+                renderer.renderer,
+                SDL_PIXELFORMAT_ARGB8888,
+                SDL_TextureAccess::TARGET,
+                width,
+                height,
+            )
+        };
+
+        Self::from_texture(tex)
+    }
+
     fn from_texture(tex: *mut SDL_Texture) -> Result<Texture> {
         if tex.is_null() {
             return Err(SdlError::get_error("Couldn't convert image into texture").into());
@@ -323,6 +332,22 @@ impl Texture {
         }
     }
 
+    pub fn set_blendmode(&mut self, mode: BlendMode) {
+        let mode = match mode {
+            BlendMode::AlphaBlend => SDL_BLENDMODE_BLEND,
+            BlendMode::Add => SDL_BLENDMODE_ADD,
+            BlendMode::Multiply => SDL_BLENDMODE_MOD,
+        };
+
+        unsafe {
+            SDL_SetTextureBlendMode(self.tex, mode);
+        }
+    }
+
+    pub(super) fn as_ptr(&self) -> *mut SDL_Texture {
+        self.tex
+    }
+
     pub fn width(&self) -> f32 {
         self.width
     }
@@ -14,13 +14,18 @@
 // You should have received a copy of the GNU General Public License
 // along with Luola2.  If not, see <https://www.gnu.org/licenses/>.
 
+use crate::fs::datafile_origin;
 use crate::gfx::{Renderer, TextureConfigWithAlts};
 
 use super::Texture;
-use anyhow::{Result, anyhow};
+use anyhow::{Context, Result, anyhow};
 use sdl3_sys::render::SDL_Texture;
 use serde::Deserialize;
-use std::{collections::HashMap, fs, path::Path};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
 
 /**
  * Storage for shared textures that are kept loaded for the duration of the application run.
@@ -127,23 +132,43 @@ impl TextureStore {
         self.textures.len()
     }
 
-    pub fn load_from_toml(renderer: &Renderer, path: &Path) -> Result<Self> {
-        let content = fs::read_to_string(path)?;
-        let config: HashMap<String, TextureConfigWithAlts> = toml::from_str(&content)?;
+    /**
+     * Load textures listed in the given textures.toml files.
+     *
+     * A texture defined in a later file replaces a texture with the same name
+     * in an earlier one. This is used to merge mod textures with the base set.
+     */
+    pub fn load_from_toml(renderer: &Renderer, paths: &[PathBuf]) -> Result<Self> {
+        let mut configs: HashMap<String, (&Path, TextureConfigWithAlts)> = HashMap::new();
+
+        for path in paths {
+            let content = fs::read_to_string(path).with_context(|| datafile_origin(path))?;
+            let config: HashMap<String, TextureConfigWithAlts> =
+                toml::from_str(&content).with_context(|| datafile_origin(path))?;
+
+            let root = path
+                .parent()
+                .expect("textures.toml should have a parent directory");
+
+            for (name, config) in config {
+                configs.insert(name, (root, config));
+            }
+        }
 
         let mut store = Self::new();
 
-        let root = path
-            .parent()
-            .expect("textures.toml should have a parent directory");
+        // Image files are shared among textures loaded from the same directory
+        let mut shared_textures: HashMap<&Path, HashMap<String, *mut SDL_Texture>> = HashMap::new();
 
-        let mut shared_textures: HashMap<String, *mut SDL_Texture> = HashMap::new();
+        for (name, (root, config)) in configs {
+            let shared_textures = shared_textures.entry(root).or_default();
+            let context = || format!("{}: texture \"{name}\"", datafile_origin(root));
 
-        for (name, config) in config {
             let main = store
                 .add_texture(
-                    name.into_bytes(),
-                    Texture::from_config(renderer, root, &config.main, None, &mut shared_textures)?,
+                    name.clone().into_bytes(),
+                    Texture::from_config(renderer, root, &config.main, None, shared_textures)
+                        .with_context(context)?,
                 )
                 .expect("duplicates shouldn't be possible here");
 
@@ -157,8 +182,9 @@ impl TextureStore {
                             root,
                             &config.main,
                             Some(&altconfig),
-                            &mut shared_textures,
-                        )?,
+                            shared_textures,
+                        )
+                        .with_context(context)?,
                     )?;
                 }
             }
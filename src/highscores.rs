@@ -0,0 +1,111 @@
+// This file is part of Luola2
+// Copyright (C) 2025 Calle Laakkonen
+//
+// Luola2 is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Luola2 is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Luola2.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::{
+    collections::HashMap,
+    fs::{read_to_string, write},
+};
+
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::fs::get_savefile_path;
+
+const HIGHSCORE_FILE: &str = "highscores.toml";
+
+/// Best scores of co-op rounds, keyed by level ID (levelpack/name)
+#[derive(Serialize, Deserialize, Default)]
+struct HighScores {
+    #[serde(default)]
+    levels: HashMap<String, i32>,
+}
+
+impl HighScores {
+    /// Record a score. Returns true if it beat the previous high score.
+    fn record(&mut self, level: &str, score: i32) -> bool {
+        let best = self.levels.entry(level.to_owned()).or_insert(0);
+        if score > *best {
+            *best = score;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+fn load_high_scores() -> HighScores {
+    let filename = get_savefile_path(HIGHSCORE_FILE);
+    let content = match read_to_string(&filename) {
+        Ok(c) => c,
+        Err(e) => {
+            warn!("Couldn't read high score file ({:?}): {}", filename, e);
+            return HighScores::default();
+        }
+    };
+
+    toml::from_str(&content).unwrap_or_else(|e| {
+        error!("Couldn't parse high score file ({:?}): {}", filename, e);
+        HighScores::default()
+    })
+}
+
+/// Get the high score of the given level (0 if none has been recorded)
+pub fn get_high_score(level: &str) -> i32 {
+    load_high_scores().levels.get(level).copied().unwrap_or(0)
+}
+
+/// Save the score if it is a new high score for the level.
+/// Returns true if a new high score was set.
+pub fn record_high_score(level: &str, score: i32) -> bool {
+    let mut scores = load_high_scores();
+    if !scores.record(level, score) {
+        return false;
+    }
+
+    let filename = get_savefile_path(HIGHSCORE_FILE);
+    let content = match toml::to_string(&scores) {
+        Ok(c) => c,
+        Err(err) => {
+            error!("Failed to serialize high scores! {err}");
+            return true;
+        }
+    };
+
+    if let Err(e) = write(&filename, content) {
+        error!("Failed to write high score file {:?}: {e}", filename);
+    } else {
+        info!("New high score {score} for level {level}");
+    }
+
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_high_score() {
+        let mut scores = HighScores::default();
+        assert!(!scores.record("demos/demo", 0));
+        assert!(scores.record("demos/demo", 3));
+        assert!(!scores.record("demos/demo", 2));
+        assert!(!scores.record("demos/demo", 3));
+        assert!(scores.record("mymod/demo", 1));
+        assert_eq!(scores.levels["demos/demo"], 3);
+        assert_eq!(scores.levels["mymod/demo"], 1);
+    }
+}
@@ -0,0 +1,235 @@
+// This file is part of Luola2
+// Copyright (C) 2025 Calle Laakkonen
+//
+// Luola2 is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Luola2 is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Luola2.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    fs::read_to_string,
+    path::Path,
+    sync::{LazyLock, RwLock},
+};
+
+use anyhow::{Context, Result};
+use log::{error, info};
+use mlua::{Lua, Variadic};
+use serde::{Deserialize, Serialize};
+
+use crate::fs::{datafile_origin, find_datafile_path, find_mod_datafiles, glob_datafiles};
+
+/// The language the game's strings are written in. This needs no string table.
+pub const DEFAULT_LANGUAGE: &str = "en";
+
+/**
+ * A string table file (data/lang/<code>.toml)
+ *
+ * The source (English) text is used as the key. Strings missing
+ * from the table are shown untranslated.
+ */
+#[derive(Deserialize)]
+struct LanguageFile {
+    /// Name of the language in the language itself
+    name: Option<String>,
+    #[serde(default)]
+    strings: HashMap<String, String>,
+}
+
+#[derive(Serialize, Clone)]
+pub struct LanguageInfo {
+    pub code: String,
+    pub name: String,
+}
+
+struct StringTable {
+    language: String,
+    strings: HashMap<String, String>,
+}
+
+static STRINGS: LazyLock<RwLock<StringTable>> = LazyLock::new(|| {
+    RwLock::new(StringTable {
+        language: DEFAULT_LANGUAGE.to_owned(),
+        strings: HashMap::new(),
+    })
+});
+
+fn read_language_file(path: &Path) -> Result<LanguageFile> {
+    let content = read_to_string(path).with_context(|| datafile_origin(path))?;
+    toml::from_str(&content).with_context(|| datafile_origin(path))
+}
+
+/**
+ * Load the string table for the given language.
+ *
+ * Mods can add strings to existing languages or add new ones.
+ * Strings in mods loaded later replace earlier ones.
+ */
+pub fn load_language(code: &str) {
+    let mut strings = HashMap::new();
+
+    if code != DEFAULT_LANGUAGE {
+        let filename = format!("lang/{code}.toml");
+        let mut files: Vec<_> = find_datafile_path(&filename).into_iter().collect();
+        files.extend(find_mod_datafiles(&filename));
+
+        if files.is_empty() {
+            error!("Language \"{code}\" not found");
+        }
+
+        for file in files {
+            match read_language_file(&file) {
+                Ok(f) => strings.extend(f.strings),
+                Err(err) => error!("Couldn't load string table: {err:?}"),
+            }
+        }
+
+        info!("Loaded {} strings for language \"{code}\"", strings.len());
+    }
+
+    *STRINGS.write().unwrap() = StringTable {
+        language: code.to_owned(),
+        strings,
+    };
+}
+
+/// Get the code of the currently loaded language
+pub fn current_language() -> String {
+    STRINGS.read().unwrap().language.clone()
+}
+
+/**
+ * Get a list of all languages that have a string table.
+ *
+ * The built-in default language is always first.
+ */
+pub fn available_languages() -> Vec<LanguageInfo> {
+    let mut languages = vec![LanguageInfo {
+        code: DEFAULT_LANGUAGE.to_owned(),
+        name: "English".to_owned(),
+    }];
+
+    let files = match glob_datafiles("lang", "*.toml") {
+        Ok(f) => f,
+        Err(err) => {
+            error!("Couldn't list languages: {err}");
+            return languages;
+        }
+    };
+
+    for file in files {
+        let Some(code) = file.file_stem().and_then(|s| s.to_str()) else {
+            continue;
+        };
+
+        match read_language_file(&file) {
+            Ok(f) => languages.push(LanguageInfo {
+                code: code.to_owned(),
+                name: f.name.unwrap_or_else(|| code.to_owned()),
+            }),
+            Err(err) => error!("Couldn't load string table: {err:?}"),
+        }
+    }
+
+    languages[1..].sort_by(|a, b| a.name.cmp(&b.name));
+    languages
+}
+
+/**
+ * Translate a string to the current language.
+ *
+ * If there is no translation, the text is returned as is.
+ */
+pub fn tr(text: &str) -> String {
+    STRINGS
+        .read()
+        .unwrap()
+        .strings
+        .get(text)
+        .cloned()
+        .unwrap_or_else(|| text.to_owned())
+}
+
+/**
+ * Translate a string and fill in its positional placeholders.
+ *
+ * Placeholders are written as {1}, {2}, etc., so translations
+ * can put them in a different order.
+ */
+pub fn tr_fmt(text: &str, args: &[&dyn Display]) -> String {
+    let text = tr(text);
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text.as_str();
+
+    // Substitute in a single pass so placeholders appearing in the
+    // arguments themselves are not replaced
+    while let Some(start) = rest.find('{') {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let arg = rest.find('}').and_then(|end| {
+            let idx = rest[1..end].parse::<usize>().ok()?;
+            let arg = args.get(idx.checked_sub(1)?)?;
+            Some((arg, end))
+        });
+
+        match arg {
+            Some((arg, end)) => {
+                result.push_str(&arg.to_string());
+                rest = &rest[end + 1..];
+            }
+            None => {
+                result.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Add the global tr(text, ...) function to a Lua environment
+pub fn register_lua_functions(lua: &Lua) -> mlua::Result<()> {
+    lua.globals().set(
+        "tr",
+        lua.create_function(|_, (text, args): (String, Variadic<String>)| {
+            let args: Vec<&dyn Display> = args.iter().map(|a| a as &dyn Display).collect();
+            Ok(tr_fmt(&text, &args))
+        })?,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tr_fmt() {
+        // No string table is loaded, so the text is used as is
+        assert_eq!(tr_fmt("Round {1}", &[&3]), "Round 3");
+        assert_eq!(
+            tr_fmt("{2} and {1}", &[&"first", &"second"]),
+            "second and first"
+        );
+        assert_eq!(tr("Untranslated"), "Untranslated");
+    }
+
+    #[test]
+    fn test_tr_fmt_single_pass() {
+        // Placeholders in the arguments are not substituted
+        assert_eq!(tr_fmt("{1} vs {2}", &[&"{2}", &"b"]), "{2} vs b");
+        // Unknown or malformed placeholders are left as is
+        assert_eq!(tr_fmt("{0} {3} {x} {1", &[&"a"]), "{0} {3} {x} {1");
+        assert_eq!(tr_fmt("{{1}}", &[&"a"]), "{a}");
+    }
+}
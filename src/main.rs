@@ -21,7 +21,8 @@ use sdl3_main::{AppResult, AppResultWithState, app_impl};
 use sdl3_sys::events::{
     SDL_EVENT_GAMEPAD_ADDED, SDL_EVENT_GAMEPAD_AXIS_MOTION, SDL_EVENT_GAMEPAD_BUTTON_DOWN,
     SDL_EVENT_GAMEPAD_BUTTON_UP, SDL_EVENT_GAMEPAD_REMOVED, SDL_EVENT_KEY_DOWN, SDL_EVENT_KEY_UP,
-    SDL_EVENT_QUIT, SDL_EVENT_USER, SDL_EVENT_WINDOW_RESIZED, SDL_Event, SDL_EventType,
+    SDL_EVENT_QUIT, SDL_EVENT_TEXT_INPUT, SDL_EVENT_USER, SDL_EVENT_WINDOW_RESIZED, SDL_Event,
+    SDL_EventType,
 };
 use sdl3_sys::gamepad::{SDL_GamepadAxis, SDL_GamepadButton};
 use sdl3_sys::init::{SDL_INIT_GAMEPAD, SDL_INIT_VIDEO, SDL_Init, SDL_SetAppMetadata};
@@ -39,14 +40,17 @@ use crate::configfile::{GAME_CONFIG, load_user_config};
 use crate::events::CUSTOM_EVENTS;
 use crate::game::{GameControllerSet, MenuButton};
 use crate::gfx::{Renderer, SdlError};
-use crate::states::{GameInitState, StateStack};
+use crate::states::{GameInitState, SIMULATION_RATE, StateStack};
 
+mod campaign;
 mod configfile;
 mod demos;
 mod events;
 mod fs;
 mod game;
 mod gfx;
+mod highscores;
+mod lang;
 mod math;
 mod menu;
 mod states;
@@ -55,6 +59,10 @@ struct AppState {
     renderer: Rc<RefCell<Renderer>>,
     controllers: Rc<RefCell<GameControllerSet>>,
     statestack: StateStack,
+    /// Target frame duration
+    nanoseconds_per_frame: u64,
+    /// Start time of the previous frame
+    last_frame_ticks: u64,
 }
 
 #[derive(FromArgs)]
@@ -68,6 +76,18 @@ struct Arguments {
 
     #[argh(switch, short = 'w', description = "start in windowed mode")]
     window: bool,
+
+    #[argh(
+        option,
+        description = "write Lua API type definitions (LuaLS annotations) to a file and exit"
+    )]
+    lua_api: Option<String>,
+
+    #[argh(
+        option,
+        description = "capture every Nth frame to a PNG sequence from the start"
+    )]
+    capture: Option<u32>,
 }
 
 unsafe impl Send for AppState {}
@@ -81,6 +101,19 @@ impl AppState {
             .filter_level(log::LevelFilter::Info)
             .init();
 
+        if let Some(path) = args.lua_api {
+            return match game::luaapi::write_lua_api(std::path::Path::new(&path)) {
+                Ok(()) => {
+                    log::info!("Wrote Lua API definitions to {path}");
+                    AppResultWithState::Success(None)
+                }
+                Err(err) => {
+                    log::error!("Couldn't write Lua API definitions: {err}");
+                    AppResultWithState::Failure(None)
+                }
+            };
+        }
+
         unsafe {
             if !SDL_SetAppMetadata(
                 c"Luola II".as_ptr(),
@@ -101,15 +134,19 @@ impl AppState {
 
         load_user_config();
         let config = GAME_CONFIG.read().unwrap();
+        fs::load_mods(&config.mods.disabled);
+        lang::load_language(&config.game.language);
 
-        let renderer =
-            match Renderer::create(!args.window && (args.fullscreen || config.video.fullscreen)) {
-                Ok(r) => Rc::new(RefCell::new(r)),
-                Err(err) => {
-                    log::error!("Couldn't create renderer: {}", err);
-                    return AppResultWithState::Failure(None);
-                }
-            };
+        let renderer = match Renderer::create(
+            !args.window && (args.fullscreen || config.video.fullscreen),
+            &config.video,
+        ) {
+            Ok(r) => Rc::new(RefCell::new(r)),
+            Err(err) => {
+                log::error!("Couldn't create renderer: {}", err);
+                return AppResultWithState::Failure(None);
+            }
+        };
 
         let mut controllers = GameControllerSet::new();
         controllers.reload_keymaps();
@@ -117,31 +154,56 @@ impl AppState {
         let controllers = Rc::new(RefCell::new(controllers));
 
         let mut statestack = StateStack::new(renderer.clone());
+        if let Some(interval) = args.capture
+            && let Err(err) = statestack.start_capture(interval)
+        {
+            log::error!("Couldn't start frame capture: {err}");
+        }
         statestack.push(Box::new(GameInitState::new(
             args.launch,
             controllers.clone(),
             renderer.clone(),
         )));
 
+        let frame_rate = snap_frame_rate(config.video.frame_rate) as u64;
+
         AppResultWithState::Continue(Box::new(Mutex::new(AppState {
             renderer,
             controllers,
             statestack,
+            nanoseconds_per_frame: 1_000_000_000 / frame_rate,
+            last_frame_ticks: unsafe { SDL_GetTicksNS() },
         })))
     }
 
     fn app_iterate(&mut self) -> AppResult {
         let ticks = unsafe { SDL_GetTicksNS() };
-        let result = self.statestack.state_iterate(1.0 / 60.0);
+
+        // Advance by the measured time, so dropped frames or a display refresh rate
+        // that doesn't match the frame rate don't change the game speed.
+        // Small timing jitter is ignored so every frame takes the same number of simulation steps.
+        let elapsed = ticks - self.last_frame_ticks;
+        self.last_frame_ticks = ticks;
+        let elapsed = if elapsed.abs_diff(self.nanoseconds_per_frame)
+            < self.nanoseconds_per_frame / FRAME_TIME_TOLERANCE
+        {
+            self.nanoseconds_per_frame
+        } else {
+            elapsed.min(MAX_FRAME_TIME_NS)
+        };
+
+        let result = self
+            .statestack
+            .state_iterate(elapsed as f32 / 1_000_000_000.0);
         let ticks2 = unsafe { SDL_GetTicksNS() };
 
         // Limit framerate
         let dticks = ticks2 - ticks;
-        if dticks < NANOSECONDS_PER_FRAME {
+        if dticks < self.nanoseconds_per_frame {
             unsafe {
-                SDL_DelayNS(NANOSECONDS_PER_FRAME - dticks);
+                SDL_DelayNS(self.nanoseconds_per_frame - dticks);
             }
-        } else if dticks > 33_000_000 {
+        } else if dticks > 2 * self.nanoseconds_per_frame {
             log::warn!("Tick took {} milliseconds!", dticks / 1_000_000);
         }
 
@@ -167,6 +229,13 @@ impl AppState {
                     self.controllers.borrow_mut().handle_sdl_key_event(key);
                 }
             }
+            SDL_EVENT_TEXT_INPUT => {
+                let event = unsafe { &event.text };
+                if !event.text.is_null() {
+                    let text = unsafe { CStr::from_ptr(event.text) }.to_string_lossy();
+                    self.statestack.handle_text_input(&text);
+                }
+            }
             SDL_EVENT_GAMEPAD_AXIS_MOTION => {
                 let event = unsafe { &event.gaxis };
                 self.controllers.borrow_mut().handle_gamepad_axis(
@@ -194,13 +263,21 @@ impl AppState {
             t if t >= SDL_EVENT_USER => {
                 let custom = &CUSTOM_EVENTS;
                 if t.0 == custom.grabkey {
-                    self.controllers.borrow_mut().start_keygrab();
+                    if unsafe { event.user.code } != 0 {
+                        self.controllers.borrow_mut().start_padgrab();
+                    } else {
+                        self.controllers.borrow_mut().start_keygrab();
+                    }
                 } else if t.0 == custom.config_changed {
                     self.controllers.borrow_mut().reload_keymaps();
                 } else if t.0 == custom.menu_button {
                     let userev = unsafe { &event.user };
                     self.statestack
                         .handle_menu_button(MenuButton::from_event_code(userev.code, userev.data1));
+                } else if t.0 == custom.text_input {
+                    let enabled = unsafe { event.user.code } != 0;
+                    self.renderer.borrow().set_text_input(enabled);
+                    self.controllers.borrow_mut().set_text_input(enabled);
                 }
             }
             _ => {}
@@ -210,6 +287,25 @@ impl AppState {
     }
 }
 
-// weirdness: if I use const here instead of static, rustc (1.90.0) and rust-analyzer will consume
-// all memory and crash
-static NANOSECONDS_PER_FRAME: u64 = 1_000_000_000 / 60;
+/// Frame rate limits. The game world is simulated at a fixed rate
+/// regardless of the frame rate (see GameRoundState.)
+const MIN_FRAME_RATE: u32 = SIMULATION_RATE / 2;
+const MAX_FRAME_RATE: u32 = SIMULATION_RATE * 4;
+
+/// Frame times within 1/N of the target frame duration are treated as on time
+const FRAME_TIME_TOLERANCE: u64 = 10;
+
+/// Longest time a single frame may advance the game, so that a stall
+/// (e.g. while the window is being dragged) doesn't cause a sudden jump
+const MAX_FRAME_TIME_NS: u64 = 100_000_000;
+
+/// Round the frame rate to a multiple of the simulation rate (or half of it),
+/// so each frame shows a whole number of evenly spaced simulation steps.
+fn snap_frame_rate(frame_rate: u32) -> u32 {
+    let frame_rate = frame_rate.clamp(MIN_FRAME_RATE, MAX_FRAME_RATE);
+    if frame_rate < SIMULATION_RATE * 3 / 4 {
+        MIN_FRAME_RATE
+    } else {
+        (frame_rate + SIMULATION_RATE / 2) / SIMULATION_RATE * SIMULATION_RATE
+    }
+}
@@ -37,6 +37,12 @@ impl Vec2 {
         f32::atan2(-self.1, self.0) * (180.0 / PI)
     }
 
+    /// Rotate the vector by the given angle (in degrees, same direction as for_angle)
+    pub fn rotated(self, a: f32) -> Self {
+        let (sin, cos) = (a * PI / 180.0).sin_cos();
+        Vec2(self.0 * cos - self.1 * sin, self.0 * sin + self.1 * cos)
+    }
+
     pub fn dist_squared(self, other: Self) -> f32 {
         (self.0 - other.0).powf(2.0) + (self.1 - other.1).powf(2.0)
     }
@@ -189,5 +195,7 @@ mod tests {
         assert_eq!(Vec2::for_angle(0.0, 1.0), Vec2(1.0, 0.0));
         assert_eq!(Vec2::for_angle(90.0, 2.0), Vec2(0.0, 2.0));
         assert_eq!(Vec2::for_angle(180.0, 3.0), Vec2(-3.0, 0.0));
+        assert_eq!(Vec2(2.0, 0.0).rotated(90.0), Vec2::for_angle(90.0, 2.0));
+        assert_eq!(Vec2::for_angle(30.0, 1.0).rotated(-30.0), Vec2(1.0, 0.0));
     }
 }
@@ -2,18 +2,22 @@ use anyhow::{Result, anyhow};
 use core::ops::Deref;
 use mlua::{FromLua, Function, Lua, LuaSerdeExt, String as LuaString, Table, UserData, Value};
 use sdl3_sys::keyboard::SDL_GetKeyName;
-use sdl3_sys::keycode::SDL_Keycode;
+use sdl3_sys::keycode::{SDL_Keycode, SDLK_ESCAPE, SDLK_KP_ENTER, SDLK_RETURN};
 use std::{cell::RefCell, ffi::CStr, rc::Rc, sync::Arc};
 
 use crate::{
     configfile::{GAME_CONFIG, UserConfig, save_user_config},
     events,
-    fs::find_datafile_path,
-    game::{GameControllerSet, MenuButton},
+    fs::{find_datafile_path, installed_mods},
+    game::{
+        GameControllerSet, GamepadInput, GamepadProfile, KEYBOARDS, MAX_PLAYERS, MenuButton,
+        PlayerKeymap,
+    },
     gfx::{
         Color, RenderDest, RenderOptions, RenderTextDest, RenderTextOptions, Renderer, Text,
-        TextOutline, TextureId,
+        TextInput, TextOutline, TextureId, displays,
     },
+    lang::{self, available_languages, tr},
     math::{RectF, Vec2},
 };
 
@@ -22,7 +26,8 @@ enum MenuAction {
     Push(MenuScreen),
     Pop,
     Return(String),
-    KeyGrab, // activate keygrab mode and assign KeyGrab value to current selection when done
+    KeyGrab,  // activate keygrab mode and assign KeyGrab value to current selection when done
+    TextEdit, // activate text editing mode for the current selection's Text value
 }
 
 type CachedText = RefCell<Option<Text>>;
@@ -31,8 +36,21 @@ enum MenuItemValue {
     None,
     Toggle(bool, CachedText),
     KeyGrab(SDL_Keycode, CachedText),
+    Slider {
+        value: f32,
+        min: f32,
+        max: f32,
+        step: f32,
+        text: CachedText,
+    },
+    Choice(Vec<String>, usize, CachedText),
+    Text(String, CachedText),
+    PadGrab(Vec<String>, CachedText),
 }
 
+/// Maximum length (in characters) of a Text value
+const MAX_TEXT_VALUE_LENGTH: usize = 24;
+
 enum MenuItemContent {
     Text(Text),
     Image(TextureId),
@@ -68,6 +86,12 @@ struct MenuScreen {
     animated_offset: Vec2,
     cursorpos: Vec2,
 
+    /// The area the menu is laid out in
+    window: RectF,
+
+    /// Vertical scroll offset when the menu is taller than the window
+    scroll: f32,
+
     on_exit: Option<Function>,
 }
 
@@ -80,6 +104,7 @@ pub struct LuaMenu {
     cursor_bounce: f32, // cursor bounce animation state
     key_grabbing: bool, // key grab in progress
     key_grab_text: Text,
+    text_edit: Option<TextInput>, // text editing in progress
 }
 
 impl UserData for MenuAction {}
@@ -96,6 +121,7 @@ impl UserData for MenuItem {
                 Err(anyhow!("toggle called on non-toggleable menu item!").into())
             }
         });
+        methods.add_method("value", |lua, this, _: ()| this.value.to_lua(lua));
     }
 }
 impl UserData for MenuItemValue {}
@@ -193,14 +219,18 @@ fn make_menu(table: Table, window: RectF) -> mlua::Result<MenuScreen> {
     let current = items.iter().position(|i| i.is_selectable()).unwrap_or(0);
     MenuScreen::layout_items(&mut items, window);
     let cursorpos = items[0].rect.topleft() - Vec2(MenuScreen::SPACING, 0.0);
-    Ok(MenuScreen {
+    let mut menu = MenuScreen {
         items,
         current,
         state: MenuScreenState::Appearing(0.0),
         animated_offset: Vec2::ZERO,
         cursorpos,
+        window,
+        scroll: 0.0,
         on_exit: table.get("on_exit")?,
-    })
+    };
+    menu.scroll = menu.scroll_target();
+    Ok(menu)
 }
 
 fn make_heading(table: Table, renderer: &Renderer) -> mlua::Result<MenuItem> {
@@ -321,9 +351,11 @@ impl LuaMenu {
             )?;
         }
 
+        // Spacer heights are in unscaled UI pixels
+        let ui_scale = renderer.borrow().ui_scale();
         lua.globals().set(
             "Spacer",
-            lua.create_function(move |_lua, height: f32| Ok(make_spacer(height)))?,
+            lua.create_function(move |_lua, height: f32| Ok(make_spacer(height * ui_scale)))?,
         )?;
 
         let menuactions = lua.create_table()?;
@@ -363,6 +395,51 @@ impl LuaMenu {
             })?,
         )?;
 
+        itemvalues.set(
+            "Slider",
+            lua.create_function(
+                |_lua, (min, max, step, value): (f32, f32, f32, Option<f32>)| {
+                    if max <= min || step <= 0.0 {
+                        return Err(anyhow!("invalid slider range {min}..{max} step {step}").into());
+                    }
+                    Ok(MenuItemValue::Slider {
+                        value: value.unwrap_or(min).clamp(min, max),
+                        min,
+                        max,
+                        step,
+                        text: RefCell::new(None),
+                    })
+                },
+            )?,
+        )?;
+        itemvalues.set(
+            "Choice",
+            lua.create_function(|_lua, (options, selected): (Vec<String>, Option<usize>)| {
+                if options.is_empty() {
+                    return Err(anyhow!("choice needs at least one option").into());
+                }
+                // Note: selection index is 1-based on the Lua side
+                let selected = selected.unwrap_or(1).clamp(1, options.len()) - 1;
+                Ok(MenuItemValue::Choice(options, selected, RefCell::new(None)))
+            })?,
+        )?;
+        itemvalues.set(
+            "Text",
+            lua.create_function(|_lua, value: Option<String>| {
+                Ok(MenuItemValue::Text(
+                    value.unwrap_or_default(),
+                    RefCell::new(None),
+                ))
+            })?,
+        )?;
+
+        itemvalues.set(
+            "PadGrab",
+            lua.create_function(|_lua, inputs: Vec<String>| {
+                Ok(MenuItemValue::PadGrab(inputs, RefCell::new(None)))
+            })?,
+        )?;
+
         lua.globals().set("Value", itemvalues)?;
 
         lua.globals().set(
@@ -388,14 +465,52 @@ impl LuaMenu {
         lua.globals().set(
             "get_default_keymap",
             lua.create_function(|lua, id: usize| {
-                if id < 1 || id > GameControllerSet::DEFAULT_KEYMAP.len() {
+                if !(1..=KEYBOARDS).contains(&id) {
                     Err(anyhow!("invalid keymap ID {id}").into())
+                } else if let Some(keymap) = GameControllerSet::DEFAULT_KEYMAP.get(id - 1) {
+                    lua.to_value(keymap)
                 } else {
-                    lua.to_value(&GameControllerSet::DEFAULT_KEYMAP[id - 1])
+                    // Extra keymaps have no default keys
+                    lua.to_value(&PlayerKeymap {
+                        thrust: 0,
+                        down: 0,
+                        left: 0,
+                        right: 0,
+                        fire1: 0,
+                        fire2: 0,
+                        fire3: 0,
+                    })
                 }
             })?,
         )?;
 
+        lua.globals().set("MAX_KEYMAPS", KEYBOARDS)?;
+        lua.globals().set("MAX_PLAYERS", MAX_PLAYERS)?;
+
+        lua.globals().set(
+            "get_default_gamepad_profile",
+            lua.create_function(|lua, _: ()| lua.to_value(&GamepadProfile::default()))?,
+        )?;
+
+        lua.globals().set(
+            "get_mods",
+            lua.create_function(|lua, _: ()| lua.to_value(&installed_mods()))?,
+        )?;
+
+        lua.globals().set(
+            "get_displays",
+            lua.create_function(|lua, _: ()| {
+                lua.to_value(&displays().into_iter().map(|d| d.1).collect::<Vec<_>>())
+            })?,
+        )?;
+
+        lua.globals().set(
+            "get_languages",
+            lua.create_function(|lua, _: ()| lua.to_value(&available_languages()))?,
+        )?;
+
+        lang::register_lua_functions(&lua)?;
+
         // Load menu script file and get main menu by running entrypoint function
         lua.load(format!(r#"require "{}""#, script_file)).exec()?;
 
@@ -414,7 +529,7 @@ impl LuaMenu {
             .borrow()
             .fontset()
             .menu
-            .create_text(&renderer.borrow(), "Press a key")?;
+            .create_text(&renderer.borrow(), &tr("Press a key"))?;
         Ok(Self {
             lua,
             renderer,
@@ -424,13 +539,27 @@ impl LuaMenu {
             window,
             key_grabbing: false,
             key_grab_text,
+            text_edit: None,
         })
     }
 
+    /// Make the list of connected gamepads available to the menu script
+    pub fn set_controllers(&self, controllers: Rc<RefCell<GameControllerSet>>) -> Result<()> {
+        self.lua.globals().set(
+            "get_gamepads",
+            self.lua.create_function(move |lua, _: ()| {
+                lua.to_value(&controllers.borrow().gamepads())
+            })?,
+        )?;
+        Ok(())
+    }
+
     pub fn relayout(&mut self, window: RectF) {
         self.window.replace(window);
         for menu in self.menu_stack.iter_mut() {
             MenuScreen::layout_items(&mut menu.items, window);
+            menu.window = window;
+            menu.scroll = menu.scroll_target();
         }
     }
 
@@ -464,11 +593,7 @@ impl LuaMenu {
                     let values = self.lua.create_table()?;
 
                     for item in &menu.items {
-                        values.push(match item.value {
-                            MenuItemValue::None => Value::NULL,
-                            MenuItemValue::Toggle(v, _) => Value::Boolean(v),
-                            MenuItemValue::KeyGrab(v, _) => Value::Integer(v.0 as _),
-                        })?;
+                        values.push(item.value.to_lua(&self.lua)?)?;
                     }
                     on_exit.call::<()>(values)?;
                 }
@@ -482,6 +607,10 @@ impl LuaMenu {
             self.menu_stack.pop();
         }
 
+        if let Some(input) = self.text_edit.as_mut() {
+            input.step(timestep);
+        }
+
         // Cursor animation
         self.cursor_bounce += 4.0 * timestep;
         if self.cursor_bounce > std::f32::consts::PI {
@@ -495,8 +624,19 @@ impl LuaMenu {
         let renderer = self.renderer.borrow();
         let cursor_animation_offset =
             Vec2(-self.cursor_bounce.sin() * self.cursor.width() / 2.0, 0.0);
-        for menu in self.menu_stack.iter().filter(|m| m.state.is_visible()) {
-            menu.render(&renderer);
+        let stacklen = self.menu_stack.len();
+        for (idx, menu) in self
+            .menu_stack
+            .iter()
+            .enumerate()
+            .filter(|(_, m)| m.state.is_visible())
+        {
+            let text_edit = if idx + 1 == stacklen {
+                self.text_edit.as_ref()
+            } else {
+                None
+            };
+            menu.render(&renderer, text_edit);
 
             if matches!(
                 menu.state,
@@ -504,7 +644,7 @@ impl LuaMenu {
             ) {
                 self.cursor.render(&RenderTextOptions {
                     dest: RenderTextDest::TopRight(
-                        menu.cursorpos + cursor_animation_offset + menu.animated_offset,
+                        menu.cursorpos + cursor_animation_offset + menu.render_offset(),
                     ),
                     ..Default::default()
                 });
@@ -540,7 +680,10 @@ impl LuaMenu {
     }
 
     pub fn handle_button(&mut self, button: MenuButton) -> Result<String> {
-        if matches!(button, MenuButton::GrabbedKey(_)) {
+        if matches!(
+            button,
+            MenuButton::GrabbedKey(_) | MenuButton::GrabbedPadInput(_)
+        ) {
             self.key_grabbing = false;
         }
 
@@ -548,6 +691,10 @@ impl LuaMenu {
             return Ok(String::new());
         }
 
+        if self.text_edit.is_some() {
+            return self.handle_text_edit_button(button);
+        }
+
         if matches!(button, MenuButton::Back) {
             let stacksize = self.menu_stack.len();
             if stacksize > 1 {
@@ -565,47 +712,249 @@ impl LuaMenu {
         });
 
         if let Some(active_menu) = active_menu {
-            match active_menu.handle_button(&self.lua, button)? {
-                MenuAction::None => {}
-                MenuAction::Push(m) => {
-                    if let Some(top) = self.menu_stack.last_mut() {
-                        top.hide();
-                    }
-                    debug_assert!(matches!(m.state, MenuScreenState::Appearing(_)));
-                    self.menu_stack.push(m);
+            let action = active_menu.handle_button(&self.lua, button)?;
+            return self.apply_action(action);
+        }
+        Ok(String::new())
+    }
+
+    /// Feed text input to the Text value being edited
+    pub fn handle_text_input(&mut self, text: &str) {
+        if let Some(input) = self.text_edit.as_mut() {
+            let room = MAX_TEXT_VALUE_LENGTH.saturating_sub(input.value().chars().count());
+            let text: String = text.chars().take(room).collect();
+            if !text.is_empty() {
+                input.insert(&text);
+            }
+        }
+    }
+
+    fn handle_text_edit_button(&mut self, button: MenuButton) -> Result<String> {
+        let input = self
+            .text_edit
+            .as_mut()
+            .expect("text edit mode should be active");
+        match button {
+            MenuButton::TextEdit(SDLK_RETURN | SDLK_KP_ENTER) => {
+                let value = input.take();
+                self.end_text_edit();
+                if let Some(menu) = self.menu_stack.last_mut() {
+                    let action = menu.set_text_value(&self.lua, value)?;
+                    return self.apply_action(action);
                 }
-                MenuAction::Pop => {
-                    if let Some(top) = self.menu_stack.last_mut() {
-                        top.hide();
-                    }
-                    let stacklen = self.menu_stack.len();
-                    if stacklen > 1 {
-                        self.menu_stack[stacklen - 2].appear();
-                    }
+            }
+            MenuButton::TextEdit(SDLK_ESCAPE) => self.end_text_edit(),
+            MenuButton::TextEdit(key) => {
+                input.handle_key(key);
+            }
+            _ => {}
+        }
+        Ok(String::new())
+    }
+
+    fn end_text_edit(&mut self) {
+        if self.text_edit.take().is_some() {
+            events::push_text_input_event(false);
+        }
+    }
+
+    fn apply_action(&mut self, action: MenuAction) -> Result<String> {
+        match action {
+            MenuAction::None => {}
+            MenuAction::Push(m) => {
+                if let Some(top) = self.menu_stack.last_mut() {
+                    top.hide();
                 }
-                MenuAction::Return(val) => {
-                    active_menu.hide();
-                    return Ok(val);
+                debug_assert!(matches!(m.state, MenuScreenState::Appearing(_)));
+                self.menu_stack.push(m);
+            }
+            MenuAction::Pop => {
+                if let Some(top) = self.menu_stack.last_mut() {
+                    top.hide();
                 }
-                MenuAction::KeyGrab => {
-                    self.key_grabbing = true;
-                    events::push_grabkey_event();
+                let stacklen = self.menu_stack.len();
+                if stacklen > 1 {
+                    self.menu_stack[stacklen - 2].appear();
                 }
-            };
-        }
+            }
+            MenuAction::Return(val) => {
+                if let Some(top) = self.menu_stack.last_mut() {
+                    top.hide();
+                }
+                return Ok(val);
+            }
+            MenuAction::KeyGrab => {
+                let gamepad = matches!(
+                    self.menu_stack.last().map(|m| &m.items[m.current].value),
+                    Some(MenuItemValue::PadGrab(..))
+                );
+                self.key_grabbing = true;
+                events::push_grabkey_event(gamepad);
+            }
+            MenuAction::TextEdit => {
+                let renderer = self.renderer.borrow();
+                let mut input = TextInput::new(&renderer, &renderer.fontset().menu)?;
+                if let Some(MenuItemValue::Text(value, _)) =
+                    self.menu_stack.last().map(|m| &m.items[m.current].value)
+                {
+                    input.set_value(value);
+                }
+                self.text_edit = Some(input);
+                events::push_text_input_event(true);
+            }
+        };
         Ok(String::new())
     }
 }
 
+impl Drop for LuaMenu {
+    fn drop(&mut self) {
+        self.end_text_edit();
+    }
+}
+
 impl MenuItem {
     fn is_selectable(&self) -> bool {
         self.action.is_some()
     }
 }
 
+impl MenuItemValue {
+    fn to_lua(&self, lua: &Lua) -> mlua::Result<Value> {
+        Ok(match self {
+            MenuItemValue::None => Value::NULL,
+            MenuItemValue::Toggle(v, _) => Value::Boolean(*v),
+            MenuItemValue::KeyGrab(v, _) => Value::Integer(v.0 as _),
+            MenuItemValue::Slider { value, .. } => Value::Number(*value as _),
+            MenuItemValue::Choice(_, selected, _) => Value::Integer(*selected as i64 + 1),
+            MenuItemValue::Text(v, _) => Value::String(lua.create_string(v)?),
+            MenuItemValue::PadGrab(v, _) => lua.to_value(v)?,
+        })
+    }
+
+    /// Change the value with Left/Right buttons.
+    /// Returns true if the value was changed.
+    fn adjust(&mut self, direction: i32) -> bool {
+        match self {
+            MenuItemValue::Slider {
+                value,
+                min,
+                max,
+                step,
+                text,
+            } => {
+                let new_value = (*value + *step * direction as f32).clamp(*min, *max);
+                if new_value == *value {
+                    return false;
+                }
+                *value = new_value;
+                text.replace(None);
+                true
+            }
+            MenuItemValue::Choice(options, selected, text) => {
+                *selected =
+                    (*selected as i32 + direction).rem_euclid(options.len() as i32) as usize;
+                text.replace(None);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// The text to show next to the item label
+    fn display_text(&self) -> Option<(String, Color)> {
+        match self {
+            MenuItemValue::None => None,
+            MenuItemValue::Toggle(value, _) => Some(if *value {
+                (tr("yes"), Color::new(0.0, 0.8, 0.0))
+            } else {
+                (tr("no"), Color::new(0.8, 0.0, 0.0))
+            }),
+            MenuItemValue::KeyGrab(key, _) if key.0 == 0 => {
+                Some((tr("(none)"), Color::new(0.6, 0.6, 0.8)))
+            }
+            MenuItemValue::KeyGrab(key, _) => {
+                let keystr = unsafe { CStr::from_ptr(SDL_GetKeyName(*key)) };
+                Some((
+                    keystr.to_str().unwrap().to_string(),
+                    Color::new(0.6, 0.6, 0.8),
+                ))
+            }
+            MenuItemValue::Slider { value, step, .. } => Some((
+                if step.fract() == 0.0 {
+                    format!("{value:.0}")
+                } else {
+                    format!("{value:.2}")
+                },
+                Color::new(0.6, 0.6, 0.8),
+            )),
+            MenuItemValue::Choice(options, selected, _) => Some((
+                format!("< {} >", options[*selected]),
+                Color::new(0.6, 0.6, 0.8),
+            )),
+            MenuItemValue::Text(value, _) => Some((value.clone(), Color::new(0.6, 0.6, 0.8))),
+            MenuItemValue::PadGrab(inputs, _) => Some((
+                if inputs.is_empty() {
+                    tr("(none)")
+                } else {
+                    inputs.join(" + ")
+                },
+                Color::new(0.6, 0.6, 0.8),
+            )),
+        }
+    }
+
+    fn cached_text(&self) -> Option<&CachedText> {
+        match self {
+            MenuItemValue::None => None,
+            MenuItemValue::Toggle(_, text)
+            | MenuItemValue::KeyGrab(_, text)
+            | MenuItemValue::Slider { text, .. }
+            | MenuItemValue::Choice(_, _, text)
+            | MenuItemValue::Text(_, text)
+            | MenuItemValue::PadGrab(_, text) => Some(text),
+        }
+    }
+}
+
 impl MenuScreen {
     const SPACING: f32 = 3.0;
 
+    /// Space kept between the selected item and the window edge when scrolling
+    const SCROLL_MARGIN: f32 = 32.0;
+
+    /// Width of the slider bar
+    const SLIDER_WIDTH: f32 = 120.0;
+
+    fn render_offset(&self) -> Vec2 {
+        self.animated_offset - Vec2(0.0, self.scroll)
+    }
+
+    /// Find the scroll offset needed to keep the selected item visible
+    fn scroll_target(&self) -> f32 {
+        let (top, bottom) = match (self.items.first(), self.items.last()) {
+            (Some(first), Some(last)) => (first.rect.y(), last.rect.bottom()),
+            _ => return 0.0,
+        };
+
+        // No scrolling needed if everything fits in the window
+        if bottom - top <= self.window.h() {
+            return 0.0;
+        }
+
+        let max_scroll = bottom + Self::SCROLL_MARGIN - self.window.bottom();
+        let current = self.items[self.current].rect;
+
+        let mut scroll = self.scroll;
+        if current.y() - scroll < self.window.y() + Self::SCROLL_MARGIN {
+            scroll = current.y() - self.window.y() - Self::SCROLL_MARGIN;
+        } else if current.bottom() - scroll > self.window.bottom() - Self::SCROLL_MARGIN {
+            scroll = current.bottom() - self.window.bottom() + Self::SCROLL_MARGIN;
+        }
+
+        scroll.clamp(0.0, max_scroll.max(0.0))
+    }
+
     fn appear(&mut self) {
         self.state = match self.state {
             MenuScreenState::Hiding(a) | MenuScreenState::Appearing(a) => {
@@ -650,6 +999,8 @@ impl MenuScreen {
             _ => {}
         };
 
+        self.scroll += (self.scroll_target() - self.scroll) * (10.0 * timestep).min(1.0);
+
         let cursor_target_pos = self.items[self.current].rect.topleft() - Vec2(Self::SPACING, 0.0);
         self.cursorpos = Vec2(
             cursor_target_pos.0,
@@ -671,7 +1022,13 @@ impl MenuScreen {
             .unwrap();
 
         let x = window.x() + (window.w() - width) / 2.0;
-        let mut y = window.y() + (window.h() - height) / 2.0;
+
+        // Menus taller than the window start from the top and are scrolled
+        let mut y = if height > window.h() {
+            window.y() + Self::SCROLL_MARGIN
+        } else {
+            window.y() + (window.h() - height) / 2.0
+        };
 
         for item in items.iter_mut() {
             if item.center {
@@ -688,76 +1045,90 @@ impl MenuScreen {
         }
     }
 
-    fn render(&self, renderer: &Renderer) {
+    fn render(&self, renderer: &Renderer, text_edit: Option<&TextInput>) {
         let alpha = match self.state {
             MenuScreenState::Normal => 1.0,
             MenuScreenState::Appearing(a) | MenuScreenState::Hiding(a) => a,
             MenuScreenState::Hidden => 0.0,
         };
-        for item in &self.items {
+        let offset = self.render_offset();
+        for (idx, item) in self.items.iter().enumerate() {
+            let rect = item.rect + offset;
+
+            // Skip items scrolled out of view
+            if rect.bottom() < self.window.y() || rect.y() > self.window.bottom() {
+                continue;
+            }
+
             match &item.content {
                 MenuItemContent::Text(text) => {
                     text.render(&RenderTextOptions {
-                        dest: RenderTextDest::TopLeft(item.rect.topleft() + self.animated_offset),
+                        dest: RenderTextDest::TopLeft(rect.topleft()),
                         outline: TextOutline::Shadow,
                         alpha,
                         ..Default::default()
                     });
 
-                    match &item.value {
-                        MenuItemValue::None => {}
-                        MenuItemValue::Toggle(value, text) => {
-                            if text.borrow().is_none() {
-                                text.replace(Some(
-                                    renderer
-                                        .fontset()
-                                        .menu
-                                        .create_text(renderer, if *value { "yes" } else { "no" })
-                                        .unwrap()
-                                        .with_color(if *value {
-                                            Color::new(0.0, 0.8, 0.0)
-                                        } else {
-                                            Color::new(0.8, 0.0, 0.0)
-                                        }),
-                                ));
-                            }
-
-                            text.borrow().as_ref().unwrap().render(&RenderTextOptions {
-                                dest: RenderTextDest::TopLeft(
-                                    item.rect.topright() + Vec2(10.0, 0.0) + self.animated_offset,
-                                ),
-                                alpha,
-                                ..Default::default()
-                            });
-                        }
-                        MenuItemValue::KeyGrab(key, text) => {
-                            if text.borrow().is_none() {
-                                let keystr = unsafe { CStr::from_ptr(SDL_GetKeyName(*key)) };
-                                text.replace(Some(
-                                    renderer
-                                        .fontset()
-                                        .menu
-                                        .create_text(renderer, keystr.to_str().unwrap())
-                                        .unwrap()
-                                        .with_color(Color::new(0.6, 0.6, 0.8)),
-                                ));
-                            }
-
-                            text.borrow().as_ref().unwrap().render(&RenderTextOptions {
-                                dest: RenderTextDest::TopLeft(
-                                    item.rect.topright() + Vec2(10.0, 0.0) + self.animated_offset,
-                                ),
-                                alpha,
-                                ..Default::default()
-                            });
+                    let mut value_pos = rect.topright() + Vec2(10.0, 0.0);
+
+                    if let MenuItemValue::Slider {
+                        value, min, max, ..
+                    } = &item.value
+                    {
+                        let slider_width = Self::SLIDER_WIDTH * renderer.ui_scale();
+                        let bar = RectF::new(
+                            value_pos.0,
+                            value_pos.1 + rect.h() / 4.0,
+                            slider_width,
+                            rect.h() / 2.0,
+                        );
+                        let color = Color::new(0.6, 0.6, 0.8);
+                        renderer.draw_filled_rectangle(bar, &color.with_alpha(alpha * 0.3));
+                        renderer.draw_filled_rectangle(
+                            RectF::new(
+                                bar.x(),
+                                bar.y(),
+                                bar.w() * (value - min) / (max - min),
+                                bar.h(),
+                            ),
+                            &color.with_alpha(alpha),
+                        );
+                        value_pos = value_pos + Vec2(slider_width + 10.0, 0.0);
+                    }
+
+                    if let Some(input) = text_edit.filter(|_| idx == self.current) {
+                        input.render(
+                            renderer,
+                            &renderer.fontset().menu,
+                            value_pos,
+                            Color::WHITE.with_alpha(alpha),
+                        );
+                    } else if let (Some((value_text, color)), Some(text)) =
+                        (item.value.display_text(), item.value.cached_text())
+                    {
+                        if text.borrow().is_none() {
+                            text.replace(Some(
+                                renderer
+                                    .fontset()
+                                    .menu
+                                    .create_text(renderer, &value_text)
+                                    .unwrap()
+                                    .with_color(color),
+                            ));
                         }
-                    };
+
+                        text.borrow().as_ref().unwrap().render(&RenderTextOptions {
+                            dest: RenderTextDest::TopLeft(value_pos),
+                            alpha,
+                            ..Default::default()
+                        });
+                    }
                 }
                 MenuItemContent::Image(texture) => {
                     renderer.texture_store().get_texture(*texture).render(
                         renderer,
                         &RenderOptions {
-                            dest: RenderDest::Rect(item.rect + self.animated_offset),
+                            dest: RenderDest::Rect(rect),
                             color: Color::WHITE.with_alpha(alpha),
                             ..Default::default()
                         },
@@ -768,6 +1139,25 @@ impl MenuScreen {
         }
     }
 
+    fn call_action(lua: &Lua, item: &mut MenuItem) -> mlua::Result<MenuAction> {
+        if let Some(action) = item.action.clone() {
+            let result =
+                lua.scope(|scope| action.call::<Value>(scope.create_userdata_ref_mut(item)))?;
+
+            if !result.is_nil() {
+                return MenuAction::from_lua(result, lua);
+            }
+        }
+        Ok(MenuAction::None)
+    }
+
+    /// Set the value of the selected Text item and run its action
+    fn set_text_value(&mut self, lua: &Lua, value: String) -> mlua::Result<MenuAction> {
+        let item = &mut self.items[self.current];
+        item.value = MenuItemValue::Text(value, RefCell::new(None));
+        Self::call_action(lua, item)
+    }
+
     fn handle_button(&mut self, lua: &Lua, button: MenuButton) -> mlua::Result<MenuAction> {
         match button {
             MenuButton::Up(_) => loop {
@@ -790,19 +1180,42 @@ impl MenuScreen {
                     break;
                 }
             },
+            MenuButton::Left(_) | MenuButton::Right(_) => {
+                let direction = if matches!(button, MenuButton::Left(_)) {
+                    -1
+                } else {
+                    1
+                };
+                let item = &mut self.items[self.current];
+                if item.value.adjust(direction) {
+                    return Self::call_action(lua, item);
+                }
+            }
             MenuButton::Start | MenuButton::Select(_) => {
                 let item = &mut self.items[self.current];
-                if let Some(action) = item.action.clone() {
-                    let result = lua
-                        .scope(|scope| action.call::<Value>(scope.create_userdata_ref_mut(item)))?;
-
-                    if !result.is_nil() {
-                        return MenuAction::from_lua(result, lua);
+                match item.value {
+                    MenuItemValue::Text(..) => return Ok(MenuAction::TextEdit),
+                    MenuItemValue::Choice(..) => {
+                        item.value.adjust(1);
                     }
+                    _ => {}
                 }
+                return Self::call_action(lua, item);
             }
             MenuButton::GrabbedKey(key) => {
-                self.items[self.current].value = MenuItemValue::KeyGrab(key, RefCell::new(None));
+                let item = &mut self.items[self.current];
+                if matches!(item.value, MenuItemValue::KeyGrab(..)) {
+                    item.value = MenuItemValue::KeyGrab(key, RefCell::new(None));
+                }
+            }
+            MenuButton::GrabbedPadInput(inputs) => {
+                let item = &mut self.items[self.current];
+                if !inputs.is_empty() && matches!(item.value, MenuItemValue::PadGrab(..)) {
+                    item.value = MenuItemValue::PadGrab(
+                        inputs.iter().map(GamepadInput::name).collect(),
+                        RefCell::new(None),
+                    );
+                }
             }
             _ => {}
         }
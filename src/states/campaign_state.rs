@@ -0,0 +1,444 @@
+// This file is part of Luola2
+// Copyright (C) 2025 Calle Laakkonen
+//
+// Luola2 is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Luola2 is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Luola2.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::{cell::RefCell, rc::Rc};
+
+use anyhow::{Result, anyhow};
+
+use super::{StackableState, StackableStateResult};
+use crate::{
+    campaign::{CampaignProgress, MAX_STARS, star_rating},
+    demos::AnimatedStarfield,
+    game::{GameControllerSet, GameRules, MenuButton, Player},
+    gfx::{Color, RenderTextDest, RenderTextOptions, Renderer, Text, TextOutline},
+    lang::{tr, tr_fmt},
+    math::{RectF, Vec2},
+    states::{
+        game_assets::GameAssets,
+        round_state::{GameRoundState, RoundWinner},
+    },
+};
+
+const PIP_SIZE: f32 = 12.0;
+
+/**
+ * Single player campaign progression screen.
+ *
+ * Lists the missions of all level packs. A mission is unlocked once the
+ * previous mission of the same pack has been completed. The mission is
+ * played by whoever presses fire on it, using a regular game round with
+ * a single viewport.
+ */
+pub struct CampaignState {
+    assets: Rc<GameAssets>,
+    starfield: Rc<RefCell<AnimatedStarfield>>,
+    controllers: Rc<RefCell<GameControllerSet>>,
+    renderer: Rc<RefCell<Renderer>>,
+    progress: CampaignProgress,
+    title_text: Text,
+    prompt_text: Text,
+    par_text: Text,
+    empty_text: Text,
+    entries: Vec<MissionEntry>,
+    selection: usize,
+    /// Result of the last played mission
+    result_text: Option<Text>,
+    /// Index of the mission currently being played
+    playing: Option<usize>,
+    fadein: f32,
+}
+
+struct MissionEntry {
+    title: Text,
+    /// Best time, or a note that the mission is locked
+    info: Text,
+    unlocked: bool,
+    stars: i32,
+}
+
+impl CampaignState {
+    const TOP_MARGIN: f32 = 96.0;
+    const BOTTOM_MARGIN: f32 = 128.0;
+    const WIDTH: f32 = 640.0;
+
+    const SELECTION_COLOR: Color = Color::new(0.328, 0.371, 0.496);
+
+    pub fn new(
+        assets: Rc<GameAssets>,
+        starfield: Rc<RefCell<AnimatedStarfield>>,
+        controllers: Rc<RefCell<GameControllerSet>>,
+        renderer: Rc<RefCell<Renderer>>,
+    ) -> Result<Self> {
+        let r = renderer.borrow();
+        let font = &r.fontset().menu;
+
+        let title_text = r
+            .fontset()
+            .menu_big
+            .create_text(&r, &tr("Missions"))?
+            .with_color(Color::new(0.9, 0.2, 0.2));
+        let prompt_text = font
+            .create_text(&r, &tr("Press Fire to start the mission"))?
+            .with_outline_color(Color::new(0.2, 0.2, 0.4));
+        let par_text = font
+            .create_text(&r, "")?
+            .with_color(Color::new(0.9, 0.6, 0.2));
+        let empty_text = font
+            .create_text(&r, &tr("No missions available"))?
+            .with_outline_color(Color::new(0.2, 0.2, 0.4));
+        drop(r);
+
+        let mut state = Self {
+            assets,
+            starfield,
+            controllers,
+            renderer,
+            progress: CampaignProgress::load(),
+            title_text,
+            prompt_text,
+            par_text,
+            empty_text,
+            entries: Vec::new(),
+            selection: 0,
+            result_text: None,
+            playing: None,
+            fadein: 0.0,
+        };
+
+        state.update_entries()?;
+
+        // Start from the first mission that hasn't been completed yet
+        state.selection = state
+            .entries
+            .iter()
+            .position(|e| e.unlocked && e.stars == 0)
+            .unwrap_or(0);
+        state.update_par_text();
+
+        Ok(state)
+    }
+
+    fn is_unlocked(&self, idx: usize) -> bool {
+        let missions = &self.assets.missions;
+        let previous = idx
+            .checked_sub(1)
+            .map(|prev| &missions[prev])
+            .filter(|prev| prev.levelpack() == missions[idx].levelpack())
+            .map(|prev| prev.id());
+
+        self.progress.is_unlocked(previous.as_deref())
+    }
+
+    fn update_entries(&mut self) -> Result<()> {
+        let r = self.renderer.borrow();
+        let font = &r.fontset().menu;
+
+        let entries = self
+            .assets
+            .missions
+            .iter()
+            .enumerate()
+            .map(|(idx, mission)| {
+                let unlocked = self.is_unlocked(idx);
+                let record = self.progress.get(&mission.id());
+                let info = if !unlocked {
+                    tr("Locked")
+                } else if let Some(record) = record {
+                    format_time(record.time)
+                } else {
+                    "-:--".to_owned()
+                };
+
+                Ok(MissionEntry {
+                    title: font.create_text(&r, mission.title())?,
+                    info: font.create_text(&r, &info)?,
+                    unlocked,
+                    stars: record.map(|r| r.stars).unwrap_or(0),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        drop(r);
+        self.entries = entries;
+        Ok(())
+    }
+
+    fn update_par_text(&mut self) {
+        if let Some(mission) = self.assets.missions.get(self.selection) {
+            self.par_text
+                .set_text(&tr_fmt("Par time: {1}", &[&format_time(mission.par())]));
+        }
+    }
+
+    fn start_mission(&mut self, controller: i32) -> StackableStateResult {
+        let mission = &self.assets.missions[self.selection];
+        let Some(level) = self
+            .assets
+            .levels
+            .iter()
+            .find(|l| l.levelpack() == mission.levelpack() && l.name() == mission.level())
+        else {
+            return StackableStateResult::Error(anyhow!(
+                "Level {} of mission {} not found",
+                mission.level(),
+                mission.id()
+            ));
+        };
+
+        let mut player = Player::new(controller);
+        player.ship = mission
+            .ship()
+            .unwrap_or(&self.assets.default_ship)
+            .to_owned();
+        player.weapons = mission.weapons().to_vec();
+
+        self.controllers.borrow().set_player_leds(controller, 1);
+        self.playing = Some(self.selection);
+        self.result_text = None;
+
+        match GameRoundState::new(
+            vec![player],
+            GameRules::default(),
+            level,
+            Some(mission),
+            self.controllers.clone(),
+            self.renderer.clone(),
+        ) {
+            Ok(g) => StackableStateResult::Push(Box::new(g)),
+            Err(err) => StackableStateResult::Error(err),
+        }
+    }
+
+    fn mission_finished(&mut self, idx: usize, winner: &RoundWinner) -> Result<()> {
+        let mission = &self.assets.missions[idx];
+
+        // The mission script sets the score to the completion time on success
+        let (text, color) = match winner.3 {
+            Some(time) if winner.0 == 1 => {
+                let id = mission.id();
+                let replay = self.progress.get(&id).is_some();
+                let best = self
+                    .progress
+                    .record(&id, time, star_rating(time, mission.par()));
+                self.progress.save();
+
+                let text = if replay && best {
+                    tr_fmt("New best time: {1}", &[&format_time(time)])
+                } else {
+                    tr_fmt("Mission complete! Time: {1}", &[&format_time(time)])
+                };
+                (text, Color::new(0.5, 1.0, 0.5))
+            }
+            _ => (tr("Mission failed"), Color::new(0.9, 0.2, 0.2)),
+        };
+
+        let r = self.renderer.borrow();
+        self.result_text = Some(r.fontset().menu.create_text(&r, &text)?.with_color(color));
+        drop(r);
+
+        self.update_entries()?;
+
+        // Move on to the next mission if it was just unlocked
+        if let Some(next) = self.entries.get(idx + 1)
+            && next.unlocked
+            && next.stars == 0
+        {
+            self.selection = idx + 1;
+            self.update_par_text();
+        }
+
+        Ok(())
+    }
+
+    fn render(&self) {
+        let renderer = self.renderer.borrow();
+        renderer.clear();
+        let w = renderer.width() as f32;
+        let h = renderer.height() as f32;
+
+        self.starfield.borrow().render(&renderer);
+
+        self.title_text.render(&RenderTextOptions {
+            dest: RenderTextDest::TopCenter(Vec2(w / 2.0, 10.0)),
+            outline: TextOutline::Outline,
+            alpha: self.fadein,
+            ..Default::default()
+        });
+
+        if self.entries.is_empty() {
+            self.empty_text.render(&RenderTextOptions {
+                dest: RenderTextDest::Centered(Vec2(w / 2.0, h / 2.0)),
+                outline: TextOutline::Shadow,
+                alpha: self.fadein,
+                ..Default::default()
+            });
+            renderer.present();
+            return;
+        }
+
+        // Mission list, scrolled so that the selection is always visible
+        let row_h = self.entries[0].title.height() + 8.0;
+        let visible_rows =
+            (((h - Self::TOP_MARGIN - Self::BOTTOM_MARGIN) / row_h).floor() as usize).max(1);
+        let first = (self.selection + 1).saturating_sub(visible_rows);
+
+        let left = (w - Self::WIDTH) / 2.0;
+        for (row, (idx, entry)) in self
+            .entries
+            .iter()
+            .enumerate()
+            .skip(first)
+            .take(visible_rows)
+            .enumerate()
+        {
+            let y = Self::TOP_MARGIN + row as f32 * row_h;
+
+            if idx == self.selection {
+                renderer.draw_filled_rectangle(
+                    RectF::new(left - 8.0, y - 4.0, Self::WIDTH + 16.0, row_h),
+                    &Self::SELECTION_COLOR.with_alpha(self.fadein),
+                );
+            }
+
+            let alpha = self.fadein * if entry.unlocked { 1.0 } else { 0.4 };
+            entry.title.render(&RenderTextOptions {
+                dest: RenderTextDest::TopLeft(Vec2(left, y)),
+                outline: TextOutline::Shadow,
+                alpha,
+                ..Default::default()
+            });
+            entry.info.render(&RenderTextOptions {
+                dest: RenderTextDest::TopRight(Vec2(left + Self::WIDTH, y)),
+                outline: TextOutline::Shadow,
+                alpha,
+                ..Default::default()
+            });
+
+            // Star rating
+            if entry.unlocked {
+                let pip_y = y + (entry.title.height() - PIP_SIZE) / 2.0;
+                let mut x = left + Self::WIDTH - entry.info.width() - 16.0;
+                for star in (0..MAX_STARS).rev() {
+                    x -= PIP_SIZE + 4.0;
+                    let color = if star < entry.stars {
+                        Color::new_rgba(1.0, 0.84, 0.0, self.fadein)
+                    } else {
+                        Color::new_rgba(0.3, 0.3, 0.3, self.fadein)
+                    };
+                    renderer
+                        .draw_filled_rectangle(RectF::new(x, pip_y, PIP_SIZE, PIP_SIZE), &color);
+                }
+            }
+        }
+
+        // Selected mission info and the result of the last mission
+        let mut y = h - Self::BOTTOM_MARGIN + 10.0;
+        self.par_text.render(&RenderTextOptions {
+            dest: RenderTextDest::TopCenter(Vec2(w / 2.0, y)),
+            outline: TextOutline::Shadow,
+            alpha: self.fadein,
+            ..Default::default()
+        });
+        y += self.par_text.height();
+
+        if let Some(result) = &self.result_text {
+            result.render(&RenderTextOptions {
+                dest: RenderTextDest::TopCenter(Vec2(w / 2.0, y)),
+                outline: TextOutline::Outline,
+                alpha: self.fadein,
+                ..Default::default()
+            });
+        }
+
+        if self.entries[self.selection].unlocked {
+            self.prompt_text.render(&RenderTextOptions {
+                dest: RenderTextDest::BottomCenter(Vec2(w / 2.0, h - 10.0)),
+                outline: TextOutline::Shadow,
+                alpha: self.fadein,
+                ..Default::default()
+            });
+        }
+
+        renderer.present();
+    }
+}
+
+/// Format a time in seconds as minutes and seconds
+fn format_time(seconds: i32) -> String {
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+impl StackableState for CampaignState {
+    fn receive_return(&mut self, retval: Box<dyn std::any::Any>) -> StackableStateResult {
+        if let Some(winner) = retval.downcast_ref::<RoundWinner>()
+            && let Some(idx) = self.playing.take()
+        {
+            self.controllers.borrow().clear_player_leds();
+            self.fadein = 0.0;
+            if let Err(err) = self.mission_finished(idx, winner) {
+                return StackableStateResult::Error(err);
+            }
+            StackableStateResult::Continue
+        } else {
+            StackableStateResult::Error(anyhow!(
+                "Unhandled campaign state return type: {:?}",
+                retval.type_id()
+            ))
+        }
+    }
+
+    fn handle_menu_button(&mut self, button: MenuButton) -> StackableStateResult {
+        match button {
+            MenuButton::Back => {
+                return StackableStateResult::Pop;
+            }
+            MenuButton::Up(_) if !self.entries.is_empty() => {
+                self.selection =
+                    (self.selection as i32 - 1).rem_euclid(self.entries.len() as i32) as usize;
+                self.update_par_text();
+            }
+            MenuButton::Down(_) if !self.entries.is_empty() => {
+                self.selection = (self.selection + 1) % self.entries.len();
+                self.update_par_text();
+            }
+            MenuButton::Select(controller)
+                if controller > 0
+                    && self.entries.get(self.selection).is_some_and(|e| e.unlocked) =>
+            {
+                return self.start_mission(controller);
+            }
+            _ => {}
+        }
+        StackableStateResult::Continue
+    }
+
+    fn resize_screen(&mut self) {
+        self.starfield
+            .borrow_mut()
+            .update_screensize(self.renderer.borrow().size());
+    }
+
+    fn state_iterate(&mut self, timestep: f32) -> StackableStateResult {
+        self.starfield.borrow_mut().step(timestep);
+
+        if self.fadein < 1.0 {
+            self.fadein = (self.fadein + timestep * 2.0).min(1.0);
+        }
+
+        self.render();
+        StackableStateResult::Continue
+    }
+}
@@ -0,0 +1,399 @@
+// This file is part of Luola2
+// Copyright (C) 2025 Calle Laakkonen
+//
+// Luola2 is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Luola2 is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Luola2.  If not, see <https://www.gnu.org/licenses/>.
+
+use anyhow::Result;
+use mlua::{Function, Lua, MultiValue, Value};
+use sdl3_sys::keycode::{
+    SDLK_DOWN, SDLK_ESCAPE, SDLK_KP_ENTER, SDLK_PAGEDOWN, SDLK_PAGEUP, SDLK_RETURN, SDLK_UP,
+};
+use std::{
+    cell::{Cell, RefCell},
+    collections::VecDeque,
+    rc::Rc,
+};
+
+use crate::{
+    game::{GameControllerSet, MenuButton},
+    gfx::{
+        Color, RenderDest, RenderOptions, RenderTextDest, RenderTextOptions, Renderer, Text,
+        TextInput, Texture,
+    },
+    math::{RectF, Vec2},
+    states::{StackableState, StackableStateResult},
+};
+
+/// Maximum number of lines kept in the scrollback buffer
+const MAX_LINES: usize = 500;
+
+/// Maximum number of commands kept in the history
+const MAX_HISTORY: usize = 100;
+
+#[derive(Clone, Copy)]
+enum LineKind {
+    Input,
+    Output,
+    Error,
+}
+
+/**
+ * Console scrollback and command history.
+ *
+ * This outlives the console state itself, so the history
+ * is retained when the console is closed and reopened during a round.
+ */
+pub struct ConsoleLog {
+    lines: VecDeque<(String, LineKind)>,
+    history: Vec<String>,
+    changed: bool,
+}
+
+impl ConsoleLog {
+    pub fn new() -> Self {
+        Self {
+            lines: VecDeque::new(),
+            history: Vec::new(),
+            changed: false,
+        }
+    }
+
+    fn push(&mut self, text: &str, kind: LineKind) {
+        for line in text.lines() {
+            if self.lines.len() >= MAX_LINES {
+                self.lines.pop_front();
+            }
+            self.lines.push_back((line.replace('\t', "    "), kind));
+        }
+        self.changed = true;
+    }
+
+    fn push_history(&mut self, command: &str) {
+        if self.history.last().is_some_and(|h| h == command) {
+            return;
+        }
+        if self.history.len() >= MAX_HISTORY {
+            self.history.remove(0);
+        }
+        self.history.push(command.to_string());
+    }
+}
+
+/**
+ * A drop-down developer console for evaluating Lua code
+ * in the script environment of the current round.
+ *
+ * The round is paused while the console is open. Output of the
+ * `print` function is captured and shown in the console.
+ */
+pub struct ConsoleState {
+    renderer: Rc<RefCell<Renderer>>,
+    controllers: Rc<RefCell<GameControllerSet>>,
+    lua: Lua,
+    /// Script instruction counter, reset before each command
+    instruction_count: Rc<Cell<u32>>,
+    log: Rc<RefCell<ConsoleLog>>,
+
+    /// The print function that was replaced while the console is open
+    original_print: Value,
+
+    input: TextInput,
+    prompt: Text,
+
+    /// Rendered scrollback lines (only the visible ones)
+    texts: Vec<Text>,
+
+    /// Scrollback position (number of lines from the bottom)
+    scroll: usize,
+
+    /// Position in command history when browsing it
+    history_pos: Option<usize>,
+
+    background: Texture,
+    slide: f32,
+}
+
+impl ConsoleState {
+    pub fn new(
+        lua: Lua,
+        instruction_count: Rc<Cell<u32>>,
+        log: Rc<RefCell<ConsoleLog>>,
+        renderer: Rc<RefCell<Renderer>>,
+        controllers: Rc<RefCell<GameControllerSet>>,
+    ) -> Result<Self> {
+        let original_print = lua.globals().get::<Value>("print")?;
+
+        let print_log = log.clone();
+        lua.globals().set(
+            "print",
+            lua.create_function(move |lua, args: MultiValue| {
+                let line = Self::values_to_string(lua, args)?;
+                print_log.borrow_mut().push(&line, LineKind::Output);
+                Ok(())
+            })?,
+        )?;
+
+        let (input, prompt, background) = {
+            let r = renderer.borrow();
+            let font = &r.fontset().flavotext;
+            (
+                TextInput::new(&r, font)?,
+                font.create_text(&r, "> ")?,
+                Texture::from_image(&r, &r.screenshot()?)?,
+            )
+        };
+
+        renderer.borrow().set_text_input(true);
+        controllers.borrow_mut().set_text_input(true);
+        log.borrow_mut().changed = true;
+
+        Ok(Self {
+            renderer,
+            controllers,
+            lua,
+            instruction_count,
+            log,
+            original_print,
+            input,
+            prompt,
+            texts: Vec::new(),
+            scroll: 0,
+            history_pos: None,
+            background,
+            slide: 0.0,
+        })
+    }
+
+    fn values_to_string(lua: &Lua, values: MultiValue) -> mlua::Result<String> {
+        let tostring = lua.globals().get::<Function>("tostring")?;
+        Ok(values
+            .into_iter()
+            .map(|v| tostring.call::<String>(v))
+            .collect::<mlua::Result<Vec<String>>>()?
+            .join("\t"))
+    }
+
+    fn execute(&mut self, code: String) {
+        if code.trim().is_empty() {
+            return;
+        }
+
+        // The game is paused while the console is open, so the instruction
+        // budget is not reset by the game tick
+        self.instruction_count.set(0);
+
+        {
+            let mut log = self.log.borrow_mut();
+            log.push(&format!("> {code}"), LineKind::Input);
+            log.push_history(&code);
+        }
+        self.history_pos = None;
+        self.scroll = 0;
+
+        // Try evaluating the line as an expression first so its value can be shown,
+        // then fall back to executing it as a statement.
+        let result = match self
+            .lua
+            .load(format!("return {code}"))
+            .set_name("=console")
+            .into_function()
+        {
+            Ok(f) => f.call::<MultiValue>(()),
+            Err(_) => self
+                .lua
+                .load(&code)
+                .set_name("=console")
+                .eval::<MultiValue>(),
+        }
+        .and_then(|values| {
+            if values.is_empty() {
+                Ok(None)
+            } else {
+                Self::values_to_string(&self.lua, values).map(Some)
+            }
+        });
+
+        match result {
+            Ok(Some(output)) => self.log.borrow_mut().push(&output, LineKind::Output),
+            Ok(None) => {}
+            Err(err) => self
+                .log
+                .borrow_mut()
+                .push(&err.to_string(), LineKind::Error),
+        }
+    }
+
+    fn browse_history(&mut self, older: bool) {
+        let log = self.log.borrow();
+        if log.history.is_empty() {
+            return;
+        }
+
+        self.history_pos = match (self.history_pos, older) {
+            (None, true) => Some(log.history.len() - 1),
+            (None, false) => None,
+            (Some(p), true) => Some(p.saturating_sub(1)),
+            (Some(p), false) if p + 1 < log.history.len() => Some(p + 1),
+            (Some(_), false) => None,
+        };
+
+        match self.history_pos {
+            Some(p) => self.input.set_value(&log.history[p]),
+            None => self.input.set_value(""),
+        }
+    }
+
+    fn line_height(&self) -> f32 {
+        self.input
+            .height(&self.renderer.borrow().fontset().flavotext)
+            + 2.0
+    }
+
+    fn visible_lines(&self) -> usize {
+        let height = self.renderer.borrow().height() as f32 / 2.0;
+        ((height / self.line_height()) as usize).saturating_sub(1)
+    }
+
+    /// Recreate the text objects for the visible part of the scrollback
+    fn sync_texts(&mut self) -> Result<()> {
+        if !self.log.borrow().changed {
+            return Ok(());
+        }
+
+        let visible = self.visible_lines();
+        let renderer = self.renderer.borrow();
+        let font = &renderer.fontset().flavotext;
+        let mut log = self.log.borrow_mut();
+        log.changed = false;
+
+        self.scroll = self.scroll.min(log.lines.len().saturating_sub(visible));
+
+        self.texts.clear();
+        for (line, kind) in log.lines.iter().rev().skip(self.scroll).take(visible).rev() {
+            self.texts
+                .push(font.create_text(&renderer, line)?.with_color(match kind {
+                    LineKind::Input => Color::new(0.7, 0.7, 0.8),
+                    LineKind::Output => Color::WHITE,
+                    LineKind::Error => Color::new(1.0, 0.4, 0.4),
+                }));
+        }
+
+        Ok(())
+    }
+
+    fn render(&self) {
+        let renderer = self.renderer.borrow();
+        renderer.clear();
+        self.background.render(
+            &renderer,
+            &RenderOptions {
+                dest: RenderDest::Fill,
+                ..Default::default()
+            },
+        );
+
+        let line_height = self.line_height();
+        let height = (self.visible_lines() + 1) as f32 * line_height + 8.0;
+        let top = -height * (1.0 - self.slide);
+
+        renderer.draw_filled_rectangle(
+            RectF::new(0.0, top, renderer.width() as f32, height),
+            &Color::new_rgba(0.0, 0.0, 0.1, 0.85),
+        );
+
+        let input_y = top + height - line_height - 4.0;
+        for (i, text) in self.texts.iter().rev().enumerate() {
+            text.render(&RenderTextOptions {
+                dest: RenderTextDest::TopLeft(Vec2(4.0, input_y - (i + 1) as f32 * line_height)),
+                ..Default::default()
+            });
+        }
+
+        self.prompt.render(&RenderTextOptions {
+            dest: RenderTextDest::TopLeft(Vec2(4.0, input_y)),
+            ..Default::default()
+        });
+        self.input.render(
+            &renderer,
+            &renderer.fontset().flavotext,
+            Vec2(4.0 + self.prompt.width(), input_y),
+            Color::WHITE,
+        );
+
+        renderer.present();
+    }
+}
+
+impl Drop for ConsoleState {
+    fn drop(&mut self) {
+        if let Err(err) = self.lua.globals().set("print", self.original_print.clone()) {
+            log::error!("Couldn't restore print function: {err}");
+        }
+        self.renderer.borrow().set_text_input(false);
+        self.controllers.borrow_mut().set_text_input(false);
+    }
+}
+
+impl StackableState for ConsoleState {
+    fn resize_screen(&mut self) {
+        // Number of visible lines may have changed
+        self.log.borrow_mut().changed = true;
+    }
+
+    fn handle_text_input(&mut self, text: &str) -> StackableStateResult {
+        // The console toggle key also produces text input
+        if !text.is_empty() && !self.controllers.borrow().console_key_down() {
+            self.input.insert(text);
+        }
+        StackableStateResult::Continue
+    }
+
+    fn handle_menu_button(&mut self, button: MenuButton) -> StackableStateResult {
+        match button {
+            MenuButton::Console | MenuButton::Back | MenuButton::TextEdit(SDLK_ESCAPE) => {
+                return StackableStateResult::Pop;
+            }
+            MenuButton::TextEdit(SDLK_RETURN | SDLK_KP_ENTER) => {
+                let code = self.input.take();
+                self.execute(code);
+            }
+            MenuButton::TextEdit(SDLK_UP) => self.browse_history(true),
+            MenuButton::TextEdit(SDLK_DOWN) => self.browse_history(false),
+            MenuButton::TextEdit(SDLK_PAGEUP) => {
+                self.scroll += self.visible_lines() / 2;
+                self.log.borrow_mut().changed = true;
+            }
+            MenuButton::TextEdit(SDLK_PAGEDOWN) => {
+                self.scroll = self.scroll.saturating_sub(self.visible_lines() / 2);
+                self.log.borrow_mut().changed = true;
+            }
+            MenuButton::TextEdit(key) => {
+                self.input.handle_key(key);
+            }
+            _ => {}
+        }
+        StackableStateResult::Continue
+    }
+
+    fn state_iterate(&mut self, timestep: f32) -> StackableStateResult {
+        if let Err(err) = self.sync_texts() {
+            return StackableStateResult::Error(err);
+        }
+
+        self.slide = (self.slide + timestep * 6.0).min(1.0);
+        self.input.step(timestep);
+        self.render();
+        StackableStateResult::Continue
+    }
+}
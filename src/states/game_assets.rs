@@ -14,21 +14,39 @@
 // You should have received a copy of the GNU General Public License
 // along with Luola2.  If not, see <https://www.gnu.org/licenses/>.
 
-use crate::{game::level::LevelInfo, gfx::TextureId};
+use std::collections::HashMap;
+
+use crate::{
+    game::{
+        level::{LevelInfo, MissionInfo},
+        objects::MAX_SECONDARY_WEAPONS,
+    },
+    gfx::TextureId,
+};
 
 /// Game assets (levels, weapons, etc.) loaded in the beginning
 pub struct GameAssets {
     pub levels: Vec<LevelInfo>,
+    /// Single player missions in campaign order
+    pub missions: Vec<MissionInfo>,
     pub weapons: Vec<SelectableWeapon>,
     pub ships: Vec<SelectableShip>,
+    pub shop: Vec<ShopItem>,
     pub default_weapon: String,
     pub default_ship: String,
+    /// Loadout points available for special weapons
+    pub weapon_budget: i32,
+    /// Special weapon slots available without shop purchases
+    pub weapon_slots: i32,
 }
 
 pub struct SelectableWeapon {
     pub name: String,
     pub title: String,
     pub flavortext: String,
+    /// Loadout points this weapon costs
+    pub cost: i32,
+    pub icon: Option<TextureId>,
 }
 
 pub struct SelectableShip {
@@ -38,14 +56,64 @@ pub struct SelectableShip {
     pub texture: TextureId,
 }
 
+pub struct ShopItem {
+    pub name: String,
+    pub title: String,
+    pub flavortext: String,
+    pub price: i32,
+    /// Maximum number of these items a player can own
+    pub max_count: i32,
+    /// Item is used up after one round
+    pub consumable: bool,
+    /// Extra loadout points this item gives
+    pub weapon_points: i32,
+    /// Extra special weapon slots this item gives
+    pub weapon_slots: i32,
+}
+
 impl GameAssets {
     pub fn new() -> Self {
         Self {
             levels: Vec::new(),
+            missions: Vec::new(),
             weapons: Vec::new(),
             ships: Vec::new(),
+            shop: Vec::new(),
             default_weapon: String::new(),
             default_ship: String::new(),
+            weapon_budget: 0,
+            weapon_slots: 1,
         }
     }
+
+    /// Get the special weapon loadout points a player with these purchases has
+    pub fn weapon_budget(&self, purchases: &HashMap<String, i32>) -> i32 {
+        self.weapon_budget
+            + self
+                .shop
+                .iter()
+                .map(|item| item.weapon_points * purchases.get(&item.name).copied().unwrap_or(0))
+                .sum::<i32>()
+    }
+
+    /// Get the number of special weapon slots a player with these purchases has
+    pub fn weapon_slots(&self, purchases: &HashMap<String, i32>) -> usize {
+        let slots = self.weapon_slots
+            + self
+                .shop
+                .iter()
+                .map(|item| item.weapon_slots * purchases.get(&item.name).copied().unwrap_or(0))
+                .sum::<i32>();
+        slots.clamp(1, MAX_SECONDARY_WEAPONS as i32) as usize
+    }
+
+    /// Get the loadout point cost of the cheapest special weapon
+    pub fn cheapest_weapon_cost(&self) -> i32 {
+        self.weapons.iter().map(|w| w.cost).min().unwrap_or(0)
+    }
+
+    /// Check if the named shop item is used up after a round
+    pub fn is_consumable(&self, item: &str) -> bool {
+        self.shop.iter().any(|i| i.name == item && i.consumable)
+    }
 }
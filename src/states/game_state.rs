@@ -19,8 +19,12 @@ use std::{cell::RefCell, mem::take, rc::Rc};
 
 use crate::{
     demos::AnimatedStarfield,
-    game::{GameControllerSet, GameInitConfig, MenuButton, Player, PlayerId, level::LevelInfo},
+    game::{
+        GameControllerSet, GameInitConfig, GameRules, MenuButton, Player, PlayerId,
+        level::LevelInfo,
+    },
     gfx::{Renderer, Texture},
+    highscores::record_high_score,
     states::{
         StackableState, StackableStateResult,
         game_assets::GameAssets,
@@ -28,6 +32,7 @@ use crate::{
         levelsel_state::LevelSelection,
         round_state::{GameRoundState, RoundWinner},
         roundresults_state::RoundResultsState,
+        shop_state::{ShopPurchases, ShopState},
         weaponsel_state::{SelectedWeapons, WeaponSelection},
     },
 };
@@ -39,7 +44,10 @@ pub struct GameState {
     players: Vec<Player>,
     level: Option<LevelInfo>,
     rounds: i32,
+    rules: GameRules,
     round_winners: Vec<PlayerId>,
+    /// Score of the last round (if scored) and whether it was a new high score
+    last_score: Option<(i32, bool)>,
     substate: GameSubState,
     controllers: Rc<RefCell<GameControllerSet>>,
     renderer: Rc<RefCell<Renderer>>,
@@ -52,14 +60,19 @@ enum GameSubState {
     SelectWeapons,
     PlayRound,
     RoundResults,
+    Shop,
     GameResults,
 }
 
+/// Credits awarded for winning a round when the shop is enabled
+const ROUND_WIN_CREDITS: i32 = 250;
+
 impl GameState {
     pub fn new(
         assets: Rc<GameAssets>,
         players: Vec<Player>,
         rounds: i32,
+        rules: GameRules,
         starfield: Rc<RefCell<AnimatedStarfield>>,
         controllers: Rc<RefCell<GameControllerSet>>,
         renderer: Rc<RefCell<Renderer>>,
@@ -69,7 +82,9 @@ impl GameState {
             starfield,
             players,
             rounds,
+            rules,
             round_winners: Vec::new(),
+            last_score: None,
             level: None,
             substate: GameSubState::SelectNextLevel,
             controllers,
@@ -113,7 +128,8 @@ impl GameState {
             }
         }
 
-        if substate == GameSubState::SelectWeapons && players.iter().all(|p| !p.weapon.is_empty()) {
+        if substate == GameSubState::SelectWeapons && players.iter().all(|p| !p.weapons.is_empty())
+        {
             // level and weapons set, skip weapon selector too
             substate = GameSubState::PlayRound;
         }
@@ -126,7 +142,9 @@ impl GameState {
             players,
             level,
             rounds,
+            rules: config.rules,
             round_winners,
+            last_score: None,
             substate,
             controllers,
             renderer,
@@ -142,13 +160,30 @@ impl StackableState for GameState {
         } else if let Some(weapons) = retval.downcast_ref::<SelectedWeapons>() {
             self.players.iter_mut().zip(&weapons.0).for_each(|(p, w)| {
                 p.ship = w.0.clone();
-                p.weapon = w.1.clone();
+                p.weapons = w.1.clone();
             });
             self.substate = GameSubState::PlayRound;
         } else if let Some(winner) = retval.downcast_ref::<RoundWinner>() {
             if winner.0 > 0 {
                 let plr = &mut self.players[winner.0 as usize - 1];
                 plr.wins += 1;
+                if self.rules.economy {
+                    plr.credits += ROUND_WIN_CREDITS;
+                }
+            }
+
+            self.last_score = winner.3.map(|score| {
+                let record = self
+                    .level
+                    .as_ref()
+                    .is_some_and(|level| record_high_score(&level.id(), score));
+                (score, record)
+            });
+
+            for (plr, credits) in self.players.iter_mut().zip(&winner.2) {
+                plr.credits += credits;
+                plr.purchases
+                    .retain(|item, _| !self.assets.is_consumable(item));
             }
             self.round_winners.push(winner.0);
             if winner.1 || self.round_winners.len() as i32 >= self.rounds {
@@ -156,6 +191,14 @@ impl StackableState for GameState {
             } else {
                 self.substate = GameSubState::RoundResults;
             }
+        } else if let Some(purchases) = retval.downcast_ref::<ShopPurchases>() {
+            self.players
+                .iter_mut()
+                .zip(&purchases.0)
+                .for_each(|(p, (credits, items))| {
+                    p.credits = *credits;
+                    p.purchases = items.clone();
+                });
         } else {
             return StackableStateResult::Error(anyhow!(
                 "Unhandled game state return type: {:?}",
@@ -181,7 +224,11 @@ impl StackableState for GameState {
         // where the player chooses to cancel the game early.
         match self.substate {
             GameSubState::RoundResults => {
-                self.substate = GameSubState::SelectNextLevel;
+                self.substate = if self.rules.economy {
+                    GameSubState::Shop
+                } else {
+                    GameSubState::SelectNextLevel
+                };
                 let last_winner = *self
                     .round_winners
                     .last()
@@ -190,6 +237,7 @@ impl StackableState for GameState {
                     match RoundResultsState::new(
                         self.round_winners.len() as i32,
                         last_winner,
+                        self.last_score,
                         self.starfield.clone(),
                         self.renderer.clone(),
                     ) {
@@ -200,6 +248,23 @@ impl StackableState for GameState {
                     },
                 ))
             }
+            GameSubState::Shop => {
+                self.substate = GameSubState::SelectNextLevel;
+                StackableStateResult::Push(Box::new(
+                    match ShopState::new(
+                        self.assets.clone(),
+                        &self.players,
+                        self.starfield.clone(),
+                        self.renderer.clone(),
+                        &self.controllers.borrow(),
+                    ) {
+                        Ok(s) => s,
+                        Err(err) => {
+                            return StackableStateResult::Error(err);
+                        }
+                    },
+                ))
+            }
             GameSubState::SelectNextLevel | GameSubState::SelectLevel => {
                 let fadein_round_text = matches!(self.substate, GameSubState::SelectNextLevel);
                 self.substate = GameSubState::GameResults;
@@ -247,6 +312,7 @@ impl StackableState for GameState {
                     match WeaponSelection::new(
                         self.assets.clone(),
                         &self.players,
+                        self.rules.economy,
                         self.round_winners.len() as i32 + 1,
                         level_art,
                         self.starfield.clone(),
@@ -266,9 +332,11 @@ impl StackableState for GameState {
                 StackableStateResult::Push(Box::new(
                     match GameRoundState::new(
                         self.players.clone(),
+                        self.rules,
                         self.level
                             .as_ref()
                             .expect("Level should have been loaded at this point"),
+                        None,
                         self.controllers.clone(),
                         self.renderer.clone(),
                     ) {
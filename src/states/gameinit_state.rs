@@ -14,20 +14,24 @@
 // You should have received a copy of the GNU General Public License
 // along with Luola2.  If not, see <https://www.gnu.org/licenses/>.
 
-use anyhow::{Result, anyhow};
+use anyhow::{Context, Result, anyhow};
+use log::error;
 use std::{cell::RefCell, fs::read_to_string, rc::Rc};
 
 use crate::{
     demos::AnimatedStarfield,
-    fs::find_datafile_path,
+    fs::{find_datafile_path, find_mod_datafiles},
     game::{
-        GameControllerSet, GameInitConfig, MenuButton, level::LevelInfo,
+        GameControllerSet, GameInitConfig, MenuButton,
+        level::{LevelInfo, MissionInfo},
+        objects::load_particle_presets,
         scripting::ScriptEnvironment,
     },
     gfx::Renderer,
+    lang::{current_language, tr},
     states::{
         MainMenu,
-        game_assets::{GameAssets, SelectableShip, SelectableWeapon},
+        game_assets::{GameAssets, SelectableShip, SelectableWeapon, ShopItem},
         game_state::GameState,
     },
 };
@@ -59,13 +63,21 @@ impl GameInitState {
 }
 
 fn load_resources(renderer: Rc<RefCell<Renderer>>) -> Result<Rc<GameAssets>> {
+    // Mods may add to or replace parts of the base fontset and textures
+    let mut fontsets = vec![find_datafile_path("fonts/fonts.toml")?];
+    fontsets.extend(find_mod_datafiles("fonts/fonts.toml"));
     renderer
         .borrow_mut()
-        .load_fontset(&find_datafile_path("fonts/fonts.toml")?)?;
+        .load_fontset(&fontsets, &current_language())?;
 
-    renderer
-        .borrow_mut()
-        .load_textures(&find_datafile_path("textures/textures.toml")?)?;
+    let mut textures = vec![find_datafile_path("textures/textures.toml")?];
+    textures.extend(find_mod_datafiles("textures/textures.toml"));
+    renderer.borrow_mut().load_textures(&textures)?;
+
+    // Particle emitter presets refer to textures, so they're loaded afterwards
+    let mut particles = vec![find_datafile_path("particles.toml")?];
+    particles.extend(find_mod_datafiles("particles.toml"));
+    load_particle_presets(&particles, &renderer.borrow())?;
 
     // Load list of levels
     let mut levels = LevelInfo::load_level_packs(&renderer.borrow())?;
@@ -82,12 +94,30 @@ fn load_resources(renderer: Rc<RefCell<Renderer>>) -> Result<Rc<GameAssets>> {
         }
     });
 
+    // Missions refer to levels by name, so skip the ones whose level is missing
+    let missions = MissionInfo::load_mission_packs()?
+        .into_iter()
+        .filter(|m| {
+            let found = levels
+                .iter()
+                .any(|l| l.levelpack() == m.levelpack() && l.name() == m.level());
+            if !found {
+                error!(
+                    "Mission {} refers to unknown level \"{}\"",
+                    m.id(),
+                    m.level()
+                );
+            }
+            found
+        })
+        .collect();
+
     // Load scripts and extract weapon list
     // The full API isn't initialized and shouldn't be needed
     // just to load the scripts without executing the entrypoint function
     let lua = ScriptEnvironment::create_lua(renderer.clone())?;
 
-    lua.load(r#"require "luola_main""#).exec()?;
+    ScriptEnvironment::load_main_script(&lua)?;
 
     let secondary_weapon_table = lua.globals().get::<mlua::Table>("luola_weapons")?;
 
@@ -95,13 +125,26 @@ fn load_resources(renderer: Rc<RefCell<Renderer>>) -> Result<Rc<GameAssets>> {
         .pairs::<String, mlua::Table>()
         .map(|pair| {
             let (name, v) = pair?;
-            let title: String = v.get("title")?;
-            let flavortext: String = v.get("description")?;
+            let title: String = v
+                .get("title")
+                .with_context(|| format!("weapon \"{name}\""))?;
+            let flavortext: String = v
+                .get("description")
+                .with_context(|| format!("weapon \"{name}\""))?;
+            let cost = v
+                .get::<Option<i32>>("cost")
+                .with_context(|| format!("weapon \"{name}\""))?
+                .unwrap_or(1);
+            let icon = v
+                .get("icon")
+                .with_context(|| format!("weapon \"{name}\""))?;
 
             Ok(SelectableWeapon {
                 name,
-                title,
-                flavortext,
+                title: tr(&title),
+                flavortext: tr(&flavortext),
+                cost,
+                icon,
             })
         })
         .collect::<Result<Vec<_>>>()?;
@@ -117,19 +160,26 @@ fn load_resources(renderer: Rc<RefCell<Renderer>>) -> Result<Rc<GameAssets>> {
         return Err(anyhow!("Default weapon \"{}\" not found!", default_weapon));
     }
 
+    let weapon_budget = lua.globals().get("luola_weapons_budget")?;
+    let weapon_slots = lua
+        .globals()
+        .get::<Option<i32>>("luola_weapons_slots")?
+        .unwrap_or(1);
+
     let ship_table = lua.globals().get::<mlua::Table>("luola_ships")?;
     let mut ships: Vec<_> = ship_table
         .pairs::<String, mlua::Table>()
         .map(|pair| {
             let (name, v) = pair?;
-            let title = v.get("title")?;
-            let flavortext = v.get("description")?;
-            let texture = v.get("texture")?;
+            let context = || format!("ship \"{name}\"");
+            let title: String = v.get("title").with_context(context)?;
+            let flavortext: String = v.get("description").with_context(context)?;
+            let texture = v.get("texture").with_context(context)?;
 
             Ok(SelectableShip {
                 name,
-                title,
-                flavortext,
+                title: tr(&title),
+                flavortext: tr(&flavortext),
                 texture,
             })
         })
@@ -146,12 +196,57 @@ fn load_resources(renderer: Rc<RefCell<Renderer>>) -> Result<Rc<GameAssets>> {
         return Err(anyhow!("Default ship \"{}\" not found!", default_ship));
     }
 
+    let shop_table = lua.globals().get::<mlua::Table>("luola_shop")?;
+    let mut shop: Vec<_> = shop_table
+        .pairs::<String, mlua::Table>()
+        .map(|pair| {
+            let (name, v) = pair?;
+            let context = || format!("shop item \"{name}\"");
+            let title: String = v.get("title").with_context(context)?;
+            let flavortext: String = v.get("description").with_context(context)?;
+            let price = v.get("price").with_context(context)?;
+            let max_count = v
+                .get::<Option<i32>>("max")
+                .with_context(context)?
+                .unwrap_or(1);
+            let consumable = v
+                .get::<Option<bool>>("consumable")
+                .with_context(context)?
+                .unwrap_or(false);
+            let weapon_points = v
+                .get::<Option<i32>>("weapon_points")
+                .with_context(context)?
+                .unwrap_or(0);
+            let weapon_slots = v
+                .get::<Option<i32>>("weapon_slots")
+                .with_context(context)?
+                .unwrap_or(0);
+
+            Ok(ShopItem {
+                name,
+                title: tr(&title),
+                flavortext: tr(&flavortext),
+                price,
+                max_count,
+                consumable,
+                weapon_points,
+                weapon_slots,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    shop.sort_by(|a, b| a.price.cmp(&b.price).then_with(|| a.title.cmp(&b.title)));
+
     Ok(Rc::new(GameAssets {
         levels,
+        missions,
         weapons,
         ships,
+        shop,
         default_ship,
         default_weapon,
+        weapon_budget,
+        weapon_slots,
     }))
 }
 
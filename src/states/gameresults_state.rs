@@ -19,9 +19,11 @@ use std::{cell::RefCell, rc::Rc};
 use anyhow::Result;
 
 use crate::{
+    configfile::GAME_CONFIG,
     demos::{AnimatedStarfield, Fireworks},
     game::{MenuButton, Player, PlayerId},
     gfx::{Color, RenderTextDest, RenderTextOptions, Renderer, Text, TextOutline},
+    lang::tr,
     math::{RectF, Vec2, interpolation},
     states::{StackableState, StackableStateResult},
 };
@@ -90,13 +92,17 @@ impl GameResultsState {
         }
 
         // Player ranking table
+        let game_options = GAME_CONFIG.read().unwrap().game.clone();
         let mut player_ranking = players
             .iter()
             .enumerate()
             .map(|(idx, p)| {
                 r.fontset()
                     .menu
-                    .create_text(&r, &format!("Player {} - {}", idx + 1, p.wins))
+                    .create_text(
+                        &r,
+                        &format!("{} - {}", game_options.player_name(idx as i32 + 1), p.wins),
+                    )
                     .map(|t| {
                         (
                             p.wins,
@@ -119,7 +125,7 @@ impl GameResultsState {
         let gameover_text = r
             .fontset()
             .menu_big
-            .create_text(&r, "Game Over!")?
+            .create_text(&r, &tr("Game Over!"))?
             .with_outline_color(Color::new(0.2, 0.2, 0.4));
 
         // There must be a clear winner to count
@@ -26,6 +26,7 @@ use crate::{
         Color, RenderDest, RenderOptions, RenderTextDest, RenderTextOptions, Renderer, Text,
         TextOutline, Texture,
     },
+    lang::tr_fmt,
     math::{RectF, Vec2},
     states::game_assets::GameAssets,
 };
@@ -74,7 +75,7 @@ impl LevelSelection {
             .borrow()
             .fontset()
             .menu_big
-            .create_text(&renderer.borrow(), &format!("Round {}", round))?
+            .create_text(&renderer.borrow(), &tr_fmt("Round {1}", &[&round]))?
             .with_color(Color::new(0.9, 0.2, 0.2));
 
         let prev_round_text = if round > 1 {
@@ -83,7 +84,7 @@ impl LevelSelection {
                     .borrow()
                     .fontset()
                     .menu_big
-                    .create_text(&renderer.borrow(), &format!("Round {}", round - 1))?
+                    .create_text(&renderer.borrow(), &tr_fmt("Round {1}", &[&(round - 1)]))?
                     .with_color(Color::new(0.9, 0.2, 0.2)),
             )
         } else {
@@ -24,7 +24,10 @@ use crate::{
     gfx::{Color, RenderDest, RenderOptions, Renderer, TextureId},
     math::RectF,
     menu::LuaMenu,
-    states::{PlayerSelection, StackableState, StackableStateResult, game_assets::GameAssets},
+    states::{
+        CampaignState, PlayerSelection, StackableState, StackableStateResult,
+        game_assets::GameAssets,
+    },
 };
 
 pub struct MainMenu {
@@ -63,6 +66,7 @@ impl MainMenu {
                 renderer.borrow().height() as f32,
             ),
         )?;
+        luamenu.set_controllers(controllers.clone())?;
 
         let r = renderer.borrow();
         let background = r.texture_store().find_texture(b"menubackground")?;
@@ -173,6 +177,11 @@ impl StackableState for MainMenu {
         self.starfield.borrow_mut().update_screensize(size);
     }
 
+    fn handle_text_input(&mut self, text: &str) -> StackableStateResult {
+        self.luamenu.handle_text_input(text);
+        StackableStateResult::Continue
+    }
+
     fn handle_menu_button(&mut self, button: MenuButton) -> StackableStateResult {
         let result = match self.luamenu.handle_button(button) {
             Ok(res) => res,
@@ -193,6 +202,21 @@ impl StackableState for MainMenu {
                         self.renderer.clone(),
                     ))))
             }
+            "missions" => {
+                let campaign = match CampaignState::new(
+                    self.assets.clone(),
+                    self.starfield.clone(),
+                    self.controllers.clone(),
+                    self.renderer.clone(),
+                ) {
+                    Ok(c) => c,
+                    Err(e) => {
+                        return StackableStateResult::Error(e);
+                    }
+                };
+                self.intro_outro_anim = 0.0;
+                self.anim_state = AnimState::Outro(StackableStateResult::Push(Box::new(campaign)))
+            }
             "quit" => {
                 self.intro_outro_anim = 0.0;
                 self.anim_state = AnimState::Outro(StackableStateResult::Pop)
@@ -14,6 +14,8 @@
 // You should have received a copy of the GNU General Public License
 // along with Luola2.  If not, see <https://www.gnu.org/licenses/>.
 
+mod campaign_state;
+mod console_state;
 mod error_screen;
 mod game_assets;
 mod game_state;
@@ -25,12 +27,15 @@ mod pause_state;
 mod playersel_state;
 mod round_state;
 mod roundresults_state;
+mod shop_state;
 mod state;
 mod weaponsel_state;
 
+use campaign_state::CampaignState;
 pub use error_screen::*;
 use game_state::GameState;
 pub use gameinit_state::GameInitState;
 use mainmenu::MainMenu;
 use playersel_state::*;
+pub use round_state::SIMULATION_RATE;
 pub use state::*;
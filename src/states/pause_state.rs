@@ -64,6 +64,11 @@ impl StackableState for PauseState {
             .relayout(RectF::new(0.0, 0.0, size.0 as f32, size.1 as f32));
     }
 
+    fn handle_text_input(&mut self, text: &str) -> StackableStateResult {
+        self.menu.handle_text_input(text);
+        StackableStateResult::Continue
+    }
+
     fn handle_menu_button(&mut self, button: MenuButton) -> StackableStateResult {
         if matches!(button, MenuButton::Back) {
             return StackableStateResult::Return(Box::new(PauseReturn::Resume));
@@ -18,12 +18,14 @@ use std::{cell::RefCell, rc::Rc};
 
 use super::{StackableState, StackableStateResult};
 use crate::{
+    configfile::GAME_CONFIG,
     demos::AnimatedStarfield,
-    game::{GameControllerSet, MenuButton, Player},
+    game::{GameControllerSet, GameRules, MAX_PLAYERS, MenuButton, Player},
     gfx::{
         Color, RenderDest, RenderOptions, RenderTextDest, RenderTextOptions, Renderer, Text,
         TextOutline, Texture, make_controller_icon,
     },
+    lang::tr,
     math::{RectF, Vec2},
     states::{GameState, game_assets::GameAssets},
 };
@@ -43,8 +45,15 @@ pub struct PlayerSelection {
 
     rounds_to_win: i32,
     rounds_to_win_text: Text,
+
+    rules: GameRules,
+    rules_text: Text,
+
     players: Vec<JoiningPlayer>,
 
+    /// Maximum number of players that can join
+    max_players: usize,
+
     /// Fade out timer after which the game will start
     start_timer: Option<f32>,
 }
@@ -70,17 +79,17 @@ impl PlayerSelection {
         let red = Color::new(0.9, 0.2, 0.2);
 
         let prompt_text = font
-            .create_text(&r, "Press Fire to join!")
+            .create_text(&r, &tr("Press Fire to join!"))
             .unwrap()
             .with_outline_color(Color::new(0.2, 0.2, 0.4));
         let rounds_text = r
             .fontset()
             .menu
-            .create_text(&r, "ROUNDS")
+            .create_text(&r, &tr("ROUNDS"))
             .unwrap()
             .with_color(red);
         let start_text = font
-            .create_text(&r, "Press Enter to start the game!")
+            .create_text(&r, &tr("Press Enter to start the game!"))
             .unwrap()
             //.with_color(red);
             .with_outline_color(Color::new(0.2, 0.2, 0.4));
@@ -92,6 +101,12 @@ impl PlayerSelection {
             .create_text(&r, &format!("{:02}", rounds_to_win))
             .unwrap()
             .with_color(Color::new(0.9, 0.2, 0.2));
+
+        let rules = GameRules::default();
+        let rules_text = font
+            .create_text(&r, &rules.description())
+            .unwrap()
+            .with_color(Color::new(0.9, 0.6, 0.2));
         drop(r);
 
         Self {
@@ -104,7 +119,15 @@ impl PlayerSelection {
             start_text,
             rounds_to_win,
             rounds_to_win_text,
+            rules,
+            rules_text,
             players: Vec::new(),
+            max_players: GAME_CONFIG
+                .read()
+                .unwrap()
+                .game
+                .max_players
+                .clamp(1, MAX_PLAYERS),
             start_timer: None,
         }
     }
@@ -170,6 +193,15 @@ impl PlayerSelection {
             ..Default::default()
         });
 
+        // Rule selector
+        self.rules_text.render(&RenderTextOptions {
+            dest: RenderTextDest::TopCenter(Vec2(
+                w / 2.0 - offset_x,
+                offset_y + self.rounds_to_win_text.height() + self.rounds_text.height(),
+            )),
+            ..Default::default()
+        });
+
         // Start game prompt
         if !self.players.is_empty() {
             self.start_text.render(&RenderTextOptions {
@@ -190,9 +222,10 @@ impl PlayerSelection {
 
         const SPACING: f32 = 32.0;
 
-        let columns =
-            ((renderer.width() as f32 / (size + SPACING)).floor() as usize).min(player_count);
-        let rows = player_count.div_ceil(columns);
+        // Use as few rows as possible, with the players split evenly between them
+        let max_columns = ((renderer.width() as f32 / (size + SPACING)).floor() as usize).max(1);
+        let rows = player_count.div_ceil(max_columns);
+        let columns = player_count.div_ceil(rows);
 
         let left = (renderer.width() as f32 - columns as f32 * (size + SPACING)) / 2.0;
         let top = (renderer.height() as f32 - rows as f32 * (size + SPACING)) / 2.0;
@@ -244,6 +277,14 @@ impl StackableState for PlayerSelection {
                         .set_text(&format!("{:02}", self.rounds_to_win));
                 }
             }
+            MenuButton::Up(_) => {
+                self.rules = self.rules.prev();
+                self.rules_text.set_text(&self.rules.description());
+            }
+            MenuButton::Down(_) => {
+                self.rules = self.rules.next();
+                self.rules_text.set_text(&self.rules.description());
+            }
             MenuButton::Start => {
                 if !self.players.is_empty() {
                     self.start_timer = Some(0.0);
@@ -270,7 +311,7 @@ impl StackableState for PlayerSelection {
                             p.text
                                 .set_default_color(Color::player_color(idx as i32 + 1));
                         });
-                } else {
+                } else if self.players.len() < self.max_players {
                     // Add a player
                     self.players.push(JoiningPlayer {
                         controller,
@@ -339,6 +380,7 @@ impl StackableState for PlayerSelection {
                     self.assets.clone(),
                     players,
                     self.rounds_to_win,
+                    self.rules,
                     self.starfield.clone(),
                     self.controllers.clone(),
                     self.renderer.clone(),
@@ -20,15 +20,34 @@ use std::{cell::RefCell, rc::Rc};
 use anyhow::{Result, anyhow};
 
 use crate::{
-    game::{GameControllerSet, MenuButton, Player, PlayerId, level::LevelInfo, world::World},
+    emit_event,
+    game::{
+        GameControllerSet, GameRules, MAX_PLAYERS, MenuButton, Player, PlayerId,
+        level::{LevelInfo, MissionInfo},
+        world::World,
+    },
     gfx::{Color, RenderOptions, Renderer, TextureId},
+    highscores::get_high_score,
     math::{Rect, RectF, Vec2},
     states::{
         StackableState, StackableStateResult,
+        console_state::{ConsoleLog, ConsoleState},
         pause_state::{PauseReturn, PauseState},
     },
 };
 
+/// The game world is always simulated at this rate, regardless of the frame rate
+pub const SIMULATION_RATE: u32 = 60;
+
+const SIMULATION_TIMESTEP: f32 = 1.0 / SIMULATION_RATE as f32;
+
+/// Rounding error ignored when deciding whether a simulation step is due
+const STEP_TOLERANCE: f32 = SIMULATION_TIMESTEP / 100.0;
+
+/// If the simulation can't keep up, the game slows down
+/// rather than taking ever more steps per frame
+const MAX_STEPS_PER_FRAME: u32 = 4;
+
 pub struct GameRoundState {
     renderer: Rc<RefCell<Renderer>>,
     controllers: Rc<RefCell<GameControllerSet>>,
@@ -42,24 +61,34 @@ pub struct GameRoundState {
     /// Extra blank viewport to fill in when there's an uneven number of players
     filler_viewport: Option<RectF>,
 
+    /// Frame time not yet consumed by simulation steps
+    step_accumulator: f32,
+
     /// Game logo to draw in the filler viewport
     filler_logo: TextureId,
     filler_logo_rect: RectF,
     filler_logo_vel: Vec2,
 
+    /// Developer console scrollback and history
+    console_log: Rc<RefCell<ConsoleLog>>,
+
     // Exit substate
     winner: Option<RoundWinner>,
     fadeout: f32,
 }
 
-/// Return round winner (0 for draw) and whether to quit the game early
+/// Return round winner (0 for draw), whether to quit the game early,
+/// the credits earned by each player during the round and the score
+/// (if the round was scored, as in horde mode)
 #[derive(Clone)]
-pub struct RoundWinner(pub PlayerId, pub bool);
+pub struct RoundWinner(pub PlayerId, pub bool, pub Vec<i32>, pub Option<i32>);
 
 impl GameRoundState {
     pub fn new(
         players: Vec<Player>,
+        rules: GameRules,
         level: &LevelInfo,
+        mission: Option<&MissionInfo>,
         controllers: Rc<RefCell<GameControllerSet>>,
         renderer: Rc<RefCell<Renderer>>,
     ) -> Result<Self> {
@@ -73,7 +102,8 @@ impl GameRoundState {
             player.set("player", idx + 1)?;
             player.set("controller", p.controller)?;
             player.set("ship", p.ship.clone())?;
-            player.set("weapon", p.weapon.clone())?;
+            player.set("weapons", p.weapons.clone())?;
+            player.set("purchases", p.purchases.clone())?;
             player.set("spawn", p.spawn.map(|p| p.as_world_coordinate()))?;
             player.set(
                 "pilot_spawn",
@@ -82,15 +112,35 @@ impl GameRoundState {
             player_settings.push(player)?;
         }
 
+        let level_settings = lua.to_value(level.script_settings())?;
+        let rule_settings = lua.create_table()?;
+        rule_settings.set("fuel", rules.fuel)?;
+        rule_settings.set("heat", rules.heat)?;
+        rule_settings.set("economy", rules.economy)?;
+        rule_settings.set("horde", rules.horde)?;
+
         let settings = lua.create_table()?;
         settings.set("players", player_settings)?;
-        settings.set("level", lua.to_value(level.script_settings())?)?;
+        settings.set("rules", rule_settings)?;
+        settings.set("level", &level_settings)?;
+        settings.set("high_score", get_high_score(&level.id()))?;
+
+        if let Some(mission) = mission {
+            let mission_settings = lua.create_table()?;
+            mission_settings.set("title", mission.title())?;
+            mission_settings.set("par", mission.par())?;
+            mission_settings.set("objective", lua.to_value(mission.objective())?)?;
+            settings.set("mission", mission_settings)?;
+        }
 
         world
             .scripting()
             .get_function("luola_init_game")?
             .call::<()>(settings)?;
 
+        emit_event!(lua, "round_start", &level_settings);
+        world.scripting().check_instruction_limit()?;
+
         let filler_logo = renderer
             .borrow()
             .texture_store()
@@ -102,9 +152,11 @@ impl GameRoundState {
             players,
             world,
             filler_viewport: None,
+            step_accumulator: 0.0,
             filler_logo,
             filler_logo_rect: RectF::new(0.0, 0.0, 1.0, 1.0),
             filler_logo_vel: Vec2(5.0 + fastrand::f32() * 10.0, 5.0 + fastrand::f32() * 10.0),
+            console_log: Rc::new(RefCell::new(ConsoleLog::new())),
             winner: None,
             fadeout: 0.0,
         };
@@ -126,6 +178,18 @@ impl StackableState for GameRoundState {
                 return StackableStateResult::Push(pause_state);
             }
             MenuButton::Debug => self.world.toggle_debugmode(),
+            MenuButton::Console => {
+                return match ConsoleState::new(
+                    self.world.scripting().lua().clone(),
+                    self.world.scripting().instruction_counter(),
+                    self.console_log.clone(),
+                    self.renderer.clone(),
+                    self.controllers.clone(),
+                ) {
+                    Ok(s) => StackableStateResult::Push(Box::new(s)),
+                    Err(err) => StackableStateResult::Error(err),
+                };
+            }
             _ => {}
         }
         StackableStateResult::Continue
@@ -150,6 +214,8 @@ impl StackableState for GameRoundState {
                     self.winner = Some(RoundWinner(
                         winner.unwrap_or(0),
                         matches!(pauseret, PauseReturn::EndGame),
+                        Vec::new(),
+                        None,
                     ));
                 }
             }
@@ -196,12 +262,29 @@ impl StackableState for GameRoundState {
     }
 
     fn state_iterate(&mut self, timestep: f32) -> StackableStateResult {
-        let winner = self.world.step(&self.controllers.borrow().states, timestep);
+        // Step the world at a fixed rate. The frame rate only affects rendering.
+        // Rounding errors are discarded so a frame rate that is a multiple
+        // of the simulation rate always takes the same number of steps per frame.
+        self.step_accumulator = (self.step_accumulator + timestep)
+            .min(SIMULATION_TIMESTEP * MAX_STEPS_PER_FRAME as f32);
+        while self.step_accumulator > SIMULATION_TIMESTEP - STEP_TOLERANCE {
+            self.step_accumulator -= SIMULATION_TIMESTEP;
+            let winner = self
+                .world
+                .step(&self.controllers.borrow().states, SIMULATION_TIMESTEP);
+
+            if let Err(err) = self.world.scripting().check_instruction_limit() {
+                return StackableStateResult::Error(err);
+            }
 
-        if self.winner.is_none()
-            && let Some(winner) = winner
-        {
-            self.winner = Some(RoundWinner(winner, false));
+            if self.winner.is_none()
+                && let Some(winner) = winner
+            {
+                self.winner = Some(RoundWinner(winner, false, Vec::new(), None));
+            }
+        }
+        if self.step_accumulator.abs() < STEP_TOLERANCE {
+            self.step_accumulator = 0.0;
         }
 
         let mut renderer = self.renderer.borrow_mut();
@@ -261,7 +344,12 @@ impl StackableState for GameRoundState {
         if let Some(winner) = &self.winner {
             self.fadeout += timestep;
             if self.fadeout > 1.0 {
-                return StackableStateResult::Return(Box::new(winner.clone()));
+                return StackableStateResult::Return(Box::new(RoundWinner(
+                    winner.0,
+                    winner.1,
+                    self.world.earned_credits(),
+                    self.world.score(),
+                )));
             }
             renderer.draw_filled_rectangle(
                 RectF::new(0.0, 0.0, renderer.width() as f32, renderer.height() as f32),
@@ -273,66 +361,90 @@ impl StackableState for GameRoundState {
     }
 }
 
+/// Viewport grid (columns, rows) for each player count
+fn viewport_grid(player_count: usize) -> (usize, usize) {
+    match player_count {
+        0 | 1 => (1, 1),
+        2 => (2, 1),
+        3 | 4 => (2, 2),
+        5 | 6 => (3, 2),
+        _ => (4, 2),
+    }
+}
+
+/**
+ * Split the screen into viewports for each player.
+ *
+ * Viewports are laid out in a grid, filling the top row first.
+ * Viewports are not made larger than the level.
+ * If the grid has an unused cell, it is returned as a filler viewport.
+ */
 fn assign_viewports(
     screen: Rect,
     max_width: i32,
     max_height: i32,
     player_count: usize,
 ) -> (Vec<Rect>, Option<RectF>) {
-    if player_count < 2 {
-        (
-            vec![Rect::new(
-                screen.x(),
-                screen.y(),
-                screen.w().min(max_width),
-                screen.h().min(max_height),
-            )],
-            None,
-        )
-    } else if player_count < 3 {
-        let w2 = screen.w() / 2;
-        let hmax = screen.h().min(max_height);
-        (
-            vec![
-                Rect::new(screen.x(), screen.y(), w2.min(max_width), hmax),
-                Rect::new(screen.x() + w2, screen.y(), w2.min(max_width), hmax),
-            ],
-            None,
-        )
-    } else {
-        let mut viewports = Vec::with_capacity(player_count);
-        let w = screen.w() * 2 / (player_count + player_count % 2) as i32;
-        let h = screen.h() / 2;
-        let bottom = player_count / 2;
-        let top = player_count - bottom;
-        for i in 0..top {
-            viewports.push(Rect::new(
-                screen.x() + w * i as i32,
-                screen.y(),
-                w.min(max_width),
-                h.min(max_height),
-            ));
-        }
-        for i in 0..bottom {
-            viewports.push(Rect::new(
-                screen.x() + w * i as i32,
-                screen.y() + h,
+    debug_assert!(player_count <= MAX_PLAYERS);
+
+    let (columns, rows) = viewport_grid(player_count);
+    let w = screen.w() / columns as i32;
+    let h = screen.h() / rows as i32;
+
+    let viewports = (0..player_count.max(1))
+        .map(|i| {
+            Rect::new(
+                screen.x() + w * (i % columns) as i32,
+                screen.y() + h * (i / columns) as i32,
                 w.min(max_width),
                 h.min(max_height),
-            ));
-        }
+            )
+        })
+        .collect();
+
+    // The grids have at most one unused cell, which is always the last one
+    let filler = if player_count > 1 && player_count < columns * rows {
+        Some(RectF::new(
+            (screen.x() + w * (columns - 1) as i32) as f32,
+            (screen.y() + h * (rows - 1) as i32) as f32,
+            w as f32,
+            h as f32,
+        ))
+    } else {
+        None
+    };
 
-        let filler = if player_count % 2 == 1 {
-            Some(RectF::new(
-                (screen.x() + w * bottom as i32) as f32,
-                (screen.y() + h) as f32,
-                w as f32,
-                h as f32,
-            ))
-        } else {
-            None
-        };
+    (viewports, filler)
+}
 
-        (viewports, filler)
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_assign_viewports() {
+        let screen = Rect::new(0, 0, 1920, 1080);
+
+        for count in 1..=MAX_PLAYERS {
+            let (viewports, filler) = assign_viewports(screen, 10000, 10000, count);
+            assert_eq!(viewports.len(), count);
+
+            let (columns, rows) = viewport_grid(count);
+            assert_eq!(filler.is_some(), count < columns * rows);
+
+            for (i, a) in viewports.iter().enumerate() {
+                assert!(a.x() >= 0 && a.right() <= screen.w());
+                assert!(a.y() >= 0 && a.bottom() <= screen.h());
+                for b in &viewports[i + 1..] {
+                    assert!(
+                        a.right() <= b.x()
+                            || b.right() <= a.x()
+                            || a.bottom() <= b.y()
+                            || b.bottom() <= a.y(),
+                        "viewports overlap with {count} players"
+                    );
+                }
+            }
+        }
     }
 }
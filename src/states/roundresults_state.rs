@@ -19,9 +19,11 @@ use std::{cell::RefCell, rc::Rc};
 use anyhow::Result;
 
 use crate::{
+    configfile::GAME_CONFIG,
     demos::AnimatedStarfield,
     game::{MenuButton, PlayerId},
     gfx::{Color, RenderTextDest, RenderTextOptions, Renderer, Text, TextOutline},
+    lang::{tr, tr_fmt},
     math::Vec2,
     states::{StackableState, StackableStateResult},
 };
@@ -31,6 +33,7 @@ pub struct RoundResultsState {
     starfield: Rc<RefCell<AnimatedStarfield>>,
     round_text: Text,
     winner_text: Text,
+    record_text: Option<Text>,
     timer: f32,
 }
 
@@ -38,6 +41,7 @@ impl RoundResultsState {
     pub fn new(
         round_number: i32,
         winner: PlayerId,
+        score: Option<(i32, bool)>,
         starfield: Rc<RefCell<AnimatedStarfield>>,
         renderer: Rc<RefCell<Renderer>>,
     ) -> Result<Self> {
@@ -45,17 +49,32 @@ impl RoundResultsState {
         let font = &r.fontset().menu_big;
 
         let round_text = font
-            .create_text(&r, &format!("Round {}", round_number))?
+            .create_text(&r, &tr_fmt("Round {1}", &[&round_number]))?
             .with_color(Color::new(0.9, 0.2, 0.2));
 
-        let winner_text = if winner != 0 {
-            font.create_text(&r, &format!("Player {winner} wins!"))?
+        let winner_text = if let Some((score, _)) = score {
+            font.create_text(&r, &tr_fmt("Score: {1}", &[&score]))?
+                .with_color(Color::new(0.8, 0.8, 0.8))
+        } else if winner != 0 {
+            let name = GAME_CONFIG.read().unwrap().game.player_name(winner);
+            font.create_text(&r, &tr_fmt("{1} wins!", &[&name]))?
                 .with_color(Color::player_color(winner))
         } else {
-            font.create_text(&r, "Draw!")?
+            font.create_text(&r, &tr("Draw!"))?
                 .with_color(Color::new(0.8, 0.8, 0.8))
         };
 
+        let record_text = if let Some((_, true)) = score {
+            Some(
+                r.fontset()
+                    .menu
+                    .create_text(&r, &tr("New high score!"))?
+                    .with_color(Color::new(1.0, 0.84, 0.0)),
+            )
+        } else {
+            None
+        };
+
         drop(r);
 
         Ok(Self {
@@ -63,6 +82,7 @@ impl RoundResultsState {
             starfield,
             round_text,
             winner_text,
+            record_text,
             timer: 0.0,
         })
     }
@@ -92,6 +112,17 @@ impl RoundResultsState {
             alpha: fadeinout,
             ..Default::default()
         });
+        if let Some(record) = &self.record_text {
+            record.render(&RenderTextOptions {
+                dest: RenderTextDest::TopCenter(Vec2(
+                    r.width() as f32 / 2.0,
+                    (r.height() as f32 + self.winner_text.height()) / 2.0 + 10.0,
+                )),
+                alpha: fadeinout,
+                outline: TextOutline::Outline,
+                ..Default::default()
+            });
+        }
 
         r.present();
     }
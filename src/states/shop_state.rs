@@ -0,0 +1,425 @@
+// This file is part of Luola2
+// Copyright (C) 2025 Calle Laakkonen
+//
+// Luola2 is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Luola2 is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Luola2.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use anyhow::Result;
+
+use super::{StackableState, StackableStateResult};
+use crate::{
+    demos::AnimatedStarfield,
+    game::{GameControllerSet, MappedKey, MenuButton, Player, PlayerId},
+    gfx::{
+        Color, RenderDest, RenderOptions, RenderTextDest, RenderTextOptions, Renderer, Text,
+        TextOutline, Texture, make_button_icon,
+    },
+    lang::{tr, tr_fmt},
+    math::{RectF, Vec2},
+    states::game_assets::GameAssets,
+};
+
+struct FlavorTexts {
+    title: Text,
+    text: Text,
+}
+
+/**
+ * The between-round shop where players spend the credits they've earned.
+ *
+ * Each player browses the item list with Left/Right and buys the selected item with Fire1.
+ * The last entry in the list is "Done", which marks the player as ready.
+ */
+pub struct ShopState {
+    assets: Rc<GameAssets>,
+    starfield: Rc<RefCell<AnimatedStarfield>>,
+    renderer: Rc<RefCell<Renderer>>,
+    title_text: Text,
+    flavortexts: Vec<FlavorTexts>,
+    players: Vec<PlayerShopping>,
+    flavortext_selection: usize,
+    fadein: f32,
+    start_timer: Option<f32>,
+}
+
+struct PlayerShopping {
+    controller: i32,
+    credits: i32,
+    purchases: HashMap<String, i32>,
+    /// Selected item. The index one past the last item is the "Done" entry
+    selection: usize,
+    item_text: Text,
+    credits_text: Text,
+    left_button_icon: Texture,
+    right_button_icon: Texture,
+    select_button_icon: Texture,
+    decided: bool,
+}
+
+/// Remaining credits and owned items of each player
+pub struct ShopPurchases(pub Vec<(i32, HashMap<String, i32>)>);
+
+impl PlayerShopping {
+    fn owned(&self, item: &str) -> i32 {
+        self.purchases.get(item).copied().unwrap_or(0)
+    }
+
+    fn update_texts(&mut self, assets: &GameAssets) {
+        if let Some(item) = assets.shop.get(self.selection) {
+            self.item_text.set_text(&tr_fmt(
+                "{1} ({2}/{3}): {4} cr",
+                &[
+                    &item.title,
+                    &self.owned(&item.name),
+                    &item.max_count,
+                    &item.price,
+                ],
+            ));
+        } else {
+            self.item_text.set_text(&tr("Done"));
+        }
+        self.credits_text
+            .set_text(&tr_fmt("{1} cr", &[&self.credits]));
+    }
+
+    /// Buy the selected item if the player can afford it
+    fn buy(&mut self, assets: &GameAssets) {
+        if let Some(item) = assets.shop.get(self.selection)
+            && self.credits >= item.price
+            && self.owned(&item.name) < item.max_count
+        {
+            self.credits -= item.price;
+            *self.purchases.entry(item.name.clone()).or_insert(0) += 1;
+            self.update_texts(assets);
+        }
+    }
+}
+
+impl ShopState {
+    pub fn new(
+        assets: Rc<GameAssets>,
+        players: &[Player],
+        starfield: Rc<RefCell<AnimatedStarfield>>,
+        renderer: Rc<RefCell<Renderer>>,
+        controllers: &GameControllerSet,
+    ) -> Result<Self> {
+        let r = renderer.borrow();
+
+        let title_text = r
+            .fontset()
+            .menu_big
+            .create_text(&r, &tr("Shop"))?
+            .with_color(Color::new(0.9, 0.2, 0.2));
+
+        let flavortext_max_width = Self::flavortext_max_width(r.width());
+        let flavortexts = assets
+            .shop
+            .iter()
+            .map(|item| {
+                Ok(FlavorTexts {
+                    title: r
+                        .fontset()
+                        .flavotext
+                        .create_text(&r, &item.title)?
+                        .with_color(Color::new(1.0, 1.0, 0.8)),
+                    text: r
+                        .fontset()
+                        .flavotext
+                        .create_text(&r, &item.flavortext)?
+                        .with_color(Color::new(0.9, 0.9, 0.9))
+                        .with_wrapwidth(flavortext_max_width),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let players = players
+            .iter()
+            .enumerate()
+            .map(|(idx, p)| {
+                let mut shopper = PlayerShopping {
+                    controller: p.controller,
+                    credits: p.credits,
+                    purchases: p.purchases.clone(),
+                    selection: 0,
+                    item_text: r.fontset().menu.create_text(&r, " ")?,
+                    credits_text: r
+                        .fontset()
+                        .menu
+                        .create_text(&r, " ")?
+                        .with_color(Color::player_color(idx as PlayerId + 1)),
+                    left_button_icon: make_button_icon(
+                        p.controller,
+                        MappedKey::Left,
+                        &r,
+                        controllers,
+                    )?,
+                    right_button_icon: make_button_icon(
+                        p.controller,
+                        MappedKey::Right,
+                        &r,
+                        controllers,
+                    )?,
+                    select_button_icon: make_button_icon(
+                        p.controller,
+                        MappedKey::Fire1,
+                        &r,
+                        controllers,
+                    )?,
+                    decided: false,
+                };
+                shopper.update_texts(&assets);
+                Ok(shopper)
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        drop(r);
+
+        Ok(Self {
+            assets,
+            starfield,
+            renderer,
+            title_text,
+            flavortexts,
+            players,
+            flavortext_selection: 0,
+            fadein: 0.0,
+            start_timer: None,
+        })
+    }
+
+    fn flavortext_max_width(screen_width: i32) -> i32 {
+        screen_width / 4
+    }
+
+    fn find_player_mut(&mut self, controller: i32) -> Option<&mut PlayerShopping> {
+        self.players.iter_mut().find(|p| p.controller == controller)
+    }
+
+    fn render_player_box(&self, player: &PlayerShopping, rect: RectF) {
+        let renderer = &self.renderer.borrow();
+        let white = Color::WHITE.with_alpha(self.fadein);
+        let icon_w = player.left_button_icon.width();
+        let icon_h = player.left_button_icon.height();
+
+        let mut x = rect.x() + 8.0;
+
+        player.credits_text.render(&RenderTextOptions {
+            dest: RenderTextDest::TopLeft(Vec2(x, rect.y())),
+            outline: TextOutline::Outline,
+            alpha: self.fadein,
+            ..Default::default()
+        });
+
+        x += rect.w() * 0.2;
+
+        if !player.decided {
+            player.left_button_icon.render(
+                renderer,
+                &RenderOptions {
+                    dest: RenderDest::Centered(Vec2(x + icon_w / 2.0, rect.y() + icon_h / 2.0)),
+                    color: white,
+                    ..Default::default()
+                },
+            );
+        }
+        x += icon_w + 8.0;
+
+        player.item_text.render(&RenderTextOptions {
+            dest: RenderTextDest::TopLeft(Vec2(x, rect.y())),
+            outline: TextOutline::Outline,
+            alpha: if player.decided {
+                self.fadein * 0.5
+            } else {
+                self.fadein
+            },
+            ..Default::default()
+        });
+        x += player.item_text.width() + 8.0;
+
+        if !player.decided {
+            player.right_button_icon.render(
+                renderer,
+                &RenderOptions {
+                    dest: RenderDest::Centered(Vec2(x + icon_w / 2.0, rect.y() + icon_h / 2.0)),
+                    color: white,
+                    ..Default::default()
+                },
+            );
+
+            x += icon_w + 16.0;
+
+            player.select_button_icon.render(
+                renderer,
+                &RenderOptions {
+                    dest: RenderDest::Centered(Vec2(x + icon_w / 2.0, rect.y() + icon_h / 2.0)),
+                    color: white,
+                    ..Default::default()
+                },
+            );
+        }
+    }
+
+    fn render(&self) {
+        let renderer = &self.renderer.borrow();
+        renderer.clear();
+
+        self.starfield.borrow().render(renderer);
+
+        self.title_text.render(&RenderTextOptions {
+            dest: RenderTextDest::TopCenter(Vec2(renderer.width() as f32 / 2.0, 10.0)),
+            outline: TextOutline::Outline,
+            ..Default::default()
+        });
+
+        // Player shopping rows
+        let row_h = self.players[0]
+            .item_text
+            .height()
+            .max(self.players[0].left_button_icon.height())
+            + 16.0;
+        let row_w = renderer.width() as f32 * 0.6;
+
+        let mut row = RectF::new(
+            32.0,
+            (renderer.height() as f32 - row_h * self.players.len() as f32) / 2.0,
+            row_w,
+            row_h,
+        );
+
+        for player in &self.players {
+            self.render_player_box(player, row);
+            row = row + Vec2(0.0, row_h);
+        }
+
+        // Item description box
+        if let Some(texts) = self.flavortexts.get(self.flavortext_selection) {
+            let box_w = texts.title.width().max(texts.text.width());
+            let box_h = texts.title.height() + texts.text.height();
+            let hint_box = RectF::new(
+                renderer.width() as f32 - box_w - 32.0,
+                renderer.height() as f32 - box_h - 32.0,
+                box_w + 16.0,
+                box_h + 24.0,
+            );
+
+            renderer.draw_filled_rectangle(hint_box, &Color::new_rgba(0.1, 0.1, 0.2, 0.9));
+            texts.title.render(&RenderTextOptions {
+                dest: RenderTextDest::TopCenter(hint_box.topleft() + Vec2(hint_box.w() / 2.0, 8.0)),
+                ..Default::default()
+            });
+            texts.text.render(&RenderTextOptions {
+                dest: RenderTextDest::TopLeft(
+                    hint_box.topleft() + Vec2(8.0, texts.title.height() + 16.0),
+                ),
+                ..Default::default()
+            });
+        }
+
+        // Fadeout when everyone is done
+        if let Some(t) = self.start_timer {
+            renderer.draw_filled_rectangle(
+                RectF::new(0.0, 0.0, renderer.width() as f32, renderer.height() as f32),
+                &Color::new_rgba(0.0, 0.0, 0.0, 1.0 - t),
+            );
+        }
+
+        renderer.present();
+    }
+}
+
+impl StackableState for ShopState {
+    fn handle_menu_button(&mut self, button: MenuButton) -> StackableStateResult {
+        // The extra entry is the "Done" button
+        let entries = self.assets.shop.len() + 1;
+        let assets = self.assets.clone();
+
+        match button {
+            MenuButton::Back => {
+                return StackableStateResult::Pop;
+            }
+            MenuButton::Left(plr) if plr > 0 => {
+                if let Some(p) = self.find_player_mut(plr)
+                    && !p.decided
+                {
+                    p.selection = (p.selection + entries - 1) % entries;
+                    p.update_texts(&assets);
+                    self.flavortext_selection = p.selection;
+                }
+            }
+            MenuButton::Right(plr) if plr > 0 => {
+                if let Some(p) = self.find_player_mut(plr)
+                    && !p.decided
+                {
+                    p.selection = (p.selection + 1) % entries;
+                    p.update_texts(&assets);
+                    self.flavortext_selection = p.selection;
+                }
+            }
+            MenuButton::Select(plr) if plr > 0 => {
+                if let Some(p) = self.find_player_mut(plr) {
+                    if p.decided || p.selection == entries - 1 {
+                        p.decided = !p.decided;
+                    } else {
+                        p.buy(&assets);
+                    }
+                }
+
+                if self.players.iter().all(|p| p.decided) {
+                    self.start_timer = Some(1.0);
+                }
+            }
+            _ => {}
+        }
+
+        StackableStateResult::Continue
+    }
+
+    fn resize_screen(&mut self) {
+        self.starfield
+            .borrow_mut()
+            .update_screensize(self.renderer.borrow().size());
+
+        let ww = Self::flavortext_max_width(self.renderer.borrow().width());
+        self.flavortexts
+            .iter_mut()
+            .for_each(|t| t.text.set_wrapwidth(ww));
+    }
+
+    fn state_iterate(&mut self, timestep: f32) -> StackableStateResult {
+        self.starfield.borrow_mut().step(timestep);
+        self.fadein = (self.fadein + timestep).min(1.0);
+
+        if let Some(t) = self.start_timer {
+            if self.players.iter().any(|p| !p.decided) {
+                self.start_timer = None;
+            } else {
+                let t = t - timestep;
+                if t <= 0.0 {
+                    return StackableStateResult::Return(Box::new(ShopPurchases(
+                        self.players
+                            .iter()
+                            .map(|p| (p.credits, p.purchases.clone()))
+                            .collect(),
+                    )));
+                }
+                self.start_timer = Some(t);
+            }
+        }
+
+        self.render();
+
+        StackableStateResult::Continue
+    }
+}
@@ -20,6 +20,7 @@ use sdl3_main::AppResult;
 use std::{any::Any, cell::RefCell, rc::Rc, time::SystemTime};
 
 use crate::{
+    configfile::GAME_CONFIG,
     fs::get_screenshot_path,
     game::MenuButton,
     gfx::{Color, Renderer},
@@ -54,6 +55,11 @@ pub trait StackableState {
         StackableStateResult::Error(anyhow!("Unexpected return with value!"))
     }
 
+    /// Text input event. Received only by states that have enabled text input.
+    fn handle_text_input(&mut self, _text: &str) -> StackableStateResult {
+        StackableStateResult::Continue
+    }
+
     fn resize_screen(&mut self);
     fn handle_menu_button(&mut self, button: MenuButton) -> StackableStateResult;
     fn state_iterate(&mut self, timestep: f32) -> StackableStateResult;
@@ -111,6 +117,8 @@ impl StateStack {
             if let Err(e) = self.take_screenshot() {
                 log::warn!("Couldn't save screenshot: {e}");
             }
+        } else if matches!(button, MenuButton::Capture) {
+            self.toggle_capture();
         } else {
             let result = match self.states.last_mut() {
                 Some(s) => s.handle_menu_button(button),
@@ -120,6 +128,14 @@ impl StateStack {
         }
     }
 
+    pub fn handle_text_input(&mut self, text: &str) {
+        let result = match self.states.last_mut() {
+            Some(s) => s.handle_text_input(text),
+            None => return,
+        };
+        self.handle_state_result(result);
+    }
+
     pub fn state_iterate(&mut self, timestep: f32) -> AppResult {
         let result = if let Some(state) = self.states.last_mut() {
             state.state_iterate(timestep)
@@ -136,16 +152,34 @@ impl StateStack {
         }
     }
 
+    /**
+     * Start capturing every Nth frame into a new directory in the screenshot folder
+     */
+    pub fn start_capture(&self, interval: u32) -> anyhow::Result<()> {
+        let mut path = get_screenshot_path()?;
+        path.push(format!("luola2-capture-{}", timestamp()));
+
+        self.renderer.borrow().start_capture(path, interval)
+    }
+
+    fn toggle_capture(&self) {
+        if self.renderer.borrow().is_capturing() {
+            self.renderer.borrow().stop_capture();
+        } else {
+            let interval = GAME_CONFIG.read().unwrap().video.capture_interval;
+            if let Err(e) = self.start_capture(interval) {
+                log::warn!("Couldn't start frame capture: {e}");
+            }
+        }
+    }
+
     fn take_screenshot(&self) -> anyhow::Result<()> {
         let image = self.renderer.borrow().screenshot()?;
         let mut path = get_screenshot_path()?;
-        let ts = SystemTime::now()
-            .duration_since(SystemTime::UNIX_EPOCH)
-            .expect("valid time expected");
-        path.push(format!("luola2-{}.bmp", ts.as_secs()));
+        path.push(format!("luola2-{}.png", timestamp()));
 
         log::info!("Saving screenshot to: {:?}", path);
-        image.save_bmp(path)?;
+        image.save_png(path)?;
 
         // Flash the screen to indicate a screenshot was taken
         let r = self.renderer.borrow_mut();
@@ -157,3 +191,10 @@ impl StateStack {
         Ok(())
     }
 }
+
+fn timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .expect("valid time expected")
+        .as_secs()
+}
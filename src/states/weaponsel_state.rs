@@ -21,15 +21,22 @@ use anyhow::Result;
 use super::{StackableState, StackableStateResult};
 use crate::{
     demos::AnimatedStarfield,
-    game::{GameControllerSet, MappedKey, MenuButton, Player, PlayerId, level::LEVEL_SCALE},
+    game::{
+        GameControllerSet, MappedKey, MenuButton, Player, PlayerId, level::LEVEL_SCALE,
+        objects::MAX_SECONDARY_WEAPONS,
+    },
     gfx::{
         Color, RenderDest, RenderMode, RenderOptions, RenderTextDest, RenderTextOptions, Renderer,
         Text, TextOutline, Texture, make_button_icon,
     },
+    lang::{tr, tr_fmt},
     math::{RectF, Vec2},
     states::game_assets::GameAssets,
 };
 
+/// Size of the loadout point indicators
+const PIP_SIZE: f32 = 8.0;
+
 struct Texts {
     menu: Text,
     flavor_title: Text,
@@ -46,6 +53,7 @@ pub struct WeaponSelection {
     renderer: Rc<RefCell<Renderer>>,
     players: Vec<PlayerWeaponChoice>,
     texts: Vec<Texts>,
+    empty_slot_text: Text,
     longest_weapon_text_width: f32,
     flavortext_selection: usize,
     fadein: f32,
@@ -55,7 +63,7 @@ pub struct WeaponSelection {
 
 struct PlayerWeaponChoice {
     controller: usize,
-    selection: usize,
+    loadout: Loadout,
     ship_selection: usize,
     up_button_icon: Texture,
     down_button_icon: Texture,
@@ -65,26 +73,155 @@ struct PlayerWeaponChoice {
     decided: bool,
 }
 
-pub struct SelectedWeapons(pub Vec<(String, String)>);
+/// A player's special weapon loadout being picked
+struct Loadout {
+    /// Selected special weapons. The first slot is always filled
+    weapons: [Option<usize>; MAX_SECONDARY_WEAPONS],
+    /// The slot being edited
+    slot: usize,
+    /// Number of slots this player can fill
+    slots: usize,
+    /// Loadout points available to this player
+    budget: i32,
+}
+
+/// Selected ship and special weapon loadout of each player
+pub struct SelectedWeapons(pub Vec<(String, Vec<String>)>);
+
+impl Loadout {
+    /// Start with the given loadout (from the previous round) as far as it fits
+    /// in the slots and the budget
+    fn new(names: &[String], budget: i32, slots: usize, assets: &GameAssets) -> Self {
+        let slots = (budget / assets.cheapest_weapon_cost().max(1))
+            .clamp(1, slots.clamp(1, MAX_SECONDARY_WEAPONS) as i32) as usize;
+
+        let mut weapons = [None; MAX_SECONDARY_WEAPONS];
+        let mut points = 0;
+        let mut filled = 0;
+        for name in names {
+            if filled == slots {
+                break;
+            }
+            if let Some(idx) = assets.weapons.iter().position(|w| &w.name == name)
+                && !weapons.contains(&Some(idx))
+                && points + assets.weapons[idx].cost <= budget
+            {
+                weapons[filled] = Some(idx);
+                points += assets.weapons[idx].cost;
+                filled += 1;
+            }
+        }
+
+        if weapons[0].is_none() {
+            weapons[0] = Some(
+                assets
+                    .weapons
+                    .iter()
+                    .position(|s| s.name == assets.default_weapon)
+                    .expect("A default weapon should have been set in luola_main.lua"),
+            );
+        }
+
+        Self {
+            weapons,
+            slot: 0,
+            slots,
+            budget,
+        }
+    }
+
+    /// The weapon in the slot being edited
+    fn current(&self) -> Option<usize> {
+        self.weapons[self.slot]
+    }
+
+    /// Loadout points spent on all slots
+    fn points_used(&self, assets: &GameAssets) -> i32 {
+        self.weapons
+            .iter()
+            .flatten()
+            .map(|&w| assets.weapons[w].cost)
+            .sum()
+    }
+
+    /// Loadout points available for the slot being edited
+    fn points_available(&self, assets: &GameAssets) -> i32 {
+        self.budget - self.points_used(assets)
+            + self.current().map_or(0, |w| assets.weapons[w].cost)
+    }
+
+    /// Cycle the weapon in the slot being edited.
+    ///
+    /// Weapons that don't fit in the budget or are already in the loadout are skipped.
+    /// Slots other than the first one can also be left empty.
+    fn cycle_weapon(&mut self, assets: &GameAssets, forward: bool) {
+        let count = assets.weapons.len();
+        // the last position stands for an empty slot
+        let positions = if self.slot > 0 { count + 1 } else { count };
+        let available = self.points_available(assets);
+
+        let mut pos = self.current().unwrap_or(count);
+        for _ in 0..positions {
+            pos = if forward {
+                (pos + 1) % positions
+            } else {
+                (pos + positions - 1) % positions
+            };
+
+            if pos == count {
+                self.weapons[self.slot] = None;
+                return;
+            }
+
+            if assets.weapons[pos].cost <= available && !self.weapons.contains(&Some(pos)) {
+                self.weapons[self.slot] = Some(pos);
+                return;
+            }
+        }
+    }
+
+    /// Move on to the next slot. Returns true if there is nothing more to pick.
+    fn next_slot(&mut self, assets: &GameAssets) -> bool {
+        let remaining = self.budget - self.points_used(assets);
+        if self.current().is_some()
+            && self.slot + 1 < self.slots
+            && remaining >= assets.cheapest_weapon_cost()
+        {
+            self.slot += 1;
+            if let Some(w) = self.current()
+                && assets.weapons[w].cost > self.points_available(assets)
+            {
+                self.weapons[self.slot] = None;
+            }
+            false
+        } else {
+            self.weapons[self.slot + 1..].fill(None);
+            true
+        }
+    }
+}
 
 impl PlayerWeaponChoice {
     fn from_weapon_name(
         player: &Player,
         assets: &GameAssets,
+        shop: bool,
         renderer: &Renderer,
         controllers: &GameControllerSet,
     ) -> Result<Self> {
-        let selection = assets
-            .weapons
-            .iter()
-            .position(|w| w.name == player.weapon)
-            .unwrap_or(
-                assets
-                    .weapons
-                    .iter()
-                    .position(|s| s.name == assets.default_weapon)
-                    .expect("A default weapon should have been set in luola_main.lua"),
-            );
+        // Extra weapon slots are bought from the shop. Without it, every slot is available.
+        let slots = if shop {
+            assets.weapon_slots(&player.purchases)
+        } else {
+            MAX_SECONDARY_WEAPONS
+        };
+
+        let loadout = Loadout::new(
+            &player.weapons,
+            assets.weapon_budget(&player.purchases),
+            slots,
+            assets,
+        );
 
         let ship_selection = assets
             .ships
@@ -101,7 +238,7 @@ impl PlayerWeaponChoice {
         Ok(Self {
             controller: player.controller as usize - 1,
             ship_selection,
-            selection,
+            loadout,
             up_button_icon: make_button_icon(
                 player.controller,
                 MappedKey::Up,
@@ -141,6 +278,7 @@ impl WeaponSelection {
     pub fn new(
         assets: Rc<GameAssets>,
         players: &[Player],
+        shop: bool,
         round: i32,
         background: Option<Texture>,
         starfield: Rc<RefCell<AnimatedStarfield>>,
@@ -151,7 +289,7 @@ impl WeaponSelection {
             .borrow()
             .fontset()
             .menu_big
-            .create_text(&renderer.borrow(), &format!("Round {}", round))?
+            .create_text(&renderer.borrow(), &tr_fmt("Round {1}", &[&round]))?
             .with_color(Color::new(0.9, 0.2, 0.2));
 
         let flavortext_max_width = Self::flavortext_max_width(renderer.borrow().width());
@@ -195,6 +333,12 @@ impl WeaponSelection {
             }))
             .collect::<Result<Vec<_>>>()?;
 
+        let empty_slot_text = renderer
+            .borrow()
+            .fontset()
+            .menu
+            .create_text(&renderer.borrow(), &tr("No weapon"))?;
+
         let longest_weapon_text_width = texts
             .iter()
             .fold(0.0, |acc, t| f32::max(acc, t.menu.width()));
@@ -205,6 +349,7 @@ impl WeaponSelection {
                 PlayerWeaponChoice::from_weapon_name(
                     player,
                     &assets,
+                    shop,
                     &renderer.borrow(),
                     controllers,
                 )
@@ -217,13 +362,15 @@ impl WeaponSelection {
             RectF::new(0.0, 0.0, 0.0, 0.0)
         };
 
-        let flavortext_selection = choices[0].selection;
+        let flavortext_selection =
+            choices[0].loadout.weapons[0].expect("First slot should be filled");
         Ok(Self {
             assets,
             background,
             background_rect,
             background_scroll: Vec2(15.0, 8.0),
             texts,
+            empty_slot_text,
             players: choices,
             round_text,
             starfield,
@@ -314,9 +461,7 @@ impl WeaponSelection {
 
         x += shiptex.width() + 8.0;
 
-        // Selected weapon
-        let title_text = &self.texts[player.selection].menu;
-
+        // Selected weapons
         if !player.decided {
             player.left_button_icon.render(
                 renderer,
@@ -330,14 +475,41 @@ impl WeaponSelection {
             x += icon_w + 8.0;
         }
 
-        title_text.render(&RenderTextOptions {
-            dest: RenderTextDest::TopLeft(Vec2(x, rect.y())),
-            outline: TextOutline::Outline,
-            alpha: self.fadein,
-            ..Default::default()
-        });
+        for (idx, weapon) in player.loadout.weapons.iter().enumerate() {
+            let editing = idx == player.loadout.slot && !player.decided;
+            let text = match weapon {
+                Some(w) => &self.texts[*w].menu,
+                None if editing => &self.empty_slot_text,
+                None => continue,
+            };
+
+            text.render(&RenderTextOptions {
+                dest: RenderTextDest::TopLeft(Vec2(x, rect.y())),
+                outline: TextOutline::Outline,
+                alpha: if editing || player.decided {
+                    self.fadein
+                } else {
+                    self.fadein * 0.5
+                },
+                ..Default::default()
+            });
 
-        x += title_text.width() + 8.0;
+            x += text.width() + 8.0;
+        }
+
+        // Loadout points
+        let used = player.loadout.points_used(&self.assets);
+        let pip_y = rect.y() + (text_h - PIP_SIZE) / 2.0;
+        for point in 0..player.loadout.budget {
+            let color = if point < used {
+                Color::new_rgba(1.0, 0.8, 0.2, self.fadein)
+            } else {
+                Color::new_rgba(0.3, 0.3, 0.4, self.fadein)
+            };
+            renderer.draw_filled_rectangle(RectF::new(x, pip_y, PIP_SIZE, PIP_SIZE), &color);
+            x += PIP_SIZE + 4.0;
+        }
+        x += 4.0;
 
         if !player.decided {
             player.right_button_icon.render(
@@ -392,7 +564,21 @@ impl WeaponSelection {
         let flavortext_box_w = texts.flavor_title.width().max(texts.flavor_text.width());
         let flavortext_box_x = renderer.width() as f32 - flavortext_box_w - 32.0;
 
-        let player_box_w = 90.0 + self.longest_weapon_text_width;
+        let weapon_columns = self
+            .players
+            .iter()
+            .map(|p| p.loadout.slots)
+            .max()
+            .unwrap_or(1);
+        let max_budget = self
+            .players
+            .iter()
+            .map(|p| p.loadout.budget)
+            .max()
+            .unwrap_or(0);
+        let player_box_w = 90.0
+            + (self.longest_weapon_text_width + 8.0) * weapon_columns as f32
+            + (PIP_SIZE + 4.0) * max_budget as f32;
         let player_box_h = self.texts[0]
             .menu
             .height()
@@ -451,8 +637,9 @@ impl WeaponSelection {
 
 impl StackableState for WeaponSelection {
     fn handle_menu_button(&mut self, button: MenuButton) -> StackableStateResult {
-        let weapon_count = self.assets.weapons.len();
-        let ship_count = self.assets.ships.len();
+        let assets = self.assets.clone();
+        let weapon_count = assets.weapons.len();
+        let ship_count = assets.ships.len();
 
         match button {
             MenuButton::Back => {
@@ -482,25 +669,30 @@ impl StackableState for WeaponSelection {
                 if let Some(p) = self.find_player_mut(plr)
                     && !p.decided
                 {
-                    if p.selection > 0 {
-                        p.selection -= 1;
-                    } else {
-                        p.selection = weapon_count - 1;
+                    p.loadout.cycle_weapon(&assets, false);
+                    if let Some(w) = p.loadout.current() {
+                        self.flavortext_selection = w;
                     }
-                    self.flavortext_selection = p.selection;
                 }
             }
             MenuButton::Right(plr) if plr > 0 => {
                 if let Some(p) = self.find_player_mut(plr)
                     && !p.decided
                 {
-                    p.selection = (p.selection + 1) % weapon_count;
-                    self.flavortext_selection = p.selection;
+                    p.loadout.cycle_weapon(&assets, true);
+                    if let Some(w) = p.loadout.current() {
+                        self.flavortext_selection = w;
+                    }
                 }
             }
             MenuButton::Select(plr) if plr > 0 => {
                 if let Some(p) = self.find_player_mut(plr) {
-                    p.decided = !p.decided;
+                    if p.decided {
+                        p.decided = false;
+                        p.loadout.slot = 0;
+                    } else {
+                        p.decided = p.loadout.next_slot(&assets);
+                    }
                 }
 
                 if self.players.iter().all(|p| p.decided) {
@@ -561,7 +753,12 @@ impl StackableState for WeaponSelection {
                             .map(|p| {
                                 (
                                     self.assets.ships[p.ship_selection].name.clone(),
-                                    self.assets.weapons[p.selection].name.clone(),
+                                    p.loadout
+                                        .weapons
+                                        .iter()
+                                        .flatten()
+                                        .map(|&w| self.assets.weapons[w].name.clone())
+                                        .collect(),
                                 )
                             })
                             .collect(),
@@ -576,3 +773,142 @@ impl StackableState for WeaponSelection {
         StackableStateResult::Continue
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::states::game_assets::{SelectableWeapon, ShopItem};
+
+    /// Weapons a, b, c and d costing 1, 2, 5 and 1 points
+    fn test_assets() -> GameAssets {
+        let mut assets = GameAssets::new();
+        for (name, cost) in [("a", 1), ("b", 2), ("c", 5), ("d", 1)] {
+            assets.weapons.push(SelectableWeapon {
+                name: name.to_string(),
+                title: name.to_string(),
+                flavortext: String::new(),
+                cost,
+                icon: None,
+            });
+        }
+        assets.default_weapon = "a".to_string();
+        assets
+    }
+
+    #[test]
+    fn test_initial_loadout() {
+        let assets = test_assets();
+
+        let loadout = Loadout::new(&[], 4, 3, &assets);
+        assert_eq!(loadout.slots, 3);
+        assert_eq!(loadout.weapons, [Some(0), None, None]);
+
+        // Unknown, duplicate and over budget weapons are dropped
+        let names = ["x", "c", "b", "b", "a", "d"].map(String::from);
+        let loadout = Loadout::new(&names, 4, 3, &assets);
+        assert_eq!(loadout.weapons, [Some(1), Some(0), Some(3)]);
+
+        // A ship can't carry more weapons than it has slots, however big the budget
+        let names = ["a", "b", "c", "d"].map(String::from);
+        let loadout = Loadout::new(&names, 20, 5, &assets);
+        assert_eq!(loadout.slots, MAX_SECONDARY_WEAPONS);
+        assert_eq!(loadout.weapons, [Some(0), Some(1), Some(2)]);
+    }
+
+    #[test]
+    fn test_cycle_weapon() {
+        let assets = test_assets();
+        let mut loadout = Loadout::new(&[], 4, 3, &assets);
+        assert_eq!(loadout.points_available(&assets), 4);
+
+        // The first slot can't be left empty and over budget weapons are skipped
+        loadout.cycle_weapon(&assets, true);
+        assert_eq!(loadout.current(), Some(1));
+        loadout.cycle_weapon(&assets, true);
+        assert_eq!(loadout.current(), Some(3));
+        loadout.cycle_weapon(&assets, true);
+        assert_eq!(loadout.current(), Some(0));
+        loadout.cycle_weapon(&assets, false);
+        assert_eq!(loadout.current(), Some(3));
+
+        // Weapons already in the loadout are skipped and other slots can be emptied
+        loadout.cycle_weapon(&assets, true);
+        assert!(!loadout.next_slot(&assets));
+        assert_eq!(loadout.slot, 1);
+        assert_eq!(loadout.points_available(&assets), 3);
+        loadout.cycle_weapon(&assets, true);
+        assert_eq!(loadout.current(), Some(1));
+        loadout.cycle_weapon(&assets, false);
+        assert_eq!(loadout.current(), None);
+    }
+
+    #[test]
+    fn test_next_slot() {
+        let assets = test_assets();
+        let mut loadout = Loadout::new(&[], 4, 3, &assets);
+
+        assert!(!loadout.next_slot(&assets));
+        loadout.cycle_weapon(&assets, true);
+        assert_eq!(loadout.current(), Some(1));
+        assert!(!loadout.next_slot(&assets));
+        assert_eq!(loadout.points_available(&assets), 1);
+        loadout.cycle_weapon(&assets, true);
+        assert_eq!(loadout.current(), Some(3));
+        assert_eq!(loadout.points_used(&assets), 4);
+
+        // The last slot finishes the loadout
+        assert!(loadout.next_slot(&assets));
+        assert_eq!(loadout.weapons, [Some(0), Some(1), Some(3)]);
+
+        // Leaving a slot empty finishes the loadout and clears the remaining slots
+        let names = ["a", "b"].map(String::from);
+        let mut loadout = Loadout::new(&names, 4, 3, &assets);
+        assert!(!loadout.next_slot(&assets));
+        assert_eq!(loadout.current(), Some(1));
+        loadout.cycle_weapon(&assets, false);
+        assert_eq!(loadout.current(), None);
+        assert!(loadout.next_slot(&assets));
+        assert_eq!(loadout.weapons, [Some(0), None, None]);
+
+        // Finish when the budget has been used up
+        let names = ["b"].map(String::from);
+        let mut loadout = Loadout::new(&names, 2, 3, &assets);
+        assert_eq!(loadout.slots, 2);
+        assert!(loadout.next_slot(&assets));
+    }
+
+    #[test]
+    fn test_weapon_slots() {
+        let mut assets = test_assets();
+        assets.shop.push(ShopItem {
+            name: "slot".to_string(),
+            title: String::new(),
+            flavortext: String::new(),
+            price: 1,
+            max_count: 2,
+            consumable: false,
+            weapon_points: 0,
+            weapon_slots: 1,
+        });
+
+        // Only one weapon fits in the base loadout
+        let mut purchases = HashMap::new();
+        let names = ["a", "d"].map(String::from);
+        let mut loadout = Loadout::new(&names, 4, assets.weapon_slots(&purchases), &assets);
+        assert_eq!(loadout.slots, 1);
+        assert_eq!(loadout.weapons, [Some(0), None, None]);
+        assert!(loadout.next_slot(&assets));
+
+        // Extra slots are bought from the shop
+        purchases.insert("slot".to_string(), 1);
+        let loadout = Loadout::new(&names, 4, assets.weapon_slots(&purchases), &assets);
+        assert_eq!(loadout.slots, 2);
+        assert_eq!(loadout.weapons, [Some(0), Some(3), None]);
+
+        // but a ship can't carry more than the maximum number of weapons
+        purchases.insert("slot".to_string(), 5);
+        assert_eq!(assets.weapon_slots(&purchases), MAX_SECONDARY_WEAPONS);
+    }
+}